@@ -0,0 +1,8 @@
+ALTER TABLE progress
+DROP COLUMN waist_cm,
+DROP COLUMN hips_cm,
+DROP COLUMN chest_cm,
+DROP COLUMN neck_cm,
+DROP COLUMN arms_cm,
+DROP COLUMN thighs_cm,
+DROP COLUMN body_fat_pct;
@@ -0,0 +1,8 @@
+ALTER TABLE progress
+ADD COLUMN waist_cm NUMERIC(5, 2),
+ADD COLUMN hips_cm NUMERIC(5, 2),
+ADD COLUMN chest_cm NUMERIC(5, 2),
+ADD COLUMN neck_cm NUMERIC(5, 2),
+ADD COLUMN arms_cm NUMERIC(5, 2),
+ADD COLUMN thighs_cm NUMERIC(5, 2),
+ADD COLUMN body_fat_pct NUMERIC(4, 2);
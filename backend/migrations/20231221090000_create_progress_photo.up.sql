@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS
+    progress_photo (
+        id UUID PRIMARY KEY DEFAULT uuid_generate_v4 (),
+        progress_id UUID NOT NULL,
+        user_id UUID NOT NULL,
+        image_key VARCHAR(255) NOT NULL,
+        thumbnail_key VARCHAR(255) NOT NULL,
+        content_type VARCHAR(50) NOT NULL,
+        width INTEGER NOT NULL,
+        height INTEGER NOT NULL,
+        file_size INTEGER NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        created_by_id UUID NOT NULL,
+        CONSTRAINT fk_progress_id FOREIGN KEY (progress_id) REFERENCES progress (id) ON DELETE CASCADE,
+        CONSTRAINT fk_user_id FOREIGN KEY (user_id) REFERENCES users_user (id),
+        CONSTRAINT fk_created_by FOREIGN KEY (created_by_id) REFERENCES users_user (id)
+    )
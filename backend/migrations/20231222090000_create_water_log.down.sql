@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS water_log;
+
+ALTER TABLE diet_target
+DROP COLUMN water_ml;
@@ -0,0 +1,29 @@
+ALTER TABLE diet_target
+ADD COLUMN water_ml INTEGER;
+
+UPDATE diet_target
+SET
+    water_ml = ROUND(weight * 35);
+
+ALTER TABLE diet_target
+ALTER COLUMN water_ml
+SET NOT NULL;
+
+CREATE TABLE IF NOT EXISTS
+    water_log (
+        id UUID PRIMARY KEY DEFAULT uuid_generate_v4 (),
+        user_id UUID NOT NULL,
+        "date" DATE NOT NULL,
+        "time" TIME NOT NULL DEFAULT CURRENT_TIME,
+        volume_ml INTEGER NOT NULL,
+        beverage VARCHAR(50) NOT NULL DEFAULT 'Water',
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMPTZ,
+        created_by_id UUID NOT NULL,
+        updated_by_id UUID,
+        CONSTRAINT fk_user_id FOREIGN KEY (user_id) REFERENCES users_user (id),
+        CONSTRAINT fk_created_by FOREIGN KEY (created_by_id) REFERENCES users_user (id),
+        CONSTRAINT fk_updated_by FOREIGN KEY (updated_by_id) REFERENCES users_user (id)
+    );
+
+CREATE INDEX water_log_user_id_date_idx ON water_log (user_id, "date");
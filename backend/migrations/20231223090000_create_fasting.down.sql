@@ -0,0 +1,8 @@
+DROP TABLE IF EXISTS fast;
+
+DROP TABLE IF EXISTS eating_window;
+
+DROP INDEX IF EXISTS food_log_user_id_eaten_at_idx;
+
+ALTER TABLE food_log
+DROP COLUMN eaten_at;
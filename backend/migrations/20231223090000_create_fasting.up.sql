@@ -0,0 +1,49 @@
+ALTER TABLE food_log
+ADD COLUMN eaten_at TIMESTAMPTZ;
+
+CREATE INDEX food_log_user_id_eaten_at_idx ON food_log (user_id, eaten_at)
+WHERE
+    eaten_at IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS
+    eating_window (
+        id UUID PRIMARY KEY DEFAULT uuid_generate_v4 (),
+        user_id UUID NOT NULL UNIQUE,
+        start_time TIME NOT NULL,
+        end_time TIME NOT NULL,
+        target_fast_hours INTEGER NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMPTZ,
+        created_by_id UUID NOT NULL,
+        updated_by_id UUID,
+        CONSTRAINT fk_user_id FOREIGN KEY (user_id) REFERENCES users_user (id),
+        CONSTRAINT fk_created_by FOREIGN KEY (created_by_id) REFERENCES users_user (id),
+        CONSTRAINT fk_updated_by FOREIGN KEY (updated_by_id) REFERENCES users_user (id)
+    );
+
+CREATE TABLE IF NOT EXISTS
+    fast (
+        id UUID PRIMARY KEY DEFAULT uuid_generate_v4 (),
+        user_id UUID NOT NULL,
+        started_at TIMESTAMPTZ NOT NULL,
+        ended_at TIMESTAMPTZ,
+        target_hours INTEGER NOT NULL,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TIMESTAMPTZ,
+        created_by_id UUID NOT NULL,
+        updated_by_id UUID,
+        CONSTRAINT fk_user_id FOREIGN KEY (user_id) REFERENCES users_user (id),
+        CONSTRAINT fk_created_by FOREIGN KEY (created_by_id) REFERENCES users_user (id),
+        CONSTRAINT fk_updated_by FOREIGN KEY (updated_by_id) REFERENCES users_user (id),
+        CONSTRAINT fast_ended_after_started CHECK (
+            ended_at IS NULL
+            OR ended_at > started_at
+        )
+    );
+
+CREATE INDEX fast_user_id_started_at_idx ON fast (user_id, started_at);
+
+-- Only one fast may be in progress per user.
+CREATE UNIQUE INDEX fast_user_id_active_idx ON fast (user_id)
+WHERE
+    ended_at IS NULL;
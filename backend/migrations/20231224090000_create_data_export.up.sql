@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS
+    data_export (
+        id UUID PRIMARY KEY DEFAULT uuid_generate_v4 (),
+        user_id UUID NOT NULL,
+        status VARCHAR(10) NOT NULL DEFAULT 'pending',
+        file_key VARCHAR(255),
+        file_size INTEGER,
+        error TEXT,
+        created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        completed_at TIMESTAMPTZ,
+        expires_at TIMESTAMPTZ,
+        CONSTRAINT fk_user_id FOREIGN KEY (user_id) REFERENCES users_user (id) ON DELETE CASCADE,
+        CONSTRAINT data_export_status_check CHECK (
+            status IN ('pending', 'running', 'complete', 'failed')
+        )
+    );
+
+CREATE INDEX data_export_user_id_idx ON data_export (user_id, created_at);
@@ -0,0 +1,33 @@
+-- Catalogue rows created by deleted accounts are reassigned to this user.
+INSERT INTO
+    users_user (
+        id,
+        name,
+        username,
+        password,
+        email,
+        is_active,
+        privacy_level
+    )
+VALUES
+    (
+        '00000000-0000-0000-0000-000000000000',
+        'Deleted user',
+        'deleted-user',
+        '!',
+        'deleted-user@trackedfitness.invalid',
+        FALSE,
+        3
+    )
+ON CONFLICT (id) DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS
+    account_deletion (
+        id UUID PRIMARY KEY DEFAULT uuid_generate_v4 (),
+        user_id UUID NOT NULL UNIQUE,
+        requested_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        scheduled_for TIMESTAMPTZ NOT NULL,
+        CONSTRAINT fk_user_id FOREIGN KEY (user_id) REFERENCES users_user (id) ON DELETE CASCADE
+    );
+
+CREATE INDEX account_deletion_scheduled_for_idx ON account_deletion (scheduled_for);
@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS food_log_user_id_import_key_idx;
+
+ALTER TABLE food_log
+DROP COLUMN IF EXISTS import_key;
@@ -0,0 +1,5 @@
+-- Add up migration script here
+ALTER TABLE food_log
+ADD COLUMN import_key UUID;
+
+CREATE UNIQUE INDEX IF NOT EXISTS food_log_user_id_import_key_idx ON food_log (user_id, import_key);
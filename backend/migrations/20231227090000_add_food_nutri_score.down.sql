@@ -0,0 +1,12 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS food_nutri_grade_idx;
+
+ALTER TABLE food
+DROP COLUMN IF EXISTS nutri_grade,
+DROP COLUMN IF EXISTS nutri_score;
+
+DROP FUNCTION IF EXISTS nutri_score_grade (INTEGER);
+
+DROP FUNCTION IF EXISTS nutri_score_points (INTEGER, VARCHAR, INTEGER, NUMERIC, NUMERIC, NUMERIC, NUMERIC, NUMERIC);
+
+DROP FUNCTION IF EXISTS nutri_score_band (NUMERIC, NUMERIC[]);
@@ -0,0 +1,88 @@
+-- Add up migration script here
+-- Points per band follow the Nutri-Score thresholds for solid foods per 100g/ml.
+-- Kept in step with src/food/nutri_score.rs.
+CREATE OR REPLACE FUNCTION nutri_score_band (value NUMERIC, thresholds NUMERIC[]) RETURNS INTEGER LANGUAGE SQL IMMUTABLE AS $$
+    SELECT
+        COUNT(*)::INTEGER
+    FROM
+        UNNEST(thresholds) AS t (threshold)
+    WHERE
+        value > threshold
+$$;
+
+CREATE OR REPLACE FUNCTION nutri_score_points (
+    data_value INTEGER,
+    data_measurement VARCHAR,
+    energy INTEGER,
+    saturates NUMERIC,
+    sugars NUMERIC,
+    salt NUMERIC,
+    fibre NUMERIC,
+    protein NUMERIC
+) RETURNS INTEGER LANGUAGE SQL IMMUTABLE AS $$
+    SELECT
+        CASE
+            WHEN negative >= 11 THEN negative - fibre_points
+            ELSE negative - fibre_points - protein_points
+        END
+    FROM
+        (
+            SELECT
+                nutri_score_band(energy * 4.184 * f, ARRAY[335, 670, 1005, 1340, 1675, 2010, 2345, 2680, 3015, 3350])
+                + nutri_score_band(saturates * f, ARRAY[1, 2, 3, 4, 5, 6, 7, 8, 9, 10])
+                + nutri_score_band(sugars * f, ARRAY[4.5, 9, 13.5, 18, 22.5, 27, 31, 36, 40, 45])
+                + nutri_score_band(salt * 400 * f, ARRAY[90, 180, 270, 360, 450, 540, 630, 720, 810, 900]) AS negative,
+                nutri_score_band(fibre * f, ARRAY[0.9, 1.9, 2.8, 3.7, 4.7]) AS fibre_points,
+                nutri_score_band(protein * f, ARRAY[1.6, 3.2, 4.8, 6.4, 8.0]) AS protein_points
+            FROM
+                (
+                    SELECT
+                        100.0 / data_value AS f
+                    WHERE
+                        data_measurement IN ('g', 'ml')
+                        AND data_value > 0
+                ) per_100
+        ) points
+$$;
+
+CREATE OR REPLACE FUNCTION nutri_score_grade (points INTEGER) RETURNS CHAR(1) LANGUAGE SQL IMMUTABLE AS $$
+    SELECT
+        CASE
+            WHEN points IS NULL THEN NULL
+            WHEN points <= -1 THEN 'A'
+            WHEN points <= 2 THEN 'B'
+            WHEN points <= 10 THEN 'C'
+            WHEN points <= 18 THEN 'D'
+            ELSE 'E'
+        END
+$$;
+
+ALTER TABLE food
+ADD COLUMN nutri_score INTEGER GENERATED ALWAYS AS (
+    nutri_score_points (
+        data_value,
+        data_measurement,
+        energy,
+        saturates,
+        sugars,
+        salt,
+        fibre,
+        protein
+    )
+) STORED,
+ADD COLUMN nutri_grade CHAR(1) GENERATED ALWAYS AS (
+    nutri_score_grade (
+        nutri_score_points (
+            data_value,
+            data_measurement,
+            energy,
+            saturates,
+            sugars,
+            salt,
+            fibre,
+            protein
+        )
+    )
+) STORED;
+
+CREATE INDEX IF NOT EXISTS food_nutri_grade_idx ON food (nutri_grade);
@@ -0,0 +1,18 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS food_review_status_idx;
+
+DROP INDEX IF EXISTS food_brand_review_status_idx;
+
+ALTER TABLE food
+DROP CONSTRAINT IF EXISTS fk_reviewed_by,
+DROP COLUMN IF EXISTS reviewed_by_id,
+DROP COLUMN IF EXISTS reviewed_at,
+DROP COLUMN IF EXISTS review_reason,
+DROP COLUMN IF EXISTS review_status;
+
+ALTER TABLE food_brand
+DROP CONSTRAINT IF EXISTS fk_reviewed_by,
+DROP COLUMN IF EXISTS reviewed_by_id,
+DROP COLUMN IF EXISTS reviewed_at,
+DROP COLUMN IF EXISTS review_reason,
+DROP COLUMN IF EXISTS review_status;
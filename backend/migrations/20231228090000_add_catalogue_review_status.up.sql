@@ -0,0 +1,27 @@
+-- Add up migration script here
+ALTER TABLE food_brand
+ADD COLUMN review_status VARCHAR(10) NOT NULL DEFAULT 'approved' CHECK (review_status IN ('pending', 'approved', 'rejected')),
+ADD COLUMN review_reason VARCHAR(500),
+ADD COLUMN reviewed_at TIMESTAMPTZ,
+ADD COLUMN reviewed_by_id UUID,
+ADD CONSTRAINT fk_reviewed_by FOREIGN KEY (reviewed_by_id) REFERENCES users_user (id) ON DELETE SET NULL;
+
+ALTER TABLE food
+ADD COLUMN review_status VARCHAR(10) NOT NULL DEFAULT 'approved' CHECK (review_status IN ('pending', 'approved', 'rejected')),
+ADD COLUMN review_reason VARCHAR(500),
+ADD COLUMN reviewed_at TIMESTAMPTZ,
+ADD COLUMN reviewed_by_id UUID,
+ADD CONSTRAINT fk_reviewed_by FOREIGN KEY (reviewed_by_id) REFERENCES users_user (id) ON DELETE SET NULL;
+
+-- New rows start pending, existing catalogue rows are treated as approved.
+ALTER TABLE food_brand
+ALTER COLUMN review_status
+SET DEFAULT 'pending';
+
+ALTER TABLE food
+ALTER COLUMN review_status
+SET DEFAULT 'pending';
+
+CREATE INDEX IF NOT EXISTS food_brand_review_status_idx ON food_brand (review_status);
+
+CREATE INDEX IF NOT EXISTS food_review_status_idx ON food (review_status);
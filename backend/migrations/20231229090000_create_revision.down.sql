@@ -0,0 +1,12 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS muscle_group_revision ON muscle_group;
+
+DROP TRIGGER IF EXISTS movement_revision ON movement;
+
+DROP TRIGGER IF EXISTS food_brand_revision ON food_brand;
+
+DROP TRIGGER IF EXISTS food_revision ON food;
+
+DROP FUNCTION IF EXISTS record_revision ();
+
+DROP TABLE IF EXISTS revision;
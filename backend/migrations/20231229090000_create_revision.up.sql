@@ -0,0 +1,149 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS revision (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4 (),
+    table_name VARCHAR(50) NOT NULL,
+    row_id UUID NOT NULL,
+    version INTEGER NOT NULL,
+    action VARCHAR(10) NOT NULL CHECK (action IN ('create', 'update', 'delete')),
+    data JSONB NOT NULL,
+    diff JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    created_by_id UUID,
+    CONSTRAINT fk_created_by FOREIGN KEY (created_by_id) REFERENCES users_user (id) ON DELETE SET NULL,
+    UNIQUE (table_name, row_id, version)
+);
+
+-- Records every write to a catalogue table. The actor is whoever the row says last
+-- edited or reviewed it; deletes carry no actor.
+CREATE OR REPLACE FUNCTION record_revision () RETURNS TRIGGER LANGUAGE plpgsql AS $$
+DECLARE
+    old_data JSONB;
+    new_data JSONB;
+    row_diff JSONB;
+    actor UUID;
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        new_data := to_jsonb(NEW);
+        actor := NEW.created_by_id;
+    ELSIF TG_OP = 'UPDATE' THEN
+        old_data := to_jsonb(OLD);
+        new_data := to_jsonb(NEW);
+        SELECT
+            jsonb_object_agg(t.key, jsonb_build_object('old', old_data -> t.key, 'new', t.value))
+        INTO row_diff
+        FROM
+            jsonb_each(new_data) AS t
+        WHERE
+            t.value IS DISTINCT FROM old_data -> t.key
+            AND t.key NOT IN ('updated_at', 'updated_by_id');
+        IF row_diff IS NULL THEN
+            RETURN NEW;
+        END IF;
+        actor := CASE
+            WHEN new_data -> 'updated_at' IS DISTINCT FROM old_data -> 'updated_at' THEN (new_data ->> 'updated_by_id')::UUID
+            WHEN new_data -> 'reviewed_at' IS DISTINCT FROM old_data -> 'reviewed_at' THEN (new_data ->> 'reviewed_by_id')::UUID
+        END;
+    ELSE
+        new_data := to_jsonb(OLD);
+    END IF;
+
+    INSERT INTO
+        revision (table_name, row_id, version, action, data, diff, created_by_id)
+    SELECT
+        TG_TABLE_NAME,
+        (new_data ->> 'id')::UUID,
+        COALESCE(MAX(version), 0) + 1,
+        CASE TG_OP
+            WHEN 'INSERT' THEN 'create'
+            WHEN 'UPDATE' THEN 'update'
+            ELSE 'delete'
+        END,
+        new_data,
+        row_diff,
+        actor
+    FROM
+        revision
+    WHERE
+        table_name = TG_TABLE_NAME
+        AND row_id = (new_data ->> 'id')::UUID;
+
+    RETURN NULL;
+END;
+$$;
+
+CREATE TRIGGER food_revision
+AFTER INSERT
+OR
+UPDATE
+OR DELETE ON food FOR EACH ROW
+EXECUTE FUNCTION record_revision ();
+
+CREATE TRIGGER food_brand_revision
+AFTER INSERT
+OR
+UPDATE
+OR DELETE ON food_brand FOR EACH ROW
+EXECUTE FUNCTION record_revision ();
+
+CREATE TRIGGER movement_revision
+AFTER INSERT
+OR
+UPDATE
+OR DELETE ON movement FOR EACH ROW
+EXECUTE FUNCTION record_revision ();
+
+CREATE TRIGGER muscle_group_revision
+AFTER INSERT
+OR
+UPDATE
+OR DELETE ON muscle_group FOR EACH ROW
+EXECUTE FUNCTION record_revision ();
+
+-- Existing rows start their history from their current values.
+INSERT INTO
+    revision (table_name, row_id, version, action, data, created_at, created_by_id)
+SELECT
+    'food',
+    t.id,
+    1,
+    'create',
+    to_jsonb(t),
+    COALESCE(t.updated_at, t.created_at),
+    COALESCE(t.updated_by_id, t.created_by_id)
+FROM
+    food t
+UNION ALL
+SELECT
+    'food_brand',
+    t.id,
+    1,
+    'create',
+    to_jsonb(t),
+    COALESCE(t.updated_at, t.created_at),
+    COALESCE(t.updated_by_id, t.created_by_id)
+FROM
+    food_brand t
+UNION ALL
+SELECT
+    'movement',
+    t.id,
+    1,
+    'create',
+    to_jsonb(t),
+    COALESCE(t.updated_at, t.created_at),
+    COALESCE(t.updated_by_id, t.created_by_id)
+FROM
+    movement t
+UNION ALL
+SELECT
+    'muscle_group',
+    t.id,
+    1,
+    'create',
+    to_jsonb(t),
+    COALESCE(t.updated_at, t.created_at),
+    COALESCE(t.updated_by_id, t.created_by_id)
+FROM
+    muscle_group t;
+
+CREATE INDEX IF NOT EXISTS revision_created_by_id_idx ON revision (created_by_id);
@@ -0,0 +1,17 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS catalogue_merge (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4 (),
+    table_name VARCHAR(50) NOT NULL,
+    source_id UUID NOT NULL,
+    source_name VARCHAR(255) NOT NULL,
+    target_id UUID NOT NULL,
+    target_name VARCHAR(255) NOT NULL,
+    repointed JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    created_by_id UUID,
+    CONSTRAINT fk_created_by FOREIGN KEY (created_by_id) REFERENCES users_user (id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS catalogue_merge_target_id_idx ON catalogue_merge (target_id);
+
+CREATE INDEX IF NOT EXISTS catalogue_merge_created_by_id_idx ON catalogue_merge (created_by_id);
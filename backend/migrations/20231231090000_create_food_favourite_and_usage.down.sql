@@ -0,0 +1,12 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS food_log_refresh_usage ON food_log;
+
+DROP FUNCTION IF EXISTS food_log_refresh_usage ();
+
+DROP FUNCTION IF EXISTS refresh_user_food_usage (UUID, UUID, UUID);
+
+DROP INDEX IF EXISTS food_log_user_food_meal_idx;
+
+DROP TABLE IF EXISTS user_food_usage;
+
+DROP TABLE IF EXISTS food_favourite;
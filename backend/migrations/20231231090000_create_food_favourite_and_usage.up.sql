@@ -0,0 +1,116 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS food_favourite (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4 (),
+    user_id UUID NOT NULL,
+    food_id UUID NOT NULL,
+    meal_of_day_id UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT fk_user FOREIGN KEY (user_id) REFERENCES users_user (id),
+    CONSTRAINT fk_food FOREIGN KEY (food_id) REFERENCES food (id) ON DELETE CASCADE,
+    CONSTRAINT fk_meal_of_day FOREIGN KEY (meal_of_day_id) REFERENCES meal_of_day (id) ON DELETE CASCADE,
+    CONSTRAINT food_favourite_unique UNIQUE NULLS NOT DISTINCT (user_id, food_id, meal_of_day_id)
+);
+
+CREATE INDEX IF NOT EXISTS food_favourite_food_id_idx ON food_favourite (food_id);
+
+-- One row per user, food and meal slot, kept current by a trigger on food_log
+-- so recent and frequent lookups never scan the log itself.
+CREATE TABLE IF NOT EXISTS user_food_usage (
+    user_id UUID NOT NULL,
+    food_id UUID NOT NULL,
+    meal_of_day_id UUID NOT NULL,
+    log_count INTEGER NOT NULL,
+    last_quantity DECIMAL(5, 2) NOT NULL,
+    last_date DATE NOT NULL,
+    last_logged_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (user_id, food_id, meal_of_day_id),
+    CONSTRAINT fk_user FOREIGN KEY (user_id) REFERENCES users_user (id),
+    CONSTRAINT fk_food FOREIGN KEY (food_id) REFERENCES food (id) ON DELETE CASCADE,
+    CONSTRAINT fk_meal_of_day FOREIGN KEY (meal_of_day_id) REFERENCES meal_of_day (id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS user_food_usage_recent_idx ON user_food_usage (user_id, last_logged_at DESC);
+
+CREATE INDEX IF NOT EXISTS user_food_usage_frequent_idx ON user_food_usage (user_id, log_count DESC);
+
+CREATE INDEX IF NOT EXISTS food_log_user_food_meal_idx ON food_log (user_id, food_id, meal_of_day_id);
+
+CREATE OR REPLACE FUNCTION refresh_user_food_usage (p_user_id UUID, p_food_id UUID, p_meal_of_day_id UUID)
+    RETURNS VOID
+    AS $$
+BEGIN
+    -- Serialise writers on the same key so each recount sees the other's rows.
+    PERFORM pg_advisory_xact_lock(hashtext(p_user_id::TEXT || p_food_id::TEXT || p_meal_of_day_id::TEXT));
+    INSERT INTO user_food_usage (user_id, food_id, meal_of_day_id, log_count, last_quantity, last_date, last_logged_at)
+    SELECT
+        p_user_id,
+        p_food_id,
+        p_meal_of_day_id,
+        COUNT(*),
+        (ARRAY_AGG(quantity ORDER BY created_at DESC))[1],
+        MAX(date),
+        MAX(created_at)
+    FROM
+        food_log
+    WHERE
+        user_id = p_user_id
+        AND food_id = p_food_id
+        AND meal_of_day_id = p_meal_of_day_id
+    HAVING
+        COUNT(*) > 0
+    ON CONFLICT (user_id, food_id, meal_of_day_id)
+        DO UPDATE SET
+            log_count = EXCLUDED.log_count,
+            last_quantity = EXCLUDED.last_quantity,
+            last_date = EXCLUDED.last_date,
+            last_logged_at = EXCLUDED.last_logged_at;
+    IF NOT FOUND THEN
+        DELETE FROM user_food_usage
+        WHERE user_id = p_user_id
+            AND food_id = p_food_id
+            AND meal_of_day_id = p_meal_of_day_id;
+    END IF;
+END;
+$$
+LANGUAGE plpgsql;
+
+CREATE OR REPLACE FUNCTION food_log_refresh_usage ()
+    RETURNS TRIGGER
+    AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        PERFORM refresh_user_food_usage(NEW.user_id, NEW.food_id, NEW.meal_of_day_id);
+    ELSIF TG_OP = 'DELETE' THEN
+        PERFORM refresh_user_food_usage(OLD.user_id, OLD.food_id, OLD.meal_of_day_id);
+    ELSE
+        PERFORM refresh_user_food_usage(NEW.user_id, NEW.food_id, NEW.meal_of_day_id);
+        IF (OLD.user_id, OLD.food_id, OLD.meal_of_day_id) IS DISTINCT FROM (NEW.user_id, NEW.food_id, NEW.meal_of_day_id) THEN
+            PERFORM refresh_user_food_usage(OLD.user_id, OLD.food_id, OLD.meal_of_day_id);
+        END IF;
+    END IF;
+    RETURN NULL;
+END;
+$$
+LANGUAGE plpgsql;
+
+CREATE TRIGGER food_log_refresh_usage
+    AFTER INSERT OR UPDATE OF user_id, food_id, meal_of_day_id, quantity, date OR DELETE ON food_log
+    FOR EACH ROW
+    EXECUTE FUNCTION food_log_refresh_usage ();
+
+INSERT INTO user_food_usage (user_id, food_id, meal_of_day_id, log_count, last_quantity, last_date, last_logged_at)
+SELECT
+    user_id,
+    food_id,
+    meal_of_day_id,
+    COUNT(*),
+    (ARRAY_AGG(quantity ORDER BY created_at DESC))[1],
+    MAX(date),
+    MAX(created_at)
+FROM
+    food_log
+GROUP BY
+    user_id,
+    food_id,
+    meal_of_day_id
+ON CONFLICT DO NOTHING;
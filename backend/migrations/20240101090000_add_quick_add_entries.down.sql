@@ -0,0 +1,49 @@
+-- Add down migration script here
+CREATE OR REPLACE FUNCTION food_log_refresh_usage ()
+    RETURNS TRIGGER
+    AS $$
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        PERFORM refresh_user_food_usage(NEW.user_id, NEW.food_id, NEW.meal_of_day_id);
+    ELSIF TG_OP = 'DELETE' THEN
+        PERFORM refresh_user_food_usage(OLD.user_id, OLD.food_id, OLD.meal_of_day_id);
+    ELSE
+        PERFORM refresh_user_food_usage(NEW.user_id, NEW.food_id, NEW.meal_of_day_id);
+        IF (OLD.user_id, OLD.food_id, OLD.meal_of_day_id) IS DISTINCT FROM (NEW.user_id, NEW.food_id, NEW.meal_of_day_id) THEN
+            PERFORM refresh_user_food_usage(OLD.user_id, OLD.food_id, OLD.meal_of_day_id);
+        END IF;
+    END IF;
+    RETURN NULL;
+END;
+$$
+LANGUAGE plpgsql;
+
+DELETE FROM meal_food WHERE food_id IS NULL;
+
+ALTER TABLE meal_food
+    DROP CONSTRAINT IF EXISTS meal_food_food_or_quick_add,
+    DROP COLUMN IF EXISTS label,
+    DROP COLUMN IF EXISTS energy,
+    DROP COLUMN IF EXISTS fat,
+    DROP COLUMN IF EXISTS saturates,
+    DROP COLUMN IF EXISTS carbohydrate,
+    DROP COLUMN IF EXISTS sugars,
+    DROP COLUMN IF EXISTS fibre,
+    DROP COLUMN IF EXISTS protein,
+    DROP COLUMN IF EXISTS salt,
+    ALTER COLUMN food_id SET NOT NULL;
+
+DELETE FROM food_log WHERE food_id IS NULL;
+
+ALTER TABLE food_log
+    DROP CONSTRAINT IF EXISTS food_log_food_or_quick_add,
+    DROP COLUMN IF EXISTS label,
+    DROP COLUMN IF EXISTS energy,
+    DROP COLUMN IF EXISTS fat,
+    DROP COLUMN IF EXISTS saturates,
+    DROP COLUMN IF EXISTS carbohydrate,
+    DROP COLUMN IF EXISTS sugars,
+    DROP COLUMN IF EXISTS fibre,
+    DROP COLUMN IF EXISTS protein,
+    DROP COLUMN IF EXISTS salt,
+    ALTER COLUMN food_id SET NOT NULL;
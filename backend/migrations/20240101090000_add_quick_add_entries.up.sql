@@ -0,0 +1,65 @@
+-- Add up migration script here
+-- A quick-add entry has no food record; its label and nutrients are stored on
+-- the row itself with a quantity of one.
+ALTER TABLE food_log
+    ALTER COLUMN food_id DROP NOT NULL,
+    ADD COLUMN IF NOT EXISTS label VARCHAR(100),
+    ADD COLUMN IF NOT EXISTS energy INTEGER,
+    ADD COLUMN IF NOT EXISTS fat DECIMAL(6, 2),
+    ADD COLUMN IF NOT EXISTS saturates DECIMAL(6, 2),
+    ADD COLUMN IF NOT EXISTS carbohydrate DECIMAL(6, 2),
+    ADD COLUMN IF NOT EXISTS sugars DECIMAL(6, 2),
+    ADD COLUMN IF NOT EXISTS fibre DECIMAL(6, 2),
+    ADD COLUMN IF NOT EXISTS protein DECIMAL(6, 2),
+    ADD COLUMN IF NOT EXISTS salt DECIMAL(6, 2),
+    ADD CONSTRAINT food_log_food_or_quick_add CHECK (
+        (
+            food_id IS NOT NULL
+            AND num_nonnulls (label, energy, fat, saturates, carbohydrate, sugars, fibre, protein, salt) = 0
+        )
+        OR (
+            food_id IS NULL
+            AND num_nulls (label, energy, fat, saturates, carbohydrate, sugars, fibre, protein, salt) = 0
+        )
+    );
+
+ALTER TABLE meal_food
+    ALTER COLUMN food_id DROP NOT NULL,
+    ADD COLUMN IF NOT EXISTS label VARCHAR(100),
+    ADD COLUMN IF NOT EXISTS energy INTEGER,
+    ADD COLUMN IF NOT EXISTS fat DECIMAL(6, 2),
+    ADD COLUMN IF NOT EXISTS saturates DECIMAL(6, 2),
+    ADD COLUMN IF NOT EXISTS carbohydrate DECIMAL(6, 2),
+    ADD COLUMN IF NOT EXISTS sugars DECIMAL(6, 2),
+    ADD COLUMN IF NOT EXISTS fibre DECIMAL(6, 2),
+    ADD COLUMN IF NOT EXISTS protein DECIMAL(6, 2),
+    ADD COLUMN IF NOT EXISTS salt DECIMAL(6, 2),
+    ADD CONSTRAINT meal_food_food_or_quick_add CHECK (
+        (
+            food_id IS NOT NULL
+            AND num_nonnulls (label, energy, fat, saturates, carbohydrate, sugars, fibre, protein, salt) = 0
+        )
+        OR (
+            food_id IS NULL
+            AND num_nulls (label, energy, fat, saturates, carbohydrate, sugars, fibre, protein, salt) = 0
+        )
+    );
+
+-- Quick-add rows have no food to count towards recent or frequent use.
+CREATE OR REPLACE FUNCTION food_log_refresh_usage ()
+    RETURNS TRIGGER
+    AS $$
+BEGIN
+    IF TG_OP IN ('INSERT', 'UPDATE') AND NEW.food_id IS NOT NULL THEN
+        PERFORM refresh_user_food_usage(NEW.user_id, NEW.food_id, NEW.meal_of_day_id);
+    END IF;
+    IF TG_OP = 'DELETE' AND OLD.food_id IS NOT NULL THEN
+        PERFORM refresh_user_food_usage(OLD.user_id, OLD.food_id, OLD.meal_of_day_id);
+    END IF;
+    IF TG_OP = 'UPDATE' AND OLD.food_id IS NOT NULL AND (OLD.user_id, OLD.food_id, OLD.meal_of_day_id) IS DISTINCT FROM (NEW.user_id, NEW.food_id, NEW.meal_of_day_id) THEN
+        PERFORM refresh_user_food_usage(OLD.user_id, OLD.food_id, OLD.meal_of_day_id);
+    END IF;
+    RETURN NULL;
+END;
+$$
+LANGUAGE plpgsql;
@@ -0,0 +1,49 @@
+-- Add up migration script here
+-- Planned diary entries. Marking one eaten copies it into food_log and links the
+-- two, so food_log stays a record of what was actually eaten.
+CREATE TABLE IF NOT EXISTS planned_food (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4 (),
+    date DATE NOT NULL,
+    user_id UUID NOT NULL,
+    meal_of_day_id UUID NOT NULL,
+    food_id UUID,
+    quantity DECIMAL(5, 2) NOT NULL,
+    meal_id UUID,
+    food_log_id UUID,
+    label VARCHAR(100),
+    energy INTEGER,
+    fat DECIMAL(6, 2),
+    saturates DECIMAL(6, 2),
+    carbohydrate DECIMAL(6, 2),
+    sugars DECIMAL(6, 2),
+    fibre DECIMAL(6, 2),
+    protein DECIMAL(6, 2),
+    salt DECIMAL(6, 2),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ,
+    created_by_id UUID NOT NULL,
+    updated_by_id UUID,
+    CONSTRAINT fk_user FOREIGN KEY (user_id) REFERENCES users_user (id),
+    CONSTRAINT fk_meal_of_day FOREIGN KEY (meal_of_day_id) REFERENCES meal_of_day (id),
+    CONSTRAINT fk_food FOREIGN KEY (food_id) REFERENCES food (id),
+    CONSTRAINT fk_meal FOREIGN KEY (meal_id) REFERENCES meal (id) ON DELETE SET NULL,
+    CONSTRAINT fk_food_log FOREIGN KEY (food_log_id) REFERENCES food_log (id) ON DELETE SET NULL,
+    CONSTRAINT fk_created_by FOREIGN KEY (created_by_id) REFERENCES users_user (id),
+    CONSTRAINT fk_updated_by FOREIGN KEY (updated_by_id) REFERENCES users_user (id),
+    CONSTRAINT planned_food_food_or_quick_add CHECK (
+        (
+            food_id IS NOT NULL
+            AND num_nonnulls (label, energy, fat, saturates, carbohydrate, sugars, fibre, protein, salt) = 0
+        )
+        OR (
+            food_id IS NULL
+            AND num_nulls (label, energy, fat, saturates, carbohydrate, sugars, fibre, protein, salt) = 0
+        )
+    )
+);
+
+CREATE INDEX IF NOT EXISTS planned_food_user_id_date_idx ON planned_food (user_id, date);
+
+CREATE INDEX IF NOT EXISTS planned_food_food_id_idx ON planned_food (food_id);
+
+CREATE UNIQUE INDEX IF NOT EXISTS planned_food_food_log_id_idx ON planned_food (food_log_id);
@@ -0,0 +1,34 @@
+-- Add up migration script here
+-- Personal records found as sets are added. Volume records belong to a whole
+-- workout for the movement, so they have no set and are replaced as it grows.
+CREATE TABLE IF NOT EXISTS personal_record (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4 (),
+    user_id UUID NOT NULL,
+    movement_id UUID NOT NULL,
+    workout_id UUID NOT NULL,
+    exercise_id UUID NOT NULL,
+    set_id UUID,
+    record_type VARCHAR(10) NOT NULL,
+    formula VARCHAR(10),
+    weight DECIMAL(8, 2),
+    reps INTEGER,
+    value DECIMAL(10, 2) NOT NULL,
+    previous_value DECIMAL(10, 2) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT fk_user FOREIGN KEY (user_id) REFERENCES users_user (id),
+    CONSTRAINT fk_movement FOREIGN KEY (movement_id) REFERENCES movement (id) ON DELETE CASCADE,
+    CONSTRAINT fk_workout FOREIGN KEY (workout_id) REFERENCES workout (id) ON DELETE CASCADE,
+    CONSTRAINT fk_exercise FOREIGN KEY (exercise_id) REFERENCES exercise (id) ON DELETE CASCADE,
+    CONSTRAINT fk_set FOREIGN KEY (set_id) REFERENCES tracked_set (id) ON DELETE CASCADE,
+    CONSTRAINT personal_record_type CHECK (record_type IN ('weight', 'e1rm', 'reps', 'volume')),
+    CONSTRAINT personal_record_formula CHECK (
+        (record_type = 'e1rm' AND formula IN ('epley', 'brzycki'))
+        OR (record_type <> 'e1rm' AND formula IS NULL)
+    )
+);
+
+CREATE INDEX IF NOT EXISTS personal_record_user_id_movement_id_idx ON personal_record (user_id, movement_id);
+
+CREATE INDEX IF NOT EXISTS personal_record_workout_id_idx ON personal_record (workout_id);
+
+CREATE INDEX IF NOT EXISTS personal_record_set_id_idx ON personal_record (set_id);
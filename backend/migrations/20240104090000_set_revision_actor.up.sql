@@ -0,0 +1,62 @@
+-- Add up migration script here
+-- Records every write to a catalogue table. The actor is the app.actor_id setting of
+-- the transaction when the app sets it, otherwise whoever the row says last edited or
+-- reviewed it.
+CREATE OR REPLACE FUNCTION record_revision () RETURNS TRIGGER LANGUAGE plpgsql AS $$
+DECLARE
+    old_data JSONB;
+    new_data JSONB;
+    row_diff JSONB;
+    actor UUID := NULLIF(current_setting('app.actor_id', TRUE), '')::UUID;
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        new_data := to_jsonb(NEW);
+        actor := COALESCE(actor, NEW.created_by_id);
+    ELSIF TG_OP = 'UPDATE' THEN
+        old_data := to_jsonb(OLD);
+        new_data := to_jsonb(NEW);
+        SELECT
+            jsonb_object_agg(t.key, jsonb_build_object('old', old_data -> t.key, 'new', t.value))
+        INTO row_diff
+        FROM
+            jsonb_each(new_data) AS t
+        WHERE
+            t.value IS DISTINCT FROM old_data -> t.key
+            AND t.key NOT IN ('updated_at', 'updated_by_id');
+        IF row_diff IS NULL THEN
+            RETURN NEW;
+        END IF;
+        actor := COALESCE(
+            actor,
+            CASE
+                WHEN new_data -> 'updated_at' IS DISTINCT FROM old_data -> 'updated_at' THEN (new_data ->> 'updated_by_id')::UUID
+                WHEN new_data -> 'reviewed_at' IS DISTINCT FROM old_data -> 'reviewed_at' THEN (new_data ->> 'reviewed_by_id')::UUID
+            END
+        );
+    ELSE
+        new_data := to_jsonb(OLD);
+    END IF;
+
+    INSERT INTO
+        revision (table_name, row_id, version, action, data, diff, created_by_id)
+    SELECT
+        TG_TABLE_NAME,
+        (new_data ->> 'id')::UUID,
+        COALESCE(MAX(version), 0) + 1,
+        CASE TG_OP
+            WHEN 'INSERT' THEN 'create'
+            WHEN 'UPDATE' THEN 'update'
+            ELSE 'delete'
+        END,
+        new_data,
+        row_diff,
+        actor
+    FROM
+        revision
+    WHERE
+        table_name = TG_TABLE_NAME
+        AND row_id = (new_data ->> 'id')::UUID;
+
+    RETURN NULL;
+END;
+$$;
@@ -0,0 +1,6 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS food_brand_name_trgm_idx;
+
+DROP INDEX IF EXISTS food_name_trgm_idx;
+
+DROP FUNCTION IF EXISTS normalize_name (TEXT);
@@ -0,0 +1,24 @@
+-- Add up migration script here
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- Mirrors util::similarity::normalize: lowercased words without punctuation or a
+-- plural s, sorted and deduplicated, so word order does not affect matching.
+CREATE OR REPLACE FUNCTION normalize_name (name TEXT) RETURNS TEXT LANGUAGE sql IMMUTABLE PARALLEL SAFE AS $$
+    SELECT
+        COALESCE(string_agg(DISTINCT t.word COLLATE "C", ' ' ORDER BY t.word COLLATE "C"), '')
+    FROM (
+        SELECT
+            CASE
+                WHEN length(w) > 3 AND w ~ '[^s]s$' THEN left(w, -1)
+                ELSE w
+            END AS word
+        FROM
+            regexp_split_to_table(lower(name), '[^[:alnum:]]+') AS w
+        WHERE
+            w <> ''
+    ) t;
+$$;
+
+CREATE INDEX IF NOT EXISTS food_name_trgm_idx ON food USING GIN (normalize_name (name) gin_trgm_ops);
+
+CREATE INDEX IF NOT EXISTS food_brand_name_trgm_idx ON food_brand USING GIN (normalize_name (name) gin_trgm_ops);
@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TABLE data_export
+DROP COLUMN IF EXISTS heartbeat_at;
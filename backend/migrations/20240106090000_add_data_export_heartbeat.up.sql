@@ -0,0 +1,5 @@
+-- Add up migration script here
+-- Workers touch heartbeat_at while they build an export, so exports left behind by a
+-- stopped instance can be told apart from those another instance is still building.
+ALTER TABLE data_export
+ADD COLUMN heartbeat_at TIMESTAMPTZ;
@@ -0,0 +1,131 @@
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    storage::{Storage, StorageError},
+    util::image::{decode_image, encode_png, resize_to_fit},
+};
+
+pub const BRAND_LOGO_URL: &str = "/media/brand-logos";
+pub const MAX_LOGO_SIZE: usize = 5 * 1024 * 1024;
+// Square bounding boxes, smallest first. The largest is stored as image_url.
+pub const LOGO_SIZES: [u32; 3] = [64, 128, 256];
+const LOGO_CONTENT_TYPE: &str = "image/png";
+
+pub struct ProcessedLogo(Vec<(u32, Vec<u8>)>);
+
+// Logos are kept as PNG so transparent backgrounds survive.
+pub fn process_logo(data: &[u8]) -> Result<ProcessedLogo, AppError> {
+    let image = decode_image(data)?;
+    let mut sizes = Vec::new();
+    for size in LOGO_SIZES {
+        sizes.push((size, encode_png(&resize_to_fit(&image, size))?));
+    }
+    Ok(ProcessedLogo(sizes))
+}
+
+fn logo_key(brand_id: &Uuid, version: &Uuid, size: u32) -> String {
+    format!("{}/{}-{}.png", brand_id, version, size)
+}
+
+pub fn logo_urls(brand_id: &Uuid, version: &Uuid) -> BTreeMap<u32, String> {
+    LOGO_SIZES
+        .iter()
+        .map(|size| {
+            let url = format!("{}/{}", BRAND_LOGO_URL, logo_key(brand_id, version, *size));
+            (*size, url)
+        })
+        .collect()
+}
+
+// Recovers the upload version from an image_url written by this module, so older
+// files can be removed. Anything else, such as a legacy external URL, is ignored.
+pub fn logo_version(brand_id: &Uuid, image_url: &str) -> Option<Uuid> {
+    let largest = LOGO_SIZES[LOGO_SIZES.len() - 1];
+    let file = image_url
+        .strip_prefix(BRAND_LOGO_URL)?
+        .strip_prefix(&format!("/{}/", brand_id))?
+        .strip_suffix(&format!("-{}.png", largest))?;
+    Uuid::parse_str(file).ok()
+}
+
+// Each upload gets a new version in its file names, so cached copies of a
+// replaced logo are never served in its place.
+pub async fn save_logo(
+    storage: &impl Storage,
+    brand_id: &Uuid,
+    logo: ProcessedLogo,
+) -> Result<BTreeMap<u32, String>, StorageError> {
+    let version = Uuid::new_v4();
+    for (size, data) in logo.0 {
+        let key = logo_key(brand_id, &version, size);
+        if let Err(err) = storage.put(&key, LOGO_CONTENT_TYPE, data).await {
+            delete_version(storage, brand_id, &version).await;
+            return Err(err);
+        }
+    }
+    Ok(logo_urls(brand_id, &version))
+}
+
+// Removes every size of an upload that could not be saved. Missing files are
+// skipped by the storage, so this also covers a partly written upload.
+async fn delete_version(storage: &impl Storage, brand_id: &Uuid, version: &Uuid) {
+    for size in LOGO_SIZES {
+        let key = logo_key(brand_id, version, size);
+        if let Err(err) = storage.delete(&key).await {
+            tracing::warn!("could not delete stored file {}: {:?}", key, err);
+        }
+    }
+}
+
+pub async fn discard_logo(
+    storage: &impl Storage,
+    brand_id: &Uuid,
+    image_urls: &BTreeMap<u32, String>,
+) {
+    let version = image_urls
+        .values()
+        .last()
+        .and_then(|url| logo_version(brand_id, url));
+    if let Some(version) = version {
+        delete_version(storage, brand_id, &version).await;
+    }
+}
+
+pub async fn delete_logo(
+    storage: &impl Storage,
+    brand_id: &Uuid,
+    image_url: &Option<String>,
+) -> Result<(), StorageError> {
+    let Some(version) = image_url
+        .as_ref()
+        .and_then(|url| logo_version(brand_id, url))
+    else {
+        return Ok(());
+    };
+    for size in LOGO_SIZES {
+        storage.delete(&logo_key(brand_id, &version, size)).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_logo_version_round_trip() {
+        let brand_id = Uuid::new_v4();
+        let version = Uuid::new_v4();
+        let urls = logo_urls(&brand_id, &version);
+        assert_eq!(urls.len(), LOGO_SIZES.len());
+        assert_eq!(logo_version(&brand_id, &urls[&256]), Some(version));
+        assert_eq!(logo_version(&brand_id, &urls[&64]), None);
+        assert_eq!(logo_version(&Uuid::new_v4(), &urls[&256]), None);
+        assert_eq!(
+            logo_version(&brand_id, "https://example.com/logo.png"),
+            None
+        );
+    }
+}
@@ -1,10 +1,10 @@
 use chrono::prelude::*;
 use futures::TryStreamExt;
 use serde::Serialize;
-use sqlx::{FromRow, PgPool, Row};
+use sqlx::{FromRow, PgExecutor, PgPool, Row};
 use uuid::Uuid;
 
-use crate::{db::Filters, util::query::QueryParams};
+use crate::{db::Filters, middleware::RequestUser, util::query::QueryParams};
 
 use super::serializer::BrandCreateSerializer;
 
@@ -18,6 +18,10 @@ pub struct Brand {
     pub created_by_id: Uuid,
     pub updated_by_id: Option<Uuid>,
     pub image_url: Option<String>,
+    pub review_status: String,
+    pub review_reason: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub reviewed_by_id: Option<Uuid>,
 }
 
 const ORDERING_FIELDS: &[&str] = &[
@@ -44,13 +48,14 @@ impl Brand {
         pool: &PgPool,
         data: BrandCreateSerializer,
         created_by_id: Uuid,
+        review_status: &str,
     ) -> Result<Self, sqlx::Error> {
         let trimmed_name = data.name.trim();
         let slug = slug::slugify(&trimmed_name);
         let query = sqlx::query_as(
             "
             INSERT INTO
-                food_brand (name, slug, image_url, created_by_id)
+                food_brand (name, slug, created_by_id, review_status)
             VALUES
                 ($1, $2, $3, $4)
             RETURNING
@@ -59,8 +64,8 @@ impl Brand {
         )
         .bind(trimmed_name)
         .bind(slug)
-        .bind(data.image_url)
         .bind(created_by_id)
+        .bind(review_status)
         .fetch_one(pool)
         .await?;
         Ok(query)
@@ -72,8 +77,15 @@ impl Brand {
             .await?;
         Ok(query)
     }
+    pub async fn get_opt(pool: &PgPool, id: &Uuid) -> Result<Option<Self>, sqlx::Error> {
+        let query = sqlx::query_as("SELECT * FROM food_brand WHERE id = $1")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(query)
+    }
     pub async fn update(
-        pool: &PgPool,
+        executor: impl PgExecutor<'_>,
         id: Uuid,
         data: BrandCreateSerializer,
         updated_by_id: Uuid,
@@ -86,29 +98,54 @@ impl Brand {
             SET
                 NAME = $1,
                 slug = $2,
-                image_url = $3,
-                updated_at = $4,
-                updated_by_id = $5
+                updated_at = $3,
+                updated_by_id = $4
             WHERE
-                id = $6
+                id = $5
             RETURNING
                 *
             ",
         )
         .bind(trimmed_name)
         .bind(slug)
-        .bind(data.image_url)
         .bind(Utc::now())
         .bind(updated_by_id)
         .bind(id)
-        .fetch_one(pool)
+        .fetch_one(executor)
+        .await?;
+        Ok(query)
+    }
+    pub async fn set_image_url(
+        executor: impl PgExecutor<'_>,
+        id: &Uuid,
+        image_url: Option<&str>,
+        updated_by_id: &Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let query = sqlx::query_as(
+            "
+            UPDATE food_brand
+            SET
+                image_url = $1,
+                updated_at = $2,
+                updated_by_id = $3
+            WHERE
+                id = $4
+            RETURNING
+                *
+            ",
+        )
+        .bind(image_url)
+        .bind(Utc::now())
+        .bind(updated_by_id)
+        .bind(id)
+        .fetch_one(executor)
         .await?;
         Ok(query)
     }
-    pub async fn delete(pool: &PgPool, id: &Uuid) -> Result<Self, sqlx::Error> {
+    pub async fn delete(executor: impl PgExecutor<'_>, id: &Uuid) -> Result<Self, sqlx::Error> {
         let query = sqlx::query_as("DELETE FROM food_brand WHERE id = $1 RETURNING *")
             .bind(id)
-            .fetch_one(pool)
+            .fetch_one(executor)
             .await?;
         Ok(query)
     }
@@ -120,7 +157,7 @@ impl Brand {
         Ok(query)
     }
     pub async fn delete_id_range(
-        pool: &PgPool,
+        executor: impl PgExecutor<'_>,
         id_range: Vec<Uuid>,
     ) -> Result<Vec<Self>, sqlx::Error> {
         let query = sqlx::query_as(
@@ -133,7 +170,7 @@ impl Brand {
             ",
         )
         .bind(id_range)
-        .fetch_all(pool)
+        .fetch_all(executor)
         .await?;
         Ok(query)
     }
@@ -146,9 +183,9 @@ pub struct BrandFilter {
 }
 
 impl BrandFilter {
-    pub async fn all(pool: &PgPool) -> Result<Vec<Self>, sqlx::Error> {
+    pub async fn all(pool: &PgPool, request_user: &RequestUser) -> Result<Vec<Self>, sqlx::Error> {
         let mut stream = Vec::new();
-        let mut rows = sqlx::query_as(
+        let mut q = sqlx::QueryBuilder::new(
             "
             SELECT
                 t1.slug AS value,
@@ -156,13 +193,13 @@ impl BrandFilter {
             FROM
                 food_brand t1
                 LEFT JOIN food t2 ON t2.brand_id = t1.id
-            GROUP BY
-                t1.id
-            ORDER BY
-                t1.name
+            WHERE
+                TRUE
             ",
-        )
-        .fetch(pool);
+        );
+        q.filter_review_status("t1", &None, request_user);
+        q.push(" GROUP BY t1.id ORDER BY t1.name");
+        let mut rows = q.build_query_as().fetch(pool);
         while let Some(row) = rows.try_next().await? {
             stream.push(row);
         }
@@ -177,20 +214,22 @@ pub struct BrandSelect {
 }
 
 impl BrandSelect {
-    pub async fn all(pool: &PgPool) -> Result<Vec<Self>, sqlx::Error> {
+    pub async fn all(pool: &PgPool, request_user: &RequestUser) -> Result<Vec<Self>, sqlx::Error> {
         let mut stream = Vec::new();
-        let mut rows = sqlx::query_as(
+        let mut q = sqlx::QueryBuilder::new(
             "
             SELECT
-                id,
-                name
+                t1.id,
+                t1.name
             FROM
-                food_brand
-            ORDER BY
-                name
+                food_brand t1
+            WHERE
+                TRUE
             ",
-        )
-        .fetch(pool);
+        );
+        q.filter_review_status("t1", &None, request_user);
+        q.push(" ORDER BY t1.name");
+        let mut rows = q.build_query_as().fetch(pool);
         while let Some(row) = rows.try_next().await? {
             stream.push(row);
         }
@@ -208,19 +247,30 @@ pub struct BrandSerializer {
     pub created_by_id: Uuid,
     pub updated_by_id: Option<Uuid>,
     pub image_url: Option<String>,
+    pub review_status: String,
+    pub review_reason: Option<String>,
     pub food_count: Option<i64>,
     pub created_by: String,
     pub updated_by: Option<String>,
 }
 
 impl BrandSerializer {
-    pub async fn count(pool: &PgPool, query: &QueryParams) -> Result<i64, sqlx::Error> {
+    pub async fn count(
+        pool: &PgPool,
+        query: &QueryParams,
+        request_user: &RequestUser,
+    ) -> Result<i64, sqlx::Error> {
         let mut q = sqlx::QueryBuilder::new("SELECT COUNT(t1.*) FROM food_brand t1 WHERE TRUE");
         q.search_filter("t1.name", query);
+        q.filter_review_status("t1", &query.status, request_user);
         let count = q.build().fetch_one(pool).await?.get("count");
         Ok(count)
     }
-    pub async fn builder(pool: &PgPool, query: &QueryParams) -> Result<Vec<Self>, sqlx::Error> {
+    pub async fn builder(
+        pool: &PgPool,
+        query: &QueryParams,
+        request_user: &RequestUser,
+    ) -> Result<Vec<Self>, sqlx::Error> {
         let mut stream = Vec::new();
         let mut q = sqlx::QueryBuilder::new(
             "
@@ -244,6 +294,7 @@ impl BrandSerializer {
             ",
         );
         q.search_filter("t1.name", query);
+        q.filter_review_status("t1", &query.status, request_user);
 
         q.ordering_filter(query, ORDERING_FIELDS, "name");
 
@@ -1,4 +1,5 @@
 use axum::{
+    extract::DefaultBodyLimit,
     routing::{delete, get, post, put},
     Router,
 };
@@ -6,10 +7,12 @@ use std::sync::Arc;
 
 use crate::AppState;
 
+use super::logo::MAX_LOGO_SIZE;
 use super::view::{
     admin_brand_delete_view, admin_brand_detail_view, admin_brand_update_view, brand_create_view,
     brand_delete_id_range_view, brand_delete_view, brand_detail_view, brand_filter_view,
-    brand_list_view, brand_select_view, brand_update_view, new_brand_list_view,
+    brand_list_view, brand_logo_delete_view, brand_logo_file_view, brand_logo_upload_view,
+    brand_select_view, brand_update_view, new_brand_list_view,
 };
 
 pub fn brand_router() -> Router<Arc<AppState>> {
@@ -22,8 +25,17 @@ pub fn brand_router() -> Router<Arc<AppState>> {
         .route("/admin/:id", get(admin_brand_detail_view))
         .route("/admin/:id", put(admin_brand_update_view))
         .route("/admin/:id", delete(admin_brand_delete_view))
+        .route(
+            "/admin/:id/logo",
+            post(brand_logo_upload_view).layer(DefaultBodyLimit::max(MAX_LOGO_SIZE + 1024 * 64)),
+        )
+        .route("/admin/:id/logo", delete(brand_logo_delete_view))
         .route("/filter", get(brand_filter_view))
         .route("/select", get(brand_select_view))
         .route("/delete-id-range", delete(brand_delete_id_range_view))
         .route("/new", get(new_brand_list_view))
 }
+
+pub fn brand_logo_router() -> Router<Arc<AppState>> {
+    Router::new().route("/:brand_id/:file", get(brand_logo_file_view))
+}
@@ -25,8 +25,6 @@ pub struct BrandCreateSerializer {
         )
     )]
     pub name: String,
-    #[validate(length(min = 3, message = "Minimum of 3 characters"))]
-    pub image_url: Option<String>,
 }
 
 fn validate_brand_name(name: &str) -> Result<(), ValidationError> {
@@ -1,32 +1,60 @@
 use axum::{
-    extract::{Path, Query, State},
+    extract::{Multipart, Path, Query, Request, State},
+    http::{header, HeaderValue},
+    response::IntoResponse,
     Extension, Json,
 };
+use serde::Serialize;
 use serde_json::{json, Value};
+use std::collections::BTreeMap;
 use std::sync::Arc;
+use tower::ServiceExt;
+use tower_http::services::ServeFile;
 use uuid::Uuid;
 
 use crate::{
     error::AppError,
     extractor::{DBJsonExtractor, JsonExtractor},
     middleware::RequestUser,
+    moderation::model::{
+        edit_required, initial_review_status, is_visible, CatalogueTable, Review, REVIEW_APPROVED,
+    },
+    revision::model::set_actor,
+    storage::Storage,
     util::{extract::IdRange, query::QueryParams},
     AppState,
 };
 
 use super::{
+    logo::{delete_logo, discard_logo, process_logo, save_logo, MAX_LOGO_SIZE},
     model::{Brand, BrandFilter, BrandListResponse, BrandSelect, BrandSerializer},
     serializer::BrandCreateSerializer,
 };
 
+async fn brand_update(
+    state: &AppState,
+    request_user: &RequestUser,
+    brand: Brand,
+    data: BrandCreateSerializer,
+) -> Result<Brand, AppError> {
+    edit_required(request_user, &brand.created_by_id, &brand.review_status)?;
+    let mut tx = state.pool.begin().await?;
+    if !request_user.is_moderator() {
+        Review::resubmit(&mut tx, CatalogueTable::Brand, &brand.id).await?;
+    }
+    let query = Brand::update(&mut *tx, brand.id, data, request_user.id).await?;
+    tx.commit().await?;
+    Ok(query)
+}
+
 pub async fn brand_list_view(
     Query(query): Query<QueryParams>,
     State(state): State<Arc<AppState>>,
     Extension(request_user): Extension<RequestUser>,
 ) -> Result<Json<Value>, AppError> {
     request_user.superuser_required()?;
-    let count = BrandSerializer::count(&state.pool, &query).await?;
-    let query = BrandSerializer::builder(&state.pool, &query).await?;
+    let count = BrandSerializer::count(&state.pool, &query, &request_user).await?;
+    let query = BrandSerializer::builder(&state.pool, &query, &request_user).await?;
     let response = json!({"count": count, "results": query});
     Ok(Json(response))
 }
@@ -37,17 +65,20 @@ pub async fn brand_create_view(
     // ExtractSuperuser(request_user): ExtractSuperuser,
     DBJsonExtractor(data): DBJsonExtractor<BrandCreateSerializer>,
 ) -> Result<Json<Brand>, AppError> {
-    request_user.superuser_required()?;
-    let query = Brand::create(&state.pool, data, request_user.id).await?;
+    request_user.login_required()?;
+    let review_status = initial_review_status(&request_user);
+    let query = Brand::create(&state.pool, data, request_user.id, review_status).await?;
     Ok(Json(query))
 }
 
 pub async fn brand_detail_view(
     Path(slug): Path<String>,
     State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
 ) -> Result<Json<Brand>, AppError> {
     let query = Brand::get_from_slug(&state.pool, slug)
         .await?
+        .filter(|brand| is_visible(&request_user, &brand.created_by_id, &brand.review_status))
         .ok_or(AppError::NotFound)?;
     Ok(Json(query))
 }
@@ -58,11 +89,10 @@ pub async fn brand_update_view(
     Extension(request_user): Extension<RequestUser>,
     JsonExtractor(data): JsonExtractor<BrandCreateSerializer>,
 ) -> Result<Json<Brand>, AppError> {
-    request_user.superuser_required()?;
     let query = Brand::get_from_slug(&state.pool, slug)
         .await?
         .ok_or(AppError::NotFound)?;
-    let query = Brand::update(&state.pool, query.id, data, request_user.id).await?;
+    let query = brand_update(&state, &request_user, query, data).await?;
     Ok(Json(query))
 }
 
@@ -71,25 +101,31 @@ pub async fn brand_delete_view(
     State(state): State<Arc<AppState>>,
     Extension(request_user): Extension<RequestUser>,
 ) -> Result<Json<Brand>, AppError> {
-    request_user.superuser_required()?;
     let query = Brand::get_from_slug(&state.pool, slug)
         .await?
         .ok_or(AppError::NotFound)?;
-    let result = Brand::delete(&state.pool, &query.id).await?;
+    edit_required(&request_user, &query.created_by_id, &query.review_status)?;
+    let mut tx = state.pool.begin().await?;
+    set_actor(&mut tx, &request_user.id).await?;
+    let result = Brand::delete(&mut *tx, &query.id).await?;
+    tx.commit().await?;
+    delete_logo(&*state.brand_logos, &result.id, &result.image_url).await?;
     Ok(Json(result))
 }
 
 pub async fn brand_filter_view(
     State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
 ) -> Result<Json<Vec<BrandFilter>>, AppError> {
-    let query = BrandFilter::all(&state.pool).await?;
+    let query = BrandFilter::all(&state.pool, &request_user).await?;
     Ok(Json(query))
 }
 
 pub async fn brand_select_view(
     State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
 ) -> Result<Json<Vec<BrandSelect>>, AppError> {
-    let query = BrandSelect::all(&state.pool).await?;
+    let query = BrandSelect::all(&state.pool, &request_user).await?;
     Ok(Json(query))
 }
 
@@ -98,8 +134,11 @@ pub async fn brand_delete_id_range_view(
     Extension(request_user): Extension<RequestUser>,
     JsonExtractor(data): JsonExtractor<IdRange>,
 ) -> Result<Json<Vec<Brand>>, AppError> {
-    request_user.superuser_required()?;
-    let query = Brand::delete_id_range(&state.pool, data.id_range).await?;
+    request_user.staff_required()?;
+    let mut tx = state.pool.begin().await?;
+    set_actor(&mut tx, &request_user.id).await?;
+    let query = Brand::delete_id_range(&mut *tx, data.id_range).await?;
+    tx.commit().await?;
     Ok(Json(query))
 }
 
@@ -117,9 +156,8 @@ pub async fn admin_brand_update_view(
     Extension(request_user): Extension<RequestUser>,
     JsonExtractor(data): JsonExtractor<BrandCreateSerializer>,
 ) -> Result<Json<Brand>, AppError> {
-    request_user.superuser_required()?;
     let query = Brand::get(&state.pool, &id).await?;
-    let query = Brand::update(&state.pool, query.id, data, request_user.id).await?;
+    let query = brand_update(&state, &request_user, query, data).await?;
     Ok(Json(query))
 }
 
@@ -128,20 +166,129 @@ pub async fn admin_brand_delete_view(
     State(state): State<Arc<AppState>>,
     Extension(request_user): Extension<RequestUser>,
 ) -> Result<Json<Brand>, AppError> {
-    request_user.superuser_required()?;
     let query = Brand::get(&state.pool, &id).await?;
-    let query = Brand::delete(&state.pool, &query.id).await?;
+    edit_required(&request_user, &query.created_by_id, &query.review_status)?;
+    let mut tx = state.pool.begin().await?;
+    set_actor(&mut tx, &request_user.id).await?;
+    let query = Brand::delete(&mut *tx, &query.id).await?;
+    tx.commit().await?;
+    delete_logo(&*state.brand_logos, &query.id, &query.image_url).await?;
     Ok(Json(query))
 }
 
 pub async fn new_brand_list_view(
     Query(query): Query<QueryParams>,
     State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
 ) -> Result<Json<BrandListResponse>, AppError> {
-    let count = BrandSerializer::count(&state.pool, &query).await?;
-    let results = BrandSerializer::builder(&state.pool, &query).await?;
+    let count = BrandSerializer::count(&state.pool, &query, &request_user).await?;
+    let results = BrandSerializer::builder(&state.pool, &query, &request_user).await?;
     // dbg!(&query);
     // let response = json!({ "count": count, "results": results});
     let list_response = BrandListResponse { count, results };
     Ok(Json(list_response))
 }
+
+#[derive(Debug, Serialize)]
+pub struct BrandLogoResponse {
+    pub brand: Brand,
+    pub image_urls: BTreeMap<u32, String>,
+}
+
+async fn set_logo(
+    state: &AppState,
+    request_user: &RequestUser,
+    brand_id: &Uuid,
+    image_urls: &BTreeMap<u32, String>,
+) -> Result<Brand, AppError> {
+    let mut tx = state.pool.begin().await?;
+    if !request_user.is_moderator() {
+        Review::resubmit(&mut tx, CatalogueTable::Brand, brand_id).await?;
+    }
+    let image_url = image_urls.values().last().map(String::as_str);
+    let query = Brand::set_image_url(&mut *tx, brand_id, image_url, &request_user.id).await?;
+    tx.commit().await?;
+    Ok(query)
+}
+
+pub async fn brand_logo_upload_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    mut multipart: Multipart,
+) -> Result<Json<BrandLogoResponse>, AppError> {
+    let brand = Brand::get(&state.pool, &id).await?;
+    edit_required(&request_user, &brand.created_by_id, &brand.review_status)?;
+
+    let mut data = None;
+    while let Some(field) = multipart.next_field().await? {
+        if field.name() == Some("image") {
+            data = Some(field.bytes().await?);
+            break;
+        }
+    }
+    let data = data.ok_or(AppError::BadRequestMessage(String::from(
+        "An image file is required",
+    )))?;
+    if data.len() > MAX_LOGO_SIZE {
+        return Err(AppError::BadRequestMessage(String::from(
+            "Image must be 5MB or smaller",
+        )));
+    }
+
+    let logo = tokio::task::spawn_blocking(move || process_logo(&data)).await??;
+    let image_urls = save_logo(&*state.brand_logos, &brand.id, logo).await?;
+    let query = match set_logo(&state, &request_user, &brand.id, &image_urls).await {
+        Ok(query) => query,
+        Err(err) => {
+            discard_logo(&*state.brand_logos, &brand.id, &image_urls).await;
+            return Err(err);
+        }
+    };
+    delete_logo(&*state.brand_logos, &brand.id, &brand.image_url).await?;
+    Ok(Json(BrandLogoResponse {
+        brand: query,
+        image_urls,
+    }))
+}
+
+pub async fn brand_logo_delete_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<Brand>, AppError> {
+    let brand = Brand::get(&state.pool, &id).await?;
+    edit_required(&request_user, &brand.created_by_id, &brand.review_status)?;
+    let query = Brand::set_image_url(&state.pool, &brand.id, None, &request_user.id).await?;
+    delete_logo(&*state.brand_logos, &brand.id, &brand.image_url).await?;
+    Ok(Json(query))
+}
+
+// Logos are only public once the brand is approved; until then only the creator
+// and moderators can load them, like the brand itself. ServeFile handles
+// conditional and range requests.
+pub async fn brand_logo_file_view(
+    Path((brand_id, file)): Path<(Uuid, String)>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    request: Request,
+) -> Result<impl IntoResponse, AppError> {
+    let brand = Brand::get_opt(&state.pool, &brand_id)
+        .await?
+        .filter(|brand| is_visible(&request_user, &brand.created_by_id, &brand.review_status))
+        .ok_or(AppError::NotFound)?;
+    let path = state
+        .brand_logos
+        .path(&format!("{}/{}", brand.id, file))
+        .map_err(|_| AppError::NotFound)?;
+    let Ok(mut response) = ServeFile::new(path).oneshot(request).await;
+    let cache_control = match brand.review_status.as_str() {
+        REVIEW_APPROVED => "public, max-age=86400",
+        _ => "private, no-store",
+    };
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static(cache_control),
+    );
+    Ok(response)
+}
@@ -0,0 +1,424 @@
+use futures::TryStreamExt;
+use serde::{
+    de::{MapAccess, Visitor},
+    ser::SerializeMap,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use serde_json::Value;
+use sqlx::PgPool;
+use std::{fmt, io::Write};
+use uuid::Uuid;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::{error::AppError, storage::Storage};
+
+use super::model::{DataExport, EXPORT_HEARTBEAT_SECS};
+
+pub const ARCHIVE_CONTENT_TYPE: &str = "application/zip";
+
+// Every query is bound to the exporting user's id as $1.
+const EXPORT_TABLES: &[(&str, &str)] = &[
+    (
+        "user",
+        "
+        SELECT
+            id, name, username, email, email_verified, privacy_level, is_active, is_staff,
+            is_superuser, last_login, created_at, updated_at
+        FROM users_user
+        WHERE id = $1
+        ",
+    ),
+    (
+        "user_profile",
+        "SELECT * FROM user_profile WHERE user_id = $1",
+    ),
+    (
+        "user_meal_of_day",
+        "SELECT * FROM user_meal_of_day WHERE user_id = $1 ORDER BY created_at",
+    ),
+    (
+        "follower",
+        "
+        SELECT
+            t1.id, t2.username, t3.username AS follower_username, t1.status, t1.created_at,
+            t1.updated_at
+        FROM user_follower t1
+            LEFT JOIN users_user t2 ON t2.id = t1.user_id
+            LEFT JOIN users_user t3 ON t3.id = t1.follower_id
+        WHERE t1.user_id = $1 OR t1.follower_id = $1
+        ORDER BY t1.created_at
+        ",
+    ),
+    (
+        "food_log",
+        "
+        SELECT
+            t1.*, t2.name AS food_name, t3.name AS brand_name, t4.name AS meal_of_day_name
+        FROM food_log t1
+            LEFT JOIN food t2 ON t2.id = t1.food_id
+            LEFT JOIN food_brand t3 ON t3.id = t2.brand_id
+            LEFT JOIN meal_of_day t4 ON t4.id = t1.meal_of_day_id
+        WHERE t1.user_id = $1
+        ORDER BY t1.date, t1.created_at
+        ",
+    ),
+    (
+        "food_favourite",
+        "
+        SELECT t1.*, t2.name AS food_name, t3.name AS meal_of_day_name
+        FROM food_favourite t1
+            LEFT JOIN food t2 ON t2.id = t1.food_id
+            LEFT JOIN meal_of_day t3 ON t3.id = t1.meal_of_day_id
+        WHERE t1.user_id = $1
+        ORDER BY t1.created_at
+        ",
+    ),
+    (
+        "planned_food",
+        "
+        SELECT
+            t1.*, t2.name AS food_name, t3.name AS brand_name, t4.name AS meal_of_day_name
+        FROM planned_food t1
+            LEFT JOIN food t2 ON t2.id = t1.food_id
+            LEFT JOIN food_brand t3 ON t3.id = t2.brand_id
+            LEFT JOIN meal_of_day t4 ON t4.id = t1.meal_of_day_id
+        WHERE t1.user_id = $1
+        ORDER BY t1.date, t1.created_at
+        ",
+    ),
+    (
+        "diet_target",
+        "SELECT * FROM diet_target WHERE user_id = $1 ORDER BY date",
+    ),
+    (
+        "water_log",
+        "SELECT * FROM water_log WHERE user_id = $1 ORDER BY date, time",
+    ),
+    (
+        "eating_window",
+        "SELECT * FROM eating_window WHERE user_id = $1",
+    ),
+    (
+        "fast",
+        "SELECT * FROM fast WHERE user_id = $1 ORDER BY started_at",
+    ),
+    (
+        "progress",
+        "SELECT * FROM progress WHERE user_id = $1 ORDER BY date",
+    ),
+    (
+        "progress_photo",
+        "SELECT * FROM progress_photo WHERE user_id = $1 ORDER BY created_at",
+    ),
+    (
+        "meal",
+        "SELECT * FROM meal WHERE user_id = $1 ORDER BY created_at",
+    ),
+    (
+        "meal_food",
+        "
+        SELECT t1.*, t3.name AS food_name
+        FROM meal_food t1
+            JOIN meal t2 ON t2.id = t1.meal_id
+            LEFT JOIN food t3 ON t3.id = t1.food_id
+        WHERE t2.user_id = $1
+        ORDER BY t1.created_at
+        ",
+    ),
+    (
+        "workout",
+        "SELECT * FROM workout WHERE user_id = $1 ORDER BY date",
+    ),
+    (
+        "exercise",
+        "
+        SELECT t1.*, t3.name AS movement_name
+        FROM exercise t1
+            JOIN workout t2 ON t2.id = t1.workout_id
+            LEFT JOIN movement t3 ON t3.id = t1.movement_id
+        WHERE t2.user_id = $1
+        ORDER BY t2.date, t1.order
+        ",
+    ),
+    (
+        "tracked_set",
+        "
+        SELECT t1.*
+        FROM tracked_set t1
+            JOIN exercise t2 ON t2.id = t1.exercise_id
+            JOIN workout t3 ON t3.id = t2.workout_id
+        WHERE t3.user_id = $1
+        ORDER BY t3.date, t2.order, t1.order
+        ",
+    ),
+    (
+        "personal_record",
+        "
+        SELECT t1.*, t2.date, t3.name AS movement_name
+        FROM personal_record t1
+            JOIN workout t2 ON t2.id = t1.workout_id
+            LEFT JOIN movement t3 ON t3.id = t1.movement_id
+        WHERE t1.user_id = $1
+        ORDER BY t2.date, t1.created_at
+        ",
+    ),
+    (
+        "training_plan",
+        "SELECT * FROM training_plan WHERE user_id = $1 ORDER BY created_at",
+    ),
+    (
+        "workout_plan",
+        "
+        SELECT t1.*
+        FROM workout_plan t1
+            JOIN training_plan t2 ON t2.id = t1.training_plan_id
+        WHERE t2.user_id = $1
+        ORDER BY t1.order
+        ",
+    ),
+    (
+        "exercise_plan",
+        "
+        SELECT t1.*
+        FROM exercise_plan t1
+            JOIN workout_plan t2 ON t2.id = t1.workout_plan_id
+            JOIN training_plan t3 ON t3.id = t2.training_plan_id
+        WHERE t3.user_id = $1
+        ORDER BY t1.order
+        ",
+    ),
+    (
+        "set_plan",
+        "
+        SELECT t1.*
+        FROM set_plan t1
+            JOIN exercise_plan t2 ON t2.id = t1.exercise_plan_id
+            JOIN workout_plan t3 ON t3.id = t2.workout_plan_id
+            JOIN training_plan t4 ON t4.id = t3.training_plan_id
+        WHERE t4.user_id = $1
+        ORDER BY t1.order
+        ",
+    ),
+    (
+        "food_brand",
+        "SELECT * FROM food_brand WHERE created_by_id = $1 ORDER BY created_at",
+    ),
+    (
+        "food",
+        "SELECT * FROM food WHERE created_by_id = $1 ORDER BY created_at",
+    ),
+];
+
+// A single exported row, keeping the column order of the query so that the CSV
+// headers line up with the JSON output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportRow(pub Vec<(String, Value)>);
+
+impl<'de> Deserialize<'de> for ExportRow {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct RowVisitor;
+
+        impl<'de> Visitor<'de> for RowVisitor {
+            type Value = ExportRow;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a JSON object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<ExportRow, A::Error> {
+                let mut columns = Vec::new();
+                while let Some(column) = map.next_entry()? {
+                    columns.push(column);
+                }
+                Ok(ExportRow(columns))
+            }
+        }
+
+        deserializer.deserialize_map(RowVisitor)
+    }
+}
+
+impl Serialize for ExportRow {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (column, value) in &self.0 {
+            map.serialize_entry(column, value)?;
+        }
+        map.end()
+    }
+}
+
+#[derive(Debug)]
+pub struct ExportTable {
+    pub name: &'static str,
+    pub rows: Vec<ExportRow>,
+}
+
+struct ExportTables<'a>(&'a [ExportTable]);
+
+impl Serialize for ExportTables<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for table in self.0 {
+            map.serialize_entry(table.name, &table.rows)?;
+        }
+        map.end()
+    }
+}
+
+fn csv_cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+pub fn rows_to_csv(rows: &[ExportRow]) -> Result<Vec<u8>, csv::Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    if let Some(first) = rows.first() {
+        writer.write_record(first.0.iter().map(|(column, _)| column))?;
+    }
+    for row in rows {
+        writer.write_record(row.0.iter().map(|(_, value)| csv_cell(value)))?;
+    }
+    writer
+        .into_inner()
+        .map_err(|err| csv::Error::from(err.into_error()))
+}
+
+pub fn build_archive(tables: &[ExportTable]) -> Result<Vec<u8>, AppError> {
+    let mut zip = ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    zip.start_file("data.json", options)?;
+    serde_json::to_writer_pretty(&mut zip, &ExportTables(tables))?;
+    for table in tables {
+        let csv = rows_to_csv(&table.rows)?;
+        zip.start_file(format!("csv/{}.csv", table.name), options)?;
+        zip.write_all(&csv)?;
+    }
+    let cursor = zip.finish()?;
+    Ok(cursor.into_inner())
+}
+
+pub async fn collect_tables(pool: &PgPool, user_id: &Uuid) -> Result<Vec<ExportTable>, AppError> {
+    let mut tables = Vec::new();
+    for (name, sql) in EXPORT_TABLES {
+        let mut rows = Vec::new();
+        let query = format!("SELECT ROW_TO_JSON(t)::TEXT FROM ({sql}) t");
+        let mut stream = sqlx::query_scalar::<_, String>(&query)
+            .bind(user_id)
+            .fetch(pool);
+        while let Some(row) = stream.try_next().await? {
+            rows.push(serde_json::from_str(&row)?);
+        }
+        tables.push(ExportTable { name, rows });
+    }
+    Ok(tables)
+}
+
+async fn generate(
+    pool: &PgPool,
+    storage: &dyn Storage,
+    export: &DataExport,
+) -> Result<(), AppError> {
+    DataExport::set_running(pool, &export.id).await?;
+    let tables = collect_tables(pool, &export.user_id).await?;
+    let archive = tokio::task::spawn_blocking(move || build_archive(&tables)).await??;
+    let file_key = format!("exports/{}/{}.zip", export.user_id, export.id);
+    let file_size = archive.len() as i32;
+    storage
+        .put(&file_key, ARCHIVE_CONTENT_TYPE, archive)
+        .await?;
+    DataExport::set_complete(pool, &export.id, &file_key, file_size).await?;
+    Ok(())
+}
+
+async fn keep_alive(pool: PgPool, id: Uuid) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(EXPORT_HEARTBEAT_SECS));
+    loop {
+        interval.tick().await;
+        if let Err(err) = DataExport::heartbeat(&pool, &id).await {
+            tracing::warn!("could not update data export {} heartbeat: {:?}", id, err);
+        }
+    }
+}
+
+pub async fn run_export(pool: PgPool, storage: std::sync::Arc<dyn Storage>, export: DataExport) {
+    let heartbeat = tokio::spawn(keep_alive(pool.clone(), export.id));
+    let result = generate(&pool, storage.as_ref(), &export).await;
+    heartbeat.abort();
+    if let Err(err) = result {
+        tracing::error!("data export {} failed: {:?}", export.id, err);
+        if let Err(err) =
+            DataExport::set_failed(&pool, &export.id, "Export could not be generated").await
+        {
+            tracing::error!("could not mark data export {} failed: {:?}", export.id, err);
+        }
+    }
+}
+
+// Expired archives are removed whenever the user looks at or requests exports, so
+// storage is not left holding files that can no longer be downloaded.
+pub async fn remove_expired(
+    pool: &PgPool,
+    storage: &dyn Storage,
+    user_id: &Uuid,
+) -> Result<(), AppError> {
+    for key in DataExport::clear_expired(pool, user_id).await? {
+        if let Err(err) = storage.delete(&key).await {
+            tracing::warn!("could not delete stored file {}: {:?}", key, err);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Read;
+
+    fn row(value: Value) -> ExportRow {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_export_row_keeps_column_order() {
+        let row: ExportRow = serde_json::from_str(r#"{"b": 1, "a": 2}"#).unwrap();
+        assert_eq!(row.0[0].0, "b");
+        assert_eq!(serde_json::to_string(&row).unwrap(), r#"{"b":1,"a":2}"#);
+    }
+
+    #[test]
+    fn test_rows_to_csv() {
+        let rows = vec![
+            row(json!({"name": "Oats, rolled", "energy": 370, "notes": null})),
+            row(json!({"name": "Milk", "energy": 64, "notes": "semi"})),
+        ];
+        let csv = String::from_utf8(rows_to_csv(&rows).unwrap()).unwrap();
+        assert_eq!(
+            csv,
+            "energy,name,notes\n370,\"Oats, rolled\",\n64,Milk,semi\n"
+        );
+    }
+
+    #[test]
+    fn test_build_archive_contains_json_and_csv() {
+        let tables = vec![ExportTable {
+            name: "food_log",
+            rows: vec![row(json!({"id": 1}))],
+        }];
+        let archive = build_archive(&tables).unwrap();
+        let mut zip = zip::ZipArchive::new(std::io::Cursor::new(archive)).unwrap();
+        let mut data = String::new();
+        zip.by_name("data.json")
+            .unwrap()
+            .read_to_string(&mut data)
+            .unwrap();
+        let data: Value = serde_json::from_str(&data).unwrap();
+        assert_eq!(data["food_log"][0]["id"], 1);
+        assert!(zip.by_name("csv/food_log.csv").is_ok());
+    }
+}
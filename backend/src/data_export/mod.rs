@@ -0,0 +1,4 @@
+pub mod archive;
+pub mod model;
+pub mod router;
+pub mod view;
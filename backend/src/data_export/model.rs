@@ -0,0 +1,205 @@
+use chrono::{prelude::*, Duration};
+use futures::TryStreamExt;
+use serde::Serialize;
+use sqlx::{FromRow, PgPool, Row};
+use uuid::Uuid;
+
+pub const EXPORT_EXPIRY_DAYS: i64 = 7;
+pub const EXPORT_HEARTBEAT_SECS: u64 = 30;
+// An export whose worker has not been heard from for this long is taken to be dead.
+const EXPORT_STALE_SECS: i64 = 5 * 60;
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct DataExport {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub status: String,
+    #[serde(skip_serializing)]
+    pub file_key: Option<String>,
+    pub file_size: Option<i32>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing)]
+    pub heartbeat_at: Option<DateTime<Utc>>,
+}
+
+impl DataExport {
+    pub async fn all_by_user_id(pool: &PgPool, user_id: &Uuid) -> Result<Vec<Self>, sqlx::Error> {
+        let mut stream = Vec::new();
+        let mut rows =
+            sqlx::query_as("SELECT * FROM data_export WHERE user_id = $1 ORDER BY created_at DESC")
+                .bind(user_id)
+                .fetch(pool);
+        while let Some(row) = rows.try_next().await? {
+            stream.push(row);
+        }
+        Ok(stream)
+    }
+    pub async fn get(pool: &PgPool, id: &Uuid) -> Result<Option<Self>, sqlx::Error> {
+        let query = sqlx::query_as("SELECT * FROM data_export WHERE id = $1")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(query)
+    }
+    pub async fn get_in_progress(
+        pool: &PgPool,
+        user_id: &Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let query = sqlx::query_as(
+            "SELECT * FROM data_export WHERE user_id = $1 AND status IN ('pending', 'running')",
+        )
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+        Ok(query)
+    }
+    pub async fn create(pool: &PgPool, user_id: Uuid) -> Result<Self, sqlx::Error> {
+        let query = sqlx::query_as("INSERT INTO data_export (user_id) VALUES ($1) RETURNING *")
+            .bind(user_id)
+            .fetch_one(pool)
+            .await?;
+        Ok(query)
+    }
+    pub async fn set_running(pool: &PgPool, id: &Uuid) -> Result<Self, sqlx::Error> {
+        let query = sqlx::query_as(
+            "
+            UPDATE data_export
+            SET
+                status = 'running',
+                heartbeat_at = $1
+            WHERE
+                id = $2
+            RETURNING
+                *
+            ",
+        )
+        .bind(Utc::now())
+        .bind(id)
+        .fetch_one(pool)
+        .await?;
+        Ok(query)
+    }
+    pub async fn heartbeat(pool: &PgPool, id: &Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE data_export SET heartbeat_at = $1 WHERE id = $2")
+            .bind(Utc::now())
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+    pub async fn set_complete(
+        pool: &PgPool,
+        id: &Uuid,
+        file_key: &str,
+        file_size: i32,
+    ) -> Result<Self, sqlx::Error> {
+        let now = Utc::now();
+        let query = sqlx::query_as(
+            "
+            UPDATE data_export
+            SET
+                status = 'complete',
+                file_key = $1,
+                file_size = $2,
+                completed_at = $3,
+                expires_at = $4
+            WHERE
+                id = $5
+            RETURNING
+                *
+            ",
+        )
+        .bind(file_key)
+        .bind(file_size)
+        .bind(now)
+        .bind(now + Duration::days(EXPORT_EXPIRY_DAYS))
+        .bind(id)
+        .fetch_one(pool)
+        .await?;
+        Ok(query)
+    }
+    pub async fn set_failed(pool: &PgPool, id: &Uuid, error: &str) -> Result<Self, sqlx::Error> {
+        let query = sqlx::query_as(
+            "
+            UPDATE data_export
+            SET
+                status = 'failed',
+                error = $1,
+                completed_at = $2
+            WHERE
+                id = $3
+            RETURNING
+                *
+            ",
+        )
+        .bind(error)
+        .bind(Utc::now())
+        .bind(id)
+        .fetch_one(pool)
+        .await?;
+        Ok(query)
+    }
+    // Exports interrupted by a restart would otherwise stay in progress forever. Those
+    // still being built, possibly by another instance, keep their heartbeat fresh.
+    pub async fn fail_stale(pool: &PgPool) -> Result<u64, sqlx::Error> {
+        let query = sqlx::query(
+            "
+            UPDATE data_export
+            SET
+                status = 'failed',
+                error = 'Export was interrupted',
+                completed_at = CURRENT_TIMESTAMP
+            WHERE
+                status IN ('pending', 'running')
+                AND COALESCE(heartbeat_at, created_at) < $1
+            ",
+        )
+        .bind(Utc::now() - Duration::seconds(EXPORT_STALE_SECS))
+        .execute(pool)
+        .await?;
+        Ok(query.rows_affected())
+    }
+    // Forgets the archives of the user's expired exports, returning their keys so the
+    // files can be removed. The rows stay so the export history is kept.
+    pub async fn clear_expired(pool: &PgPool, user_id: &Uuid) -> Result<Vec<String>, sqlx::Error> {
+        let query = sqlx::query(
+            "
+            UPDATE data_export t1
+            SET
+                file_key = NULL
+            FROM (
+                SELECT id, file_key FROM data_export
+                WHERE user_id = $1 AND file_key IS NOT NULL AND expires_at < $2
+                FOR UPDATE
+            ) t2
+            WHERE
+                t1.id = t2.id
+            RETURNING
+                t2.file_key
+            ",
+        )
+        .bind(user_id)
+        .bind(Utc::now())
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|row| row.get("file_key"))
+        .collect();
+        Ok(query)
+    }
+    pub async fn delete(pool: &PgPool, id: &Uuid) -> Result<Self, sqlx::Error> {
+        let query = sqlx::query_as("DELETE FROM data_export WHERE id = $1 RETURNING *")
+            .bind(id)
+            .fetch_one(pool)
+            .await?;
+        Ok(query)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at < Utc::now())
+    }
+}
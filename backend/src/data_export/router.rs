@@ -0,0 +1,20 @@
+use axum::{
+    routing::{delete, get, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::data_export::view::{
+    data_export_create_view, data_export_delete_view, data_export_detail_view,
+    data_export_download_view, data_export_list_view,
+};
+use crate::AppState;
+
+pub fn data_export_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(data_export_list_view))
+        .route("/", post(data_export_create_view))
+        .route("/:id", get(data_export_detail_view))
+        .route("/:id", delete(data_export_delete_view))
+        .route("/:id/download", get(data_export_download_view))
+}
@@ -0,0 +1,114 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Extension, Json,
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{error::AppError, middleware::RequestUser, AppState};
+
+use super::{
+    archive::{remove_expired, run_export, ARCHIVE_CONTENT_TYPE},
+    model::DataExport,
+};
+
+async fn get_own_export(
+    state: &AppState,
+    request_user: &RequestUser,
+    id: &Uuid,
+) -> Result<DataExport, AppError> {
+    request_user.login_required()?;
+    let export = DataExport::get(&state.pool, id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    if export.user_id != request_user.id {
+        return Err(AppError::NotFound);
+    }
+    Ok(export)
+}
+
+pub async fn data_export_create_view(
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<(StatusCode, Json<DataExport>), AppError> {
+    request_user.login_required()?;
+    remove_expired(&state.pool, state.storage.as_ref(), &request_user.id).await?;
+    DataExport::fail_stale(&state.pool).await?;
+    if let Some(export) = DataExport::get_in_progress(&state.pool, &request_user.id).await? {
+        return Ok((StatusCode::ACCEPTED, Json(export)));
+    }
+    let export = DataExport::create(&state.pool, request_user.id).await?;
+    tokio::spawn(run_export(
+        state.pool.clone(),
+        state.storage.clone(),
+        export.clone(),
+    ));
+    Ok((StatusCode::ACCEPTED, Json(export)))
+}
+
+pub async fn data_export_list_view(
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<Value>, AppError> {
+    request_user.login_required()?;
+    remove_expired(&state.pool, state.storage.as_ref(), &request_user.id).await?;
+    let query = DataExport::all_by_user_id(&state.pool, &request_user.id).await?;
+    let response = json!({"count": query.len(), "results": query});
+    Ok(Json(response))
+}
+
+pub async fn data_export_detail_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<DataExport>, AppError> {
+    let query = get_own_export(&state, &request_user, &id).await?;
+    Ok(Json(query))
+}
+
+pub async fn data_export_download_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<impl IntoResponse, AppError> {
+    let export = get_own_export(&state, &request_user, &id).await?;
+    let file_key = match (&export.file_key, export.is_expired()) {
+        (Some(file_key), false) => file_key,
+        _ => return Err(AppError::NotFound),
+    };
+    let data = state.storage.get(file_key).await?;
+    let filename = format!(
+        "trackedfitness-export-{}.zip",
+        export.created_at.format("%Y%m%d")
+    );
+    let headers = [
+        (header::CONTENT_TYPE, String::from(ARCHIVE_CONTENT_TYPE)),
+        (
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}\"", filename),
+        ),
+        (header::CACHE_CONTROL, String::from("private, no-store")),
+    ];
+    Ok((headers, data))
+}
+
+pub async fn data_export_delete_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<DataExport>, AppError> {
+    let export = get_own_export(&state, &request_user, &id).await?;
+    if export.status == "running" || export.status == "pending" {
+        return Err(AppError::BadRequestMessage(String::from(
+            "An export cannot be deleted while it is being generated.",
+        )));
+    }
+    let query = DataExport::delete(&state.pool, &export.id).await?;
+    if let Some(file_key) = &query.file_key {
+        state.storage.delete(file_key).await?;
+    }
+    Ok(Json(query))
+}
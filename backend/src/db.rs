@@ -2,7 +2,7 @@ use chrono::NaiveDate;
 use sqlx::{Postgres, QueryBuilder};
 use uuid::Uuid;
 
-use crate::util::query::QueryParams;
+use crate::{middleware::RequestUser, util::query::QueryParams};
 
 pub trait Filters {
     fn filter_date(
@@ -20,6 +20,13 @@ pub trait Filters {
 
     fn search_filter(&mut self, column: &'static str, query: &QueryParams) -> &mut Self;
 
+    fn filter_review_status(
+        &mut self,
+        table: &'static str,
+        query: &Option<String>,
+        request_user: &RequestUser,
+    ) -> &mut Self;
+
     fn ordering_filter(
         &mut self,
         query: &QueryParams,
@@ -81,6 +88,27 @@ impl Filters for QueryBuilder<'static, Postgres> {
         }
         self
     }
+    // Unapproved catalogue rows are only visible to moderators and their creator.
+    fn filter_review_status(
+        &mut self,
+        table: &'static str,
+        query: &Option<String>,
+        request_user: &RequestUser,
+    ) -> &mut Self {
+        if !request_user.is_moderator() {
+            self.push(format!(" AND ({table}.review_status = 'approved'"));
+            if request_user.is_authenticated {
+                self.push(format!(" OR {table}.created_by_id = "));
+                self.push_bind(request_user.id);
+            }
+            self.push(")");
+        }
+        if let Some(query) = query {
+            self.push(format!(" AND {table}.review_status = "));
+            self.push_bind(query.clone());
+        }
+        self
+    }
 
     fn ordering_filter(
         &mut self,
@@ -14,6 +14,7 @@ pub struct DietFoodJSON {
     pub diet_id: Option<Uuid>,
     pub food_name: Option<String>,
     pub brand_name: Option<String>,
+    pub eaten_at: Option<DateTime<Utc>>,
     pub data_value: Option<Decimal>,
     pub data_measurement: Option<String>,
     pub energy: Option<Decimal>,
@@ -24,6 +25,7 @@ pub struct DietFoodJSON {
     pub sugars: Option<Decimal>,
     pub fibre: Option<Decimal>,
     pub salt: Option<Decimal>,
+    pub nutri_grade: Option<String>,
 }
 
 #[derive(Debug, Default, Deserialize, Serialize, FromRow)]
@@ -65,35 +67,82 @@ pub struct DietDayJSON {
     pub protein_per_kg: Option<Decimal>,
     pub carbohydrate_per_kg: Option<Decimal>,
     pub fat_per_kg: Option<Decimal>,
+    pub water_ml: i64,
+    pub target_water_ml: Option<i32>,
+    pub nutri_score: Option<Decimal>,
+    pub nutri_grade: Option<String>,
     pub diet_meals: sqlx::types::Json<Vec<DietMealJSON>>,
 }
 
 impl DietDayJSON {
+    // Days are those with food or water logged, so a day with only drinks still shows.
     pub async fn all(pool: &PgPool, query: QueryParams) -> Result<Vec<Self>, sqlx::Error> {
         let mut stream = Vec::new();
         let mut q = sqlx::QueryBuilder::new(
             "
             SELECT
-            t1.user_id,
-            t1.date,
+            t0.user_id,
+            t0.date,
             t3.username,
-            SUM(t1.quantity * t2.energy) AS energy,
-            SUM(t1.quantity * t2.protein) AS protein,
-            SUM(t1.quantity * t2.carbohydrate) AS carbohydrate,
-            SUM(t1.quantity * t2.fat) AS fat,
-            SUM(t1.quantity * t2.saturates) AS saturates,
-            SUM(t1.quantity * t2.sugars) AS sugars,
-            SUM(t1.quantity * t2.fibre) AS fibre,
-            SUM(t1.quantity * t2.salt) AS salt,
-            SUM(t1.quantity * t2.protein * 4) / SUM(t1.quantity * t2.energy) * 100 AS protein_pct,
-            SUM(t1.quantity * t2.carbohydrate * 4) / SUM(t1.quantity * t2.energy) * 100 AS carbohydrate_pct,
-            SUM(t1.quantity * t2.fat * 9) / SUM(t1.quantity * t2.energy) * 100 AS fat_pct,
-            SUM(t1.quantity * t2.energy) / p1.weight_kg AS energy_per_kg,
-            SUM(t1.quantity * t2.protein) / p1.weight_kg AS protein_per_kg,
-            SUM(t1.quantity * t2.carbohydrate) / p1.weight_kg AS carbohydrate_per_kg,
-            SUM(t1.quantity * t2.fat) / p1.weight_kg AS fat_per_kg,
+            COALESCE(SUM(t1.quantity * COALESCE(t2.energy, t1.energy)), 0) AS energy,
+            COALESCE(SUM(t1.quantity * COALESCE(t2.protein, t1.protein)), 0) AS protein,
+            COALESCE(SUM(t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate)), 0) AS carbohydrate,
+            COALESCE(SUM(t1.quantity * COALESCE(t2.fat, t1.fat)), 0) AS fat,
+            COALESCE(SUM(t1.quantity * COALESCE(t2.saturates, t1.saturates)), 0) AS saturates,
+            COALESCE(SUM(t1.quantity * COALESCE(t2.sugars, t1.sugars)), 0) AS sugars,
+            COALESCE(SUM(t1.quantity * COALESCE(t2.fibre, t1.fibre)), 0) AS fibre,
+            COALESCE(SUM(t1.quantity * COALESCE(t2.salt, t1.salt)), 0) AS salt,
+            COALESCE(SUM(t1.quantity * COALESCE(t2.protein, t1.protein) * 4) / NULLIF(SUM(t1.quantity * COALESCE(t2.energy, t1.energy)), 0) * 100, 0) AS protein_pct,
+            COALESCE(SUM(t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate) * 4) / NULLIF(SUM(t1.quantity * COALESCE(t2.energy, t1.energy)), 0) * 100, 0) AS carbohydrate_pct,
+            COALESCE(SUM(t1.quantity * COALESCE(t2.fat, t1.fat) * 9) / NULLIF(SUM(t1.quantity * COALESCE(t2.energy, t1.energy)), 0) * 100, 0) AS fat_pct,
+            COALESCE(SUM(t1.quantity * COALESCE(t2.energy, t1.energy)), 0) / p1.weight_kg AS energy_per_kg,
+            COALESCE(SUM(t1.quantity * COALESCE(t2.protein, t1.protein)), 0) / p1.weight_kg AS protein_per_kg,
+            COALESCE(SUM(t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate)), 0) / p1.weight_kg AS carbohydrate_per_kg,
+            COALESCE(SUM(t1.quantity * COALESCE(t2.fat, t1.fat)), 0) / p1.weight_kg AS fat_per_kg,
             p1.weight_kg as latest_weight,
             p1.date as latest_weight_date,
+            ROUND(
+                SUM(t1.quantity * COALESCE(t2.energy, t1.energy) * t2.nutri_score) / NULLIF(
+                    SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) FILTER (
+                        WHERE
+                            t2.nutri_score IS NOT NULL
+                    ),
+                    0
+                ),
+                1
+            ) AS nutri_score,
+            nutri_score_grade (
+                ROUND(
+                    ROUND(
+                        SUM(t1.quantity * COALESCE(t2.energy, t1.energy) * t2.nutri_score) / NULLIF(
+                            SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) FILTER (
+                                WHERE
+                                    t2.nutri_score IS NOT NULL
+                            ),
+                            0
+                        ),
+                        1
+                    )
+                )::INTEGER
+            ) AS nutri_grade,
+            (
+                SELECT
+                    COALESCE(SUM(w1.volume_ml), 0)
+                FROM
+                    water_log w1
+                WHERE
+                    w1.user_id = t0.user_id
+                    AND w1.date = t0.date
+            ) AS water_ml,
+            (
+                SELECT
+                    w2.water_ml
+                FROM
+                    diet_target w2
+                WHERE
+                    w2.user_id = t0.user_id
+                    AND w2.date = t0.date
+            ) AS target_water_ml,
             (
                 SELECT
                     JSON_AGG(meal)
@@ -104,14 +153,14 @@ impl DietDayJSON {
                             m1.name AS meal_of_day_name,
                             m1.slug AS meal_of_day_slug,
                             m1.ordering AS meal_of_day_order,
-                            COALESCE(SUM(m2.quantity * m3.energy), 0) AS energy,
-                            COALESCE(SUM(m2.quantity * m3.protein), 0) AS protein,
-                            COALESCE(SUM(m2.quantity * m3.carbohydrate), 0) AS carbohydrate,
-                            COALESCE(SUM(m2.quantity * m3.fat), 0) AS fat,
-                            COALESCE(SUM(m2.quantity * m3.saturates), 0) AS saturates,
-                            COALESCE(SUM(m2.quantity * m3.sugars), 0) AS sugars,
-                            COALESCE(SUM(m2.quantity * m3.fibre), 0) AS fibre,
-                            COALESCE(SUM(m2.quantity * m3.salt), 0) AS salt,
+                            COALESCE(SUM(m2.quantity * COALESCE(m3.energy, m2.energy)), 0) AS energy,
+                            COALESCE(SUM(m2.quantity * COALESCE(m3.protein, m2.protein)), 0) AS protein,
+                            COALESCE(SUM(m2.quantity * COALESCE(m3.carbohydrate, m2.carbohydrate)), 0) AS carbohydrate,
+                            COALESCE(SUM(m2.quantity * COALESCE(m3.fat, m2.fat)), 0) AS fat,
+                            COALESCE(SUM(m2.quantity * COALESCE(m3.saturates, m2.saturates)), 0) AS saturates,
+                            COALESCE(SUM(m2.quantity * COALESCE(m3.sugars, m2.sugars)), 0) AS sugars,
+                            COALESCE(SUM(m2.quantity * COALESCE(m3.fibre, m2.fibre)), 0) AS fibre,
+                            COALESCE(SUM(m2.quantity * COALESCE(m3.salt, m2.salt)), 0) AS salt,
                             (
                                 SELECT
                                     JSON_AGG(diet)
@@ -119,33 +168,38 @@ impl DietDayJSON {
                                     (
                                         SELECT
                                             d1.id AS diet_id,
-                                            d2.name AS food_name,
+                                            COALESCE(d2.name, d1.label) AS food_name,
                                             d3.name AS brand_name,
+                                            d1.eaten_at,
                                             d1.quantity * d2.data_value AS data_value,
                                             d2.data_measurement AS data_measurement,
-                                            d1.quantity * d2.energy AS energy,
-                                            d1.quantity * d2.protein AS protein,
-                                            d1.quantity * d2.carbohydrate AS carbohydrate,
-                                            d1.quantity * d2.fat AS fat,
-                                            d1.quantity * d2.saturates AS saturates,
-                                            d1.quantity * d2.sugars AS sugars,
-                                            d1.quantity * d2.fibre AS fibre,
-                                            d1.quantity * d2.salt AS salt
+                                            d1.quantity * COALESCE(d2.energy, d1.energy) AS energy,
+                                            d1.quantity * COALESCE(d2.protein, d1.protein) AS protein,
+                                            d1.quantity * COALESCE(d2.carbohydrate, d1.carbohydrate) AS carbohydrate,
+                                            d1.quantity * COALESCE(d2.fat, d1.fat) AS fat,
+                                            d1.quantity * COALESCE(d2.saturates, d1.saturates) AS saturates,
+                                            d1.quantity * COALESCE(d2.sugars, d1.sugars) AS sugars,
+                                            d1.quantity * COALESCE(d2.fibre, d1.fibre) AS fibre,
+                                            d1.quantity * COALESCE(d2.salt, d1.salt) AS salt,
+                                            d2.nutri_grade
                                         FROM
                                             food_log d1
                                             LEFT JOIN food d2 ON d2.id = d1.food_id
                                             LEFT JOIN food_brand d3 ON d3.id = d2.brand_id
                                         WHERE
-                                            d1.user_id = t1.user_id
-                                            AND d1.date = t1.date
+                                            d1.user_id = t0.user_id
+                                            AND d1.date = t0.date
                                             AND d1.meal_of_day_id = m1.id
+                                        ORDER BY
+                                            d1.eaten_at NULLS LAST,
+                                            d1.created_at
                                     ) diet
                             ) AS diet_food
                         FROM
                             meal_of_day m1
                             LEFT JOIN food_log m2 ON m2.meal_of_day_id = m1.id
-                            AND m2.date = t1.date
-                            AND m2.user_id = t1.user_id
+                            AND m2.date = t0.date
+                            AND m2.user_id = t0.user_id
                             LEFT JOIN food m3 ON m3.id = m2.food_id
                         GROUP BY
                             m1.id
@@ -154,18 +208,32 @@ impl DietDayJSON {
                     ) meal
             ) AS diet_meals
         FROM
-            food_log t1
+            (
+                SELECT
+                    user_id,
+                    date
+                FROM
+                    food_log
+                UNION
+                SELECT
+                    user_id,
+                    date
+                FROM
+                    water_log
+            ) t0
+            LEFT JOIN food_log t1 ON t1.user_id = t0.user_id
+            AND t1.date = t0.date
             LEFT JOIN food t2 ON t2.id = t1.food_id
-            LEFT JOIN users_user t3 ON t3.id = t1.user_id
-            LEFT JOIN progress p1 ON p1.user_id = t1.user_id
+            LEFT JOIN users_user t3 ON t3.id = t0.user_id
+            LEFT JOIN progress p1 ON p1.user_id = t0.user_id
             AND p1.date = (
                 SELECT
                     MAX(date)
                 FROM
                     progress
                 WHERE
-                    user_id = t1.user_id
-                    AND date <= t1.date
+                    user_id = t0.user_id
+                    AND date <= t0.date
                     AND weight_kg IS NOT NULL
             )
         WHERE
@@ -177,22 +245,22 @@ impl DietDayJSON {
             q.push_bind(username);
         }
         if let Some(user_id) = query.user_id {
-            q.push(" AND t1.user_id = ");
+            q.push(" AND t0.user_id = ");
             q.push_bind(user_id);
         }
         if let Some(date) = query.date_from {
-            q.push(" AND t1.date >= ");
+            q.push(" AND t0.date >= ");
             q.push_bind(date);
         }
         if let Some(date) = query.date_to {
-            q.push(" AND t1.date <= ");
+            q.push(" AND t0.date <= ");
             q.push_bind(date);
         }
         q.push(
             "
             GROUP BY
-                t1.user_id,
-                t1.date,
+                t0.user_id,
+                t0.date,
                 p1.id,
                 t3.id
             ",
@@ -207,7 +275,7 @@ impl DietDayJSON {
                 q.push(format!("{} DESC NULLS LAST", order));
             }
         } else {
-            q.push(" ORDER BY t1.date");
+            q.push(" ORDER BY t0.date");
         }
         let limit = query.size.unwrap_or(10);
         if let Some(page) = query.page {
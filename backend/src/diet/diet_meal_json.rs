@@ -11,7 +11,7 @@ use crate::util::query::QueryParams;
 pub struct DietFoodJSON {
     pub diet_id: Uuid,
     pub food_name: String,
-    pub brand_name: String,
+    pub brand_name: Option<String>,
     pub energy: Option<Decimal>,
     pub protein: Option<Decimal>,
     pub carbohydrate: Option<Decimal>,
@@ -55,14 +55,14 @@ impl DietMealJSON {
                 m3.name AS meal_of_day_name,
                 m3.slug AS meal_of_day_slug,
                 m3.ordering AS meal_of_day_order,
-                COALESCE(SUM(m1.quantity * m2.energy), 0) AS energy,
-                COALESCE(SUM(m1.quantity * m2.protein), 0) AS protein,
-                COALESCE(SUM(m1.quantity * m2.carbohydrate), 0) AS carbohydrate,
-                COALESCE(SUM(m1.quantity * m2.fat), 0) AS fat,
-                COALESCE(SUM(m1.quantity * m2.saturates), 0) AS saturates,
-                COALESCE(SUM(m1.quantity * m2.sugars), 0) AS sugars,
-                COALESCE(SUM(m1.quantity * m2.fibre), 0) AS fibre,
-                COALESCE(SUM(m1.quantity * m2.salt), 0) AS salt,
+                COALESCE(SUM(m1.quantity * COALESCE(m2.energy, m1.energy)), 0) AS energy,
+                COALESCE(SUM(m1.quantity * COALESCE(m2.protein, m1.protein)), 0) AS protein,
+                COALESCE(SUM(m1.quantity * COALESCE(m2.carbohydrate, m1.carbohydrate)), 0) AS carbohydrate,
+                COALESCE(SUM(m1.quantity * COALESCE(m2.fat, m1.fat)), 0) AS fat,
+                COALESCE(SUM(m1.quantity * COALESCE(m2.saturates, m1.saturates)), 0) AS saturates,
+                COALESCE(SUM(m1.quantity * COALESCE(m2.sugars, m1.sugars)), 0) AS sugars,
+                COALESCE(SUM(m1.quantity * COALESCE(m2.fibre, m1.fibre)), 0) AS fibre,
+                COALESCE(SUM(m1.quantity * COALESCE(m2.salt, m1.salt)), 0) AS salt,
                 (
                     SELECT
                         JSON_AGG(diet)
@@ -70,18 +70,18 @@ impl DietMealJSON {
                         (
                             SELECT
                                 d1.id AS diet_id,
-                                d2.name AS food_name,
+                                COALESCE(d2.name, d1.label) AS food_name,
                                 d3.name AS brand_name,
                                 SUM(d1.quantity * d2.data_value) AS data_value,
                                 d2.data_measurement AS data_measurement,
-                                SUM(d1.quantity * d2.energy) AS energy,
-                                SUM(d1.quantity * d2.protein) AS protein,
-                                SUM(d1.quantity * d2.carbohydrate) AS carbohydrate,
-                                SUM(d1.quantity * d2.fat) AS fat,
-                                SUM(d1.quantity * d2.saturates) AS saturates,
-                                SUM(d1.quantity * d2.sugars) AS sugars,
-                                SUM(d1.quantity * d2.fibre) AS fibre,
-                                SUM(d1.quantity * d2.salt) AS salt
+                                SUM(d1.quantity * COALESCE(d2.energy, d1.energy)) AS energy,
+                                SUM(d1.quantity * COALESCE(d2.protein, d1.protein)) AS protein,
+                                SUM(d1.quantity * COALESCE(d2.carbohydrate, d1.carbohydrate)) AS carbohydrate,
+                                SUM(d1.quantity * COALESCE(d2.fat, d1.fat)) AS fat,
+                                SUM(d1.quantity * COALESCE(d2.saturates, d1.saturates)) AS saturates,
+                                SUM(d1.quantity * COALESCE(d2.sugars, d1.sugars)) AS sugars,
+                                SUM(d1.quantity * COALESCE(d2.fibre, d1.fibre)) AS fibre,
+                                SUM(d1.quantity * COALESCE(d2.salt, d1.salt)) AS salt
                             FROM
                                 food_log d1
                                 LEFT JOIN food d2 ON d2.id = d1.food_id
@@ -0,0 +1,172 @@
+use chrono::{prelude::*, Duration};
+use rust_decimal::prelude::*;
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::fasting::model::EatingWindow;
+
+pub const DEFAULT_MEAL_TIME_DAYS: i64 = 7;
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct DietMealTime {
+    pub date: NaiveDate,
+    pub first_meal_at: DateTime<Utc>,
+    pub last_meal_at: DateTime<Utc>,
+    pub timed_entry_count: i64,
+    #[sqlx(skip)]
+    pub eating_hours: Decimal,
+    // Hours between the previous day's last meal and this day's first meal.
+    #[sqlx(skip)]
+    pub overnight_fast_hours: Option<Decimal>,
+    #[sqlx(skip)]
+    pub within_eating_window: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DietMealTimeSummary {
+    pub username: String,
+    pub date_from: NaiveDate,
+    pub date_to: NaiveDate,
+    pub eating_window: Option<EatingWindow>,
+    pub results: Vec<DietMealTime>,
+}
+
+fn hours_between(start: DateTime<Utc>, end: DateTime<Utc>) -> Decimal {
+    (Decimal::from((end - start).num_minutes()) / Decimal::from(60)).round_dp(2)
+}
+
+impl DietMealTime {
+    pub async fn all(
+        pool: &PgPool,
+        user_id: &Uuid,
+        date_from: NaiveDate,
+        date_to: NaiveDate,
+        eating_window: Option<&EatingWindow>,
+        offset: FixedOffset,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        // Fetch the day before the range so the first overnight fast can be derived.
+        let query = sqlx::query_as(
+            "
+            SELECT
+                date,
+                MIN(eaten_at) AS first_meal_at,
+                MAX(eaten_at) AS last_meal_at,
+                COUNT(*) AS timed_entry_count
+            FROM
+                food_log
+            WHERE
+                user_id = $1
+                AND date BETWEEN $2 AND $3
+                AND eaten_at IS NOT NULL
+            GROUP BY
+                date
+            ORDER BY
+                date
+            ",
+        )
+        .bind(user_id)
+        .bind(date_from - Duration::days(1))
+        .bind(date_to)
+        .fetch_all(pool)
+        .await?;
+        let mut result = Self::annotate(query, eating_window, offset);
+        result.retain(|day| day.date >= date_from);
+        Ok(result)
+    }
+
+    // Meal times are stored in UTC, the eating window in the user's local time.
+    pub fn annotate(
+        mut days: Vec<Self>,
+        eating_window: Option<&EatingWindow>,
+        offset: FixedOffset,
+    ) -> Vec<Self> {
+        let mut previous: Option<(NaiveDate, DateTime<Utc>)> = None;
+        for day in days.iter_mut() {
+            day.eating_hours = hours_between(day.first_meal_at, day.last_meal_at);
+            day.overnight_fast_hours = previous
+                .filter(|(date, _)| day.date - *date == Duration::days(1))
+                .map(|(_, last_meal_at)| hours_between(last_meal_at, day.first_meal_at));
+            day.within_eating_window = eating_window.map(|window| {
+                window.contains(day.first_meal_at.with_timezone(&offset).time())
+                    && window.contains(day.last_meal_at.with_timezone(&offset).time())
+            });
+            previous = Some((day.date, day.last_meal_at));
+        }
+        days
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(date: u32, first: u32, last: u32) -> DietMealTime {
+        let date = NaiveDate::from_ymd_opt(2023, 12, date).unwrap();
+        let at = |hour| date.and_hms_opt(hour, 0, 0).unwrap().and_utc();
+        DietMealTime {
+            date,
+            first_meal_at: at(first),
+            last_meal_at: at(last),
+            timed_entry_count: 2,
+            eating_hours: Decimal::ZERO,
+            overnight_fast_hours: None,
+            within_eating_window: None,
+        }
+    }
+
+    fn utc() -> FixedOffset {
+        FixedOffset::east_opt(0).unwrap()
+    }
+
+    fn window(start: u32, end: u32) -> EatingWindow {
+        EatingWindow {
+            id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            start_time: NaiveTime::from_hms_opt(start, 0, 0).unwrap(),
+            end_time: NaiveTime::from_hms_opt(end, 0, 0).unwrap(),
+            target_fast_hours: 16,
+            created_at: Utc::now(),
+            updated_at: None,
+            created_by_id: Uuid::nil(),
+            updated_by_id: None,
+        }
+    }
+
+    #[test]
+    fn test_annotate_overnight_fast() {
+        let days = DietMealTime::annotate(vec![day(1, 12, 20), day(2, 12, 19)], None, utc());
+        assert_eq!(days[0].eating_hours, Decimal::from(8));
+        assert_eq!(days[0].overnight_fast_hours, None);
+        assert_eq!(days[1].overnight_fast_hours, Some(Decimal::from(16)));
+    }
+
+    #[test]
+    fn test_annotate_skips_gap_days() {
+        let days = DietMealTime::annotate(vec![day(1, 12, 20), day(3, 12, 19)], None, utc());
+        assert_eq!(days[1].overnight_fast_hours, None);
+    }
+
+    #[test]
+    fn test_annotate_eating_window() {
+        let days = DietMealTime::annotate(
+            vec![day(1, 12, 20), day(2, 9, 20)],
+            Some(&window(12, 20)),
+            utc(),
+        );
+        assert_eq!(days[0].within_eating_window, Some(true));
+        assert_eq!(days[1].within_eating_window, Some(false));
+    }
+
+    #[test]
+    fn test_annotate_eating_window_in_local_time() {
+        // 17:00 to midnight UTC is 12:00 to 19:00 in UTC-5.
+        let offset = FixedOffset::west_opt(5 * 3600).unwrap();
+        let mut late = day(1, 17, 0);
+        late.last_meal_at += Duration::days(1);
+        let days = DietMealTime::annotate(vec![late], Some(&window(12, 20)), offset);
+        assert_eq!(days[0].within_eating_window, Some(true));
+        let days = DietMealTime::annotate(vec![day(1, 12, 20)], Some(&window(12, 20)), offset);
+        assert_eq!(days[0].within_eating_window, Some(false));
+    }
+}
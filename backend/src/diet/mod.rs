@@ -1,6 +1,9 @@
 pub mod diet_day_json;
 pub mod diet_meal_json;
+pub mod meal_time;
 pub mod model;
+pub mod quick_add;
 pub mod router;
 pub mod serializer;
+pub mod suggestion;
 pub mod view;
@@ -7,11 +7,18 @@ use uuid::Uuid;
 
 use crate::{
     db::Filters,
+    food::nutri_score::{diet_nutri_grade, diet_nutri_score},
     meal_food::model::MealFood,
     meal_of_day::model::MealOfDay,
+    planned_food::model::PlannedTotal,
     util::{datetime::NaiveDateExt, query::QueryParams},
 };
 
+use super::{
+    quick_add::{QuickAdd, QuickAddColumns},
+    serializer::DietQuickAddInput,
+};
+
 const ORDERING_FIELDS: &[&str] = &["date", "user_id", "created_at", "updated_at"];
 
 #[derive(Debug, Serialize, FromRow)]
@@ -19,13 +26,27 @@ pub struct Diet {
     pub id: Uuid,
     pub date: NaiveDate,
     pub user_id: Uuid,
-    pub food_id: Uuid,
+    pub food_id: Option<Uuid>,
     pub meal_of_day_id: Uuid,
     pub quantity: Decimal,
+    pub eaten_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
     pub created_by_id: Uuid,
     pub updated_by_id: Option<Uuid>,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub quick_add: QuickAdd,
+}
+
+#[derive(Debug)]
+pub struct DietInput {
+    pub date: NaiveDate,
+    pub user_id: Uuid,
+    pub meal_of_day_id: Uuid,
+    pub food_id: Uuid,
+    pub quantity: Decimal,
+    pub eaten_at: Option<DateTime<Utc>>,
 }
 
 impl Diet {
@@ -55,11 +76,7 @@ impl Diet {
     }
     pub async fn create(
         pool: &PgPool,
-        date: NaiveDate,
-        user_id: Uuid,
-        diet_meal_id: Uuid,
-        food_id: Uuid,
-        quantity: Decimal,
+        data: DietInput,
         created_by_id: Uuid,
     ) -> Result<Self, sqlx::Error> {
         let query = sqlx::query_as(
@@ -71,24 +88,128 @@ impl Diet {
                     meal_of_day_id,
                     food_id,
                     quantity,
+                    eaten_at,
                     created_by_id
                 )
             VALUES
-                ($1, $2, $3, $4, $5, $6)
+                ($1, $2, $3, $4, $5, $6, $7)
             RETURNING
                 *
             ",
         )
-        .bind(date)
+        .bind(data.date)
+        .bind(data.user_id)
+        .bind(data.meal_of_day_id)
+        .bind(data.food_id)
+        .bind(data.quantity)
+        .bind(data.eaten_at)
+        .bind(created_by_id)
+        .fetch_one(pool)
+        .await?;
+        Ok(query)
+    }
+    pub async fn create_quick_add(
+        pool: &PgPool,
+        user_id: Uuid,
+        meal_of_day_id: Uuid,
+        data: &DietQuickAddInput,
+        created_by_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let query = sqlx::query_as(
+            "
+            INSERT INTO
+                food_log (
+                    date,
+                    user_id,
+                    meal_of_day_id,
+                    quantity,
+                    eaten_at,
+                    label,
+                    energy,
+                    fat,
+                    saturates,
+                    carbohydrate,
+                    sugars,
+                    fibre,
+                    protein,
+                    salt,
+                    created_by_id
+                )
+            VALUES
+                ($1, $2, $3, 1, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            RETURNING
+                *
+            ",
+        )
+        .bind(data.date)
         .bind(user_id)
-        .bind(diet_meal_id)
-        .bind(food_id)
-        .bind(quantity)
+        .bind(meal_of_day_id)
+        .bind(data.eaten_at)
+        .bind(data.label.trim())
+        .bind(data.energy)
+        .bind(data.fat)
+        .bind(data.saturates)
+        .bind(data.carbohydrate)
+        .bind(data.sugars)
+        .bind(data.fibre)
+        .bind(data.protein)
+        .bind(data.salt)
         .bind(created_by_id)
         .fetch_one(pool)
         .await?;
         Ok(query)
     }
+    pub async fn update_quick_add(
+        pool: &PgPool,
+        id: Uuid,
+        meal_of_day_id: Uuid,
+        data: &DietQuickAddInput,
+        updated_by_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let query = sqlx::query_as(
+            "
+            UPDATE food_log
+            SET
+                date = $1,
+                meal_of_day_id = $2,
+                eaten_at = $3,
+                label = $4,
+                energy = $5,
+                fat = $6,
+                saturates = $7,
+                carbohydrate = $8,
+                sugars = $9,
+                fibre = $10,
+                protein = $11,
+                salt = $12,
+                updated_at = $13,
+                updated_by_id = $14
+            WHERE
+                id = $15
+                AND food_id IS NULL
+            RETURNING
+                *
+            ",
+        )
+        .bind(data.date)
+        .bind(meal_of_day_id)
+        .bind(data.eaten_at)
+        .bind(data.label.trim())
+        .bind(data.energy)
+        .bind(data.fat)
+        .bind(data.saturates)
+        .bind(data.carbohydrate)
+        .bind(data.sugars)
+        .bind(data.fibre)
+        .bind(data.protein)
+        .bind(data.salt)
+        .bind(Utc::now())
+        .bind(updated_by_id)
+        .bind(id)
+        .fetch_one(pool)
+        .await?;
+        Ok(query)
+    }
     pub async fn get(pool: &PgPool, id: &Uuid) -> Result<Option<Self>, sqlx::Error> {
         let query = sqlx::query_as("SELECT * FROM food_log WHERE id = $1")
             .bind(id)
@@ -99,11 +220,7 @@ impl Diet {
     pub async fn update(
         pool: &PgPool,
         id: Uuid,
-        date: NaiveDate,
-        user_id: Uuid,
-        diet_meal_id: Uuid,
-        food_id: Uuid,
-        quantity: Decimal,
+        data: DietInput,
         updated_by_id: Uuid,
     ) -> Result<Self, sqlx::Error> {
         let query: Diet = sqlx::query_as(
@@ -115,19 +232,21 @@ impl Diet {
                 meal_of_day_id = $3,
                 food_id = $4,
                 quantity = $5,
-                updated_at = $6,
-                updated_by_id = $7
+                eaten_at = $6,
+                updated_at = $7,
+                updated_by_id = $8
             WHERE
-                id = $8
+                id = $9
             RETURNING
                 *
             ",
         )
-        .bind(date)
-        .bind(user_id)
-        .bind(diet_meal_id)
-        .bind(food_id)
-        .bind(quantity)
+        .bind(data.date)
+        .bind(data.user_id)
+        .bind(data.meal_of_day_id)
+        .bind(data.food_id)
+        .bind(data.quantity)
+        .bind(data.eaten_at)
         .bind(Utc::now())
         .bind(updated_by_id)
         .bind(id)
@@ -156,6 +275,7 @@ impl Diet {
         let mut food_id_list = Vec::new();
         let mut quantity_list = Vec::new();
         let mut created_by_id_list = Vec::new();
+        let mut quick_add_list = QuickAddColumns::default();
 
         for food in meal_food {
             date_list.push(date);
@@ -164,6 +284,7 @@ impl Diet {
             food_id_list.push(food.food_id);
             quantity_list.push(food.quantity);
             created_by_id_list.push(created_by_id.clone());
+            quick_add_list.push(food.quick_add);
         }
 
         let query = sqlx::query_as(
@@ -175,7 +296,16 @@ impl Diet {
                     meal_of_day_id,
                     food_id,
                     quantity,
-                    created_by_id
+                    created_by_id,
+                    label,
+                    energy,
+                    fat,
+                    saturates,
+                    carbohydrate,
+                    sugars,
+                    fibre,
+                    protein,
+                    salt
                 )
             SELECT
                 *
@@ -186,8 +316,19 @@ impl Diet {
                     $3::UUID[],
                     $4::UUID[],
                     $5::DECIMAL[],
-                    $6::UUID[]
+                    $6::UUID[],
+                    $7::VARCHAR[],
+                    $8::INTEGER[],
+                    $9::DECIMAL[],
+                    $10::DECIMAL[],
+                    $11::DECIMAL[],
+                    $12::DECIMAL[],
+                    $13::DECIMAL[],
+                    $14::DECIMAL[],
+                    $15::DECIMAL[]
                 )
+            RETURNING
+                *
             ",
         )
         .bind(date_list)
@@ -196,6 +337,15 @@ impl Diet {
         .bind(food_id_list)
         .bind(quantity_list)
         .bind(created_by_id_list)
+        .bind(quick_add_list.label)
+        .bind(quick_add_list.energy)
+        .bind(quick_add_list.fat)
+        .bind(quick_add_list.saturates)
+        .bind(quick_add_list.carbohydrate)
+        .bind(quick_add_list.sugars)
+        .bind(quick_add_list.fibre)
+        .bind(quick_add_list.protein)
+        .bind(quick_add_list.salt)
         .fetch_all(pool)
         .await?;
         Ok(query)
@@ -255,6 +405,7 @@ pub struct DietSerializer {
     pub user_id: Uuid,
     pub meal_of_day_id: Uuid,
     pub quantity: Decimal,
+    pub eaten_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
     // user
@@ -262,15 +413,16 @@ pub struct DietSerializer {
     // meal of day
     pub meal_name: String,
     pub meal_slug: String,
-    // food
+    // food, or the label of a quick-add
+    pub food_id: Option<Uuid>,
     pub food_name: String,
-    pub food_slug: String,
+    pub food_slug: Option<String>,
     // brand
-    pub brand_name: String,
-    pub brand_slug: String,
+    pub brand_name: Option<String>,
+    pub brand_slug: Option<String>,
     // food
     pub data_value: Option<Decimal>,
-    pub data_measurement: String,
+    pub data_measurement: Option<String>,
     pub energy: Option<Decimal>,
     pub protein: Option<Decimal>,
     pub carbohydrate: Option<Decimal>,
@@ -279,6 +431,8 @@ pub struct DietSerializer {
     pub sugars: Option<Decimal>,
     pub fibre: Option<Decimal>,
     pub salt: Option<Decimal>,
+    pub nutri_score: Option<i32>,
+    pub nutri_grade: Option<String>,
     // pub protein_pct: Option<Decimal>,
     // pub carbohydrate_pct: Option<Decimal>,
     // pub fat_pct: Option<Decimal>,
@@ -328,13 +482,15 @@ impl DietSerializer {
             t5.username,
             t1.date,
             t1.meal_of_day_id,
+            t1.eaten_at,
             t1.created_at,
             t1.updated_at,
             -- meal
             t4.name AS meal_name,
             t4.slug AS meal_slug,
             -- food
-            t2.name AS food_name,
+            t1.food_id,
+            COALESCE(t2.name, t1.label) AS food_name,
             t2.slug AS food_slug,
             -- brand
             t3.name AS brand_name,
@@ -343,149 +499,151 @@ impl DietSerializer {
             t1.quantity * t2.data_value AS data_value,
             t2.data_measurement AS data_measurement,
             -- food macros
-            t1.quantity * t2.energy AS energy,
-            t1.quantity * t2.protein AS protein,
-            t1.quantity * t2.carbohydrate AS carbohydrate,
-            t1.quantity * t2.fat AS fat,
-            t1.quantity * t2.saturates AS saturates,
-            t1.quantity * t2.sugars AS sugars,
-            t1.quantity * t2.fibre AS fibre,
-            t1.quantity * t2.salt AS salt,
+            t1.quantity * COALESCE(t2.energy, t1.energy) AS energy,
+            t1.quantity * COALESCE(t2.protein, t1.protein) AS protein,
+            t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate) AS carbohydrate,
+            t1.quantity * COALESCE(t2.fat, t1.fat) AS fat,
+            t1.quantity * COALESCE(t2.saturates, t1.saturates) AS saturates,
+            t1.quantity * COALESCE(t2.sugars, t1.sugars) AS sugars,
+            t1.quantity * COALESCE(t2.fibre, t1.fibre) AS fibre,
+            t1.quantity * COALESCE(t2.salt, t1.salt) AS salt,
+            t2.nutri_score,
+            t2.nutri_grade,
             -- meal macros
-            SUM(t1.quantity * t2.energy) OVER (
+            SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date,
                     t1.meal_of_day_id
             ) AS meal_energy,
-            SUM(t1.quantity * t2.protein) OVER (
+            SUM(t1.quantity * COALESCE(t2.protein, t1.protein)) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date,
                     t1.meal_of_day_id
             ) AS meal_protein,
-            SUM(t1.quantity * t2.carbohydrate) OVER (
+            SUM(t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate)) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date,
                     t1.meal_of_day_id
             ) AS meal_carbohydrate,
-            SUM(t1.quantity * t2.fat) OVER (
+            SUM(t1.quantity * COALESCE(t2.fat, t1.fat)) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date,
                     t1.meal_of_day_id
             ) AS meal_fat,
-            SUM(t1.quantity * t2.saturates) OVER (
+            SUM(t1.quantity * COALESCE(t2.saturates, t1.saturates)) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date,
                     t1.meal_of_day_id
             ) AS meal_saturates,
-            SUM(t1.quantity * t2.sugars) OVER (
+            SUM(t1.quantity * COALESCE(t2.sugars, t1.sugars)) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date,
                     t1.meal_of_day_id
             ) AS meal_sugars,
-            SUM(t1.quantity * t2.fibre) OVER (
+            SUM(t1.quantity * COALESCE(t2.fibre, t1.fibre)) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date,
                     t1.meal_of_day_id
             ) AS meal_fibre,
-            SUM(t1.quantity * t2.salt) OVER (
+            SUM(t1.quantity * COALESCE(t2.salt, t1.salt)) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date,
                     t1.meal_of_day_id
             ) AS meal_salt,
             -- day macros
-            SUM(t1.quantity * t2.energy) OVER (
+            SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date
             ) AS day_energy,
-            SUM(t1.quantity * t2.protein) OVER (
+            SUM(t1.quantity * COALESCE(t2.protein, t1.protein)) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date
             ) AS day_protein,
-            SUM(t1.quantity * t2.carbohydrate) OVER (
+            SUM(t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate)) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date
             ) AS day_carbohydrate,
-            SUM(t1.quantity * t2.fat) OVER (
+            SUM(t1.quantity * COALESCE(t2.fat, t1.fat)) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date
             ) AS day_fat,
-            SUM(t1.quantity * t2.saturates) OVER (
+            SUM(t1.quantity * COALESCE(t2.saturates, t1.saturates)) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date
             ) AS day_saturates,
-            SUM(t1.quantity * t2.sugars) OVER (
+            SUM(t1.quantity * COALESCE(t2.sugars, t1.sugars)) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date
             ) AS day_sugars,
-            SUM(t1.quantity * t2.fibre) OVER (
+            SUM(t1.quantity * COALESCE(t2.fibre, t1.fibre)) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date
             ) AS day_fibre,
-            SUM(t1.quantity * t2.salt) OVER (
+            SUM(t1.quantity * COALESCE(t2.salt, t1.salt)) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date
             ) AS day_salt,
             -- day percentage
-            SUM(t1.quantity * t2.protein) OVER (
+            SUM(t1.quantity * COALESCE(t2.protein, t1.protein)) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date
-            ) * 4 / SUM(t1.quantity * t2.energy) OVER (
+            ) * 4 / SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date
             ) * 100 AS day_protein_pct,
-            SUM(t1.quantity * t2.carbohydrate) OVER (
+            SUM(t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate)) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date
-            ) * 4 / SUM(t1.quantity * t2.energy) OVER (
+            ) * 4 / SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date
             ) * 100 AS day_carbohydrate_pct,
-            SUM(t1.quantity * t2.fat) OVER (
+            SUM(t1.quantity * COALESCE(t2.fat, t1.fat)) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date
-            ) * 9 / SUM(t1.quantity * t2.energy) OVER (
+            ) * 9 / SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date
             ) * 100 AS day_fat_pct,
             -- Day macros per kg of latest weight
-            SUM(t1.quantity * t2.energy / t6.weight_kg) OVER (
+            SUM(t1.quantity * COALESCE(t2.energy, t1.energy) / t6.weight_kg) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date
             ) AS day_energy_per_kg,
-            SUM(t1.quantity * t2.protein / t6.weight_kg) OVER (
+            SUM(t1.quantity * COALESCE(t2.protein, t1.protein) / t6.weight_kg) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date
             ) AS day_protein_per_kg,
-            SUM(t1.quantity * t2.carbohydrate / t6.weight_kg) OVER (
+            SUM(t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate) / t6.weight_kg) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date
             ) AS day_carbohydrate_per_kg,
-            SUM(t1.quantity * t2.fat / t6.weight_kg) OVER (
+            SUM(t1.quantity * COALESCE(t2.fat, t1.fat) / t6.weight_kg) OVER (
                 PARTITION BY
                     t1.user_id,
                     t1.date
@@ -551,6 +709,8 @@ pub struct DietDayTotal {
     pub fat_per_kg: Option<Decimal>,
     pub latest_weight: Option<Decimal>,
     pub latest_weight_date: Option<NaiveDate>,
+    #[sqlx(skip)]
+    pub planned: PlannedTotal,
 }
 
 impl DietDayTotal {
@@ -570,21 +730,21 @@ impl DietDayTotal {
             SELECT
                 -- t3.username,
                 t1.date,
-                SUM(t1.quantity * t2.energy) AS energy,
-                SUM(t1.quantity * t2.protein) AS protein,
-                SUM(t1.quantity * t2.carbohydrate) AS carbohydrate,
-                SUM(t1.quantity * t2.fat) AS fat,
-                SUM(t1.quantity * t2.saturates) AS saturates,
-                SUM(t1.quantity * t2.sugars) AS sugars,
-                SUM(t1.quantity * t2.fibre) AS fibre,
-                SUM(t1.quantity * t2.salt) AS salt,
-                SUM(t1.quantity * t2.protein * 4) / SUM(t1.quantity * t2.energy) * 100 AS protein_pct,
-                SUM(t1.quantity * t2.carbohydrate * 4) / SUM(t1.quantity * t2.energy) * 100 AS carbohydrate_pct,
-                SUM(t1.quantity * t2.fat * 9) / SUM(t1.quantity * t2.energy) * 100 AS fat_pct,
-                SUM(t1.quantity * t2.energy) / t4.weight_kg AS energy_per_kg,
-                SUM(t1.quantity * t2.protein) / t4.weight_kg AS protein_per_kg,
-                SUM(t1.quantity * t2.carbohydrate) / t4.weight_kg AS carbohydrate_per_kg,
-                SUM(t1.quantity * t2.fat) / t4.weight_kg AS fat_per_kg,
+                SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) AS energy,
+                SUM(t1.quantity * COALESCE(t2.protein, t1.protein)) AS protein,
+                SUM(t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate)) AS carbohydrate,
+                SUM(t1.quantity * COALESCE(t2.fat, t1.fat)) AS fat,
+                SUM(t1.quantity * COALESCE(t2.saturates, t1.saturates)) AS saturates,
+                SUM(t1.quantity * COALESCE(t2.sugars, t1.sugars)) AS sugars,
+                SUM(t1.quantity * COALESCE(t2.fibre, t1.fibre)) AS fibre,
+                SUM(t1.quantity * COALESCE(t2.salt, t1.salt)) AS salt,
+                SUM(t1.quantity * COALESCE(t2.protein, t1.protein) * 4) / SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) * 100 AS protein_pct,
+                SUM(t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate) * 4) / SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) * 100 AS carbohydrate_pct,
+                SUM(t1.quantity * COALESCE(t2.fat, t1.fat) * 9) / SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) * 100 AS fat_pct,
+                SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) / t4.weight_kg AS energy_per_kg,
+                SUM(t1.quantity * COALESCE(t2.protein, t1.protein)) / t4.weight_kg AS protein_per_kg,
+                SUM(t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate)) / t4.weight_kg AS carbohydrate_per_kg,
+                SUM(t1.quantity * COALESCE(t2.fat, t1.fat)) / t4.weight_kg AS fat_per_kg,
                 t4.weight_kg AS latest_weight,
                 t4.date as latest_weight_date
             FROM
@@ -639,21 +799,21 @@ impl DietDayTotal {
             SELECT
                 t3.username,
                 DATE_TRUNC('week', t1.date)::date AS date,
-                SUM(t1.quantity * t2.energy) AS energy,
-                SUM(t1.quantity * t2.protein) AS protein,
-                SUM(t1.quantity * t2.carbohydrate) AS carbohydrate,
-                SUM(t1.quantity * t2.fat) AS fat,
-                SUM(t1.quantity * t2.saturates) AS saturates,
-                SUM(t1.quantity * t2.sugars) AS sugars,
-                SUM(t1.quantity * t2.fibre) AS fibre,
-                SUM(t1.quantity * t2.salt) AS salt,
-                SUM(t1.quantity * t2.protein * 4) / SUM(t1.quantity * t2.energy) * 100 AS protein_pct,
-                SUM(t1.quantity * t2.carbohydrate * 4) / SUM(t1.quantity * t2.energy) * 100 AS carbohydrate_pct,
-                SUM(t1.quantity * t2.fat * 9) / SUM(t1.quantity * t2.energy) * 100 AS fat_pct,
-                SUM(t1.quantity * t2.energy) / t4.weight_kg AS energy_per_kg,
-                SUM(t1.quantity * t2.protein) / t4.weight_kg AS protein_per_kg,
-                SUM(t1.quantity * t2.carbohydrate) / t4.weight_kg AS carbohydrate_per_kg,
-                SUM(t1.quantity * t2.fat) / t4.weight_kg AS fat_per_kg,
+                SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) AS energy,
+                SUM(t1.quantity * COALESCE(t2.protein, t1.protein)) AS protein,
+                SUM(t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate)) AS carbohydrate,
+                SUM(t1.quantity * COALESCE(t2.fat, t1.fat)) AS fat,
+                SUM(t1.quantity * COALESCE(t2.saturates, t1.saturates)) AS saturates,
+                SUM(t1.quantity * COALESCE(t2.sugars, t1.sugars)) AS sugars,
+                SUM(t1.quantity * COALESCE(t2.fibre, t1.fibre)) AS fibre,
+                SUM(t1.quantity * COALESCE(t2.salt, t1.salt)) AS salt,
+                SUM(t1.quantity * COALESCE(t2.protein, t1.protein) * 4) / SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) * 100 AS protein_pct,
+                SUM(t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate) * 4) / SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) * 100 AS carbohydrate_pct,
+                SUM(t1.quantity * COALESCE(t2.fat, t1.fat) * 9) / SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) * 100 AS fat_pct,
+                SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) / t4.weight_kg AS energy_per_kg,
+                SUM(t1.quantity * COALESCE(t2.protein, t1.protein)) / t4.weight_kg AS protein_per_kg,
+                SUM(t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate)) / t4.weight_kg AS carbohydrate_per_kg,
+                SUM(t1.quantity * COALESCE(t2.fat, t1.fat)) / t4.weight_kg AS fat_per_kg,
                 t4.weight_kg AS latest_weight,
                 t4.date as latest_weight_date
             FROM
@@ -700,21 +860,21 @@ impl DietDayTotal {
                     SELECT
                         t1.date,
                         t1.user_id,
-                        SUM(t1.quantity * t2.energy) AS energy,
-                        SUM(t1.quantity * t2.protein) AS protein,
-                        SUM(t1.quantity * t2.carbohydrate) AS carbohydrate,
-                        SUM(t1.quantity * t2.fat) AS fat,
-                        SUM(t1.quantity * t2.saturates) AS saturates,
-                        SUM(t1.quantity * t2.sugars) AS sugars,
-                        SUM(t1.quantity * t2.fibre) AS fibre,
-                        SUM(t1.quantity * t2.salt) AS salt,
-                        SUM(t1.quantity * t2.protein * 4) / SUM(t1.quantity * t2.energy) * 100 AS protein_pct,
-                        SUM(t1.quantity * t2.carbohydrate * 4) / SUM(t1.quantity * t2.energy) * 100 AS carbohydrate_pct,
-                        SUM(t1.quantity * t2.fat * 9) / SUM(t1.quantity * t2.energy) * 100 AS fat_pct,
-                        SUM(t1.quantity * t2.energy) / MAX(t3.weight_kg) AS energy_per_kg,
-                        SUM(t1.quantity * t2.protein) / MAX(t3.weight_kg) AS protein_per_kg,
-                        SUM(t1.quantity * t2.carbohydrate) / MAX(t3.weight_kg) AS carbohydrate_per_kg,
-                        SUM(t1.quantity * t2.fat) / MAX(t3.weight_kg) AS fat_per_kg,
+                        SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) AS energy,
+                        SUM(t1.quantity * COALESCE(t2.protein, t1.protein)) AS protein,
+                        SUM(t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate)) AS carbohydrate,
+                        SUM(t1.quantity * COALESCE(t2.fat, t1.fat)) AS fat,
+                        SUM(t1.quantity * COALESCE(t2.saturates, t1.saturates)) AS saturates,
+                        SUM(t1.quantity * COALESCE(t2.sugars, t1.sugars)) AS sugars,
+                        SUM(t1.quantity * COALESCE(t2.fibre, t1.fibre)) AS fibre,
+                        SUM(t1.quantity * COALESCE(t2.salt, t1.salt)) AS salt,
+                        SUM(t1.quantity * COALESCE(t2.protein, t1.protein) * 4) / SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) * 100 AS protein_pct,
+                        SUM(t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate) * 4) / SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) * 100 AS carbohydrate_pct,
+                        SUM(t1.quantity * COALESCE(t2.fat, t1.fat) * 9) / SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) * 100 AS fat_pct,
+                        SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) / MAX(t3.weight_kg) AS energy_per_kg,
+                        SUM(t1.quantity * COALESCE(t2.protein, t1.protein)) / MAX(t3.weight_kg) AS protein_per_kg,
+                        SUM(t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate)) / MAX(t3.weight_kg) AS carbohydrate_per_kg,
+                        SUM(t1.quantity * COALESCE(t2.fat, t1.fat)) / MAX(t3.weight_kg) AS fat_per_kg,
                         MAX(t3.weight_kg) AS latest_weight,
                         MAX(t3.date) AS latest_weight_date
                     FROM
@@ -791,13 +951,14 @@ pub struct DietDetail {
     pub id: Uuid,
     pub date: NaiveDate,
     pub meal_of_day_id: Uuid,
-    pub food_id: Uuid,
+    pub food_id: Option<Uuid>,
     pub food_name: String,
-    pub food_slug: String,
-    pub brand_name: String,
-    pub brand_slug: String,
-    pub data_value: Decimal,
-    pub data_measurement: String,
+    pub food_slug: Option<String>,
+    pub brand_name: Option<String>,
+    pub brand_slug: Option<String>,
+    pub eaten_at: Option<DateTime<Utc>>,
+    pub data_value: Option<Decimal>,
+    pub data_measurement: Option<String>,
     pub energy: Decimal,
     pub fat: Decimal,
     pub saturates: Decimal,
@@ -817,21 +978,22 @@ impl DietDetail {
             t1.date,
             t1.meal_of_day_id,
             t1.food_id,
+            t1.eaten_at,
             --
-            t2.name as food_name,
+            COALESCE(t2.name, t1.label) as food_name,
             t2.slug as food_slug,
             t3.name as brand_name,
             t3.slug as brand_slug,
             t1.quantity * t2.data_value AS data_value,
             t2.data_measurement AS data_measurement,
-            t1.quantity * t2.energy AS energy,
-            t1.quantity * t2.protein AS protein,
-            t1.quantity * t2.carbohydrate AS carbohydrate,
-            t1.quantity * t2.fat AS fat,
-            t1.quantity * t2.saturates AS saturates,
-            t1.quantity * t2.sugars AS sugars,
-            t1.quantity * t2.fibre AS fibre,
-            t1.quantity * t2.salt AS salt
+            t1.quantity * COALESCE(t2.energy, t1.energy) AS energy,
+            t1.quantity * COALESCE(t2.protein, t1.protein) AS protein,
+            t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate) AS carbohydrate,
+            t1.quantity * COALESCE(t2.fat, t1.fat) AS fat,
+            t1.quantity * COALESCE(t2.saturates, t1.saturates) AS saturates,
+            t1.quantity * COALESCE(t2.sugars, t1.sugars) AS sugars,
+            t1.quantity * COALESCE(t2.fibre, t1.fibre) AS fibre,
+            t1.quantity * COALESCE(t2.salt, t1.salt) AS salt
             FROM food_log t1
             LEFT JOIN food t2 ON t1.food_id = t2.id
             LEFT JOIN food_brand t3 ON t2.brand_id = t3.id
@@ -867,6 +1029,12 @@ pub struct DietDay {
     pub fat_per_kg: Decimal,
     pub latest_weight: Decimal,
     pub latest_weight_date: NaiveDate,
+    pub water_ml: i64,
+    pub target_water_ml: Option<i32>,
+    pub nutri_score: Option<Decimal>,
+    pub nutri_grade: Option<String>,
+    #[sqlx(skip)]
+    pub planned: PlannedTotal,
 }
 
 #[derive(Debug, Default, Serialize, FromRow)]
@@ -892,10 +1060,12 @@ pub struct DietFood {
     pub username: String,
     pub date: NaiveDate,
     pub meal_slug: String,
+    pub food_id: Option<Uuid>,
     pub name: String,
-    pub brand_name: String,
+    pub brand_name: Option<String>,
+    pub eaten_at: Option<DateTime<Utc>>,
     pub data_value: Decimal,
-    pub data_measurement: String,
+    pub data_measurement: Option<String>,
     pub energy: Decimal,
     pub protein: Decimal,
     pub carbohydrate: Decimal,
@@ -904,6 +1074,7 @@ pub struct DietFood {
     pub sugars: Decimal,
     pub fibre: Decimal,
     pub salt: Decimal,
+    pub nutri_grade: Option<String>,
 }
 
 impl DietDay {
@@ -937,8 +1108,10 @@ impl DietDay {
                         username: diet.username,
                         date: diet.date,
                         meal_slug: diet.meal_slug,
+                        food_id: diet.food_id,
                         name: diet.food_name,
                         brand_name: diet.brand_name,
+                        eaten_at: diet.eaten_at,
                         data_value: diet.data_value.unwrap_or_default(),
                         data_measurement: diet.data_measurement,
                         energy: diet.energy.unwrap_or_default(),
@@ -949,6 +1122,7 @@ impl DietDay {
                         sugars: diet.sugars.unwrap_or_default(),
                         fibre: diet.fibre.unwrap_or_default(),
                         salt: diet.salt.unwrap_or_default(),
+                        nutri_grade: diet.nutri_grade,
                     })
                 }
             }
@@ -967,6 +1141,11 @@ impl DietDay {
                 .into_iter()
                 .nth(0)
                 .expect("diet_list vec to contain data");
+            let nutri_score = diet_nutri_score(
+                diet_list
+                    .iter()
+                    .map(|diet| (diet.energy.unwrap_or_default(), diet.nutri_score)),
+            );
             Self {
                 username,
                 date,
@@ -988,6 +1167,9 @@ impl DietDay {
                 fat_per_kg: diet.day_fat_per_kg.unwrap_or_default(),
                 latest_weight: diet.latest_weight.unwrap_or_default(),
                 latest_weight_date: diet.latest_weight_date.unwrap_or_default(),
+                nutri_score,
+                nutri_grade: nutri_score.map(|score| diet_nutri_grade(score).to_string()),
+                ..Default::default()
             }
         };
         result
@@ -1013,6 +1195,11 @@ pub struct DayTotal {
     pub target_protein: Option<Decimal>,
     pub target_carbohydrate: Option<Decimal>,
     pub target_fat: Option<Decimal>,
+    // water
+    pub water_ml: Option<i64>,
+    pub week_avg_water_ml: Option<Decimal>,
+    pub month_avg_water_ml: Option<Decimal>,
+    pub target_water_ml: Option<i32>,
 
     // progress
     pub progress_id: Option<Uuid>,
@@ -1063,17 +1250,17 @@ impl DayTotal {
                     SELECT
                         t1.date,
                         t1.user_id,
-                        SUM(t1.quantity * t2.energy) AS energy,
-                        SUM(t1.quantity * t2.protein) AS protein,
-                        SUM(t1.quantity * t2.carbohydrate) AS carbohydrate,
-                        SUM(t1.quantity * t2.fat) AS fat,
-                        SUM(t1.quantity * t2.saturates) AS saturates,
-                        SUM(t1.quantity * t2.sugars) AS sugars,
-                        SUM(t1.quantity * t2.fibre) AS fibre,
-                        SUM(t1.quantity * t2.salt) AS salt,
-                        SUM(t1.quantity * t2.protein * 4) / SUM(t1.quantity * t2.energy) * 100 AS protein_pct,
-                        SUM(t1.quantity * t2.carbohydrate * 4) / SUM(t1.quantity * t2.energy) * 100 AS carbohydrate_pct,
-                        SUM(t1.quantity * t2.fat * 9) / SUM(t1.quantity * t2.energy) * 100 AS fat_pct
+                        SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) AS energy,
+                        SUM(t1.quantity * COALESCE(t2.protein, t1.protein)) AS protein,
+                        SUM(t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate)) AS carbohydrate,
+                        SUM(t1.quantity * COALESCE(t2.fat, t1.fat)) AS fat,
+                        SUM(t1.quantity * COALESCE(t2.saturates, t1.saturates)) AS saturates,
+                        SUM(t1.quantity * COALESCE(t2.sugars, t1.sugars)) AS sugars,
+                        SUM(t1.quantity * COALESCE(t2.fibre, t1.fibre)) AS fibre,
+                        SUM(t1.quantity * COALESCE(t2.salt, t1.salt)) AS salt,
+                        SUM(t1.quantity * COALESCE(t2.protein, t1.protein) * 4) / SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) * 100 AS protein_pct,
+                        SUM(t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate) * 4) / SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) * 100 AS carbohydrate_pct,
+                        SUM(t1.quantity * COALESCE(t2.fat, t1.fat) * 9) / SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) * 100 AS fat_pct
                     FROM
                         food_log t1
                         LEFT JOIN food t2 ON t2.id = t1.food_id
@@ -1082,6 +1269,17 @@ impl DayTotal {
                     GROUP BY
                         t1.date,
                         t1.user_id
+                ),
+                water_day_total AS (
+                    SELECT
+                        date,
+                        SUM(volume_ml) AS water_ml
+                    FROM
+                        water_log
+                    WHERE
+                        user_id = $3
+                    GROUP BY
+                        date
                 )
             SELECT
                 t1.date,
@@ -1123,6 +1321,19 @@ impl DayTotal {
                 t4.protein AS target_protein,
                 t4.carbohydrate AS target_carbohydrate,
                 t4.fat AS target_fat,
+                -- water
+                t5.water_ml,
+                AVG(t5.water_ml) OVER (
+                    PARTITION BY
+                        DATE_TRUNC('week', t1.date),
+                        t1.user_id
+                ) AS week_avg_water_ml,
+                AVG(t5.water_ml) OVER (
+                    PARTITION BY
+                        DATE_TRUNC('month', t1.date),
+                        t1.user_id
+                ) AS month_avg_water_ml,
+                t4.water_ml AS target_water_ml,
                 
                 -- progress
                 t3.id AS progress_id,
@@ -1149,6 +1360,7 @@ impl DayTotal {
                 LEFT JOIN diet_day_total t2 ON t2.date = t1.date
                 LEFT JOIN progress t3 ON t3.date = t1.date AND t3.user_id = t1.user_id
                 LEFT JOIN diet_target t4 ON t4.date = t1.date AND t4.user_id = t1.user_id
+                LEFT JOIN water_day_total t5 ON t5.date = t1.date
             WHERE
                 t1.user_id = $3                
             ORDER BY
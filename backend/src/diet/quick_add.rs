@@ -0,0 +1,47 @@
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::FromRow;
+
+// Label and nutrients of an entry logged without a food record. Shared by
+// food_log and meal_food so quick-adds survive copying between diary and meals;
+// every field is null on rows that do have a food.
+#[derive(Debug, Default, Clone, Serialize, FromRow)]
+pub struct QuickAdd {
+    pub label: Option<String>,
+    pub energy: Option<i32>,
+    pub fat: Option<Decimal>,
+    pub saturates: Option<Decimal>,
+    pub carbohydrate: Option<Decimal>,
+    pub sugars: Option<Decimal>,
+    pub fibre: Option<Decimal>,
+    pub protein: Option<Decimal>,
+    pub salt: Option<Decimal>,
+}
+
+// Column-wise quick-add values for bulk UNNEST inserts.
+#[derive(Debug, Default)]
+pub struct QuickAddColumns {
+    pub label: Vec<Option<String>>,
+    pub energy: Vec<Option<i32>>,
+    pub fat: Vec<Option<Decimal>>,
+    pub saturates: Vec<Option<Decimal>>,
+    pub carbohydrate: Vec<Option<Decimal>>,
+    pub sugars: Vec<Option<Decimal>>,
+    pub fibre: Vec<Option<Decimal>>,
+    pub protein: Vec<Option<Decimal>>,
+    pub salt: Vec<Option<Decimal>>,
+}
+
+impl QuickAddColumns {
+    pub fn push(&mut self, quick_add: QuickAdd) {
+        self.label.push(quick_add.label);
+        self.energy.push(quick_add.energy);
+        self.fat.push(quick_add.fat);
+        self.saturates.push(quick_add.saturates);
+        self.carbohydrate.push(quick_add.carbohydrate);
+        self.sugars.push(quick_add.sugars);
+        self.fibre.push(quick_add.fibre);
+        self.protein.push(quick_add.protein);
+        self.salt.push(quick_add.salt);
+    }
+}
@@ -8,8 +8,9 @@ use crate::diet::view::{
     diet_create_from_meal_view, diet_create_view, diet_day_json_view, diet_day_month_list_view,
     diet_day_total_list_view, diet_day_view, diet_delete_date_range_view,
     diet_delete_id_range_view, diet_delete_view, diet_detail_view, diet_list_view,
-    diet_meal_json_view, diet_update_view, diet_week_average_detail_view,
-    diet_week_total_detail_view,
+    diet_meal_json_view, diet_meal_time_list_view, diet_quick_add_create_view,
+    diet_quick_add_update_view, diet_suggestion_list_view, diet_suggestion_log_view,
+    diet_update_view, diet_week_average_detail_view, diet_week_total_detail_view,
 };
 use crate::AppState;
 
@@ -23,8 +24,16 @@ pub fn diet_router() -> Router<Arc<AppState>> {
         .route("/delete-id-range", delete(diet_delete_id_range_view))
         .route("/delete-date-range", delete(diet_delete_date_range_view))
         .route("/create-from-meal-food", post(diet_create_from_meal_view))
+        .route("/quick-add", post(diet_quick_add_create_view))
+        .route("/quick-add/:id", put(diet_quick_add_update_view))
+        .route("/suggestions", post(diet_suggestion_log_view))
         // day view - detail view of day
         .route("/:username/:date", get(diet_day_view))
+        .route("/:username/:date/meal-times", get(diet_meal_time_list_view))
+        .route(
+            "/:username/:date/suggestions",
+            get(diet_suggestion_list_view),
+        )
         // week views - list per day
         .route("/:username/:date/week", get(diet_day_total_list_view))
         // week detail - total / avg
@@ -2,9 +2,18 @@ use chrono::prelude::*;
 use rust_decimal::Decimal;
 use serde::Deserialize;
 use uuid::Uuid;
-use validator::Validate;
+use validator::{Validate, ValidationError};
 
-use crate::util::validator::{validate_max_quantity, validate_min_quantity};
+use crate::{
+    food::consistency::{nutrient_issues, NutrientValues},
+    util::{
+        query::empty_string_as_none,
+        validator::{
+            validate_max_quantity, validate_min_quantity, validate_non_negative_decimal,
+            validate_not_empty_string,
+        },
+    },
+};
 
 #[derive(Debug, Deserialize, Validate)]
 pub struct DietCreateInput {
@@ -24,6 +33,7 @@ pub struct DietCreateInput {
         )
     )]
     pub quantity: Decimal,
+    pub eaten_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -43,6 +53,13 @@ pub struct DietUpdateInput {
         )
     )]
     pub quantity: Decimal,
+    pub eaten_at: Option<DateTime<Utc>>,
+}
+
+// Entries are filed against the diary date, so the eaten-at time may only fall
+// within a day either side of it to allow for time zones.
+pub fn validate_eaten_at(date: NaiveDate, eaten_at: Option<DateTime<Utc>>) -> bool {
+    eaten_at.is_none_or(|eaten_at| (eaten_at.date_naive() - date).num_days().abs() <= 1)
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -52,3 +69,165 @@ pub struct DietFromMealInput {
     pub meal_of_day_slug: String,
     pub meal_id: Uuid,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct DietMealTimeParams {
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    pub date_from: Option<NaiveDate>,
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    pub utc_offset: Option<i32>,
+}
+
+// Nutrients are for the whole entry, e.g. a restaurant dish, and are logged with
+// a quantity of one. Fields other than energy and the main macros default to zero.
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_quick_add_consistency"))]
+pub struct DietQuickAddInput {
+    pub date: NaiveDate,
+    pub username: String,
+    pub meal_of_day_id: Option<Uuid>,
+    pub meal_of_day_slug: Option<String>,
+    #[validate(
+        length(min = 1, message = "Minimum of 1 character"),
+        length(max = 100, message = "Maximum of 100 characters"),
+        custom(
+            function = "validate_not_empty_string",
+            message = "Label must not be empty"
+        )
+    )]
+    pub label: String,
+    #[validate(range(min = 1, max = 20000, message = "Must be between 1 and 20000"))]
+    pub energy: i32,
+    #[validate(
+        custom(
+            function = "validate_non_negative_decimal",
+            message = "Quantity must be a minimum of 0.00"
+        ),
+        custom(
+            function = "validate_max_quantity",
+            message = "Quantity must be a maximum of 999.99"
+        )
+    )]
+    pub fat: Decimal,
+    #[serde(default)]
+    #[validate(
+        custom(
+            function = "validate_non_negative_decimal",
+            message = "Quantity must be a minimum of 0.00"
+        ),
+        custom(
+            function = "validate_max_quantity",
+            message = "Quantity must be a maximum of 999.99"
+        )
+    )]
+    pub saturates: Decimal,
+    #[validate(
+        custom(
+            function = "validate_non_negative_decimal",
+            message = "Quantity must be a minimum of 0.00"
+        ),
+        custom(
+            function = "validate_max_quantity",
+            message = "Quantity must be a maximum of 999.99"
+        )
+    )]
+    pub carbohydrate: Decimal,
+    #[serde(default)]
+    #[validate(
+        custom(
+            function = "validate_non_negative_decimal",
+            message = "Quantity must be a minimum of 0.00"
+        ),
+        custom(
+            function = "validate_max_quantity",
+            message = "Quantity must be a maximum of 999.99"
+        )
+    )]
+    pub sugars: Decimal,
+    #[serde(default)]
+    #[validate(
+        custom(
+            function = "validate_non_negative_decimal",
+            message = "Quantity must be a minimum of 0.00"
+        ),
+        custom(
+            function = "validate_max_quantity",
+            message = "Quantity must be a maximum of 999.99"
+        )
+    )]
+    pub fibre: Decimal,
+    #[validate(
+        custom(
+            function = "validate_non_negative_decimal",
+            message = "Quantity must be a minimum of 0.00"
+        ),
+        custom(
+            function = "validate_max_quantity",
+            message = "Quantity must be a maximum of 999.99"
+        )
+    )]
+    pub protein: Decimal,
+    #[serde(default)]
+    #[validate(
+        custom(
+            function = "validate_non_negative_decimal",
+            message = "Quantity must be a minimum of 0.00"
+        ),
+        custom(
+            function = "validate_max_quantity",
+            message = "Quantity must be a maximum of 999.99"
+        )
+    )]
+    pub salt: Decimal,
+    pub eaten_at: Option<DateTime<Utc>>,
+}
+
+// Menu figures rarely add up, so unlike foods only the sub-nutrient limits apply.
+fn validate_quick_add_consistency(data: &DietQuickAddInput) -> Result<(), ValidationError> {
+    let values = NutrientValues {
+        data_value: 1,
+        data_measurement: String::from("srv"),
+        energy: data.energy,
+        fat: data.fat,
+        saturates: data.saturates,
+        carbohydrate: data.carbohydrate,
+        sugars: data.sugars,
+        fibre: data.fibre,
+        protein: data.protein,
+        salt: data.salt,
+    };
+    match nutrient_issues(&values)
+        .into_iter()
+        .find(|issue| issue.field == "saturates" || issue.field == "sugars")
+    {
+        Some(issue) => Err(issue.into()),
+        None => Ok(()),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DietSuggestionParams {
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    pub meal_of_day_id: Option<Uuid>,
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    pub tolerance: Option<i32>,
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DietSuggestionItemInput {
+    pub food_id: Option<Uuid>,
+    pub meal_id: Option<Uuid>,
+    pub scale: Decimal,
+}
+
+// The items of a suggestion, posted back as returned to log them.
+#[derive(Debug, Deserialize, Validate)]
+pub struct DietSuggestionLogInput {
+    pub date: NaiveDate,
+    pub username: String,
+    pub meal_of_day_id: Option<Uuid>,
+    pub meal_of_day_slug: Option<String>,
+    pub items: Vec<DietSuggestionItemInput>,
+}
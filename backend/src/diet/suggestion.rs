@@ -0,0 +1,485 @@
+use chrono::prelude::*;
+use futures::TryStreamExt;
+use rust_decimal::prelude::*;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+use sqlx::{FromRow, PgPool, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::food::usage::push_food_usage;
+
+use super::model::Diet;
+
+pub const DEFAULT_TOLERANCE_PCT: i32 = 10;
+pub const DEFAULT_SUGGESTION_LIMIT: usize = 10;
+const FOOD_CANDIDATES: i64 = 12;
+const MEAL_CANDIDATES: i64 = 12;
+// Pairs are only tried among the most frequent foods to keep the search small.
+const PAIR_CANDIDATES: usize = 8;
+// A saved meal is suggested, and can be logged, at half to double its size.
+pub const MEAL_SCALE_MIN: Decimal = dec!(0.5);
+pub const MEAL_SCALE_MAX: Decimal = dec!(2);
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct Macros {
+    pub energy: Decimal,
+    pub protein: Decimal,
+    pub carbohydrate: Decimal,
+    pub fat: Decimal,
+}
+
+impl Macros {
+    fn values(&self) -> [Decimal; 4] {
+        [self.energy, self.protein, self.carbohydrate, self.fat]
+    }
+    fn scaled(&self, scale: Decimal) -> Self {
+        Self {
+            energy: self.energy * scale,
+            protein: self.protein * scale,
+            carbohydrate: self.carbohydrate * scale,
+            fat: self.fat * scale,
+        }
+    }
+    fn add(&self, other: &Self) -> Self {
+        Self {
+            energy: self.energy + other.energy,
+            protein: self.protein + other.protein,
+            carbohydrate: self.carbohydrate + other.carbohydrate,
+            fat: self.fat + other.fat,
+        }
+    }
+    pub fn sub(&self, other: &Self) -> Self {
+        self.add(&other.scaled(dec!(-1)))
+    }
+    fn round(&self) -> Self {
+        Self {
+            energy: self.energy.round(),
+            protein: self.protein.round_dp(1),
+            carbohydrate: self.carbohydrate.round_dp(1),
+            fat: self.fat.round_dp(1),
+        }
+    }
+}
+
+// A frequent food per data_value (e.g. per 100g) or a saved meal as a whole.
+#[derive(Debug, Clone, FromRow)]
+pub struct SuggestionCandidate {
+    pub food_id: Option<Uuid>,
+    pub meal_id: Option<Uuid>,
+    pub name: String,
+    pub data_value: Option<i32>,
+    pub data_measurement: Option<String>,
+    pub energy: Decimal,
+    pub protein: Decimal,
+    pub carbohydrate: Decimal,
+    pub fat: Decimal,
+}
+
+impl SuggestionCandidate {
+    pub async fn frequent_foods(
+        pool: &PgPool,
+        user_id: Uuid,
+        meal_of_day_id: Option<Uuid>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let mut stream = Vec::new();
+        let mut q: QueryBuilder<'static, Postgres> = QueryBuilder::new(
+            "
+            SELECT
+                t1.id AS food_id,
+                NULL::UUID AS meal_id,
+                t1.name,
+                t1.data_value,
+                t1.data_measurement,
+                t1.energy::DECIMAL AS energy,
+                t1.protein,
+                t1.carbohydrate,
+                t1.fat
+            FROM (",
+        );
+        push_food_usage(&mut q, user_id, meal_of_day_id);
+        q.push(
+            ") t3
+            JOIN food t1 ON t1.id = t3.food_id
+            WHERE
+                t1.energy > 0
+            ORDER BY
+                t3.log_count DESC,
+                t3.last_logged_at DESC
+            LIMIT ",
+        );
+        q.push_bind(FOOD_CANDIDATES);
+        let mut rows = q.build_query_as::<Self>().fetch(pool);
+        while let Some(row) = rows.try_next().await? {
+            stream.push(row);
+        }
+        Ok(stream)
+    }
+    pub async fn saved_meals(pool: &PgPool, user_id: &Uuid) -> Result<Vec<Self>, sqlx::Error> {
+        let mut stream = Vec::new();
+        let mut rows = sqlx::query_as(
+            "
+            SELECT
+                NULL::UUID AS food_id,
+                t1.id AS meal_id,
+                t1.name,
+                NULL::INTEGER AS data_value,
+                NULL::VARCHAR AS data_measurement,
+                SUM(t2.quantity * COALESCE(t3.energy, t2.energy)) AS energy,
+                SUM(t2.quantity * COALESCE(t3.protein, t2.protein)) AS protein,
+                SUM(t2.quantity * COALESCE(t3.carbohydrate, t2.carbohydrate)) AS carbohydrate,
+                SUM(t2.quantity * COALESCE(t3.fat, t2.fat)) AS fat
+            FROM
+                meal t1
+                JOIN meal_food t2 ON t2.meal_id = t1.id
+                LEFT JOIN food t3 ON t3.id = t2.food_id
+            WHERE
+                t1.user_id = $1
+            GROUP BY
+                t1.id
+            HAVING
+                SUM(t2.quantity * COALESCE(t3.energy, t2.energy)) > 0
+            ORDER BY
+                COALESCE(t1.updated_at, t1.created_at) DESC
+            LIMIT
+                $2
+            ",
+        )
+        .bind(user_id)
+        .bind(MEAL_CANDIDATES)
+        .fetch(pool);
+        while let Some(row) = rows.try_next().await? {
+            stream.push(row);
+        }
+        Ok(stream)
+    }
+    fn macros(&self) -> Macros {
+        Macros {
+            energy: self.energy,
+            protein: self.protein,
+            carbohydrate: self.carbohydrate,
+            fat: self.fat,
+        }
+    }
+    // Minimum, maximum and step of the diary quantity multiplier: 10-500g of a
+    // per-100g food, half to four servings, or half to double a saved meal.
+    fn bounds(&self) -> (Decimal, Decimal, Decimal) {
+        match (self.meal_id, self.data_measurement.as_deref()) {
+            (Some(_), _) => (MEAL_SCALE_MIN, MEAL_SCALE_MAX, dec!(0.25)),
+            (None, Some("g" | "ml")) => (dec!(0.1), dec!(5), dec!(0.05)),
+            _ => (dec!(0.5), dec!(4), dec!(0.5)),
+        }
+    }
+    fn fit(&self, scale: Decimal) -> Decimal {
+        let (min, max, step) = self.bounds();
+        ((scale / step).round() * step).clamp(min, max)
+    }
+    fn item(&self, scale: Decimal) -> SuggestionItem {
+        SuggestionItem {
+            food_id: self.food_id,
+            meal_id: self.meal_id,
+            name: self.name.clone(),
+            scale: scale.normalize(),
+            quantity: self
+                .data_value
+                .map(|value| (scale * Decimal::from(value)).normalize()),
+            data_measurement: self.data_measurement.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SuggestionItem {
+    pub food_id: Option<Uuid>,
+    pub meal_id: Option<Uuid>,
+    pub name: String,
+    // The diary quantity multiplier, what the log endpoint expects.
+    pub scale: Decimal,
+    // The same portion in the food's own measurement, e.g. grams.
+    pub quantity: Option<Decimal>,
+    pub data_measurement: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DietSuggestion {
+    pub items: Vec<SuggestionItem>,
+    #[serde(flatten)]
+    pub macros: Macros,
+    // Mean distance from the remaining budget, as a fraction of the day's target.
+    pub score: Decimal,
+    pub within_tolerance: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DietSuggestionList {
+    pub username: String,
+    pub date: NaiveDate,
+    pub target: Macros,
+    pub eaten: Macros,
+    pub remaining: Macros,
+    pub tolerance_pct: i32,
+    pub results: Vec<DietSuggestion>,
+}
+
+// Errors are weighted by the day's target rather than what remains, so a macro
+// that is already used up still penalises overshooting it.
+fn weights(target: &Macros) -> [Decimal; 4] {
+    target.values().map(|value| {
+        if value > Decimal::ZERO {
+            Decimal::ONE / (value * value)
+        } else {
+            Decimal::ZERO
+        }
+    })
+}
+
+fn dot(a: &[Decimal; 4], b: &[Decimal; 4], w: &[Decimal; 4]) -> Decimal {
+    (0..4).map(|i| a[i] * b[i] * w[i]).sum()
+}
+
+fn solve_single(a: &Macros, remaining: &Macros, w: &[Decimal; 4]) -> Option<Decimal> {
+    let (a, r) = (a.values(), remaining.values());
+    let den = dot(&a, &a, w);
+    (den > Decimal::ZERO).then(|| dot(&a, &r, w) / den)
+}
+
+fn solve_pair(
+    a: &Macros,
+    b: &Macros,
+    remaining: &Macros,
+    w: &[Decimal; 4],
+) -> Option<(Decimal, Decimal)> {
+    let (a, b, r) = (a.values(), b.values(), remaining.values());
+    let (aa, ab, bb) = (dot(&a, &a, w), dot(&a, &b, w), dot(&b, &b, w));
+    let det = aa * bb - ab * ab;
+    if det.abs() <= aa * bb * dec!(0.0001) {
+        return None;
+    }
+    let (ar, br) = (dot(&a, &r, w), dot(&b, &r, w));
+    Some(((ar * bb - ab * br) / det, (aa * br - ab * ar) / det))
+}
+
+fn evaluate(
+    items: Vec<SuggestionItem>,
+    macros: Macros,
+    target: &Macros,
+    remaining: &Macros,
+    tolerance: Decimal,
+) -> DietSuggestion {
+    let (fitted, goal, day) = (macros.values(), remaining.values(), target.values());
+    let mut error = Decimal::ZERO;
+    let mut counted = 0;
+    let mut within_tolerance = true;
+    for i in 0..4 {
+        if day[i] <= Decimal::ZERO {
+            continue;
+        }
+        let miss = (fitted[i] - goal[i]).abs() / day[i];
+        error += miss;
+        counted += 1;
+        within_tolerance &= miss <= tolerance;
+    }
+    let score = if counted > 0 {
+        error / Decimal::from(counted)
+    } else {
+        Decimal::ZERO
+    };
+    DietSuggestion {
+        items,
+        macros: macros.round(),
+        score: score.round_dp(4),
+        within_tolerance,
+    }
+}
+
+pub fn suggest(
+    candidates: &[SuggestionCandidate],
+    target: &Macros,
+    remaining: &Macros,
+    tolerance_pct: i32,
+    limit: usize,
+) -> Vec<DietSuggestion> {
+    let w = weights(target);
+    let tolerance = Decimal::from(tolerance_pct) / dec!(100);
+    let mut results = Vec::new();
+    for candidate in candidates {
+        let Some(scale) = solve_single(&candidate.macros(), remaining, &w) else {
+            continue;
+        };
+        let scale = candidate.fit(scale);
+        let macros = candidate.macros().scaled(scale);
+        let items = vec![candidate.item(scale)];
+        results.push(evaluate(items, macros, target, remaining, tolerance));
+    }
+    let foods: Vec<_> = candidates
+        .iter()
+        .filter(|candidate| candidate.food_id.is_some())
+        .take(PAIR_CANDIDATES)
+        .collect();
+    for (i, a) in foods.iter().enumerate() {
+        for b in &foods[i + 1..] {
+            let Some((scale_a, scale_b)) = solve_pair(&a.macros(), &b.macros(), remaining, &w)
+            else {
+                continue;
+            };
+            // A negative portion means the pair pulls in opposite directions.
+            if scale_a <= Decimal::ZERO || scale_b <= Decimal::ZERO {
+                continue;
+            }
+            let (scale_a, scale_b) = (a.fit(scale_a), b.fit(scale_b));
+            let macros = a.macros().scaled(scale_a).add(&b.macros().scaled(scale_b));
+            let items = vec![a.item(scale_a), b.item(scale_b)];
+            results.push(evaluate(items, macros, target, remaining, tolerance));
+        }
+    }
+    results.sort_by(|a, b| {
+        b.within_tolerance
+            .cmp(&a.within_tolerance)
+            .then(a.score.cmp(&b.score))
+    });
+    results.truncate(limit);
+    results
+}
+
+// Logs a suggestion in one statement: a food item becomes one diary row, a saved
+// meal one row per meal item with its quantity scaled, capped to fit the column.
+pub async fn log_suggestion(
+    pool: &PgPool,
+    user_id: Uuid,
+    date: NaiveDate,
+    meal_of_day_id: Uuid,
+    items: &[(Option<Uuid>, Option<Uuid>, Decimal)],
+    created_by_id: Uuid,
+) -> Result<Vec<Diet>, sqlx::Error> {
+    let food_id_list: Vec<Option<Uuid>> = items.iter().map(|item| item.0).collect();
+    let meal_id_list: Vec<Option<Uuid>> = items.iter().map(|item| item.1).collect();
+    let scale_list: Vec<Decimal> = items.iter().map(|item| item.2).collect();
+    let query = sqlx::query_as(
+        "
+        INSERT INTO
+            food_log (
+                date,
+                user_id,
+                meal_of_day_id,
+                created_by_id,
+                food_id,
+                quantity,
+                label,
+                energy,
+                fat,
+                saturates,
+                carbohydrate,
+                sugars,
+                fibre,
+                protein,
+                salt
+            )
+        SELECT
+            $1,
+            $2,
+            $3,
+            $4,
+            COALESCE(t2.food_id, t1.food_id),
+            LEAST(GREATEST(ROUND(t1.scale * COALESCE(t2.quantity, 1), 2), 0.01), 999.99),
+            t2.label,
+            t2.energy,
+            t2.fat,
+            t2.saturates,
+            t2.carbohydrate,
+            t2.sugars,
+            t2.fibre,
+            t2.protein,
+            t2.salt
+        FROM
+            UNNEST($5::UUID[], $6::UUID[], $7::DECIMAL[]) t1 (food_id, meal_id, scale)
+            LEFT JOIN meal_food t2 ON t2.meal_id = t1.meal_id
+        RETURNING
+            *
+        ",
+    )
+    .bind(date)
+    .bind(user_id)
+    .bind(meal_of_day_id)
+    .bind(created_by_id)
+    .bind(food_id_list)
+    .bind(meal_id_list)
+    .bind(scale_list)
+    .fetch_all(pool)
+    .await?;
+    Ok(query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn food(
+        name: &str,
+        energy: i64,
+        protein: Decimal,
+        carbohydrate: Decimal,
+        fat: Decimal,
+    ) -> SuggestionCandidate {
+        SuggestionCandidate {
+            food_id: Some(Uuid::new_v4()),
+            meal_id: None,
+            name: name.to_string(),
+            data_value: Some(100),
+            data_measurement: Some(String::from("g")),
+            energy: Decimal::from(energy),
+            protein,
+            carbohydrate,
+            fat,
+        }
+    }
+
+    fn target() -> Macros {
+        Macros {
+            energy: dec!(2500),
+            protein: dec!(180),
+            carbohydrate: dec!(250),
+            fat: dec!(80),
+        }
+    }
+
+    #[test]
+    fn test_single_food_portion_fills_remaining() {
+        let chicken = food("Chicken", 165, dec!(31), dec!(0), dec!(3.6));
+        let remaining = chicken.macros().scaled(dec!(2));
+        let results = suggest(&[chicken], &target(), &remaining, 10, 10);
+        assert_eq!(results[0].items[0].scale, dec!(2));
+        assert_eq!(results[0].items[0].quantity, Some(dec!(200)));
+        assert_eq!(results[0].score, Decimal::ZERO);
+        assert!(results[0].within_tolerance);
+    }
+
+    #[test]
+    fn test_pair_ranks_above_singles() {
+        let chicken = food("Chicken", 165, dec!(31), dec!(0), dec!(3.6));
+        let rice = food("Rice", 130, dec!(2.7), dec!(28), dec!(0.3));
+        let remaining = chicken
+            .macros()
+            .scaled(dec!(1.5))
+            .add(&rice.macros().scaled(dec!(2.5)));
+        let results = suggest(&[chicken, rice], &target(), &remaining, 10, 10);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].items.len(), 2);
+        assert_eq!(results[0].items[0].scale, dec!(1.5));
+        assert_eq!(results[0].items[1].scale, dec!(2.5));
+        assert!(results[0].score < results[1].score);
+    }
+
+    #[test]
+    fn test_portions_are_clamped() {
+        let oil = food("Oil", 884, dec!(0), dec!(0), dec!(100));
+        let remaining = Macros {
+            energy: dec!(2000),
+            protein: dec!(150),
+            carbohydrate: dec!(200),
+            fat: dec!(60),
+        };
+        let results = suggest(&[oil], &target(), &remaining, 10, 10);
+        let scale = results[0].items[0].scale;
+        assert!(scale >= dec!(0.1) && scale <= dec!(5));
+        assert!(!results[0].within_tolerance);
+    }
+}
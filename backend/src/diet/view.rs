@@ -3,33 +3,47 @@ use axum::{
     http::StatusCode,
     Extension, Json,
 };
-use chrono::prelude::*;
+use chrono::{prelude::*, Duration};
 use rust_decimal::Decimal;
 use serde_json::{json, Value};
 use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::{
+    diet_target::model::DietTarget,
     error::AppError,
     extractor::JsonExtractor,
+    fasting::model::EatingWindow,
     food::model::Food,
     meal_food::model::MealFood,
     meal_of_day::model::MealOfDay,
     middleware::RequestUser,
+    moderation::model::is_visible,
+    planned_food::model::PlannedTotal,
     user::model::User,
     util::{
+        datetime::utc_offset,
         extract::{IdRange, UsernameDateRange},
         permission::user_privacy_check,
         query::QueryParams,
     },
+    water_log::model::WaterLog,
     AppState,
 };
 
 use super::{
     diet_day_json::DietDayJSON,
     diet_meal_json::DietMealJSON,
-    model::{DayTotal, Diet, DietDay, DietDayTotal, DietDetail, DietSerializer},
-    serializer::{DietCreateInput, DietFromMealInput, DietUpdateInput},
+    meal_time::{DietMealTime, DietMealTimeSummary, DEFAULT_MEAL_TIME_DAYS},
+    model::{DayTotal, Diet, DietDay, DietDayTotal, DietDetail, DietInput, DietSerializer},
+    serializer::{
+        validate_eaten_at, DietCreateInput, DietFromMealInput, DietMealTimeParams,
+        DietQuickAddInput, DietSuggestionLogInput, DietSuggestionParams, DietUpdateInput,
+    },
+    suggestion::{
+        log_suggestion, suggest, DietSuggestionList, Macros, SuggestionCandidate,
+        DEFAULT_SUGGESTION_LIMIT, DEFAULT_TOLERANCE_PCT, MEAL_SCALE_MAX, MEAL_SCALE_MIN,
+    },
 };
 
 pub async fn diet_list_view(
@@ -56,6 +70,7 @@ pub async fn diet_create_view(
         )))?;
     let food = Food::get_opt(&state.pool, &data.food_id)
         .await?
+        .filter(|food| is_visible(&request_user, &food.created_by_id, &food.review_status))
         .ok_or(AppError::APIBadRequest(format!(
             "Food {} not found",
             data.food_id
@@ -81,19 +96,101 @@ pub async fn diet_create_view(
         "srv" => data.quantity * Decimal::new(1, 0),
         _ => data.quantity * Decimal::new(1, 0),
     };
-    let result = Diet::create(
-        &state.pool,
-        data.date,
-        user.id,
+    if !validate_eaten_at(data.date, data.eaten_at) {
+        return Err(AppError::BadRequestMessage(String::from(
+            "Eaten at must be within a day of the diary date.",
+        )));
+    }
+    let input = DietInput {
+        date: data.date,
+        user_id: user.id,
         meal_of_day_id,
-        food.id,
+        food_id: food.id,
         quantity,
-        request_user.id,
-    )
-    .await?;
+        eaten_at: data.eaten_at,
+    };
+    let result = Diet::create(&state.pool, input, request_user.id).await?;
+    Ok((StatusCode::CREATED, Json(result)))
+}
+
+pub async fn diet_quick_add_create_view(
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    JsonExtractor(data): JsonExtractor<DietQuickAddInput>,
+) -> Result<(StatusCode, Json<Diet>), AppError> {
+    let user = User::get_from_username(&state.pool, &data.username)
+        .await?
+        .ok_or(AppError::APIBadRequest(format!(
+            "User {} not found",
+            data.username
+        )))?;
+    if request_user.id != user.id {
+        return Err(AppError::BadRequestMessage(String::from(
+            "You are unable to add to another users food diary.",
+        )));
+    }
+    let meal_of_day_id =
+        get_meal_of_day_id(&state, data.meal_of_day_id, &data.meal_of_day_slug).await?;
+    if !validate_eaten_at(data.date, data.eaten_at) {
+        return Err(AppError::BadRequestMessage(String::from(
+            "Eaten at must be within a day of the diary date.",
+        )));
+    }
+    let result =
+        Diet::create_quick_add(&state.pool, user.id, meal_of_day_id, &data, request_user.id)
+            .await?;
     Ok((StatusCode::CREATED, Json(result)))
 }
 
+pub async fn diet_quick_add_update_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    JsonExtractor(data): JsonExtractor<DietQuickAddInput>,
+) -> Result<Json<Diet>, AppError> {
+    let diet = Diet::get(&state.pool, &id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    if diet.food_id.is_some() {
+        return Err(AppError::NotFound);
+    }
+    if request_user.id != diet.user_id {
+        return Err(AppError::BadRequestMessage(String::from(
+            "You are unable to update another users food diary.",
+        )));
+    }
+    let meal_of_day_id =
+        get_meal_of_day_id(&state, data.meal_of_day_id, &data.meal_of_day_slug).await?;
+    if !validate_eaten_at(data.date, data.eaten_at) {
+        return Err(AppError::BadRequestMessage(String::from(
+            "Eaten at must be within a day of the diary date.",
+        )));
+    }
+    let result =
+        Diet::update_quick_add(&state.pool, diet.id, meal_of_day_id, &data, request_user.id)
+            .await?;
+    Ok(Json(result))
+}
+
+pub async fn get_meal_of_day_id(
+    state: &AppState,
+    id: Option<Uuid>,
+    slug: &Option<String>,
+) -> Result<Uuid, AppError> {
+    if let Some(id) = id {
+        return Ok(MealOfDay::get(&state.pool, &id).await?.id);
+    }
+    let slug = slug.as_ref().ok_or(AppError::BadRequest)?;
+    let meal_of_day =
+        MealOfDay::get_from_slug(&state.pool, slug)
+            .await?
+            .ok_or(AppError::APIBadRequest(format!(
+                "Meal of day {} not found",
+                slug
+            )))?;
+    Ok(meal_of_day.id)
+}
+
 pub async fn diet_detail_view(
     Path(id): Path<Uuid>,
     State(state): State<Arc<AppState>>,
@@ -124,7 +221,18 @@ pub async fn diet_update_view(
             "You are unable to update another users food diary.",
         )));
     }
-    let food = Food::get(&state.pool, &data.food_id).await?;
+    if diet.food_id.is_none() {
+        return Err(AppError::BadRequestMessage(String::from(
+            "Quick-add entries are updated through the quick-add endpoint.",
+        )));
+    }
+    let food = Food::get_opt(&state.pool, &data.food_id)
+        .await?
+        .filter(|food| is_visible(&request_user, &food.created_by_id, &food.review_status))
+        .ok_or(AppError::APIBadRequest(format!(
+            "Food {} not found",
+            data.food_id
+        )))?;
     let meal_of_day = MealOfDay::get(&state.pool, &data.meal_of_day_id).await?;
     let quantity = match food.data_measurement.as_str() {
         "g" => data.quantity * Decimal::new(1, 2),
@@ -132,17 +240,20 @@ pub async fn diet_update_view(
         "srv" => data.quantity * Decimal::new(1, 0),
         _ => data.quantity * Decimal::new(1, 0),
     };
-    let result = Diet::update(
-        &state.pool,
-        diet.id,
-        data.date,
-        user.id,
-        meal_of_day.id,
-        food.id,
+    if !validate_eaten_at(data.date, data.eaten_at) {
+        return Err(AppError::BadRequestMessage(String::from(
+            "Eaten at must be within a day of the diary date.",
+        )));
+    }
+    let input = DietInput {
+        date: data.date,
+        user_id: user.id,
+        meal_of_day_id: meal_of_day.id,
+        food_id: food.id,
         quantity,
-        request_user.id,
-    )
-    .await?;
+        eaten_at: data.eaten_at,
+    };
+    let result = Diet::update(&state.pool, diet.id, input, request_user.id).await?;
     Ok(Json(result))
 }
 
@@ -168,23 +279,85 @@ pub async fn diet_day_view(
     user_privacy_check(&state.pool, &request_user, &user).await?;
     let meal_of_day_list = MealOfDay::all(&state.pool).await?;
     let diet_list = DietSerializer::all(&state.pool, &user.id, &date).await?;
-    let query = DietDay::build_dataset(username, date, meal_of_day_list, diet_list).await;
+    let mut query = DietDay::build_dataset(username, date, meal_of_day_list, diet_list).await;
+    query.water_ml = WaterLog::day_total(&state.pool, &user.id, &date).await?;
+    query.target_water_ml = DietTarget::get_from_user_id_date(&state.pool, &user.id, &date)
+        .await?
+        .map(|target| target.water_ml);
+    query.planned = PlannedTotal::day(&state.pool, &query.username, &date).await?;
     Ok(Json(query))
 }
 
+pub async fn diet_meal_time_list_view(
+    Path((username, date)): Path<(String, NaiveDate)>,
+    Query(params): Query<DietMealTimeParams>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<DietMealTimeSummary>, AppError> {
+    let user = User::get_from_username(&state.pool, &username)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    user_privacy_check(&state.pool, &request_user, &user).await?;
+    let date_from = params
+        .date_from
+        .unwrap_or(date - Duration::days(DEFAULT_MEAL_TIME_DAYS - 1));
+    if date_from > date {
+        return Err(AppError::BadRequest);
+    }
+    let offset = utc_offset(params.utc_offset).ok_or(AppError::BadRequest)?;
+    let eating_window = EatingWindow::get_from_user_id(&state.pool, &user.id).await?;
+    let results = DietMealTime::all(
+        &state.pool,
+        &user.id,
+        date_from,
+        date,
+        eating_window.as_ref(),
+        offset,
+    )
+    .await?;
+    Ok(Json(DietMealTimeSummary {
+        username,
+        date_from,
+        date_to: date,
+        eating_window,
+        results,
+    }))
+}
+
 pub async fn diet_day_total_list_view(
     Path((username, date)): Path<(String, NaiveDate)>,
     State(state): State<Arc<AppState>>,
 ) -> Result<Json<Vec<DietDayTotal>>, AppError> {
-    let query = DietDayTotal::stream(&state.pool, &username, &date).await?;
+    let mut query = DietDayTotal::stream(&state.pool, &username, &date).await?;
+    let (start, end) = iso_week(&date);
+    // Days that are planned but have nothing eaten yet still get a row.
+    for planned in PlannedTotal::by_date(&state.pool, &username, &start, &end).await? {
+        match query.iter_mut().find(|day| day.date == planned.date) {
+            Some(day) => day.planned = planned,
+            None => query.push(DietDayTotal {
+                date: planned.date,
+                planned,
+                ..Default::default()
+            }),
+        }
+    }
+    query.sort_by_key(|day| day.date);
     Ok(Json(query))
 }
 
+fn iso_week(date: &NaiveDate) -> (NaiveDate, NaiveDate) {
+    let start = date.week(Weekday::Mon).first_day();
+    (start, start + Duration::days(6))
+}
+
 pub async fn diet_week_total_detail_view(
     Path((username, date)): Path<(String, NaiveDate)>,
     State(state): State<Arc<AppState>>,
 ) -> Result<Json<DietDayTotal>, AppError> {
-    let query = DietDayTotal::week_total(&state.pool, &username, &date).await?;
+    let mut query = DietDayTotal::week_total(&state.pool, &username, &date).await?;
+    let (start, end) = iso_week(&date);
+    let planned = PlannedTotal::by_date(&state.pool, &username, &start, &end).await?;
+    query.planned = PlannedTotal::total(start, &planned);
     Ok(Json(query))
 }
 
@@ -192,7 +365,10 @@ pub async fn diet_week_average_detail_view(
     Path((username, date)): Path<(String, NaiveDate)>,
     State(state): State<Arc<AppState>>,
 ) -> Result<Json<DietDayTotal>, AppError> {
-    let query = DietDayTotal::week_average(&state.pool, &username, &date).await?;
+    let mut query = DietDayTotal::week_average(&state.pool, &username, &date).await?;
+    let (start, end) = iso_week(&date);
+    let planned = PlannedTotal::by_date(&state.pool, &username, &start, &end).await?;
+    query.planned = PlannedTotal::average(start, &planned);
     Ok(Json(query))
 }
 
@@ -268,3 +444,145 @@ pub async fn diet_day_json_view(
     }
     Ok(Json(query))
 }
+
+pub async fn diet_suggestion_list_view(
+    Path((username, date)): Path<(String, NaiveDate)>,
+    Query(params): Query<DietSuggestionParams>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<DietSuggestionList>, AppError> {
+    let user = User::get_from_username(&state.pool, &username)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    if request_user.id != user.id {
+        return Err(AppError::NotFound);
+    }
+    let diet_target = DietTarget::get_from_user_id_date(&state.pool, &user.id, &date)
+        .await?
+        .ok_or(AppError::BadRequestMessage(String::from(
+            "Set a diet target for this date to get suggestions.",
+        )))?;
+    let target = Macros {
+        energy: Decimal::from(diet_target.energy),
+        protein: diet_target.protein,
+        carbohydrate: diet_target.carbohydrate,
+        fat: diet_target.fat,
+    };
+    let eaten = DietDayTotal::stream(&state.pool, &username, &date)
+        .await?
+        .into_iter()
+        .find(|day| day.date == Some(date))
+        .map(|day| Macros {
+            energy: day.energy.unwrap_or_default(),
+            protein: day.protein.unwrap_or_default(),
+            carbohydrate: day.carbohydrate.unwrap_or_default(),
+            fat: day.fat.unwrap_or_default(),
+        })
+        .unwrap_or_default();
+    let remaining = target.sub(&eaten);
+    let tolerance_pct = params
+        .tolerance
+        .unwrap_or(DEFAULT_TOLERANCE_PCT)
+        .clamp(1, 100);
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_SUGGESTION_LIMIT)
+        .clamp(1, 50);
+    let results = if remaining.energy > Decimal::ZERO {
+        let mut candidates =
+            SuggestionCandidate::frequent_foods(&state.pool, user.id, params.meal_of_day_id)
+                .await?;
+        candidates.extend(SuggestionCandidate::saved_meals(&state.pool, &user.id).await?);
+        suggest(&candidates, &target, &remaining, tolerance_pct, limit)
+    } else {
+        Vec::new()
+    };
+    Ok(Json(DietSuggestionList {
+        username,
+        date,
+        target,
+        eaten,
+        remaining,
+        tolerance_pct,
+        results,
+    }))
+}
+
+pub async fn diet_suggestion_log_view(
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    JsonExtractor(data): JsonExtractor<DietSuggestionLogInput>,
+) -> Result<(StatusCode, Json<Vec<Diet>>), AppError> {
+    let user = User::get_from_username(&state.pool, &data.username)
+        .await?
+        .ok_or(AppError::APIBadRequest(format!(
+            "User {} not found",
+            data.username
+        )))?;
+    if request_user.id != user.id {
+        return Err(AppError::BadRequestMessage(String::from(
+            "You are unable to add to another users food diary.",
+        )));
+    }
+    let meal_of_day_id =
+        get_meal_of_day_id(&state, data.meal_of_day_id, &data.meal_of_day_slug).await?;
+    if data.items.is_empty() || data.items.len() > 10 {
+        return Err(AppError::BadRequestMessage(String::from(
+            "Choose between 1 and 10 items.",
+        )));
+    }
+    let mut items = Vec::new();
+    for item in &data.items {
+        if item.scale < Decimal::new(1, 2) || item.scale > Decimal::new(99999, 2) {
+            return Err(AppError::BadRequestMessage(String::from(
+                "Scale must be between 0.01 and 999.99.",
+            )));
+        }
+        match (item.food_id, item.meal_id) {
+            (Some(food_id), None) => {
+                Food::get_opt(&state.pool, &food_id)
+                    .await?
+                    .filter(|food| {
+                        is_visible(&request_user, &food.created_by_id, &food.review_status)
+                    })
+                    .ok_or(AppError::APIBadRequest(format!(
+                        "Food {} not found",
+                        food_id
+                    )))?;
+            }
+            (None, Some(meal_id)) => {
+                if item.scale < MEAL_SCALE_MIN || item.scale > MEAL_SCALE_MAX {
+                    return Err(AppError::BadRequestMessage(format!(
+                        "Meal scale must be between {} and {}.",
+                        MEAL_SCALE_MIN, MEAL_SCALE_MAX
+                    )));
+                }
+                if MealFood::food_from_meal(&state.pool, &meal_id)
+                    .await?
+                    .is_empty()
+                {
+                    return Err(AppError::APIBadRequest(format!(
+                        "Meal {} has no food",
+                        meal_id
+                    )));
+                }
+            }
+            _ => {
+                return Err(AppError::BadRequestMessage(String::from(
+                    "Each item needs either a food or a meal.",
+                )))
+            }
+        }
+        items.push((item.food_id, item.meal_id, item.scale));
+    }
+    let query = log_suggestion(
+        &state.pool,
+        user.id,
+        data.date,
+        meal_of_day_id,
+        &items,
+        request_user.id,
+    )
+    .await?;
+    Ok((StatusCode::CREATED, Json(query)))
+}
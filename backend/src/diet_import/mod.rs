@@ -0,0 +1,4 @@
+pub mod model;
+pub mod parser;
+pub mod router;
+pub mod view;
@@ -0,0 +1,456 @@
+use chrono::prelude::*;
+use rust_decimal::prelude::*;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+use sqlx::{PgConnection, PgPool, Row};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::{
+    meal_of_day::model::MealOfDay, middleware::RequestUser,
+    moderation::model::initial_review_status,
+};
+
+use super::parser::{ImportRow, ImportSource, ParsedDiary, SkippedRow};
+
+const MIN_QUANTITY: Decimal = dec!(0.01);
+const MAX_QUANTITY: Decimal = dec!(999.99);
+
+#[derive(Debug, Serialize)]
+pub struct ImportFood {
+    pub name: String,
+    pub brand: String,
+    pub row_count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnmatchedMeal {
+    pub meal: String,
+    pub row_count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DietImportReport {
+    pub source: ImportSource,
+    pub dry_run: bool,
+    pub row_count: usize,
+    pub date_from: Option<NaiveDate>,
+    pub date_to: Option<NaiveDate>,
+    pub entry_count: usize,
+    pub duplicate_count: usize,
+    pub matched_food_count: usize,
+    pub new_foods: Vec<ImportFood>,
+    pub unmatched_meals: Vec<UnmatchedMeal>,
+    pub skipped: Vec<SkippedRow>,
+}
+
+pub struct MealMatcher(HashMap<String, Uuid>);
+
+impl MealMatcher {
+    pub fn new(meals: &[MealOfDay]) -> Self {
+        let mut map = HashMap::new();
+        for meal in meals {
+            map.insert(slug::slugify(&meal.name), meal.id);
+            map.insert(meal.slug.clone(), meal.id);
+        }
+        Self(map)
+    }
+    // Other apps use plural meal names ("Snacks"), so try both forms.
+    pub fn find(&self, meal: &str) -> Option<Uuid> {
+        let key = slug::slugify(meal);
+        self.0
+            .get(&key)
+            .or_else(|| key.strip_suffix('s').and_then(|key| self.0.get(key)))
+            .or_else(|| self.0.get(&format!("{}s", key)))
+            .copied()
+    }
+}
+
+pub fn scale_quantity(row_energy: i32, food_energy: i32) -> Decimal {
+    if row_energy <= 0 || food_energy <= 0 {
+        return Decimal::ONE;
+    }
+    (Decimal::from(row_energy) / Decimal::from(food_energy))
+        .round_dp(2)
+        .clamp(MIN_QUANTITY, MAX_QUANTITY)
+}
+
+#[derive(Clone, Copy)]
+struct ImportedFood {
+    id: Uuid,
+    energy: i32,
+    index: Option<usize>,
+}
+
+struct FoodResolver {
+    source: ImportSource,
+    created_by_id: Uuid,
+    // Brands and foods created by an import are moderated like any other submission.
+    review_status: &'static str,
+    fallback_brand_id: Option<Uuid>,
+    brands: HashMap<String, Option<Uuid>>,
+    foods: HashMap<(Uuid, String), ImportedFood>,
+    new_foods: Vec<ImportFood>,
+}
+
+impl FoodResolver {
+    async fn fallback_brand(&mut self, conn: &mut PgConnection) -> Result<Uuid, sqlx::Error> {
+        if let Some(id) = self.fallback_brand_id {
+            return Ok(id);
+        }
+        let name = self.source.brand_name();
+        let slug = slug::slugify(name);
+        let existing = sqlx::query("SELECT id FROM food_brand WHERE slug = $1")
+            .bind(&slug)
+            .fetch_optional(&mut *conn)
+            .await?;
+        let id = match existing {
+            Some(row) => row.get("id"),
+            None => sqlx::query(
+                "
+                INSERT INTO
+                    food_brand (name, slug, created_by_id, review_status)
+                VALUES
+                    ($1, $2, $3, $4)
+                RETURNING
+                    id
+                ",
+            )
+            .bind(name)
+            .bind(&slug)
+            .bind(self.created_by_id)
+            .bind(self.review_status)
+            .fetch_one(&mut *conn)
+            .await?
+            .get("id"),
+        };
+        self.fallback_brand_id = Some(id);
+        Ok(id)
+    }
+
+    async fn brand(
+        &mut self,
+        conn: &mut PgConnection,
+        name: &str,
+    ) -> Result<Option<Uuid>, sqlx::Error> {
+        let key = name.to_lowercase();
+        if let Some(id) = self.brands.get(&key) {
+            return Ok(*id);
+        }
+        let id = sqlx::query(
+            "
+            SELECT
+                id
+            FROM
+                food_brand
+            WHERE
+                LOWER(name) = $1
+                AND (
+                    review_status = 'approved'
+                    OR created_by_id = $2
+                )
+            ",
+        )
+        .bind(&key)
+        .bind(self.created_by_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .map(|row| row.get("id"));
+        self.brands.insert(key, id);
+        Ok(id)
+    }
+
+    // Branded rows use the existing brand when there is one; everything else is filed
+    // under a brand named after the app the diary came from. Only approved brands and
+    // foods, or the importing user's own submissions, are reused.
+    async fn resolve(
+        &mut self,
+        conn: &mut PgConnection,
+        row: &ImportRow,
+    ) -> Result<ImportedFood, sqlx::Error> {
+        let fallback = self.source.brand_name();
+        let (brand_id, brand_name, name) = match &row.brand {
+            Some(brand) => match self.brand(conn, brand).await? {
+                Some(id) => (id, brand.as_str(), row.name.clone()),
+                None => (
+                    self.fallback_brand(conn).await?,
+                    fallback,
+                    format!("{} - {}", brand, row.name),
+                ),
+            },
+            None => (self.fallback_brand(conn).await?, fallback, row.name.clone()),
+        };
+        let key = (brand_id, name.to_lowercase());
+        if let Some(food) = self.foods.get(&key).copied() {
+            if let Some(index) = food.index {
+                self.new_foods[index].row_count += 1;
+            }
+            return Ok(food);
+        }
+
+        let existing = sqlx::query(
+            "
+            SELECT
+                id,
+                energy
+            FROM
+                food
+            WHERE
+                brand_id = $1
+                AND LOWER(name) = $2
+                AND (
+                    review_status = 'approved'
+                    OR created_by_id = $3
+                )
+            ORDER BY
+                created_at
+            LIMIT
+                1
+            ",
+        )
+        .bind(brand_id)
+        .bind(&key.1)
+        .bind(self.created_by_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+        let food = match existing {
+            Some(food) => ImportedFood {
+                id: food.get("id"),
+                energy: food.get("energy"),
+                index: None,
+            },
+            None => {
+                let id = self.create_food(conn, brand_id, &name, row).await?;
+                self.new_foods.push(ImportFood {
+                    name: name.clone(),
+                    brand: brand_name.to_string(),
+                    row_count: 1,
+                });
+                ImportedFood {
+                    id,
+                    energy: row.energy,
+                    index: Some(self.new_foods.len() - 1),
+                }
+            }
+        };
+        self.foods.insert(key, food);
+        Ok(food)
+    }
+
+    // Imported foods are stored per serving, using the nutrients of the first row seen.
+    async fn create_food(
+        &self,
+        conn: &mut PgConnection,
+        brand_id: Uuid,
+        name: &str,
+        row: &ImportRow,
+    ) -> Result<Uuid, sqlx::Error> {
+        let mut slug = slug::slugify(name);
+        let slug_taken: bool = sqlx::query("SELECT EXISTS (SELECT 1 FROM food WHERE slug = $1)")
+            .bind(&slug)
+            .fetch_one(&mut *conn)
+            .await?
+            .get(0);
+        if slug_taken {
+            slug = format!("{}-{}", slug, &Uuid::new_v4().simple().to_string()[..8]);
+        }
+        let query = sqlx::query(
+            "
+            INSERT INTO
+                food (
+                    name,
+                    slug,
+                    brand_id,
+                    data_value,
+                    data_measurement,
+                    energy,
+                    fat,
+                    saturates,
+                    carbohydrate,
+                    sugars,
+                    fibre,
+                    protein,
+                    salt,
+                    created_by_id,
+                    review_status
+                )
+            VALUES
+                ($1, $2, $3, 1, 'srv', $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            RETURNING
+                id
+            ",
+        )
+        .bind(name)
+        .bind(slug)
+        .bind(brand_id)
+        .bind(row.energy)
+        .bind(row.fat)
+        .bind(row.saturates)
+        .bind(row.carbohydrate)
+        .bind(row.sugars)
+        .bind(row.fibre)
+        .bind(row.protein)
+        .bind(row.salt)
+        .bind(self.created_by_id)
+        .bind(self.review_status)
+        .fetch_one(&mut *conn)
+        .await?;
+        Ok(query.get("id"))
+    }
+}
+
+// The whole import runs in one transaction; a dry run rolls it back so the report
+// reflects exactly what a real run would create.
+pub async fn import_diary(
+    pool: &PgPool,
+    request_user: &RequestUser,
+    parsed: ParsedDiary,
+    dry_run: bool,
+) -> Result<DietImportReport, sqlx::Error> {
+    let user_id = request_user.id;
+    let meals = MealOfDay::all(pool).await?;
+    let matcher = MealMatcher::new(&meals);
+    let mut resolver = FoodResolver {
+        source: parsed.source,
+        created_by_id: user_id,
+        review_status: initial_review_status(request_user),
+        fallback_brand_id: None,
+        brands: HashMap::new(),
+        foods: HashMap::new(),
+        new_foods: Vec::new(),
+    };
+
+    let row_count = parsed.rows.len() + parsed.skipped.len();
+    let date_from = parsed.rows.iter().map(|row| row.date).min();
+    let date_to = parsed.rows.iter().map(|row| row.date).max();
+
+    let mut unmatched_meals: Vec<UnmatchedMeal> = Vec::new();
+    let mut date_list = Vec::new();
+    let mut meal_of_day_id_list = Vec::new();
+    let mut food_id_list = Vec::new();
+    let mut quantity_list = Vec::new();
+    let mut import_key_list = Vec::new();
+
+    let mut tx = pool.begin().await?;
+    for row in &parsed.rows {
+        let Some(meal_of_day_id) = matcher.find(&row.meal) else {
+            match unmatched_meals.iter_mut().find(|m| m.meal == row.meal) {
+                Some(unmatched) => unmatched.row_count += 1,
+                None => unmatched_meals.push(UnmatchedMeal {
+                    meal: row.meal.clone(),
+                    row_count: 1,
+                }),
+            }
+            continue;
+        };
+        let food = resolver.resolve(&mut tx, row).await?;
+        date_list.push(row.date);
+        meal_of_day_id_list.push(meal_of_day_id);
+        food_id_list.push(food.id);
+        quantity_list.push(scale_quantity(row.energy, food.energy));
+        import_key_list.push(row.import_key);
+    }
+
+    let inserted = sqlx::query(
+        "
+        INSERT INTO
+            food_log (
+                date,
+                user_id,
+                meal_of_day_id,
+                food_id,
+                quantity,
+                import_key,
+                created_by_id
+            )
+        SELECT
+            t.date,
+            $1,
+            t.meal_of_day_id,
+            t.food_id,
+            t.quantity,
+            t.import_key,
+            $1
+        FROM
+            UNNEST($2::DATE[], $3::UUID[], $4::UUID[], $5::DECIMAL[], $6::UUID[]) AS t (
+                date,
+                meal_of_day_id,
+                food_id,
+                quantity,
+                import_key
+            )
+        ON CONFLICT (user_id, import_key) DO NOTHING
+        RETURNING
+            id
+        ",
+    )
+    .bind(user_id)
+    .bind(&date_list)
+    .bind(meal_of_day_id_list)
+    .bind(food_id_list)
+    .bind(quantity_list)
+    .bind(import_key_list)
+    .fetch_all(&mut *tx)
+    .await?
+    .len();
+
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+
+    let matched_food_count = resolver
+        .foods
+        .values()
+        .filter(|food| food.index.is_none())
+        .count();
+    Ok(DietImportReport {
+        source: parsed.source,
+        dry_run,
+        row_count,
+        date_from,
+        date_to,
+        entry_count: inserted,
+        duplicate_count: date_list.len() - inserted,
+        matched_food_count,
+        new_foods: resolver.new_foods,
+        unmatched_meals,
+        skipped: parsed.skipped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meal(name: &str, slug: &str) -> MealOfDay {
+        MealOfDay {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            slug: slug.to_string(),
+            ordering: 1,
+            created_at: Utc::now(),
+            updated_at: None,
+            created_by_id: Uuid::nil(),
+            updated_by_id: None,
+        }
+    }
+
+    #[test]
+    fn test_meal_matcher() {
+        let meals = vec![meal("Breakfast", "breakfast"), meal("Snack", "snack")];
+        let matcher = MealMatcher::new(&meals);
+        assert_eq!(matcher.find("BREAKFAST"), Some(meals[0].id));
+        assert_eq!(matcher.find("Snacks"), Some(meals[1].id));
+        assert_eq!(matcher.find("Uncategorized"), None);
+    }
+
+    #[test]
+    fn test_scale_quantity() {
+        assert_eq!(scale_quantity(370, 370), dec!(1));
+        assert_eq!(scale_quantity(185, 370), dec!(0.50));
+        assert_eq!(scale_quantity(0, 370), dec!(1));
+        assert_eq!(scale_quantity(1, 1000), dec!(0.01));
+    }
+}
@@ -0,0 +1,369 @@
+use chrono::prelude::*;
+use rust_decimal::prelude::*;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::error::AppError;
+
+const MAX_NUTRIENT: Decimal = dec!(999.9);
+const MAX_SALT: Decimal = dec!(999.99);
+const MAX_NAME_LENGTH: usize = 255;
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%m/%d/%Y", "%d-%m-%Y"];
+
+const DATE_COLUMNS: &[&str] = &["date", "day"];
+const MEAL_COLUMNS: &[&str] = &["meal", "group"];
+const FOOD_COLUMNS: &[&str] = &["food name", "food"];
+const AMOUNT_COLUMNS: &[&str] = &["amount", "servings"];
+const ENERGY_COLUMNS: &[&str] = &["energy (kcal)", "calories"];
+const FAT_COLUMNS: &[&str] = &["fat (g)", "fat"];
+const SATURATES_COLUMNS: &[&str] = &["saturated (g)", "saturated fat", "saturated fat (g)"];
+const CARBOHYDRATE_COLUMNS: &[&str] = &["carbs (g)", "carbohydrates (g)", "carbohydrates"];
+const SUGARS_COLUMNS: &[&str] = &["sugars (g)", "sugar", "sugar (g)"];
+const FIBRE_COLUMNS: &[&str] = &["fiber (g)", "fiber", "fibre (g)"];
+const PROTEIN_COLUMNS: &[&str] = &["protein (g)", "protein"];
+const SODIUM_COLUMNS: &[&str] = &["sodium (mg)", "sodium"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportSource {
+    MyFitnessPal,
+    Cronometer,
+}
+
+impl ImportSource {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "myfitnesspal" | "mfp" => Some(Self::MyFitnessPal),
+            "cronometer" => Some(Self::Cronometer),
+            _ => None,
+        }
+    }
+    pub fn brand_name(&self) -> &'static str {
+        match self {
+            Self::MyFitnessPal => "MyFitnessPal",
+            Self::Cronometer => "Cronometer",
+        }
+    }
+    fn detect(columns: &Columns) -> Option<Self> {
+        if columns.find(&["food name"]).is_some() || columns.find(&["group"]).is_some() {
+            Some(Self::Cronometer)
+        } else if columns.find(&["meal"]).is_some() {
+            Some(Self::MyFitnessPal)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportRow {
+    pub line: u64,
+    pub date: NaiveDate,
+    pub meal: String,
+    pub name: String,
+    pub brand: Option<String>,
+    pub amount: String,
+    pub energy: i32,
+    pub fat: Decimal,
+    pub saturates: Decimal,
+    pub carbohydrate: Decimal,
+    pub sugars: Decimal,
+    pub fibre: Decimal,
+    pub protein: Decimal,
+    pub salt: Decimal,
+    #[serde(skip)]
+    pub import_key: Uuid,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SkippedRow {
+    pub line: u64,
+    pub reason: String,
+}
+
+#[derive(Debug)]
+pub struct ParsedDiary {
+    pub source: ImportSource,
+    pub rows: Vec<ImportRow>,
+    pub skipped: Vec<SkippedRow>,
+}
+
+struct Columns(Vec<String>);
+
+impl Columns {
+    fn find(&self, names: &[&str]) -> Option<usize> {
+        names
+            .iter()
+            .find_map(|name| self.0.iter().position(|column| column == name))
+    }
+}
+
+struct ColumnMap {
+    date: usize,
+    meal: usize,
+    food: Option<usize>,
+    amount: Option<usize>,
+    energy: usize,
+    fat: Option<usize>,
+    saturates: Option<usize>,
+    carbohydrate: Option<usize>,
+    sugars: Option<usize>,
+    fibre: Option<usize>,
+    protein: Option<usize>,
+    sodium: Option<usize>,
+}
+
+impl ColumnMap {
+    fn new(columns: &Columns) -> Result<Self, AppError> {
+        let required = |names: &[&str], label: &str| {
+            columns
+                .find(names)
+                .ok_or(AppError::BadRequestMessage(format!(
+                    "Missing required column: {}",
+                    label
+                )))
+        };
+        Ok(Self {
+            date: required(DATE_COLUMNS, "Date")?,
+            meal: required(MEAL_COLUMNS, "Meal")?,
+            food: columns.find(FOOD_COLUMNS),
+            amount: columns.find(AMOUNT_COLUMNS),
+            energy: required(ENERGY_COLUMNS, "Calories")?,
+            fat: columns.find(FAT_COLUMNS),
+            saturates: columns.find(SATURATES_COLUMNS),
+            carbohydrate: columns.find(CARBOHYDRATE_COLUMNS),
+            sugars: columns.find(SUGARS_COLUMNS),
+            fibre: columns.find(FIBRE_COLUMNS),
+            protein: columns.find(PROTEIN_COLUMNS),
+            sodium: columns.find(SODIUM_COLUMNS),
+        })
+    }
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value.trim(), format).ok())
+}
+
+fn parse_number(value: &str) -> Option<Decimal> {
+    let value = value.trim().replace(',', "");
+    if value.is_empty() {
+        return Some(Decimal::ZERO);
+    }
+    Decimal::from_str(&value).ok()
+}
+
+// Branded entries are exported as "Brand - Food name".
+pub fn split_brand(name: &str) -> (Option<String>, String) {
+    match name.split_once(" - ") {
+        Some((brand, food)) if !brand.trim().is_empty() && !food.trim().is_empty() => {
+            (Some(brand.trim().to_string()), food.trim().to_string())
+        }
+        _ => (None, name.trim().to_string()),
+    }
+}
+
+fn import_key(source: ImportSource, row: &ImportRow, occurrence: usize) -> Uuid {
+    let key = format!(
+        "{:?}|{}|{}|{}|{}|{}|{}|{}",
+        source,
+        row.date,
+        row.meal.to_lowercase(),
+        row.brand.as_deref().unwrap_or_default().to_lowercase(),
+        row.name.to_lowercase(),
+        row.amount,
+        row.energy,
+        occurrence
+    );
+    Uuid::new_v5(&Uuid::NAMESPACE_OID, key.as_bytes())
+}
+
+fn parse_row(
+    record: &csv::StringRecord,
+    map: &ColumnMap,
+    line: u64,
+) -> Result<Option<ImportRow>, String> {
+    let get = |index: usize| record.get(index).unwrap_or_default().trim();
+    let number = |index: Option<usize>, label: &str| match index {
+        Some(index) => parse_number(get(index)).ok_or(format!("Invalid number for {}", label)),
+        None => Ok(Decimal::ZERO),
+    };
+
+    let date_value = get(map.date);
+    if date_value.is_empty() {
+        return Ok(None);
+    }
+    let date = parse_date(date_value).ok_or(format!("Invalid date: {}", date_value))?;
+    let meal = get(map.meal).to_string();
+    if meal.is_empty() {
+        return Err(String::from("Missing meal"));
+    }
+    let full_name = match map.food.map(get) {
+        Some(name) if !name.is_empty() => name.to_string(),
+        Some(_) => return Err(String::from("Missing food name")),
+        None => format!("{} total", meal),
+    };
+    if full_name.chars().count() > MAX_NAME_LENGTH {
+        return Err(String::from("Food name is too long"));
+    }
+    let (brand, name) = split_brand(&full_name);
+
+    let energy = number(Some(map.energy), "calories")?.round();
+    let fat = number(map.fat, "fat")?.round_dp(1);
+    let saturates = number(map.saturates, "saturates")?.round_dp(1);
+    let carbohydrate = number(map.carbohydrate, "carbohydrate")?.round_dp(1);
+    let sugars = number(map.sugars, "sugars")?.round_dp(1);
+    let fibre = number(map.fibre, "fibre")?.round_dp(1);
+    let protein = number(map.protein, "protein")?.round_dp(1);
+    let salt = (number(map.sodium, "sodium")? * dec!(2.5) / dec!(1000)).round_dp(2);
+
+    let nutrients = [fat, saturates, carbohydrate, sugars, fibre, protein];
+    if energy.is_sign_negative() || nutrients.iter().any(|n| n.is_sign_negative()) {
+        return Err(String::from("Nutrient values must not be negative"));
+    }
+    if nutrients.iter().any(|n| n > &MAX_NUTRIENT) || salt > MAX_SALT {
+        return Err(String::from("Nutrient values are out of range"));
+    }
+    let energy = energy
+        .to_i32()
+        .ok_or(String::from("Calories are out of range"))?;
+
+    Ok(Some(ImportRow {
+        line,
+        date,
+        meal,
+        name,
+        brand,
+        amount: map.amount.map(get).unwrap_or_default().to_string(),
+        energy,
+        fat,
+        saturates,
+        carbohydrate,
+        sugars,
+        fibre,
+        protein,
+        salt,
+        import_key: Uuid::nil(),
+    }))
+}
+
+pub fn parse_diary(data: &[u8], source: Option<ImportSource>) -> Result<ParsedDiary, AppError> {
+    let data = data.strip_prefix("\u{feff}".as_bytes()).unwrap_or(data);
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(data);
+    let columns = Columns(
+        reader
+            .headers()?
+            .iter()
+            .map(|header| header.trim().to_lowercase())
+            .collect(),
+    );
+    let source =
+        source
+            .or_else(|| ImportSource::detect(&columns))
+            .ok_or(AppError::BadRequestMessage(String::from(
+                "Could not detect the export format",
+            )))?;
+    let map = ColumnMap::new(&columns)?;
+
+    let mut rows = Vec::new();
+    let mut skipped = Vec::new();
+    let mut occurrences: HashMap<Uuid, usize> = HashMap::new();
+    for (index, record) in reader.records().enumerate() {
+        let record = record?;
+        let line = record
+            .position()
+            .map_or(index as u64 + 2, |position| position.line());
+        match parse_row(&record, &map, line) {
+            Ok(Some(mut row)) => {
+                // Identical entries on the same day get distinct keys by their order in the file.
+                let base = import_key(source, &row, 0);
+                let occurrence = occurrences.entry(base).or_default();
+                row.import_key = import_key(source, &row, *occurrence);
+                *occurrence += 1;
+                rows.push(row);
+            }
+            Ok(None) => {}
+            Err(reason) => skipped.push(SkippedRow { line, reason }),
+        }
+    }
+    Ok(ParsedDiary {
+        source,
+        rows,
+        skipped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CRONOMETER: &str = "\
+Day,Time,Group,Food Name,Amount,Energy (kcal),Fat (g),Saturated (g),Carbs (g),Fiber (g),Sugars (g),Protein (g),Sodium (mg)
+2023-01-15,08:00 AM,Breakfast,\"Oats, Rolled\",50.00 g,185.5,3.2,0.6,30.1,5.0,0.5,6.5,1
+2023-01-15,08:00 AM,Breakfast,\"Oats, Rolled\",50.00 g,185.5,3.2,0.6,30.1,5.0,0.5,6.5,1
+2023-01-15,12:30 PM,Lunch,Tesco - Chicken Breast,150.00 g,240,4,1,0,0,0,45,400
+not a date,,Lunch,Apple,1 medium,95,0,0,25,4,19,0.5,2
+";
+
+    const MFP: &str = "\
+Date,Meal,Calories,Fat (g),Saturated Fat,Carbohydrates (g),Sugar,Fiber,Protein (g),Sodium (mg)
+2023-01-15,Breakfast,410,12.5,3,55,10,6,20,300
+2023-01-15,Snacks,150,-1,0,20,10,1,3,50
+";
+
+    #[test]
+    fn test_parse_cronometer() {
+        let parsed = parse_diary(CRONOMETER.as_bytes(), None).unwrap();
+        assert_eq!(parsed.source, ImportSource::Cronometer);
+        assert_eq!(parsed.rows.len(), 3);
+        assert_eq!(parsed.skipped.len(), 1);
+        assert_eq!(parsed.skipped[0].line, 5);
+
+        let oats = &parsed.rows[0];
+        assert_eq!(oats.name, "Oats, Rolled");
+        assert_eq!(oats.brand, None);
+        assert_eq!(oats.energy, 186);
+        assert_eq!(oats.salt, dec!(0));
+
+        let chicken = &parsed.rows[2];
+        assert_eq!(chicken.brand.as_deref(), Some("Tesco"));
+        assert_eq!(chicken.name, "Chicken Breast");
+        assert_eq!(chicken.salt, dec!(1.00));
+    }
+
+    #[test]
+    fn test_parse_mfp_summary() {
+        let parsed = parse_diary(MFP.as_bytes(), None).unwrap();
+        assert_eq!(parsed.source, ImportSource::MyFitnessPal);
+        assert_eq!(parsed.rows.len(), 1);
+        assert_eq!(parsed.rows[0].name, "Breakfast total");
+        assert_eq!(parsed.rows[0].fat, dec!(12.5));
+        assert_eq!(parsed.skipped[0].line, 3);
+    }
+
+    #[test]
+    fn test_import_keys_are_stable() {
+        let first = parse_diary(CRONOMETER.as_bytes(), None).unwrap();
+        let second = parse_diary(CRONOMETER.as_bytes(), None).unwrap();
+        let keys: Vec<Uuid> = first.rows.iter().map(|row| row.import_key).collect();
+        assert_eq!(
+            keys,
+            second
+                .rows
+                .iter()
+                .map(|row| row.import_key)
+                .collect::<Vec<Uuid>>()
+        );
+        // Repeated entries on the same day are kept as separate rows.
+        assert_ne!(keys[0], keys[1]);
+    }
+
+    #[test]
+    fn test_missing_columns() {
+        let result = parse_diary("Date,Meal\n2023-01-15,Lunch\n".as_bytes(), None);
+        assert!(matches!(result, Err(AppError::BadRequestMessage(_))));
+    }
+}
@@ -0,0 +1,57 @@
+use axum::{
+    extract::{Multipart, State},
+    Extension, Json,
+};
+use std::sync::Arc;
+
+use crate::{error::AppError, middleware::RequestUser, AppState};
+
+use super::{
+    model::{import_diary, DietImportReport},
+    parser::{parse_diary, ImportSource},
+};
+
+pub const MAX_IMPORT_SIZE: usize = 20 * 1024 * 1024;
+
+// Imports into the requesting user's own diary. Pass dry_run=true to preview the report
+// without saving anything; re-running the same file only adds entries not seen before.
+pub async fn diet_import_view(
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    mut multipart: Multipart,
+) -> Result<Json<DietImportReport>, AppError> {
+    request_user.login_required()?;
+
+    let mut data = None;
+    let mut source = None;
+    let mut dry_run = false;
+    while let Some(field) = multipart.next_field().await? {
+        match field.name() {
+            Some("file") => data = Some(field.bytes().await?),
+            Some("source") => {
+                let value = field.text().await?;
+                if value.trim() != "auto" {
+                    source = Some(ImportSource::from_name(&value).ok_or(
+                        AppError::BadRequestMessage(String::from(
+                            "Source must be one of: auto, myfitnesspal, cronometer",
+                        )),
+                    )?);
+                }
+            }
+            Some("dry_run") => dry_run = matches!(field.text().await?.trim(), "true" | "1"),
+            _ => {}
+        }
+    }
+    let data = data.ok_or(AppError::BadRequestMessage(String::from(
+        "A CSV file is required",
+    )))?;
+    if data.len() > MAX_IMPORT_SIZE {
+        return Err(AppError::BadRequestMessage(String::from(
+            "File must be 20MB or smaller",
+        )));
+    }
+
+    let parsed = parse_diary(&data, source)?;
+    let report = import_diary(&state.pool, &request_user, parsed, dry_run).await?;
+    Ok(Json(report))
+}
@@ -37,6 +37,7 @@ pub struct DietTarget {
     pub fibre: Decimal,
     pub protein: Decimal,
     pub salt: Decimal,
+    pub water_ml: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
     pub created_by_id: Uuid,
@@ -146,6 +147,7 @@ impl DietTarget {
         let fibre = Decimal::new(30, 0);
         let salt = Decimal::new(6, 0);
         let energy = energy.round().mantissa() as i32;
+        let water_ml = data.get_water_ml();
         let query = sqlx::query_as(
             "
             INSERT INTO
@@ -161,10 +163,11 @@ impl DietTarget {
                     fibre,
                     protein,
                     salt,
+                    water_ml,
                     created_by_id
                 )
             VALUES
-                ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+                ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
             RETURNING
                 *
             ",
@@ -180,6 +183,7 @@ impl DietTarget {
         .bind(fibre)
         .bind(protein)
         .bind(salt)
+        .bind(water_ml)
         .bind(created_by_id)
         .fetch_one(pool)
         .await?;
@@ -204,6 +208,7 @@ impl DietTarget {
         let fibre = Decimal::new(30, 0);
         let salt = Decimal::new(6, 0);
         let energy = energy.round().mantissa() as i32;
+        let water_ml = data.get_water_ml();
         let query = sqlx::query_as(
             "
             UPDATE diet_target
@@ -218,10 +223,11 @@ impl DietTarget {
                 sugars = $8,
                 fibre = $9,
                 salt = $10,
-                updated_at = $11,
-                updated_by_id = $12
+                water_ml = $11,
+                updated_at = $12,
+                updated_by_id = $13
             WHERE
-                id = $13
+                id = $14
             RETURNING
                 *
             ",
@@ -236,6 +242,7 @@ impl DietTarget {
         .bind(sugars)
         .bind(fibre)
         .bind(salt)
+        .bind(water_ml)
         .bind(updated_at)
         .bind(updated_by_id)
         .bind(id)
@@ -305,6 +312,7 @@ pub struct DietTargetSerializer {
     pub fibre: Decimal,
     pub protein: Decimal,
     pub salt: Decimal,
+    pub water_ml: Decimal,
     // pub created_at: Option<DateTime<Utc>>,
     // pub updated_at: Option<DateTime<Utc>>,
     // pub created_by_id: Option<Uuid>,
@@ -337,6 +345,7 @@ impl DietTargetSerializer {
                     t1.fibre,
                     t1.protein,
                     t1.salt,
+                    t1.water_ml::DECIMAL,
                     t1.created_at,
                     t1.updated_at,
                     t1.created_by_id,
@@ -386,6 +395,7 @@ impl DietTargetSerializer {
                     t1.fibre,
                     t1.protein,
                     t1.salt,
+                    t1.water_ml::DECIMAL,
                     t1.created_at,
                     t1.updated_at,
                     t1.created_by_id,
@@ -441,6 +451,7 @@ impl DietTargetSerializer {
                 SUM(t1.sugars) AS sugars,
                 SUM(t1.fibre) AS fibre,
                 SUM(t1.salt) AS salt,
+                SUM(t1.water_ml)::DECIMAL AS water_ml,
                 SUM(t1.protein * 4) / SUM(t1.energy) * 100 AS protein_pct,
                 SUM(t1.carbohydrate * 4) / SUM(t1.energy) * 100 AS carbohydrate_pct,
                 SUM(t1.fat * 9) / SUM(t1.energy) * 100 AS fat_pct,
@@ -491,6 +502,7 @@ impl DietTargetSerializer {
                 AVG(t1.sugars) AS sugars,
                 AVG(t1.fibre) AS fibre,
                 AVG(t1.salt) AS salt,
+                AVG(t1.water_ml)::DECIMAL AS water_ml,
                 AVG(t1.protein * 4) / AVG(t1.energy) * 100 AS protein_pct,
                 AVG(t1.carbohydrate * 4) / AVG(t1.energy) * 100 AS carbohydrate_pct,
                 AVG(t1.fat * 9) / AVG(t1.energy) * 100 AS fat_pct,
@@ -539,6 +551,7 @@ impl DietTargetSerializer {
                 t1.fibre,
                 t1.protein,
                 t1.salt,
+                t1.water_ml::DECIMAL,
                 t1.created_at,
                 t1.updated_at,
                 t1.created_by_id,
@@ -587,6 +600,7 @@ impl DietTargetSerializer {
                 t1.fibre,
                 t1.protein,
                 t1.salt,
+                t1.water_ml::DECIMAL,
                 t1.created_at,
                 t1.updated_at,
                 t1.created_by_id,
@@ -1,5 +1,6 @@
 use chrono::prelude::*;
-use rust_decimal::Decimal;
+use rust_decimal::prelude::*;
+use rust_decimal_macros::dec;
 use serde::Deserialize;
 use uuid::Uuid;
 use validator::{Validate, ValidationError};
@@ -53,6 +54,21 @@ pub struct DietTargetCreateInput {
         )
     )]
     pub fat_per_kg: Decimal,
+    #[validate(range(min = 0, max = 20000, message = "Must be between 0 and 20000"))]
+    pub water_ml: Option<i32>,
+}
+
+const WATER_ML_PER_KG: Decimal = dec!(35);
+
+impl DietTargetCreateInput {
+    pub fn get_water_ml(&self) -> i32 {
+        self.water_ml.unwrap_or_else(|| {
+            (self.weight * WATER_ML_PER_KG)
+                .round()
+                .to_i32()
+                .unwrap_or_default()
+        })
+    }
 }
 
 pub fn validate_max_quantity_weight(value: &Decimal) -> Result<(), ValidationError> {
@@ -46,17 +46,17 @@ impl DietDayTotal {
             SELECT
                 t1.user_id,
                 t1.date,
-                SUM(t1.quantity * t2.energy) AS energy,
-                SUM(t1.quantity * t2.protein) AS protein,
-                SUM(t1.quantity * t2.carbohydrate) AS carbohydrate,
-                SUM(t1.quantity * t2.fat) AS fat,
-                SUM(t1.quantity * t2.saturates) AS saturates,
-                SUM(t1.quantity * t2.sugars) AS sugars,
-                SUM(t1.quantity * t2.fibre) AS fibre,
-                SUM(t1.quantity * t2.salt) AS salt,
-                SUM(t1.quantity * t2.protein * 4) / SUM(t1.quantity * t2.energy) * 100 AS protein_pct,
-                SUM(t1.quantity * t2.carbohydrate * 4) / SUM(t1.quantity * t2.energy) * 100 AS carbohydrate_pct,
-                SUM(t1.quantity * t2.fat * 9) / SUM(t1.quantity * t2.energy) * 100 AS fat_pct
+                SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) AS energy,
+                SUM(t1.quantity * COALESCE(t2.protein, t1.protein)) AS protein,
+                SUM(t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate)) AS carbohydrate,
+                SUM(t1.quantity * COALESCE(t2.fat, t1.fat)) AS fat,
+                SUM(t1.quantity * COALESCE(t2.saturates, t1.saturates)) AS saturates,
+                SUM(t1.quantity * COALESCE(t2.sugars, t1.sugars)) AS sugars,
+                SUM(t1.quantity * COALESCE(t2.fibre, t1.fibre)) AS fibre,
+                SUM(t1.quantity * COALESCE(t2.salt, t1.salt)) AS salt,
+                SUM(t1.quantity * COALESCE(t2.protein, t1.protein) * 4) / SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) * 100 AS protein_pct,
+                SUM(t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate) * 4) / SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) * 100 AS carbohydrate_pct,
+                SUM(t1.quantity * COALESCE(t2.fat, t1.fat) * 9) / SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) * 100 AS fat_pct
             FROM
                 food_log t1
                 LEFT JOIN food t2 ON t2.id = t1.food_id
@@ -117,17 +117,17 @@ impl DietMealTotal {
                 t1.user_id,
                 t1.date,
                 t1.meal_of_day_id,
-                SUM(t1.quantity * t2.energy) AS energy,
-                SUM(t1.quantity * t2.protein) AS protein,
-                SUM(t1.quantity * t2.carbohydrate) AS carbohydrate,
-                SUM(t1.quantity * t2.fat) AS fat,
-                SUM(t1.quantity * t2.saturates) AS saturates,
-                SUM(t1.quantity * t2.sugars) AS sugars,
-                SUM(t1.quantity * t2.fibre) AS fibre,
-                SUM(t1.quantity * t2.salt) AS salt,
-                SUM(t1.quantity * t2.protein * 4) / SUM(t1.quantity * t2.energy) * 100 AS protein_pct,
-                SUM(t1.quantity * t2.carbohydrate * 4) / SUM(t1.quantity * t2.energy) * 100 AS carbohydrate_pct,
-                SUM(t1.quantity * t2.fat * 9) / SUM(t1.quantity * t2.energy) * 100 AS fat_pct
+                SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) AS energy,
+                SUM(t1.quantity * COALESCE(t2.protein, t1.protein)) AS protein,
+                SUM(t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate)) AS carbohydrate,
+                SUM(t1.quantity * COALESCE(t2.fat, t1.fat)) AS fat,
+                SUM(t1.quantity * COALESCE(t2.saturates, t1.saturates)) AS saturates,
+                SUM(t1.quantity * COALESCE(t2.sugars, t1.sugars)) AS sugars,
+                SUM(t1.quantity * COALESCE(t2.fibre, t1.fibre)) AS fibre,
+                SUM(t1.quantity * COALESCE(t2.salt, t1.salt)) AS salt,
+                SUM(t1.quantity * COALESCE(t2.protein, t1.protein) * 4) / SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) * 100 AS protein_pct,
+                SUM(t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate) * 4) / SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) * 100 AS carbohydrate_pct,
+                SUM(t1.quantity * COALESCE(t2.fat, t1.fat) * 9) / SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) * 100 AS fat_pct
             FROM
                 food_log t1
                 LEFT JOIN food t2 ON t2.id = t1.food_id
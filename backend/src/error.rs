@@ -1,12 +1,17 @@
 use axum::{
-    extract::rejection::{ExtensionRejection, FormRejection, JsonRejection},
+    extract::{
+        multipart::MultipartError,
+        rejection::{ExtensionRejection, FormRejection, JsonRejection},
+    },
     http::StatusCode,
     response::{IntoResponse, Response},
     Json,
 };
 use serde_json::json;
 use std::collections::HashMap;
-use validator::ValidationErrors;
+use validator::{ValidationErrors, ValidationErrorsKind};
+
+use crate::storage::StorageError;
 
 // pub struct FieldErrors {
 //     pub field: String,
@@ -26,11 +31,25 @@ pub enum AppError {
     #[error(transparent)]
     AxumExtensionRejection(#[from] ExtensionRejection),
     #[error(transparent)]
+    AxumMultipartError(#[from] MultipartError),
+    #[error(transparent)]
     JsonWebTokenError(#[from] jsonwebtoken::errors::Error),
     #[error(transparent)]
     SqlxError(#[from] sqlx::Error),
     #[error(transparent)]
     BcryptError(#[from] bcrypt::BcryptError),
+    #[error(transparent)]
+    StorageError(#[from] StorageError),
+    #[error(transparent)]
+    JoinError(#[from] tokio::task::JoinError),
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    SerdeJsonError(#[from] serde_json::Error),
+    #[error(transparent)]
+    CsvError(#[from] csv::Error),
+    #[error(transparent)]
+    ZipError(#[from] zip::result::ZipError),
     #[error("")]
     NotFound,
     #[error("")]
@@ -61,9 +80,13 @@ impl IntoResponse for AppError {
             Self::AxumJsonRejection(err) => (err.status(), err.body_text()),
             Self::AxumFormRejection(err) => (err.status(), err.body_text()),
             Self::AxumExtensionRejection(err) => (err.status(), err.body_text()),
+            Self::AxumMultipartError(err) => (err.status(), err.body_text()),
             Self::JsonWebTokenError(err) => (StatusCode::UNAUTHORIZED, err.to_string()),
             Self::SqlxError(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
             Self::BcryptError(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+            Self::StorageError(StorageError::NotFound) => {
+                (StatusCode::NOT_FOUND, String::from("Not found"))
+            }
             // app
             Self::Unauthorized(err) => (StatusCode::UNAUTHORIZED, err),
             Self::UnauthorizedMessage(err) => (StatusCode::UNAUTHORIZED, err),
@@ -88,15 +111,37 @@ impl IntoResponse for AppError {
 
 impl AppError {
     pub fn validation_field_errors(errors: ValidationErrors) -> Response {
-        let mut field_errors = HashMap::new();
+        let mut field_errors: HashMap<String, Vec<String>> = HashMap::new();
         for (field, error_list) in errors.field_errors() {
-            let mut message_list = Vec::new();
             for error in error_list.to_owned() {
+                // Schema level errors can name the field they belong to.
+                let field = match error.params.get("field").and_then(|f| f.as_str()) {
+                    Some(field) => field.to_string(),
+                    None => field.to_string(),
+                };
+                let message_list = field_errors.entry(field).or_default();
                 if let Some(msg) = error.message {
                     message_list.push(msg.to_string());
                 }
             }
-            field_errors.insert(field, message_list);
+        }
+        // Errors of nested rows are listed under the field holding them, once each.
+        for (field, kind) in errors.errors() {
+            let ValidationErrorsKind::List(rows) = kind else {
+                continue;
+            };
+            for row in rows.values() {
+                for error_list in row.field_errors().into_values() {
+                    for error in error_list {
+                        let message_list = field_errors.entry(field.to_string()).or_default();
+                        if let Some(msg) = &error.message {
+                            if !message_list.contains(&msg.to_string()) {
+                                message_list.push(msg.to_string());
+                            }
+                        }
+                    }
+                }
+            }
         }
         (StatusCode::BAD_REQUEST, Json(field_errors)).into_response()
     }
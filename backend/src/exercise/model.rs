@@ -4,7 +4,9 @@ use serde::Serialize;
 use sqlx::{FromRow, PgPool, Row};
 use uuid::Uuid;
 
-use crate::{db::Filters, set::model::Set, util::query::QueryParams};
+use crate::{
+    db::Filters, personal_record::model::PersonalRecord, set::model::Set, util::query::QueryParams,
+};
 
 const ORDERING_FIELDS: &[&str] = &["created_at", "updated_at"];
 
@@ -94,6 +96,7 @@ impl Exercise {
         data: ExerciseSetInput,
         created_by_id: Uuid,
     ) -> Result<Self, sqlx::Error> {
+        let mut tx = pool.begin().await?;
         let exercise: Exercise = sqlx::query_as(
             "
             INSERT INTO
@@ -107,7 +110,7 @@ impl Exercise {
         .bind(data.workout_id)
         .bind(data.movement_id)
         .bind(&created_by_id)
-        .fetch_one(pool)
+        .fetch_one(&mut *tx)
         .await?;
 
         let mut exercise_id_list = Vec::new();
@@ -126,7 +129,7 @@ impl Exercise {
             created_by_id_list.push(created_by_id);
         }
 
-        let _query: Vec<Set> = sqlx::query_as(
+        let sets: Vec<Set> = sqlx::query_as(
             r#"
             INSERT INTO
             tracked_set (exercise_id, "order", weight, reps, rest, created_by_id)
@@ -141,6 +144,8 @@ impl Exercise {
                     $5::INTEGER[],
                     $6::UUID[]
                 )
+            RETURNING
+                *
             "#,
         )
         .bind(&exercise_id_list)
@@ -149,8 +154,11 @@ impl Exercise {
         .bind(&reps_list)
         .bind(&rest_list)
         .bind(&created_by_id_list)
-        .fetch_all(pool)
+        .fetch_all(&mut *tx)
         .await?;
+        let set_ids: Vec<Uuid> = sets.iter().map(|set| set.id).collect();
+        PersonalRecord::detect(&mut tx, &set_ids).await?;
+        tx.commit().await?;
         Ok(exercise)
     }
     pub async fn delete_id_range(
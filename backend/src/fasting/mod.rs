@@ -0,0 +1,5 @@
+pub mod model;
+pub mod router;
+pub mod serializer;
+pub mod streak;
+pub mod view;
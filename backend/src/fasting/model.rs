@@ -0,0 +1,266 @@
+use chrono::prelude::*;
+use futures::TryStreamExt;
+use rust_decimal::prelude::*;
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use super::{
+    serializer::EatingWindowInput,
+    streak::{fast_streak, FastStreak},
+};
+
+pub const DEFAULT_TARGET_FAST_HOURS: i32 = 16;
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct EatingWindow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+    pub target_fast_hours: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub created_by_id: Uuid,
+    pub updated_by_id: Option<Uuid>,
+}
+
+impl EatingWindow {
+    pub async fn get_from_user_id(
+        pool: &PgPool,
+        user_id: &Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let query = sqlx::query_as("SELECT * FROM eating_window WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(query)
+    }
+    pub async fn upsert(
+        pool: &PgPool,
+        user_id: Uuid,
+        data: EatingWindowInput,
+        request_user_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let target_fast_hours = data.get_target_fast_hours();
+        let query = sqlx::query_as(
+            "
+            INSERT INTO
+                eating_window (
+                    user_id,
+                    start_time,
+                    end_time,
+                    target_fast_hours,
+                    created_by_id
+                )
+            VALUES
+                ($1, $2, $3, $4, $5)
+            ON CONFLICT (user_id) DO
+            UPDATE
+            SET
+                start_time = EXCLUDED.start_time,
+                end_time = EXCLUDED.end_time,
+                target_fast_hours = EXCLUDED.target_fast_hours,
+                updated_at = CURRENT_TIMESTAMP,
+                updated_by_id = EXCLUDED.created_by_id
+            RETURNING
+                *
+            ",
+        )
+        .bind(user_id)
+        .bind(data.start_time)
+        .bind(data.end_time)
+        .bind(target_fast_hours)
+        .bind(request_user_id)
+        .fetch_one(pool)
+        .await?;
+        Ok(query)
+    }
+
+    // Windows may wrap past midnight, e.g. 18:00 - 02:00.
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start_time <= self.end_time {
+            time >= self.start_time && time <= self.end_time
+        } else {
+            time >= self.start_time || time <= self.end_time
+        }
+    }
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct Fast {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub target_hours: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub created_by_id: Uuid,
+    pub updated_by_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FastDetail {
+    #[serde(flatten)]
+    pub fast: Fast,
+    pub duration_hours: Decimal,
+    pub is_active: bool,
+    pub is_complete: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FastHistory {
+    pub username: String,
+    pub active: Option<FastDetail>,
+    pub streak: FastStreak,
+    pub count: usize,
+    pub results: Vec<FastDetail>,
+}
+
+impl FastDetail {
+    pub fn new(fast: Fast, now: DateTime<Utc>) -> Self {
+        let ended_at = fast.ended_at.unwrap_or(now);
+        let minutes = (ended_at - fast.started_at).num_minutes().max(0);
+        let duration_hours = (Decimal::from(minutes) / Decimal::from(60)).round_dp(2);
+        Self {
+            is_active: fast.ended_at.is_none(),
+            is_complete: fast.ended_at.is_some()
+                && duration_hours >= Decimal::from(fast.target_hours),
+            duration_hours,
+            fast,
+        }
+    }
+}
+
+impl Fast {
+    pub async fn get(pool: &PgPool, id: &Uuid) -> Result<Option<Self>, sqlx::Error> {
+        let query = sqlx::query_as("SELECT * FROM fast WHERE id = $1")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(query)
+    }
+    pub async fn get_active(pool: &PgPool, user_id: &Uuid) -> Result<Option<Self>, sqlx::Error> {
+        let query = sqlx::query_as("SELECT * FROM fast WHERE user_id = $1 AND ended_at IS NULL")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(query)
+    }
+    pub async fn all_by_user_id(
+        pool: &PgPool,
+        user_id: &Uuid,
+        date_from: NaiveDate,
+        date_to: NaiveDate,
+        offset: FixedOffset,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let mut stream = Vec::new();
+        let mut rows = sqlx::query_as(
+            "
+            SELECT
+                *
+            FROM
+                fast
+            WHERE
+                user_id = $1
+                AND (started_at AT TIME ZONE 'UTC' + $4 * INTERVAL '1 second')::DATE BETWEEN $2 AND $3
+            ORDER BY
+                started_at DESC
+            ",
+        )
+        .bind(user_id)
+        .bind(date_from)
+        .bind(date_to)
+        .bind(offset.local_minus_utc())
+        .fetch(pool);
+        while let Some(row) = rows.try_next().await? {
+            stream.push(row);
+        }
+        Ok(stream)
+    }
+    pub async fn streak(
+        pool: &PgPool,
+        user_id: &Uuid,
+        today: NaiveDate,
+        offset: FixedOffset,
+    ) -> Result<FastStreak, sqlx::Error> {
+        let dates: Vec<(NaiveDate,)> = sqlx::query_as(
+            "
+            SELECT DISTINCT
+                (ended_at AT TIME ZONE 'UTC' + $2 * INTERVAL '1 second')::DATE
+            FROM
+                fast
+            WHERE
+                user_id = $1
+                AND ended_at IS NOT NULL
+                AND ended_at - started_at >= target_hours * INTERVAL '1 hour'
+            ",
+        )
+        .bind(user_id)
+        .bind(offset.local_minus_utc())
+        .fetch_all(pool)
+        .await?;
+        let dates: Vec<NaiveDate> = dates.into_iter().map(|(date,)| date).collect();
+        Ok(fast_streak(&dates, today))
+    }
+    pub async fn start(
+        pool: &PgPool,
+        user_id: Uuid,
+        started_at: DateTime<Utc>,
+        target_hours: i32,
+        created_by_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let query = sqlx::query_as(
+            "
+            INSERT INTO
+                fast (user_id, started_at, target_hours, created_by_id)
+            VALUES
+                ($1, $2, $3, $4)
+            RETURNING
+                *
+            ",
+        )
+        .bind(user_id)
+        .bind(started_at)
+        .bind(target_hours)
+        .bind(created_by_id)
+        .fetch_one(pool)
+        .await?;
+        Ok(query)
+    }
+    pub async fn stop(
+        pool: &PgPool,
+        id: &Uuid,
+        ended_at: DateTime<Utc>,
+        updated_by_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let query = sqlx::query_as(
+            "
+            UPDATE fast
+            SET
+                ended_at = $1,
+                updated_at = $2,
+                updated_by_id = $3
+            WHERE
+                id = $4
+            RETURNING
+                *
+            ",
+        )
+        .bind(ended_at)
+        .bind(Utc::now())
+        .bind(updated_by_id)
+        .bind(id)
+        .fetch_one(pool)
+        .await?;
+        Ok(query)
+    }
+    pub async fn delete(pool: &PgPool, id: &Uuid) -> Result<Self, sqlx::Error> {
+        let query = sqlx::query_as("DELETE FROM fast WHERE id = $1 RETURNING *")
+            .bind(id)
+            .fetch_one(pool)
+            .await?;
+        Ok(query)
+    }
+}
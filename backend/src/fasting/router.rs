@@ -0,0 +1,24 @@
+use axum::{
+    routing::{delete, get, post, put},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::fasting::view::{
+    eating_window_detail_view, eating_window_update_view, fast_delete_view, fast_history_view,
+    fast_start_view, fast_stop_view,
+};
+use crate::AppState;
+
+pub fn fasting_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/start", post(fast_start_view))
+        .route("/:id/stop", put(fast_stop_view))
+        .route("/:id", delete(fast_delete_view))
+        .route("/eating-window", put(eating_window_update_view))
+        .route("/user/:username", get(fast_history_view))
+        .route(
+            "/user/:username/eating-window",
+            get(eating_window_detail_view),
+        )
+}
@@ -0,0 +1,49 @@
+use chrono::prelude::*;
+use serde::Deserialize;
+use validator::Validate;
+
+use crate::util::query::empty_string_as_none;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct EatingWindowInput {
+    pub username: String,
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+    #[validate(range(min = 1, max = 168, message = "Must be between 1 and 168 hours"))]
+    pub target_fast_hours: Option<i32>,
+}
+
+impl EatingWindowInput {
+    // Defaults the fasting target to the time outside of the eating window.
+    pub fn get_target_fast_hours(&self) -> i32 {
+        self.target_fast_hours.unwrap_or_else(|| {
+            let window = (self.end_time - self.start_time)
+                .num_minutes()
+                .rem_euclid(24 * 60);
+            ((24 * 60 - window) / 60) as i32
+        })
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct FastStartInput {
+    pub username: String,
+    pub started_at: Option<DateTime<Utc>>,
+    #[validate(range(min = 1, max = 168, message = "Must be between 1 and 168 hours"))]
+    pub target_hours: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct FastStopInput {
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FastHistoryParams {
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    pub date_from: Option<NaiveDate>,
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    pub date_to: Option<NaiveDate>,
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    pub utc_offset: Option<i32>,
+}
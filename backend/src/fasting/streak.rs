@@ -0,0 +1,67 @@
+use chrono::prelude::*;
+use serde::Serialize;
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct FastStreak {
+    pub current: i64,
+    pub longest: i64,
+}
+
+// Counts consecutive days with at least one completed fast. The current streak
+// stays alive until a full day has passed without one.
+pub fn fast_streak(dates: &[NaiveDate], today: NaiveDate) -> FastStreak {
+    let mut dates = dates.to_vec();
+    dates.sort();
+    dates.dedup();
+
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for date in &dates {
+        run = match previous {
+            Some(previous) if *date - previous == chrono::Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(*date);
+    }
+    let current = match previous {
+        Some(last) if (today - last).num_days() <= 1 => run,
+        _ => 0,
+    };
+    FastStreak { current, longest }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, 12, day).unwrap()
+    }
+
+    #[test]
+    fn test_fast_streak_consecutive_days() {
+        let dates = vec![date(1), date(2), date(3), date(5), date(6)];
+        let streak = fast_streak(&dates, date(6));
+        assert_eq!(
+            streak,
+            FastStreak {
+                current: 2,
+                longest: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_fast_streak_survives_until_day_is_missed() {
+        let dates = vec![date(4), date(5)];
+        assert_eq!(fast_streak(&dates, date(6)).current, 2);
+        assert_eq!(fast_streak(&dates, date(7)).current, 0);
+    }
+
+    #[test]
+    fn test_fast_streak_empty() {
+        assert_eq!(fast_streak(&[], date(1)), FastStreak::default());
+    }
+}
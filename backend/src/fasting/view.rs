@@ -0,0 +1,185 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::{prelude::*, Duration};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    extractor::JsonExtractor,
+    middleware::RequestUser,
+    user::model::User,
+    util::{
+        datetime::utc_offset,
+        permission::{owner_required, user_privacy_check},
+    },
+    AppState,
+};
+
+use super::{
+    model::{EatingWindow, Fast, FastDetail, FastHistory, DEFAULT_TARGET_FAST_HOURS},
+    serializer::{EatingWindowInput, FastHistoryParams, FastStartInput, FastStopInput},
+};
+
+const DEFAULT_HISTORY_DAYS: i64 = 30;
+const OWNER_MESSAGE: &str = "You are unable to update another users fasting log.";
+
+pub async fn eating_window_detail_view(
+    Path(username): Path<String>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<EatingWindow>, AppError> {
+    let user = User::get_from_username(&state.pool, &username)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    user_privacy_check(&state.pool, &request_user, &user).await?;
+    let query = EatingWindow::get_from_user_id(&state.pool, &user.id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    Ok(Json(query))
+}
+
+pub async fn eating_window_update_view(
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    JsonExtractor(data): JsonExtractor<EatingWindowInput>,
+) -> Result<Json<EatingWindow>, AppError> {
+    let user = owner_required(&state.pool, &request_user, &data.username, OWNER_MESSAGE).await?;
+    if data.start_time == data.end_time {
+        return Err(AppError::BadRequestMessage(String::from(
+            "Eating window start and end times must differ.",
+        )));
+    }
+    let query = EatingWindow::upsert(&state.pool, user.id, data, request_user.id).await?;
+    Ok(Json(query))
+}
+
+pub async fn fast_start_view(
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    JsonExtractor(data): JsonExtractor<FastStartInput>,
+) -> Result<(StatusCode, Json<FastDetail>), AppError> {
+    let user = owner_required(&state.pool, &request_user, &data.username, OWNER_MESSAGE).await?;
+    if Fast::get_active(&state.pool, &user.id).await?.is_some() {
+        return Err(AppError::BadRequestMessage(String::from(
+            "A fast is already in progress.",
+        )));
+    }
+    let now = Utc::now();
+    let started_at = data.started_at.unwrap_or(now);
+    if started_at > now {
+        return Err(AppError::BadRequestMessage(String::from(
+            "A fast cannot start in the future.",
+        )));
+    }
+    let target_hours = match data.target_hours {
+        Some(target_hours) => target_hours,
+        None => EatingWindow::get_from_user_id(&state.pool, &user.id)
+            .await?
+            .map_or(DEFAULT_TARGET_FAST_HOURS, |window| window.target_fast_hours),
+    };
+    // A fast started at the same moment passes the check above but not the unique
+    // index on a user's active fast.
+    let query = match Fast::start(
+        &state.pool,
+        user.id,
+        started_at,
+        target_hours,
+        request_user.id,
+    )
+    .await
+    {
+        Err(sqlx::Error::Database(err)) if err.constraint() == Some("fast_user_id_active_idx") => {
+            return Err(AppError::BadRequestMessage(String::from(
+                "A fast is already in progress.",
+            )));
+        }
+        result => result?,
+    };
+    Ok((StatusCode::CREATED, Json(FastDetail::new(query, now))))
+}
+
+pub async fn fast_stop_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    JsonExtractor(data): JsonExtractor<FastStopInput>,
+) -> Result<Json<FastDetail>, AppError> {
+    let fast = Fast::get(&state.pool, &id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    if request_user.id != fast.user_id {
+        return Err(AppError::NotFound);
+    }
+    if fast.ended_at.is_some() {
+        return Err(AppError::BadRequestMessage(String::from(
+            "This fast has already ended.",
+        )));
+    }
+    let now = Utc::now();
+    let ended_at = data.ended_at.unwrap_or(now);
+    if ended_at <= fast.started_at || ended_at > now {
+        return Err(AppError::BadRequestMessage(String::from(
+            "A fast must end after it started and not in the future.",
+        )));
+    }
+    let query = Fast::stop(&state.pool, &fast.id, ended_at, request_user.id).await?;
+    Ok(Json(FastDetail::new(query, now)))
+}
+
+pub async fn fast_delete_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<Fast>, AppError> {
+    let fast = Fast::get(&state.pool, &id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    if request_user.id != fast.user_id {
+        return Err(AppError::NotFound);
+    }
+    let query = Fast::delete(&state.pool, &fast.id).await?;
+    Ok(Json(query))
+}
+
+pub async fn fast_history_view(
+    Path(username): Path<String>,
+    Query(params): Query<FastHistoryParams>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<FastHistory>, AppError> {
+    let user = User::get_from_username(&state.pool, &username)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    user_privacy_check(&state.pool, &request_user, &user).await?;
+    let offset = utc_offset(params.utc_offset).ok_or(AppError::BadRequest)?;
+    let now = Utc::now();
+    let today = now.with_timezone(&offset).date_naive();
+    let date_to = params.date_to.unwrap_or(today);
+    let date_from = params
+        .date_from
+        .unwrap_or(date_to - Duration::days(DEFAULT_HISTORY_DAYS));
+    if date_from > date_to {
+        return Err(AppError::BadRequest);
+    }
+    let active = Fast::get_active(&state.pool, &user.id)
+        .await?
+        .map(|fast| FastDetail::new(fast, now));
+    let streak = Fast::streak(&state.pool, &user.id, today, offset).await?;
+    let results: Vec<FastDetail> =
+        Fast::all_by_user_id(&state.pool, &user.id, date_from, date_to, offset)
+            .await?
+            .into_iter()
+            .map(|fast| FastDetail::new(fast, now))
+            .collect();
+    Ok(Json(FastHistory {
+        username,
+        active,
+        streak,
+        count: results.len(),
+        results,
+    }))
+}
@@ -0,0 +1,226 @@
+use rust_decimal::prelude::*;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+use sqlx::{Postgres, QueryBuilder};
+use std::borrow::Cow;
+use validator::ValidationError;
+
+// Energy may differ from 4/4/9 kcal per gram of protein/carbohydrate/fat by this much,
+// to allow for fibre, polyols and label rounding. Alcohol only adds energy, up to 7kcal
+// per gram of the weight the other nutrients leave unaccounted for, so energy above the
+// estimate but within that allowance is only reported, never a reason to reject a food.
+const ENERGY_TOLERANCE_PCT: Decimal = dec!(0.2);
+const ENERGY_TOLERANCE_KCAL: Decimal = dec!(20);
+const ALCOHOL_KCAL_PER_G: Decimal = dec!(7);
+pub const ENERGY_MISMATCH: &str = "energy_mismatch";
+
+pub struct NutrientValues {
+    pub data_value: i32,
+    pub data_measurement: String,
+    pub energy: i32,
+    pub fat: Decimal,
+    pub saturates: Decimal,
+    pub carbohydrate: Decimal,
+    pub sugars: Decimal,
+    pub fibre: Decimal,
+    pub protein: Decimal,
+    pub salt: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NutrientIssue {
+    pub field: &'static str,
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl From<NutrientIssue> for ValidationError {
+    fn from(issue: NutrientIssue) -> Self {
+        let mut error = ValidationError::new(issue.code);
+        error.message = Some(Cow::from(issue.message));
+        // Reported under this field rather than __all__, see AppError::validation_field_errors.
+        error.add_param(Cow::from("field"), &issue.field);
+        error
+    }
+}
+
+pub fn energy_from_macros(values: &NutrientValues) -> Decimal {
+    values.protein * dec!(4) + values.carbohydrate * dec!(4) + values.fat * dec!(9)
+}
+
+// The rules of nutrient_issues as a condition on food t1, so failing foods can be
+// counted and paged in SQL.
+pub fn push_issue_filter(q: &mut QueryBuilder<'static, Postgres>) {
+    q.push(
+        "
+        AND (
+            t1.saturates > t1.fat
+            OR t1.sugars > t1.carbohydrate
+            OR (t1.data_measurement = 'g' AND t1.fat + t1.carbohydrate + t1.fibre + t1.protein + t1.salt > t1.data_value)
+            OR ABS(t1.energy - (t1.protein * 4 + t1.carbohydrate * 4 + t1.fat * 9)) > GREATEST(
+                GREATEST(t1.energy, t1.protein * 4 + t1.carbohydrate * 4 + t1.fat * 9) * ",
+    );
+    q.push_bind(ENERGY_TOLERANCE_PCT);
+    q.push(", ");
+    q.push_bind(ENERGY_TOLERANCE_KCAL);
+    q.push("))");
+}
+
+pub fn nutrient_issues(values: &NutrientValues) -> Vec<NutrientIssue> {
+    let mut issues = Vec::new();
+    if values.saturates > values.fat {
+        issues.push(NutrientIssue {
+            field: "saturates",
+            code: "saturates_exceed_fat",
+            message: String::from("Saturates must not be more than fat"),
+        });
+    }
+    if values.sugars > values.carbohydrate {
+        issues.push(NutrientIssue {
+            field: "sugars",
+            code: "sugars_exceed_carbohydrate",
+            message: String::from("Sugars must not be more than carbohydrate"),
+        });
+    }
+
+    let total = values.fat + values.carbohydrate + values.fibre + values.protein + values.salt;
+    let expected = energy_from_macros(values);
+    let energy = Decimal::from(values.energy);
+    let tolerance = (expected.max(energy) * ENERGY_TOLERANCE_PCT).max(ENERGY_TOLERANCE_KCAL);
+    // Servings have no known weight, so there is no limit on their alcohol.
+    let alcohol = match values.data_measurement.as_str() {
+        "g" | "ml" => {
+            Some((Decimal::from(values.data_value) - total).max(Decimal::ZERO) * ALCOHOL_KCAL_PER_G)
+        }
+        _ => None,
+    };
+    if energy < expected - tolerance {
+        issues.push(NutrientIssue {
+            field: "energy",
+            code: "energy_below_macros",
+            message: format!(
+                "Energy is too low for the macronutrients, expected about {}kcal",
+                expected.round()
+            ),
+        });
+    } else if alcohol.is_some_and(|alcohol| energy > expected + tolerance + alcohol) {
+        issues.push(NutrientIssue {
+            field: "energy",
+            code: "energy_above_macros",
+            message: format!(
+                "Energy is too high for the macronutrients, expected about {}kcal",
+                expected.round()
+            ),
+        });
+    } else if energy > expected + tolerance {
+        issues.push(NutrientIssue {
+            field: "energy",
+            code: ENERGY_MISMATCH,
+            message: format!(
+                "Energy does not match the macronutrients, expected about {}kcal",
+                expected.round()
+            ),
+        });
+    }
+
+    if values.data_measurement == "g" && total > Decimal::from(values.data_value) {
+        issues.push(NutrientIssue {
+            field: "data_value",
+            code: "nutrients_exceed_weight",
+            message: String::from("Nutrients must not weigh more than the food"),
+        });
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn oats() -> NutrientValues {
+        NutrientValues {
+            data_value: 100,
+            data_measurement: String::from("g"),
+            energy: 370,
+            fat: dec!(7),
+            saturates: dec!(1.2),
+            carbohydrate: dec!(60),
+            sugars: dec!(1),
+            fibre: dec!(10),
+            protein: dec!(13),
+            salt: dec!(0.01),
+        }
+    }
+
+    fn fields(values: &NutrientValues) -> Vec<&'static str> {
+        nutrient_issues(values).iter().map(|i| i.field).collect()
+    }
+
+    #[test]
+    fn test_consistent_food_has_no_issues() {
+        assert!(nutrient_issues(&oats()).is_empty());
+    }
+
+    #[test]
+    fn test_subtotals_exceeding_totals() {
+        let mut values = oats();
+        values.saturates = dec!(8);
+        values.sugars = dec!(61);
+        assert_eq!(fields(&values), vec!["saturates", "sugars"]);
+    }
+
+    fn codes(values: &NutrientValues) -> Vec<&'static str> {
+        nutrient_issues(values).iter().map(|i| i.code).collect()
+    }
+
+    #[test]
+    fn test_energy_mismatch() {
+        let mut values = oats();
+        values.energy = 100;
+        assert_eq!(fields(&values), vec!["energy"]);
+        assert_eq!(codes(&values), vec!["energy_below_macros"]);
+        // Energy with no macros to account for it.
+        let mut values = oats();
+        values.energy = 900;
+        values.fat = dec!(0);
+        values.saturates = dec!(0);
+        values.carbohydrate = dec!(0);
+        values.sugars = dec!(0);
+        values.protein = dec!(0);
+        values.fibre = dec!(0);
+        values.salt = dec!(0);
+        assert_eq!(codes(&values), vec!["energy_above_macros"]);
+        // A beer's alcohol is within the unaccounted weight, so it is only reported.
+        let beer = NutrientValues {
+            data_value: 100,
+            data_measurement: String::from("ml"),
+            energy: 43,
+            fat: dec!(0),
+            saturates: dec!(0),
+            carbohydrate: dec!(3.6),
+            sugars: dec!(0),
+            fibre: dec!(0),
+            protein: dec!(0.5),
+            salt: dec!(0),
+        };
+        assert_eq!(codes(&beer), vec![ENERGY_MISMATCH]);
+        // Small foods get an absolute allowance.
+        let mut small = oats();
+        small.data_value = 1;
+        small.data_measurement = String::from("srv");
+        small.energy = 15;
+        small.fat = dec!(0);
+        small.carbohydrate = dec!(0);
+        small.protein = dec!(0);
+        small.sugars = dec!(0);
+        small.saturates = dec!(0);
+        assert!(nutrient_issues(&small).is_empty());
+    }
+
+    #[test]
+    fn test_nutrients_exceed_weight() {
+        let mut values = oats();
+        values.data_value = 50;
+        assert!(fields(&values).contains(&"data_value"));
+    }
+}
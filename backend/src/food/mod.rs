@@ -1,4 +1,7 @@
+pub mod consistency;
 pub mod model;
+pub mod nutri_score;
 pub mod router;
 pub mod serializer;
+pub mod usage;
 pub mod view;
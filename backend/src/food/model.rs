@@ -2,12 +2,19 @@ use chrono::prelude::*;
 use futures::TryStreamExt;
 use rust_decimal::Decimal;
 use serde::Serialize;
-use sqlx::{FromRow, PgPool, Row};
+use sqlx::{FromRow, PgExecutor, PgPool, Row};
 use uuid::Uuid;
 
 use crate::{db::Filters, middleware::RequestUser, util::query::QueryParams};
 
-use super::serializer::FoodDeserializer;
+use super::{
+    consistency::{
+        energy_from_macros, nutrient_issues, push_issue_filter, NutrientIssue, NutrientValues,
+    },
+    nutri_score::{nutri_score, NutriScoreInput, NutriScorePoints},
+    serializer::FoodDeserializer,
+    usage::push_food_usage,
+};
 
 pub const DATA_MEASUREMENT_OPTS: &[&str; 3] = &["g", "ml", "srv"];
 
@@ -21,6 +28,7 @@ const ORDERING_FIELDS: &[&str] = &[
     "fibre",
     "protein",
     "salt",
+    "nutri_score",
     "food_count",
     "created_at",
     "updated_at",
@@ -50,6 +58,10 @@ pub struct Food {
     pub updated_at: Option<DateTime<Utc>>,
     pub created_by_id: Uuid,
     pub updated_by_id: Option<Uuid>,
+    pub review_status: String,
+    pub review_reason: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub reviewed_by_id: Option<Uuid>,
 }
 
 impl Food {
@@ -57,6 +69,7 @@ impl Food {
         pool: &PgPool,
         data: &FoodDeserializer,
         created_by_id: &Uuid,
+        review_status: &str,
     ) -> Result<Self, sqlx::Error> {
         let trimmed_name = data.name.trim();
         let slug = slug::slugify(&trimmed_name);
@@ -77,7 +90,8 @@ impl Food {
                 fibre,
                 protein,
                 salt,
-                created_by_id
+                created_by_id,
+                review_status
             )
         VALUES
             (
@@ -94,7 +108,8 @@ impl Food {
                 $11,
                 $12,
                 $13,
-                $14
+                $14,
+                $15
             )
         RETURNING
             *
@@ -114,6 +129,7 @@ impl Food {
         .bind(data.protein)
         .bind(data.salt)
         .bind(created_by_id)
+        .bind(review_status)
         .fetch_one(pool)
         .await?;
         Ok(query)
@@ -133,7 +149,7 @@ impl Food {
         Ok(query)
     }
     pub async fn update(
-        pool: &PgPool,
+        executor: impl PgExecutor<'_>,
         id: &Uuid,
         data: &FoodDeserializer,
         updated_by_id: &Uuid,
@@ -182,14 +198,14 @@ impl Food {
         .bind(updated_at)
         .bind(updated_by_id)
         .bind(id)
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await?;
         Ok(query)
     }
-    pub async fn delete(pool: &PgPool, id: &Uuid) -> Result<Self, sqlx::Error> {
+    pub async fn delete(executor: impl PgExecutor<'_>, id: &Uuid) -> Result<Self, sqlx::Error> {
         let query = sqlx::query_as("DELETE FROM food WHERE id = $1 RETURNING *")
             .bind(id)
-            .fetch_one(pool)
+            .fetch_one(executor)
             .await?;
         Ok(query)
     }
@@ -201,7 +217,7 @@ impl Food {
         Ok(query)
     }
     pub async fn delete_id_range(
-        pool: &PgPool,
+        executor: impl PgExecutor<'_>,
         id_range: Vec<Uuid>,
     ) -> Result<Vec<Self>, sqlx::Error> {
         let query = sqlx::query_as(
@@ -214,7 +230,7 @@ impl Food {
             ",
         )
         .bind(id_range)
-        .fetch_all(pool)
+        .fetch_all(executor)
         .await?;
         Ok(query)
     }
@@ -245,14 +261,43 @@ pub struct FoodSerializer {
     pub added_count: Option<i64>,
     pub last_added_qty: Option<Decimal>,
     pub last_added_date: Option<DateTime<Utc>>,
+    pub created_by_id: Uuid,
+    pub review_status: String,
+    pub review_reason: Option<String>,
+    pub nutri_score: Option<i32>,
+    pub nutri_grade: Option<String>,
+    #[sqlx(skip)]
+    pub nutri_score_points: Option<NutriScorePoints>,
 }
 
 impl FoodSerializer {
-    pub async fn count(pool: &PgPool, query: &QueryParams) -> Result<i64, sqlx::Error> {
+    fn with_nutri_score_points(mut self) -> Self {
+        self.nutri_score_points = nutri_score(&NutriScoreInput {
+            data_value: self.data_value,
+            data_measurement: self.data_measurement.clone(),
+            energy: self.energy,
+            saturates: self.saturates,
+            sugars: self.sugars,
+            salt: self.salt,
+            fibre: self.fibre,
+            protein: self.protein,
+        });
+        self
+    }
+    pub async fn count(
+        pool: &PgPool,
+        query: &QueryParams,
+        request_user: &RequestUser,
+    ) -> Result<i64, sqlx::Error> {
         let mut q = sqlx::QueryBuilder::new("SELECT COUNT(t1.*) FROM food t1 WHERE TRUE");
         q.filter_icontains("t1.name", &query.search);
         q.filter_exact("t1.data_measurement", &query.serving);
         q.filter_exact("t2.slug", &query.brand);
+        q.filter_exact(
+            "t1.nutri_grade",
+            &query.grade.as_ref().map(|grade| grade.to_uppercase()),
+        );
+        q.filter_review_status("t1", &query.status, request_user);
         let count = q.build().fetch_one(pool).await?.get("count");
         Ok(count)
     }
@@ -275,17 +320,9 @@ impl FoodSerializer {
             ",
         );
         if request_user.is_authenticated {
-            q.push("(SELECT COUNT(*) FROM food_log WHERE food_id = t1.id AND user_id = ");
-            q.push_bind(request_user.id);
-            q.push(") AS added_count,");
-
-            q.push("(SELECT quantity FROM food_log WHERE food_id = t1.id AND user_id = ");
-            q.push_bind(request_user.id);
-            q.push(" ORDER BY food_log.created_at DESC LIMIT 1) AS last_added_qty,");
-
-            q.push("(SELECT created_at FROM food_log WHERE food_log.food_id = t1.id AND food_log.user_id = ");
-            q.push_bind(request_user.id);
-            q.push(" ORDER BY food_log.created_at DESC LIMIT 1) AS last_added_date");
+            q.push("COALESCE(t3.log_count, 0) AS added_count,");
+            q.push("t3.last_quantity AS last_added_qty,");
+            q.push("t3.last_logged_at AS last_added_date");
         } else {
             q.push("null AS added_count,");
             q.push("null AS last_added_qty,");
@@ -296,21 +333,30 @@ impl FoodSerializer {
             FROM
                 food t1
                 LEFT JOIN food_brand t2 ON t1.brand_id = t2.id
-            WHERE
-                TRUE
             ",
         );
+        if request_user.is_authenticated {
+            q.push(" LEFT JOIN (");
+            push_food_usage(&mut q, request_user.id, None);
+            q.push(") t3 ON t3.food_id = t1.id");
+        }
+        q.push(" WHERE TRUE");
         q.filter_icontains("t1.name", &params.search);
         q.filter_exact("t1.data_measurement", &params.serving);
         q.filter_exact("t2.slug", &params.brand);
+        q.filter_exact(
+            "t1.nutri_grade",
+            &params.grade.as_ref().map(|grade| grade.to_uppercase()),
+        );
+        q.filter_review_status("t1", &params.status, &request_user);
 
         q.ordering_filter(&params, ORDERING_FIELDS, "t1.name");
 
         q.paginate(params.page, params.size);
 
-        let mut rows = q.build_query_as().fetch(pool);
+        let mut rows = q.build_query_as::<Self>().fetch(pool);
         while let Some(row) = rows.try_next().await? {
-            stream.push(row);
+            stream.push(row.with_nutri_score_points());
         }
         Ok(stream)
     }
@@ -332,20 +378,12 @@ impl FoodSerializer {
         ",
         );
         if request_user.is_authenticated {
-            q.push("(SELECT COUNT(*) FROM food_log WHERE food_id = t1.id AND user_id = ");
-            q.push_bind(request_user.id);
-            q.push(") AS added_count,");
-
-            q.push("(SELECT quantity FROM food_log WHERE food_id = t1.id AND user_id = ");
-            q.push_bind(request_user.id);
-            q.push(" ORDER BY food_log.created_at DESC LIMIT 1) AS last_added_qty,");
-
-            q.push("(SELECT created_at FROM food_log WHERE food_log.food_id = t1.id AND food_log.user_id = ");
-            q.push_bind(request_user.id);
-            q.push(" ORDER BY food_log.created_at DESC LIMIT 1) AS last_added_date");
+            q.push("COALESCE(t3.log_count, 0) AS added_count,");
+            q.push("t3.last_quantity AS last_added_qty,");
+            q.push("t3.last_logged_at AS last_added_date");
         } else {
-            q.push("null AS added_count");
-            q.push("null AS last_added_qty");
+            q.push("null AS added_count,");
+            q.push("null AS last_added_qty,");
             q.push("null AS last_added_date");
         }
         q.push(
@@ -355,9 +393,19 @@ impl FoodSerializer {
             LEFT JOIN food_brand t2 ON t2.id = t1.brand_id
         ",
         );
+        if request_user.is_authenticated {
+            q.push(" LEFT JOIN (");
+            push_food_usage(&mut q, request_user.id, None);
+            q.push(") t3 ON t3.food_id = t1.id");
+        }
         q.push(" WHERE t1.id = ");
-        q.push_bind(id);
-        let query = q.build_query_as().fetch_optional(pool).await?;
+        q.push_bind(*id);
+        q.filter_review_status("t1", &None, &request_user);
+        let query = q
+            .build_query_as::<Self>()
+            .fetch_optional(pool)
+            .await?
+            .map(Self::with_nutri_score_points);
         Ok(query)
     }
 }
@@ -369,24 +417,120 @@ pub struct FoodSelect {
 }
 
 impl FoodSelect {
-    pub async fn all(pool: &PgPool) -> Result<Vec<Self>, sqlx::Error> {
+    pub async fn all(pool: &PgPool, request_user: &RequestUser) -> Result<Vec<Self>, sqlx::Error> {
         let mut stream = Vec::new();
-        let mut rows = sqlx::query_as(
+        let mut q = sqlx::QueryBuilder::new(
             "
             SELECT
-                id,
-                name
+                t1.id,
+                t1.name
             FROM
-                food
-            ORDER BY
-                name
-            LIMIT 100
+                food t1
+            WHERE
+                TRUE
             ",
-        )
-        .fetch(pool);
+        );
+        q.filter_review_status("t1", &None, request_user);
+        q.push(" ORDER BY t1.name LIMIT 100");
+        let mut rows = q.build_query_as().fetch(pool);
         while let Some(row) = rows.try_next().await? {
             stream.push(row);
         }
         Ok(stream)
     }
 }
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct FoodNutrientReport {
+    pub id: Uuid,
+    pub name: String,
+    pub slug: String,
+    pub brand_name: String,
+    pub data_value: i32,
+    pub data_measurement: String,
+    pub energy: i32,
+    pub fat: Decimal,
+    pub saturates: Decimal,
+    pub carbohydrate: Decimal,
+    pub sugars: Decimal,
+    pub fibre: Decimal,
+    pub protein: Decimal,
+    pub salt: Decimal,
+    #[sqlx(skip)]
+    pub energy_from_macros: Decimal,
+    #[sqlx(skip)]
+    pub issues: Vec<NutrientIssue>,
+}
+
+impl FoodNutrientReport {
+    pub async fn count(pool: &PgPool, params: &QueryParams) -> Result<i64, sqlx::Error> {
+        let mut q = sqlx::QueryBuilder::new(
+            "
+            SELECT
+                COUNT(t1.*)
+            FROM
+                food t1
+                LEFT JOIN food_brand t2 ON t2.id = t1.brand_id
+            WHERE
+                TRUE
+            ",
+        );
+        q.filter_icontains("t1.name", &params.search);
+        q.filter_exact("t2.slug", &params.brand);
+        push_issue_filter(&mut q);
+        let count = q.build().fetch_one(pool).await?.get("count");
+        Ok(count)
+    }
+    // SQL finds the failing foods; the issues on each are then listed by the Rust rules.
+    pub async fn all(pool: &PgPool, params: &QueryParams) -> Result<Vec<Self>, sqlx::Error> {
+        let mut stream = Vec::new();
+        let mut q = sqlx::QueryBuilder::new(
+            "
+            SELECT
+                t1.id,
+                t1.name,
+                t1.slug,
+                t2.name AS brand_name,
+                t1.data_value,
+                t1.data_measurement,
+                t1.energy,
+                t1.fat,
+                t1.saturates,
+                t1.carbohydrate,
+                t1.sugars,
+                t1.fibre,
+                t1.protein,
+                t1.salt
+            FROM
+                food t1
+                LEFT JOIN food_brand t2 ON t2.id = t1.brand_id
+            WHERE
+                TRUE
+            ",
+        );
+        q.filter_icontains("t1.name", &params.search);
+        q.filter_exact("t2.slug", &params.brand);
+        push_issue_filter(&mut q);
+        q.push(" ORDER BY t1.name");
+        q.paginate(params.page, params.size);
+        let mut rows = q.build_query_as::<Self>().fetch(pool);
+        while let Some(mut row) = rows.try_next().await? {
+            let values = NutrientValues {
+                data_value: row.data_value,
+                data_measurement: row.data_measurement.clone(),
+                energy: row.energy,
+                fat: row.fat,
+                saturates: row.saturates,
+                carbohydrate: row.carbohydrate,
+                sugars: row.sugars,
+                fibre: row.fibre,
+                protein: row.protein,
+                salt: row.salt,
+            };
+            row.issues = nutrient_issues(&values);
+            row.energy_from_macros = energy_from_macros(&values);
+            stream.push(row);
+        }
+        Ok(stream)
+    }
+}
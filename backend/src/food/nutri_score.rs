@@ -0,0 +1,216 @@
+use rust_decimal::prelude::*;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+
+// Nutri-Score thresholds for solid foods, per 100g/ml. Kept in step with the
+// nutri_score_points function in the add_food_nutri_score migration, which backs
+// filtering and sorting.
+const ENERGY_KJ: [Decimal; 10] = [
+    dec!(335),
+    dec!(670),
+    dec!(1005),
+    dec!(1340),
+    dec!(1675),
+    dec!(2010),
+    dec!(2345),
+    dec!(2680),
+    dec!(3015),
+    dec!(3350),
+];
+const SATURATES: [Decimal; 10] = [
+    dec!(1),
+    dec!(2),
+    dec!(3),
+    dec!(4),
+    dec!(5),
+    dec!(6),
+    dec!(7),
+    dec!(8),
+    dec!(9),
+    dec!(10),
+];
+const SUGARS: [Decimal; 10] = [
+    dec!(4.5),
+    dec!(9),
+    dec!(13.5),
+    dec!(18),
+    dec!(22.5),
+    dec!(27),
+    dec!(31),
+    dec!(36),
+    dec!(40),
+    dec!(45),
+];
+const SODIUM_MG: [Decimal; 10] = [
+    dec!(90),
+    dec!(180),
+    dec!(270),
+    dec!(360),
+    dec!(450),
+    dec!(540),
+    dec!(630),
+    dec!(720),
+    dec!(810),
+    dec!(900),
+];
+const FIBRE: [Decimal; 5] = [dec!(0.9), dec!(1.9), dec!(2.8), dec!(3.7), dec!(4.7)];
+const PROTEIN: [Decimal; 5] = [dec!(1.6), dec!(3.2), dec!(4.8), dec!(6.4), dec!(8.0)];
+
+const KJ_PER_KCAL: Decimal = dec!(4.184);
+const SODIUM_MG_PER_SALT_G: Decimal = dec!(400);
+// Above this many negative points protein no longer offsets the score.
+const PROTEIN_CAP: i32 = 11;
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct NutriScorePoints {
+    pub energy: i32,
+    pub saturates: i32,
+    pub sugars: i32,
+    pub sodium: i32,
+    pub fibre: i32,
+    pub protein: i32,
+    pub negative: i32,
+    pub positive: i32,
+    pub total: i32,
+    pub grade: String,
+}
+
+pub struct NutriScoreInput {
+    pub data_value: i32,
+    pub data_measurement: String,
+    pub energy: i32,
+    pub saturates: Decimal,
+    pub sugars: Decimal,
+    pub salt: Decimal,
+    pub fibre: Decimal,
+    pub protein: Decimal,
+}
+
+fn band(value: Decimal, thresholds: &[Decimal]) -> i32 {
+    thresholds.iter().filter(|t| value > **t).count() as i32
+}
+
+pub fn nutri_grade(points: i32) -> &'static str {
+    match points {
+        i32::MIN..=-1 => "A",
+        0..=2 => "B",
+        3..=10 => "C",
+        11..=18 => "D",
+        _ => "E",
+    }
+}
+
+// Foods measured per serving have no weight to normalise against, so they are not graded.
+pub fn nutri_score(food: &NutriScoreInput) -> Option<NutriScorePoints> {
+    if !matches!(food.data_measurement.as_str(), "g" | "ml") || food.data_value <= 0 {
+        return None;
+    }
+    let per_100 = dec!(100) / Decimal::from(food.data_value);
+    let energy = band(
+        Decimal::from(food.energy) * KJ_PER_KCAL * per_100,
+        &ENERGY_KJ,
+    );
+    let saturates = band(food.saturates * per_100, &SATURATES);
+    let sugars = band(food.sugars * per_100, &SUGARS);
+    let sodium = band(food.salt * SODIUM_MG_PER_SALT_G * per_100, &SODIUM_MG);
+    let fibre = band(food.fibre * per_100, &FIBRE);
+    let protein = band(food.protein * per_100, &PROTEIN);
+
+    let negative = energy + saturates + sugars + sodium;
+    let positive = if negative >= PROTEIN_CAP {
+        fibre
+    } else {
+        fibre + protein
+    };
+    let total = negative - positive;
+    Some(NutriScorePoints {
+        energy,
+        saturates,
+        sugars,
+        sodium,
+        fibre,
+        protein,
+        negative,
+        positive,
+        total,
+        grade: nutri_grade(total).to_string(),
+    })
+}
+
+pub fn diet_nutri_grade(score: Decimal) -> &'static str {
+    let points = score.round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero);
+    nutri_grade(points.to_i32().unwrap_or(i32::MAX))
+}
+
+// Energy-weighted average of the scores of the graded foods eaten in a day.
+pub fn diet_nutri_score(entries: impl Iterator<Item = (Decimal, Option<i32>)>) -> Option<Decimal> {
+    let (weighted, energy) = entries
+        .filter_map(|(energy, points)| points.map(|points| (energy, points)))
+        .fold(
+            (Decimal::ZERO, Decimal::ZERO),
+            |(weighted, total), (energy, points)| {
+                (weighted + energy * Decimal::from(points), total + energy)
+            },
+        );
+    if energy.is_zero() {
+        return None;
+    }
+    Some((weighted / energy).round_dp(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn food(data_value: i32, energy: i32, saturates: Decimal, sugars: Decimal) -> NutriScoreInput {
+        NutriScoreInput {
+            data_value,
+            data_measurement: String::from("g"),
+            energy,
+            saturates,
+            sugars,
+            salt: dec!(0.01),
+            fibre: dec!(10),
+            protein: dec!(13),
+        }
+    }
+
+    #[test]
+    fn test_oats_grade_a() {
+        let points = nutri_score(&food(100, 370, dec!(1.2), dec!(1))).unwrap();
+        assert_eq!(points.energy, 4);
+        assert_eq!(points.saturates, 1);
+        assert_eq!(points.positive, 10);
+        assert_eq!(points.total, -5);
+        assert_eq!(points.grade, "A");
+    }
+
+    #[test]
+    fn test_protein_ignored_above_cap() {
+        // Chocolate: 540kcal, 18g saturates, 50g sugars per 100g.
+        let points = nutri_score(&food(50, 270, dec!(9), dec!(25))).unwrap();
+        assert_eq!(points.negative, 26);
+        assert_eq!(points.positive, points.fibre);
+        assert_eq!(points.grade, "E");
+    }
+
+    #[test]
+    fn test_serving_foods_are_not_graded() {
+        let mut input = food(1, 200, dec!(1), dec!(1));
+        input.data_measurement = String::from("srv");
+        assert!(nutri_score(&input).is_none());
+    }
+
+    #[test]
+    fn test_diet_nutri_score_is_energy_weighted() {
+        let entries = vec![
+            (dec!(300), Some(-5)),
+            (dec!(100), Some(15)),
+            (dec!(500), None),
+        ];
+        assert_eq!(diet_nutri_score(entries.into_iter()), Some(dec!(0.0)));
+        assert_eq!(diet_nutri_score(std::iter::empty()), None);
+        assert_eq!(diet_nutri_grade(dec!(-0.5)), "A");
+        assert_eq!(diet_nutri_grade(dec!(2.4)), "B");
+    }
+}
@@ -8,7 +8,9 @@ use crate::AppState;
 
 use super::view::{
     food_create_view, food_delete_id_range_view, food_delete_view, food_detail_view,
-    food_list_view, food_select_view, food_slug_detail_view, food_update_view,
+    food_favourite_create_view, food_favourite_delete_view, food_favourite_list_view,
+    food_frequent_list_view, food_list_view, food_nutrient_issue_list_view, food_recent_list_view,
+    food_select_view, food_slug_detail_view, food_update_view,
 };
 
 pub fn food_router() -> Router<Arc<AppState>> {
@@ -20,5 +22,11 @@ pub fn food_router() -> Router<Arc<AppState>> {
         .route("/:id", delete(food_delete_view))
         .route("/slug/:slug", get(food_slug_detail_view))
         .route("/select", get(food_select_view))
+        .route("/nutrient-issues", get(food_nutrient_issue_list_view))
+        .route("/favourites", get(food_favourite_list_view))
+        .route("/favourites", post(food_favourite_create_view))
+        .route("/favourites/:id", delete(food_favourite_delete_view))
+        .route("/recent", get(food_recent_list_view))
+        .route("/frequent", get(food_frequent_list_view))
         .route("/delete-id-range", delete(food_delete_id_range_view))
 }
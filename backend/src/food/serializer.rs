@@ -1,14 +1,20 @@
 use rust_decimal::Decimal;
 use serde::Deserialize;
 use uuid::Uuid;
-use validator::Validate;
+use validator::{Validate, ValidationError};
 
 use crate::util::validator::{
     validate_data_measurement, validate_max_quantity, validate_non_negative_decimal,
     validate_not_empty_string, validate_positive_int,
 };
 
+use super::consistency::{nutrient_issues, NutrientValues, ENERGY_MISMATCH};
+
 #[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_saturates_consistency"))]
+#[validate(schema(function = "validate_sugars_consistency"))]
+#[validate(schema(function = "validate_energy_consistency"))]
+#[validate(schema(function = "validate_weight_consistency"))]
 pub struct FoodDeserializer {
     #[validate(
         length(min = 3, message = "Minimum of 3 characters"),
@@ -113,3 +119,59 @@ pub struct FoodDeserializer {
     )]
     pub salt: Decimal,
 }
+
+impl FoodDeserializer {
+    pub fn nutrient_values(&self) -> NutrientValues {
+        NutrientValues {
+            data_value: self.data_value,
+            data_measurement: self.data_measurement.clone(),
+            energy: self.energy,
+            fat: self.fat,
+            saturates: self.saturates,
+            carbohydrate: self.carbohydrate,
+            sugars: self.sugars,
+            fibre: self.fibre,
+            protein: self.protein,
+            salt: self.salt,
+        }
+    }
+}
+
+fn validate_nutrient_field(data: &FoodDeserializer, field: &str) -> Result<(), ValidationError> {
+    match nutrient_issues(&data.nutrient_values())
+        .into_iter()
+        .find(|issue| issue.field == field)
+    {
+        Some(issue) => Err(issue.into()),
+        None => Ok(()),
+    }
+}
+
+fn validate_saturates_consistency(data: &FoodDeserializer) -> Result<(), ValidationError> {
+    validate_nutrient_field(data, "saturates")
+}
+
+fn validate_sugars_consistency(data: &FoodDeserializer) -> Result<(), ValidationError> {
+    validate_nutrient_field(data, "sugars")
+}
+
+// Energy a little above the macronutrients may be alcohol and is left to the report.
+fn validate_energy_consistency(data: &FoodDeserializer) -> Result<(), ValidationError> {
+    match nutrient_issues(&data.nutrient_values())
+        .into_iter()
+        .find(|issue| issue.field == "energy" && issue.code != ENERGY_MISMATCH)
+    {
+        Some(issue) => Err(issue.into()),
+        None => Ok(()),
+    }
+}
+
+fn validate_weight_consistency(data: &FoodDeserializer) -> Result<(), ValidationError> {
+    validate_nutrient_field(data, "data_value")
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct FoodFavouriteDeserializer {
+    pub food_id: Uuid,
+    pub meal_of_day_id: Option<Uuid>,
+}
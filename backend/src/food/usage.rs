@@ -0,0 +1,254 @@
+use chrono::prelude::*;
+use futures::TryStreamExt;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::{FromRow, PgPool, Postgres, QueryBuilder, Row};
+use uuid::Uuid;
+
+use crate::{db::Filters, util::query::QueryParams};
+
+// Per-food totals for a user from the user_food_usage aggregate, across every
+// meal slot unless one is given.
+pub fn push_food_usage(
+    q: &mut QueryBuilder<'static, Postgres>,
+    user_id: Uuid,
+    meal_of_day_id: Option<Uuid>,
+) {
+    q.push(
+        "
+        SELECT
+            food_id,
+            SUM(log_count)::BIGINT AS log_count,
+            (ARRAY_AGG(last_quantity ORDER BY last_logged_at DESC))[1] AS last_quantity,
+            MAX(last_logged_at) AS last_logged_at
+        FROM
+            user_food_usage
+        WHERE
+            user_id = ",
+    );
+    q.push_bind(user_id);
+    q.filter_uuid_exact("meal_of_day_id", &meal_of_day_id);
+    q.push(" GROUP BY food_id");
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum UsageOrdering {
+    Recent,
+    Frequent,
+}
+
+impl UsageOrdering {
+    fn order_by(&self) -> &'static str {
+        match self {
+            Self::Recent => " ORDER BY t3.last_logged_at DESC, t1.name",
+            Self::Frequent => " ORDER BY t3.log_count DESC, t3.last_logged_at DESC, t1.name",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct UserFood {
+    pub id: Uuid,
+    pub name: String,
+    pub slug: String,
+    pub brand_id: Uuid,
+    pub brand_name: String,
+    pub brand_slug: String,
+    pub data_value: i32,
+    pub data_measurement: String,
+    pub energy: i32,
+    pub fat: Decimal,
+    pub saturates: Decimal,
+    pub carbohydrate: Decimal,
+    pub sugars: Decimal,
+    pub fibre: Decimal,
+    pub protein: Decimal,
+    pub salt: Decimal,
+    pub favourite_id: Option<Uuid>,
+    pub favourite_meal_of_day_id: Option<Uuid>,
+    pub log_count: Option<i64>,
+    pub last_quantity: Option<Decimal>,
+    pub last_logged_at: Option<DateTime<Utc>>,
+}
+
+const USER_FOOD_COLUMNS: &str = "
+    SELECT
+        t1.id,
+        t1.name,
+        t1.slug,
+        t1.brand_id,
+        t2.name AS brand_name,
+        t2.slug AS brand_slug,
+        t1.data_value,
+        t1.data_measurement,
+        t1.energy,
+        t1.fat,
+        t1.saturates,
+        t1.carbohydrate,
+        t1.sugars,
+        t1.fibre,
+        t1.protein,
+        t1.salt,
+        t4.id AS favourite_id,
+        t4.meal_of_day_id AS favourite_meal_of_day_id,
+        t3.log_count,
+        t3.last_quantity,
+        t3.last_logged_at
+    ";
+
+// A food may be pinned to several slots; prefer the pin for the requested slot
+// over an unscoped one so each food appears once.
+fn push_favourite(
+    q: &mut QueryBuilder<'static, Postgres>,
+    user_id: Uuid,
+    meal_of_day_id: Option<Uuid>,
+) {
+    q.push(
+        "
+        (
+            SELECT DISTINCT ON (food_id)
+                id,
+                food_id,
+                meal_of_day_id
+            FROM
+                food_favourite
+            WHERE
+                user_id = ",
+    );
+    q.push_bind(user_id);
+    if let Some(meal_of_day_id) = meal_of_day_id {
+        q.push(" AND (meal_of_day_id IS NULL OR meal_of_day_id = ");
+        q.push_bind(meal_of_day_id);
+        q.push(")");
+    }
+    q.push(" ORDER BY food_id, meal_of_day_id NULLS LAST, created_at)");
+}
+
+impl UserFood {
+    pub async fn count_favourites(
+        pool: &PgPool,
+        user_id: Uuid,
+        params: &QueryParams,
+    ) -> Result<i64, sqlx::Error> {
+        let mut q = QueryBuilder::new("SELECT COUNT(*) FROM ");
+        push_favourite(&mut q, user_id, params.meal_of_day_id);
+        q.push(" t4");
+        let count = q.build().fetch_one(pool).await?.get("count");
+        Ok(count)
+    }
+    pub async fn favourites(
+        pool: &PgPool,
+        user_id: Uuid,
+        params: &QueryParams,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let mut stream = Vec::new();
+        let mut q = QueryBuilder::new(USER_FOOD_COLUMNS);
+        q.push(" FROM ");
+        push_favourite(&mut q, user_id, params.meal_of_day_id);
+        q.push(
+            " t4
+            JOIN food t1 ON t1.id = t4.food_id
+            JOIN food_brand t2 ON t2.id = t1.brand_id
+            LEFT JOIN (",
+        );
+        push_food_usage(&mut q, user_id, params.meal_of_day_id);
+        q.push(") t3 ON t3.food_id = t1.id ORDER BY t1.name");
+        q.paginate(params.page, params.size);
+        let mut rows = q.build_query_as::<Self>().fetch(pool);
+        while let Some(row) = rows.try_next().await? {
+            stream.push(row);
+        }
+        Ok(stream)
+    }
+    pub async fn count_used(
+        pool: &PgPool,
+        user_id: Uuid,
+        params: &QueryParams,
+    ) -> Result<i64, sqlx::Error> {
+        let mut q = QueryBuilder::new(
+            "SELECT COUNT(DISTINCT food_id) FROM user_food_usage WHERE user_id = ",
+        );
+        q.push_bind(user_id);
+        q.filter_uuid_exact("meal_of_day_id", &params.meal_of_day_id);
+        let count = q.build().fetch_one(pool).await?.get("count");
+        Ok(count)
+    }
+    pub async fn used(
+        pool: &PgPool,
+        user_id: Uuid,
+        params: &QueryParams,
+        ordering: UsageOrdering,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let mut stream = Vec::new();
+        let mut q = QueryBuilder::new(USER_FOOD_COLUMNS);
+        q.push(" FROM (");
+        push_food_usage(&mut q, user_id, params.meal_of_day_id);
+        q.push(
+            ") t3
+            JOIN food t1 ON t1.id = t3.food_id
+            JOIN food_brand t2 ON t2.id = t1.brand_id
+            LEFT JOIN ",
+        );
+        push_favourite(&mut q, user_id, params.meal_of_day_id);
+        q.push(" t4 ON t4.food_id = t1.id");
+        q.push(ordering.order_by());
+        q.paginate(params.page, params.size);
+        let mut rows = q.build_query_as::<Self>().fetch(pool);
+        while let Some(row) = rows.try_next().await? {
+            stream.push(row);
+        }
+        Ok(stream)
+    }
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct FoodFavourite {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub food_id: Uuid,
+    pub meal_of_day_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl FoodFavourite {
+    // Pinning the same food to the same slot twice returns the existing pin.
+    pub async fn create(
+        pool: &PgPool,
+        user_id: &Uuid,
+        food_id: &Uuid,
+        meal_of_day_id: &Option<Uuid>,
+    ) -> Result<Self, sqlx::Error> {
+        let query = sqlx::query_as(
+            "
+            INSERT INTO
+                food_favourite (user_id, food_id, meal_of_day_id)
+            VALUES
+                ($1, $2, $3)
+            ON CONFLICT (user_id, food_id, meal_of_day_id) DO UPDATE
+            SET
+                created_at = food_favourite.created_at
+            RETURNING
+                *
+            ",
+        )
+        .bind(user_id)
+        .bind(food_id)
+        .bind(meal_of_day_id)
+        .fetch_one(pool)
+        .await?;
+        Ok(query)
+    }
+    pub async fn delete(
+        pool: &PgPool,
+        id: &Uuid,
+        user_id: &Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let query =
+            sqlx::query_as("DELETE FROM food_favourite WHERE id = $1 AND user_id = $2 RETURNING *")
+                .bind(id)
+                .bind(user_id)
+                .fetch_optional(pool)
+                .await?;
+        Ok(query)
+    }
+}
@@ -9,14 +9,18 @@ use uuid::Uuid;
 use crate::{
     error::AppError,
     extractor::JsonExtractor,
+    meal_of_day::model::MealOfDay,
     middleware::RequestUser,
+    moderation::model::{edit_required, initial_review_status, CatalogueTable, Review},
+    revision::model::set_actor,
     util::{extract::IdRange, query::QueryParams},
     AppState,
 };
 
 use super::{
-    model::{Food, FoodSelect, FoodSerializer},
-    serializer::FoodDeserializer,
+    model::{Food, FoodNutrientReport, FoodSelect, FoodSerializer},
+    serializer::{FoodDeserializer, FoodFavouriteDeserializer},
+    usage::{FoodFavourite, UsageOrdering, UserFood},
 };
 
 pub async fn food_list_view(
@@ -24,7 +28,7 @@ pub async fn food_list_view(
     State(state): State<Arc<AppState>>,
     Extension(request_user): Extension<RequestUser>,
 ) -> Result<Json<Value>, AppError> {
-    let count = FoodSerializer::count(&state.pool, &params).await?;
+    let count = FoodSerializer::count(&state.pool, &params, &request_user).await?;
     let query = FoodSerializer::all(&state.pool, &params, request_user).await?;
     let response = json!({"count": count, "results": query});
     Ok(Json(response))
@@ -35,7 +39,9 @@ pub async fn food_create_view(
     Extension(request_user): Extension<RequestUser>,
     JsonExtractor(data): JsonExtractor<FoodDeserializer>,
 ) -> Result<Json<Food>, AppError> {
-    let query = Food::create(&state.pool, &data, &request_user.id).await?;
+    request_user.login_required()?;
+    let review_status = initial_review_status(&request_user);
+    let query = Food::create(&state.pool, &data, &request_user.id, review_status).await?;
     Ok(Json(query))
 }
 
@@ -68,9 +74,16 @@ pub async fn food_update_view(
     Extension(request_user): Extension<RequestUser>,
     JsonExtractor(data): JsonExtractor<FoodDeserializer>,
 ) -> Result<Json<Food>, AppError> {
-    request_user.superuser_required()?;
-    // let query = Food::get(&state.pool, &id).await?;
-    let query = Food::update(&state.pool, &id, &data, &request_user.id).await?;
+    let food = Food::get_opt(&state.pool, &id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    edit_required(&request_user, &food.created_by_id, &food.review_status)?;
+    let mut tx = state.pool.begin().await?;
+    if !request_user.is_moderator() {
+        Review::resubmit(&mut tx, CatalogueTable::Food, &id).await?;
+    }
+    let query = Food::update(&mut *tx, &id, &data, &request_user.id).await?;
+    tx.commit().await?;
     Ok(Json(query))
 }
 
@@ -79,16 +92,22 @@ pub async fn food_delete_view(
     State(state): State<Arc<AppState>>,
     Extension(request_user): Extension<RequestUser>,
 ) -> Result<Json<Food>, AppError> {
-    request_user.superuser_required()?;
-    // let query = Food::get(&state.pool, &id).await?;
-    let query = Food::delete(&state.pool, &id).await?;
+    let food = Food::get_opt(&state.pool, &id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    edit_required(&request_user, &food.created_by_id, &food.review_status)?;
+    let mut tx = state.pool.begin().await?;
+    set_actor(&mut tx, &request_user.id).await?;
+    let query = Food::delete(&mut *tx, &id).await?;
+    tx.commit().await?;
     Ok(Json(query))
 }
 
 pub async fn food_select_view(
     State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
 ) -> Result<Json<Vec<FoodSelect>>, AppError> {
-    let query = FoodSelect::all(&state.pool).await?;
+    let query = FoodSelect::all(&state.pool, &request_user).await?;
     Ok(Json(query))
 }
 
@@ -97,7 +116,93 @@ pub async fn food_delete_id_range_view(
     Extension(request_user): Extension<RequestUser>,
     JsonExtractor(data): JsonExtractor<IdRange>,
 ) -> Result<Json<Vec<Food>>, AppError> {
+    request_user.staff_required()?;
+    let mut tx = state.pool.begin().await?;
+    set_actor(&mut tx, &request_user.id).await?;
+    let query = Food::delete_id_range(&mut *tx, data.id_range).await?;
+    tx.commit().await?;
+    Ok(Json(query))
+}
+
+pub async fn food_nutrient_issue_list_view(
+    Query(params): Query<QueryParams>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<Value>, AppError> {
     request_user.superuser_required()?;
-    let query = Food::delete_id_range(&state.pool, data.id_range).await?;
+    let count = FoodNutrientReport::count(&state.pool, &params).await?;
+    let query = FoodNutrientReport::all(&state.pool, &params).await?;
+    let response = json!({"count": count, "results": query});
+    Ok(Json(response))
+}
+
+pub async fn food_favourite_list_view(
+    Query(params): Query<QueryParams>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<Value>, AppError> {
+    request_user.login_required()?;
+    let count = UserFood::count_favourites(&state.pool, request_user.id, &params).await?;
+    let query = UserFood::favourites(&state.pool, request_user.id, &params).await?;
+    let response = json!({"count": count, "results": query});
+    Ok(Json(response))
+}
+
+pub async fn food_favourite_create_view(
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    JsonExtractor(data): JsonExtractor<FoodFavouriteDeserializer>,
+) -> Result<Json<FoodFavourite>, AppError> {
+    request_user.login_required()?;
+    let user_id = request_user.id;
+    FoodSerializer::get(&state.pool, &data.food_id, request_user)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    if let Some(meal_of_day_id) = &data.meal_of_day_id {
+        MealOfDay::get(&state.pool, meal_of_day_id).await?;
+    }
+    let query =
+        FoodFavourite::create(&state.pool, &user_id, &data.food_id, &data.meal_of_day_id).await?;
+    Ok(Json(query))
+}
+
+pub async fn food_favourite_delete_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<FoodFavourite>, AppError> {
+    request_user.login_required()?;
+    let query = FoodFavourite::delete(&state.pool, &id, &request_user.id)
+        .await?
+        .ok_or(AppError::NotFound)?;
     Ok(Json(query))
 }
+
+pub async fn food_recent_list_view(
+    Query(params): Query<QueryParams>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<Value>, AppError> {
+    food_usage_list(&state, &request_user, &params, UsageOrdering::Recent).await
+}
+
+pub async fn food_frequent_list_view(
+    Query(params): Query<QueryParams>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<Value>, AppError> {
+    food_usage_list(&state, &request_user, &params, UsageOrdering::Frequent).await
+}
+
+async fn food_usage_list(
+    state: &AppState,
+    request_user: &RequestUser,
+    params: &QueryParams,
+    ordering: UsageOrdering,
+) -> Result<Json<Value>, AppError> {
+    request_user.login_required()?;
+    let count = UserFood::count_used(&state.pool, request_user.id, params).await?;
+    let query = UserFood::used(&state.pool, request_user.id, params, ordering).await?;
+    let response = json!({"count": count, "results": query});
+    Ok(Json(response))
+}
@@ -13,53 +13,88 @@ use tracing_subscriber::prelude::*;
 
 mod auth;
 mod brand;
+mod data_export;
 mod db;
 mod diet;
+mod diet_import;
 mod diet_target;
 mod diet_total;
 mod error;
 mod exercise;
 mod extractor;
+mod fasting;
 mod follower;
 mod food;
 mod meal;
 mod meal_food;
 mod meal_of_day;
+mod merge;
 mod middleware;
+mod moderation;
 mod movement;
 mod muscle_group;
+mod personal_record;
+mod planned_food;
 mod profile;
 mod progress;
+mod progress_photo;
+mod quick_log;
+mod recipe_import;
+mod revision;
 mod set;
+mod shopping_list;
+mod storage;
 mod training_plan;
 mod user;
 mod util;
+mod water_log;
 mod workout;
+mod workout_import;
 
 use crate::auth::router::auth_router;
-use crate::brand::router::brand_router;
+use crate::brand::logo::BRAND_LOGO_URL;
+use crate::brand::router::{brand_logo_router, brand_router};
+use crate::data_export::model::DataExport;
+use crate::data_export::router::data_export_router;
 use crate::diet::router::diet_router;
+use crate::diet_import::router::diet_import_router;
 use crate::diet_target::router::diet_target_router;
 use crate::diet_total::router::diet_total_router;
 use crate::exercise::router::exercise_router;
+use crate::fasting::router::fasting_router;
 use crate::follower::router::follower_router;
 use crate::food::router::food_router;
 use crate::meal::router::meal_router;
 use crate::meal_food::router::meal_food_router;
 use crate::meal_of_day::router::meal_of_day_router;
+use crate::merge::router::merge_router;
 use crate::middleware::authorization_middleware;
+use crate::moderation::router::moderation_router;
 use crate::movement::router::movement_router;
 use crate::muscle_group::router::muscle_group_router;
+use crate::personal_record::router::personal_record_router;
+use crate::planned_food::router::planned_food_router;
 use crate::profile::router::profile_router;
 use crate::progress::router::progress_router;
+use crate::progress_photo::router::progress_photo_router;
+use crate::quick_log::router::quick_log_router;
+use crate::recipe_import::router::recipe_import_router;
+use crate::revision::router::revision_router;
 use crate::set::router::set_router;
+use crate::shopping_list::router::shopping_list_router;
+use crate::storage::{brand_logo_storage_from_env, local::LocalStorage, storage_from_env, Storage};
+use crate::user::deletion::run_scheduled_deletions;
 use crate::user::router::user_router;
+use crate::water_log::router::water_log_router;
 use crate::workout::router::workout_router;
+use crate::workout_import::router::workout_import_router;
 
 #[derive(Debug, Clone)]
 pub struct AppState {
     pub secret: String,
     pub pool: PgPool,
+    pub storage: Arc<dyn Storage>,
+    pub brand_logos: Arc<LocalStorage>,
 }
 
 #[tokio::main]
@@ -76,8 +111,22 @@ async fn main() {
         .connect(&database_url)
         .await
         .expect("could not create a database pool");
-
-    let state = Arc::new(AppState { secret, pool });
+    let storage = storage_from_env();
+    let brand_logos = brand_logo_storage_from_env();
+    DataExport::fail_stale(&pool)
+        .await
+        .expect("could not reset interrupted data exports");
+
+    let state = Arc::new(AppState {
+        secret,
+        pool,
+        storage,
+        brand_logos,
+    });
+    tokio::spawn(run_scheduled_deletions(
+        state.pool.clone(),
+        state.storage.clone(),
+    ));
 
     let filter = tracing_subscriber::filter::Targets::new()
         // .with_target("tower_http::trace::on_request", Level::DEBUG)
@@ -122,22 +171,37 @@ async fn app(state: Arc<AppState>) -> Router {
     Router::new()
         .nest("/auth", auth_router())
         .nest("/brands", brand_router())
-        .nest("/diet-target", diet_target_router())
+        .nest("/data-exports", data_export_router())
         .nest("/diet", diet_router())
+        .nest("/diet-import", diet_import_router())
+        .nest("/diet-target", diet_target_router())
         .nest("/diet-total", diet_total_router())
         .nest("/exercises", exercise_router())
+        .nest("/fasting", fasting_router())
         .nest("/followers", follower_router())
         .nest("/food", food_router())
         .nest("/meal-food", meal_food_router())
         .nest("/meal-of-day", meal_of_day_router())
         .nest("/meals", meal_router())
+        .nest("/merges", merge_router())
+        .nest("/moderation", moderation_router())
         .nest("/movements", movement_router())
         .nest("/muscle-groups", muscle_group_router())
+        .nest("/personal-records", personal_record_router())
+        .nest("/planned-food", planned_food_router())
         .nest("/profiles", profile_router())
         .nest("/progress", progress_router())
+        .nest("/progress-photos", progress_photo_router())
+        .nest("/quick-log", quick_log_router())
+        .nest("/recipe-import", recipe_import_router())
+        .nest("/revisions", revision_router())
         .nest("/sets", set_router())
+        .nest("/shopping-list", shopping_list_router())
         .nest("/users", user_router())
+        .nest("/water-log", water_log_router())
+        .nest("/workout-import", workout_import_router())
         .nest("/workouts", workout_router())
+        .nest(BRAND_LOGO_URL, brand_logo_router())
         // .layer(from_fn(print_request_response))
         .layer(
             ServiceBuilder::new()
@@ -172,8 +236,15 @@ mod tests {
             .connect(&database_url)
             .await
             .expect("could not create a database pool");
-
-        let state = Arc::new(AppState { secret, pool });
+        let storage = storage_from_env();
+        let brand_logos = brand_logo_storage_from_env();
+
+        let state = Arc::new(AppState {
+            secret,
+            pool,
+            storage,
+            brand_logos,
+        });
         return state;
     }
 
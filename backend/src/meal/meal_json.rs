@@ -8,13 +8,13 @@ use crate::util::query::QueryParams;
 
 #[derive(Debug, Default, Deserialize, Serialize, FromRow)]
 pub struct MealFoodJSON {
-    pub food_id: Uuid,
+    pub food_id: Option<Uuid>,
     pub food_name: String,
-    pub food_slug: String,
-    pub brand_name: String,
-    pub brand_slug: String,
-    pub data_value: Decimal,
-    pub data_measurement: String,
+    pub food_slug: Option<String>,
+    pub brand_name: Option<String>,
+    pub brand_slug: Option<String>,
+    pub data_value: Option<Decimal>,
+    pub data_measurement: Option<String>,
     pub energy: Decimal,
     pub protein: Decimal,
     pub carbohydrate: Decimal,
@@ -54,14 +54,14 @@ impl MealJSON {
                 m1.user_id,
                 m4.username,
                 COUNT(m2.*) AS food_count,
-                SUM(m2.quantity * m3.energy) AS energy,
-                SUM(m2.quantity * m3.protein) AS protein,
-                SUM(m2.quantity * m3.carbohydrate) AS carbohydrate,
-                SUM(m2.quantity * m3.fat) AS fat,
-                SUM(m2.quantity * m3.saturates) AS saturates,
-                SUM(m2.quantity * m3.sugars) AS sugars,
-                SUM(m2.quantity * m3.fibre) AS fibre,
-                SUM(m2.quantity * m3.salt) AS salt,
+                SUM(m2.quantity * COALESCE(m3.energy, m2.energy)) AS energy,
+                SUM(m2.quantity * COALESCE(m3.protein, m2.protein)) AS protein,
+                SUM(m2.quantity * COALESCE(m3.carbohydrate, m2.carbohydrate)) AS carbohydrate,
+                SUM(m2.quantity * COALESCE(m3.fat, m2.fat)) AS fat,
+                SUM(m2.quantity * COALESCE(m3.saturates, m2.saturates)) AS saturates,
+                SUM(m2.quantity * COALESCE(m3.sugars, m2.sugars)) AS sugars,
+                SUM(m2.quantity * COALESCE(m3.fibre, m2.fibre)) AS fibre,
+                SUM(m2.quantity * COALESCE(m3.salt, m2.salt)) AS salt,
                 (
                     SELECT
                         JSON_AGG(meal_items)
@@ -69,20 +69,20 @@ impl MealJSON {
                         (
                             SELECT
                                 t1.food_id,
-                                t2.name AS food_name,
+                                COALESCE(t2.name, t1.label) AS food_name,
                                 t2.slug AS food_slug,
                                 t3.name AS brand_name,
                                 t3.slug AS brand_slug,
                                 t1.quantity * t2.data_value AS data_value,
                                 t2.data_measurement AS data_measurement,
-                                t1.quantity * t2.energy AS energy,
-                                t1.quantity * t2.protein AS protein,
-                                t1.quantity * t2.carbohydrate AS carbohydrate,
-                                t1.quantity * t2.fat AS fat,
-                                t1.quantity * t2.saturates AS saturates,
-                                t1.quantity * t2.sugars AS sugars,
-                                t1.quantity * t2.fibre AS fibre,
-                                t1.quantity * t2.salt AS salt
+                                t1.quantity * COALESCE(t2.energy, t1.energy) AS energy,
+                                t1.quantity * COALESCE(t2.protein, t1.protein) AS protein,
+                                t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate) AS carbohydrate,
+                                t1.quantity * COALESCE(t2.fat, t1.fat) AS fat,
+                                t1.quantity * COALESCE(t2.saturates, t1.saturates) AS saturates,
+                                t1.quantity * COALESCE(t2.sugars, t1.sugars) AS sugars,
+                                t1.quantity * COALESCE(t2.fibre, t1.fibre) AS fibre,
+                                t1.quantity * COALESCE(t2.salt, t1.salt) AS salt
                             FROM
                                 meal_food t1
                                 LEFT JOIN food t2 ON t2.id = t1.food_id
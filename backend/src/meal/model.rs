@@ -2,7 +2,7 @@ use chrono::prelude::*;
 use futures::TryStreamExt;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, PgPool, Row};
+use sqlx::{FromRow, PgConnection, PgPool, Row};
 use uuid::Uuid;
 
 use crate::{db::Filters, util::query::QueryParams};
@@ -20,7 +20,7 @@ pub struct Meal {
 
 impl Meal {
     pub async fn create(
-        pool: &PgPool,
+        conn: &mut PgConnection,
         user_id: Uuid,
         name: String,
         created_by_id: Uuid,
@@ -39,7 +39,7 @@ impl Meal {
         .bind(user_id)
         .bind(trimmed_name)
         .bind(created_by_id)
-        .fetch_one(pool)
+        .fetch_one(&mut *conn)
         .await?;
         Ok(query)
     }
@@ -216,14 +216,14 @@ impl MealSerializer {
             t1.created_by_id,
             t1.updated_by_id,
             COUNT(t2.*) AS food_count,
-            SUM(t2.quantity * t3.energy) AS energy,
-            SUM(t2.quantity * t3.protein) AS protein,
-            SUM(t2.quantity * t3.carbohydrate) AS carbohydrate,
-            SUM(t2.quantity * t3.fat) AS fat,
-            SUM(t2.quantity * t3.saturates) AS saturates,
-            SUM(t2.quantity * t3.sugars) AS sugars,
-            SUM(t2.quantity * t3.fibre) AS fibre,
-            SUM(t2.quantity * t3.salt) AS salt,
+            SUM(t2.quantity * COALESCE(t3.energy, t2.energy)) AS energy,
+            SUM(t2.quantity * COALESCE(t3.protein, t2.protein)) AS protein,
+            SUM(t2.quantity * COALESCE(t3.carbohydrate, t2.carbohydrate)) AS carbohydrate,
+            SUM(t2.quantity * COALESCE(t3.fat, t2.fat)) AS fat,
+            SUM(t2.quantity * COALESCE(t3.saturates, t2.saturates)) AS saturates,
+            SUM(t2.quantity * COALESCE(t3.sugars, t2.sugars)) AS sugars,
+            SUM(t2.quantity * COALESCE(t3.fibre, t2.fibre)) AS fibre,
+            SUM(t2.quantity * COALESCE(t3.salt, t2.salt)) AS salt,
             t4.username
         FROM
             meal t1
@@ -271,7 +271,7 @@ pub struct MealDeserializer {
 pub struct MealFoodSerializer {
     pub id: Uuid,
     pub meal_id: Uuid,
-    pub food_id: Uuid,
+    pub food_id: Option<Uuid>,
     pub quantity: Decimal,
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
@@ -279,11 +279,11 @@ pub struct MealFoodSerializer {
     pub updated_by_id: Option<Uuid>,
     pub meal_name: String,
     pub food_name: String,
-    pub food_slug: String,
-    pub brand_name: String,
-    pub brand_slug: String,
+    pub food_slug: Option<String>,
+    pub brand_name: Option<String>,
+    pub brand_slug: Option<String>,
     pub data_value: Option<Decimal>,
-    pub data_measurement: String,
+    pub data_measurement: Option<String>,
     pub energy: Option<Decimal>,
     pub protein: Option<Decimal>,
     pub carbohydrate: Option<Decimal>,
@@ -318,7 +318,7 @@ impl MealFoodSerializer {
                 t1.updated_by_id,
                 t4.name AS meal_name,
                 -- food
-                t2.name AS food_name,
+                COALESCE(t2.name, t1.label) AS food_name,
                 t2.slug AS food_slug,
                 -- brand
                 t3.name AS brand_name,
@@ -327,43 +327,43 @@ impl MealFoodSerializer {
                 t1.quantity,
                 t1.quantity * t2.data_value AS data_value,
                 t2.data_measurement,
-                t1.quantity * t2.energy AS energy,
-                t1.quantity * t2.protein AS protein,
-                t1.quantity * t2.carbohydrate AS carbohydrate,
-                t1.quantity * t2.fat AS fat,
-                t1.quantity * t2.saturates AS saturates,
-                t1.quantity * t2.sugars AS sugars,
-                t1.quantity * t2.fibre AS fibre,
-                t1.quantity * t2.salt AS salt,
-                SUM(t1.quantity * t2.energy) OVER (
+                t1.quantity * COALESCE(t2.energy, t1.energy) AS energy,
+                t1.quantity * COALESCE(t2.protein, t1.protein) AS protein,
+                t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate) AS carbohydrate,
+                t1.quantity * COALESCE(t2.fat, t1.fat) AS fat,
+                t1.quantity * COALESCE(t2.saturates, t1.saturates) AS saturates,
+                t1.quantity * COALESCE(t2.sugars, t1.sugars) AS sugars,
+                t1.quantity * COALESCE(t2.fibre, t1.fibre) AS fibre,
+                t1.quantity * COALESCE(t2.salt, t1.salt) AS salt,
+                SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) OVER (
                     PARTITION BY
                         t4.id
                 ) AS meal_energy,
-                SUM(t1.quantity * t2.protein) OVER (
+                SUM(t1.quantity * COALESCE(t2.protein, t1.protein)) OVER (
                     PARTITION BY
                         t4.id
                 ) AS meal_protein,
-                SUM(t1.quantity * t2.carbohydrate) OVER (
+                SUM(t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate)) OVER (
                     PARTITION BY
                         t4.id
                 ) AS meal_carbohydrate,
-                SUM(t1.quantity * t2.fat) OVER (
+                SUM(t1.quantity * COALESCE(t2.fat, t1.fat)) OVER (
                     PARTITION BY
                         t4.id
                 ) AS meal_fat,
-                SUM(t1.quantity * t2.saturates) OVER (
+                SUM(t1.quantity * COALESCE(t2.saturates, t1.saturates)) OVER (
                     PARTITION BY
                         t4.id
                 ) AS meal_saturates,
-                SUM(t1.quantity * t2.sugars) OVER (
+                SUM(t1.quantity * COALESCE(t2.sugars, t1.sugars)) OVER (
                     PARTITION BY
                         t4.id
                 ) AS meal_sugars,
-                SUM(t1.quantity * t2.fibre) OVER (
+                SUM(t1.quantity * COALESCE(t2.fibre, t1.fibre)) OVER (
                     PARTITION BY
                         t4.id
                 ) AS meal_fibre,
-                SUM(t1.quantity * t2.salt) OVER (
+                SUM(t1.quantity * COALESCE(t2.salt, t1.salt)) OVER (
                     PARTITION BY
                         t4.id
                 ) AS meal_salt
@@ -407,10 +407,11 @@ pub struct SavedMeal {
 pub struct SavedMealFood {
     pub id: Uuid,
     pub meal_id: Uuid,
+    pub food_id: Option<Uuid>,
     pub food_name: String,
-    pub brand_name: String,
+    pub brand_name: Option<String>,
     pub data_value: Decimal,
-    pub data_measurement: String,
+    pub data_measurement: Option<String>,
     pub energy: Decimal,
     pub protein: Decimal,
     pub carbohydrate: Decimal,
@@ -446,6 +447,7 @@ impl SavedMeal {
             meal.food.push(SavedMealFood {
                 id: food.id,
                 meal_id: food.meal_id,
+                food_id: food.food_id,
                 food_name: food.food_name,
                 brand_name: food.brand_name,
                 data_value: food.data_value.unwrap_or_default(),
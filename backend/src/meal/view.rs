@@ -38,7 +38,8 @@ pub async fn meal_create_view(
     Extension(request_user): Extension<RequestUser>,
     JsonExtractor(data): JsonExtractor<MealAPIInput>,
 ) -> Result<Json<Meal>, AppError> {
-    let query = Meal::create(&state.pool, data.user_id, data.name, request_user.id).await?;
+    let mut conn = state.pool.acquire().await?;
+    let query = Meal::create(&mut conn, data.user_id, data.name, request_user.id).await?;
     Ok(Json(query))
 }
 
@@ -109,7 +110,8 @@ pub async fn meal_create_from_diet_view(
         .await?
         .ok_or(AppError::NotFound)?;
     let diet_list = Diet::from_id_range(&state.pool, data.id_range).await?;
-    let meal = Meal::create(&state.pool, user.id, data.name, request_user.id).await?;
+    let mut conn = state.pool.acquire().await?;
+    let meal = Meal::create(&mut conn, user.id, data.name, request_user.id).await?;
     MealFood::create_from_diet_range(&state.pool, meal.id, diet_list, request_user.id).await?;
     Ok(Json(meal))
 }
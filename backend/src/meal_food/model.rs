@@ -2,21 +2,30 @@ use chrono::prelude::*;
 use futures::TryStreamExt;
 use rust_decimal::Decimal;
 use serde::Serialize;
-use sqlx::{FromRow, PgPool, Row};
+use sqlx::{FromRow, PgConnection, PgPool, Row};
 use uuid::Uuid;
 
-use crate::{diet::model::Diet, util::query::QueryParams};
+use crate::{
+    diet::{
+        model::Diet,
+        quick_add::{QuickAdd, QuickAddColumns},
+    },
+    util::query::QueryParams,
+};
 
 #[derive(Debug, Serialize, FromRow)]
 pub struct MealFood {
     pub id: Uuid,
     pub meal_id: Uuid,
-    pub food_id: Uuid,
+    pub food_id: Option<Uuid>,
     pub quantity: Decimal,
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
     pub created_by_id: Uuid,
     pub updated_by_id: Option<Uuid>,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub quick_add: QuickAdd,
 }
 
 impl MealFood {
@@ -139,23 +148,53 @@ impl MealFood {
         created_by_id: Uuid,
     ) -> Result<Vec<Self>, sqlx::Error> {
         let mut meal_id_list: Vec<Uuid> = Vec::new();
-        let mut food_id_list: Vec<Uuid> = Vec::new();
+        let mut food_id_list: Vec<Option<Uuid>> = Vec::new();
         let mut quantity_list: Vec<Decimal> = Vec::new();
         let mut created_by_id_list: Vec<Uuid> = Vec::new();
+        let mut quick_add_list = QuickAddColumns::default();
         for row in diet_range.iter() {
             meal_id_list.push(meal_id);
             food_id_list.push(row.food_id);
             quantity_list.push(row.quantity);
             created_by_id_list.push(created_by_id);
+            quick_add_list.push(row.quick_add.clone());
         }
         let query = sqlx::query_as(
             "
             INSERT INTO
-                meal_food (meal_id, food_id, quantity, created_by_id)
+                meal_food (
+                    meal_id,
+                    food_id,
+                    quantity,
+                    created_by_id,
+                    label,
+                    energy,
+                    fat,
+                    saturates,
+                    carbohydrate,
+                    sugars,
+                    fibre,
+                    protein,
+                    salt
+                )
             SELECT
                 *
             FROM
-                UNNEST($1::UUID[], $2::UUID[], $3::NUMERIC[], $4::UUID[])
+                UNNEST(
+                    $1::UUID[],
+                    $2::UUID[],
+                    $3::NUMERIC[],
+                    $4::UUID[],
+                    $5::VARCHAR[],
+                    $6::INTEGER[],
+                    $7::NUMERIC[],
+                    $8::NUMERIC[],
+                    $9::NUMERIC[],
+                    $10::NUMERIC[],
+                    $11::NUMERIC[],
+                    $12::NUMERIC[],
+                    $13::NUMERIC[]
+                )
             RETURNING
                 *
             ",
@@ -164,25 +203,65 @@ impl MealFood {
         .bind(&food_id_list)
         .bind(&quantity_list)
         .bind(&created_by_id_list)
+        .bind(&quick_add_list.label)
+        .bind(&quick_add_list.energy)
+        .bind(&quick_add_list.fat)
+        .bind(&quick_add_list.saturates)
+        .bind(&quick_add_list.carbohydrate)
+        .bind(&quick_add_list.sugars)
+        .bind(&quick_add_list.fibre)
+        .bind(&quick_add_list.protein)
+        .bind(&quick_add_list.salt)
         .fetch_all(pool)
         .await?;
         Ok(query)
     }
+    pub async fn create_from_food_range(
+        conn: &mut PgConnection,
+        meal_id: Uuid,
+        food_range: &[(Uuid, Decimal)],
+        created_by_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let food_id_list: Vec<Uuid> = food_range.iter().map(|row| row.0).collect();
+        let quantity_list: Vec<Decimal> = food_range.iter().map(|row| row.1).collect();
+        let query = sqlx::query_as(
+            "
+            INSERT INTO
+                meal_food (meal_id, food_id, quantity, created_by_id)
+            SELECT
+                $1,
+                t1.food_id,
+                t1.quantity,
+                $4
+            FROM
+                UNNEST($2::UUID[], $3::NUMERIC[]) t1 (food_id, quantity)
+            RETURNING
+                *
+            ",
+        )
+        .bind(meal_id)
+        .bind(&food_id_list)
+        .bind(&quantity_list)
+        .bind(created_by_id)
+        .fetch_all(&mut *conn)
+        .await?;
+        Ok(query)
+    }
 }
 
 #[derive(Debug, Serialize, FromRow)]
 pub struct MealFoodDetail {
     pub id: Uuid,
     pub meal_id: Uuid,
-    pub food_id: Uuid,
+    pub food_id: Option<Uuid>,
     pub quantity: Decimal,
     pub meal_name: String,
     pub food_name: String,
-    pub food_slug: String,
-    pub brand_name: String,
-    pub brand_slug: String,
-    pub data_value: Decimal,
-    pub data_measurement: String,
+    pub food_slug: Option<String>,
+    pub brand_name: Option<String>,
+    pub brand_slug: Option<String>,
+    pub data_value: Option<Decimal>,
+    pub data_measurement: Option<String>,
     pub energy: Decimal,
     pub fat: Decimal,
     pub saturates: Decimal,
@@ -207,19 +286,19 @@ impl MealFoodDetail {
                 t1.quantity,
                 t1.quantity * t2.data_value AS data_value,
                 t2.data_measurement AS data_measurement,
-                t1.quantity * t2.energy AS energy,
-                t1.quantity * t2.protein AS protein,
-                t1.quantity * t2.carbohydrate AS carbohydrate,
-                t1.quantity * t2.fat AS fat,
-                t1.quantity * t2.saturates AS saturates,
-                t1.quantity * t2.sugars AS sugars,
-                t1.quantity * t2.fibre AS fibre,
-                t1.quantity * t2.salt AS salt,
-                (t2.protein * 4) / t2.energy * 100 AS protein_pct,
-                (t2.carbohydrate * 4) / t2.energy * 100 AS carbohydrate_pct,
-                (t2.fat * 9) / t2.energy * 100 AS fat_pct,
+                t1.quantity * COALESCE(t2.energy, t1.energy) AS energy,
+                t1.quantity * COALESCE(t2.protein, t1.protein) AS protein,
+                t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate) AS carbohydrate,
+                t1.quantity * COALESCE(t2.fat, t1.fat) AS fat,
+                t1.quantity * COALESCE(t2.saturates, t1.saturates) AS saturates,
+                t1.quantity * COALESCE(t2.sugars, t1.sugars) AS sugars,
+                t1.quantity * COALESCE(t2.fibre, t1.fibre) AS fibre,
+                t1.quantity * COALESCE(t2.salt, t1.salt) AS salt,
+                (COALESCE(t2.protein, t1.protein) * 4) / COALESCE(t2.energy, t1.energy) * 100 AS protein_pct,
+                (COALESCE(t2.carbohydrate, t1.carbohydrate) * 4) / COALESCE(t2.energy, t1.energy) * 100 AS carbohydrate_pct,
+                (COALESCE(t2.fat, t1.fat) * 9) / COALESCE(t2.energy, t1.energy) * 100 AS fat_pct,
                 -- food
-                t2.name AS food_name,
+                COALESCE(t2.name, t1.label) AS food_name,
                 t2.slug AS food_slug,
                 -- brand
                 t3.name AS brand_name,
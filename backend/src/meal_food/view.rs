@@ -12,6 +12,7 @@ use crate::{
     extractor::JsonExtractor,
     food::model::Food,
     middleware::RequestUser,
+    moderation::model::is_visible,
     util::{extract::IdRange, query::QueryParams},
     AppState,
 };
@@ -36,7 +37,13 @@ pub async fn meal_food_create_view(
     Extension(request_user): Extension<RequestUser>,
     JsonExtractor(data): JsonExtractor<MealFoodInput>,
 ) -> Result<Json<MealFood>, AppError> {
-    let food = Food::get(&state.pool, &data.food_id).await?;
+    let food = Food::get_opt(&state.pool, &data.food_id)
+        .await?
+        .filter(|food| is_visible(&request_user, &food.created_by_id, &food.review_status))
+        .ok_or(AppError::APIBadRequest(format!(
+            "Food {} not found",
+            data.food_id
+        )))?;
     let quantity = match food.data_measurement.as_str() {
         "g" => data.quantity * Decimal::new(1, 2),
         "ml" => data.quantity * Decimal::new(1, 2),
@@ -70,7 +77,13 @@ pub async fn meal_food_update_view(
     Extension(request_user): Extension<RequestUser>,
     JsonExtractor(data): JsonExtractor<MealFoodInput>,
 ) -> Result<Json<MealFood>, AppError> {
-    let food = Food::get(&state.pool, &data.food_id).await?;
+    let food = Food::get_opt(&state.pool, &data.food_id)
+        .await?
+        .filter(|food| is_visible(&request_user, &food.created_by_id, &food.review_status))
+        .ok_or(AppError::APIBadRequest(format!(
+            "Food {} not found",
+            data.food_id
+        )))?;
     let quantity = match food.data_measurement.as_str() {
         "g" => data.quantity * Decimal::new(1, 2),
         "ml" => data.quantity * Decimal::new(1, 2),
@@ -78,6 +91,11 @@ pub async fn meal_food_update_view(
         _ => data.quantity * Decimal::new(1, 0),
     };
     let query = MealFood::get(&state.pool, &id).await?;
+    if query.food_id.is_none() {
+        return Err(AppError::BadRequestMessage(String::from(
+            "Quick-add items cannot be changed to a food.",
+        )));
+    }
     let query = MealFood::update(
         &state.pool,
         query.id,
@@ -0,0 +1,579 @@
+use chrono::prelude::*;
+use futures::TryStreamExt;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use serde_json::{json, Value};
+use sqlx::{FromRow, PgConnection, PgPool, Postgres, QueryBuilder, Row};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::{db::Filters, error::AppError, revision::model::set_actor, util::query::QueryParams};
+
+// Scores are trigram similarities of the normalised names. Names this close are
+// flagged on their own, within a brand for foods.
+pub const SIMILAR_NAME_THRESHOLD: f64 = 0.6;
+// Identical nutrients only count when the names are at least loosely alike, as
+// plenty of unrelated foods (water, black coffee) share an all zero profile.
+pub const IDENTICAL_NUTRIENTS_THRESHOLD: f64 = 0.3;
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct DuplicateFood {
+    pub id: Uuid,
+    pub name: String,
+    pub brand_id: Uuid,
+    pub brand_name: String,
+    pub data_value: i32,
+    pub data_measurement: String,
+    pub energy: i32,
+    pub fat: Decimal,
+    pub saturates: Decimal,
+    pub carbohydrate: Decimal,
+    pub sugars: Decimal,
+    pub fibre: Decimal,
+    pub protein: Decimal,
+    pub salt: Decimal,
+    pub usage_count: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl DuplicateFood {
+    // Only the foods of the current page of pairs, so usage is counted for a handful
+    // of rows rather than the whole catalogue.
+    pub async fn for_pairs(
+        pool: &PgPool,
+        pairs: &[DuplicatePair],
+    ) -> Result<HashMap<Uuid, Self>, sqlx::Error> {
+        let ids: Vec<Uuid> = pairs
+            .iter()
+            .flat_map(|pair| [pair.a_id, pair.b_id])
+            .collect();
+        let query: Vec<Self> = sqlx::query_as(
+            "
+            SELECT
+                t1.id,
+                t1.name,
+                t1.brand_id,
+                t2.name AS brand_name,
+                t1.data_value,
+                t1.data_measurement,
+                t1.energy,
+                t1.fat,
+                t1.saturates,
+                t1.carbohydrate,
+                t1.sugars,
+                t1.fibre,
+                t1.protein,
+                t1.salt,
+                (SELECT COUNT(*) FROM food_log WHERE food_id = t1.id)
+                    + (SELECT COUNT(*) FROM meal_food WHERE food_id = t1.id) AS usage_count,
+                t1.created_at
+            FROM
+                food t1
+                LEFT JOIN food_brand t2 ON t2.id = t1.brand_id
+            WHERE
+                t1.id = ANY ($1)
+            ",
+        )
+        .bind(ids)
+        .fetch_all(pool)
+        .await?;
+        Ok(query.into_iter().map(|food| (food.id, food)).collect())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct DuplicateBrand {
+    pub id: Uuid,
+    pub name: String,
+    pub slug: String,
+    pub usage_count: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl DuplicateBrand {
+    pub async fn for_pairs(
+        pool: &PgPool,
+        pairs: &[DuplicatePair],
+    ) -> Result<HashMap<Uuid, Self>, sqlx::Error> {
+        let ids: Vec<Uuid> = pairs
+            .iter()
+            .flat_map(|pair| [pair.a_id, pair.b_id])
+            .collect();
+        let query: Vec<Self> = sqlx::query_as(
+            "
+            SELECT
+                t1.id,
+                t1.name,
+                t1.slug,
+                (SELECT COUNT(*) FROM food WHERE brand_id = t1.id) AS usage_count,
+                t1.created_at
+            FROM
+                food_brand t1
+            WHERE
+                t1.id = ANY ($1)
+            ",
+        )
+        .bind(ids)
+        .fetch_all(pool)
+        .await?;
+        Ok(query.into_iter().map(|brand| (brand.id, brand)).collect())
+    }
+}
+
+#[derive(Debug, PartialEq, FromRow)]
+pub struct DuplicatePair {
+    pub a_id: Uuid,
+    pub b_id: Uuid,
+    pub similarity: f64,
+    pub reasons: Vec<String>,
+}
+
+// Foods are compared by name within each brand, and by name across foods with an
+// identical nutrient profile. The % operator lets the trigram index on the
+// normalised name find each food's look-alikes, rather than comparing all against
+// all. Numeric equality ignores scale, so 7 and 7.0 grams of fat match.
+fn push_food_pairs(q: &mut QueryBuilder<'static, Postgres>, params: &QueryParams) {
+    q.push(
+        "
+        WITH pair AS (
+            SELECT
+                t1.id AS a_id,
+                t2.id AS b_id,
+                similarity(normalize_name(t1.name), normalize_name(t2.name))::FLOAT8 AS similarity,
+                t1.brand_id = t2.brand_id AS same_brand,
+                (
+                    t1.data_value, t1.data_measurement, t1.energy, t1.fat, t1.saturates,
+                    t1.carbohydrate, t1.sugars, t1.fibre, t1.protein, t1.salt
+                ) = (
+                    t2.data_value, t2.data_measurement, t2.energy, t2.fat, t2.saturates,
+                    t2.carbohydrate, t2.sugars, t2.fibre, t2.protein, t2.salt
+                ) AS same_nutrients
+            FROM
+                food t1
+                JOIN food t2 ON t2.id > t1.id
+                AND normalize_name(t2.name) % normalize_name(t1.name)
+            WHERE
+                TRUE
+        ",
+    );
+    if let Some(brand) = &params.brand {
+        q.push(" AND t2.brand_id = t1.brand_id");
+        q.push(" AND t1.brand_id = (SELECT id FROM food_brand WHERE slug = ");
+        q.push_bind(brand.clone());
+        q.push(")");
+    }
+    if let Some(search) = &params.search {
+        let search = format!("%{}%", search);
+        q.push(" AND (t1.name ILIKE ");
+        q.push_bind(search.clone());
+        q.push(" OR t2.name ILIKE ");
+        q.push_bind(search);
+        q.push(")");
+    }
+    q.push(
+        "
+        ),
+        candidate AS (
+            SELECT
+                *
+            FROM (
+                SELECT
+                    a_id,
+                    b_id,
+                    similarity,
+                    ARRAY_REMOVE(
+                        ARRAY[
+                            CASE WHEN same_nutrients AND similarity >= ",
+    );
+    q.push_bind(IDENTICAL_NUTRIENTS_THRESHOLD);
+    q.push(
+        " THEN 'identical_nutrients' END,
+                            CASE WHEN same_brand AND similarity >= ",
+    );
+    q.push_bind(SIMILAR_NAME_THRESHOLD);
+    q.push(
+        " THEN 'similar_name' END
+                        ],
+                        NULL
+                    ) AS reasons
+                FROM
+                    pair
+            ) t
+            WHERE
+                cardinality(t.reasons) > 0
+        )
+        ",
+    );
+}
+
+fn push_brand_pairs(q: &mut QueryBuilder<'static, Postgres>) {
+    q.push(
+        "
+        WITH candidate AS (
+            SELECT
+                t1.id AS a_id,
+                t2.id AS b_id,
+                similarity(normalize_name(t1.name), normalize_name(t2.name))::FLOAT8 AS similarity,
+                ARRAY['similar_name'] AS reasons
+            FROM
+                food_brand t1
+                JOIN food_brand t2 ON t2.id > t1.id
+                AND normalize_name(t2.name) % normalize_name(t1.name)
+            WHERE
+                similarity(normalize_name(t1.name), normalize_name(t2.name)) >= ",
+    );
+    q.push_bind(SIMILAR_NAME_THRESHOLD);
+    q.push(")");
+}
+
+impl DuplicatePair {
+    async fn count(
+        pool: &PgPool,
+        mut q: QueryBuilder<'static, Postgres>,
+    ) -> Result<i64, sqlx::Error> {
+        q.push(" SELECT COUNT(*) FROM candidate");
+        let count = q.build().fetch_one(pool).await?.get("count");
+        Ok(count)
+    }
+    // Pairs with the most reasons come first, then the closest names.
+    async fn all(
+        pool: &PgPool,
+        mut q: QueryBuilder<'static, Postgres>,
+        params: &QueryParams,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        q.push(
+            " SELECT * FROM candidate ORDER BY cardinality(reasons) DESC, similarity DESC, a_id, b_id",
+        );
+        q.paginate(params.page, params.size);
+        let query = q.build_query_as().fetch_all(pool).await?;
+        Ok(query)
+    }
+    pub async fn food_count(pool: &PgPool, params: &QueryParams) -> Result<i64, sqlx::Error> {
+        let mut q = QueryBuilder::new("");
+        push_food_pairs(&mut q, params);
+        Self::count(pool, q).await
+    }
+    pub async fn food_all(pool: &PgPool, params: &QueryParams) -> Result<Vec<Self>, sqlx::Error> {
+        let mut q = QueryBuilder::new("");
+        push_food_pairs(&mut q, params);
+        Self::all(pool, q, params).await
+    }
+    pub async fn brand_count(pool: &PgPool) -> Result<i64, sqlx::Error> {
+        let mut q = QueryBuilder::new("");
+        push_brand_pairs(&mut q);
+        Self::count(pool, q).await
+    }
+    pub async fn brand_all(pool: &PgPool, params: &QueryParams) -> Result<Vec<Self>, sqlx::Error> {
+        let mut q = QueryBuilder::new("");
+        push_brand_pairs(&mut q);
+        Self::all(pool, q, params).await
+    }
+}
+
+// The suggested target is whichever row is used more, then the older one.
+#[derive(Debug, Serialize)]
+pub struct DuplicateCandidate<T> {
+    pub similarity: f64,
+    pub reasons: Vec<String>,
+    pub target: T,
+    pub source: T,
+}
+
+impl<T: Clone> DuplicateCandidate<T> {
+    // None when either row was deleted since the pair was found.
+    pub fn from_pair(
+        pair: DuplicatePair,
+        rows: &HashMap<Uuid, T>,
+        usage: impl Fn(&T) -> (i64, DateTime<Utc>),
+    ) -> Option<Self> {
+        let (a, b) = (rows.get(&pair.a_id)?, rows.get(&pair.b_id)?);
+        let (a_count, a_created) = usage(a);
+        let (b_count, b_created) = usage(b);
+        let (target, source) = if b_count > a_count || (b_count == a_count && b_created < a_created)
+        {
+            (b, a)
+        } else {
+            (a, b)
+        };
+        Some(Self {
+            similarity: (pair.similarity * 100.0).round() / 100.0,
+            reasons: pair.reasons,
+            target: target.clone(),
+            source: source.clone(),
+        })
+    }
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct CatalogueMerge {
+    pub id: Uuid,
+    pub table_name: String,
+    pub source_id: Uuid,
+    pub source_name: String,
+    pub target_id: Uuid,
+    pub target_name: String,
+    pub repointed: Value,
+    pub created_at: DateTime<Utc>,
+    pub created_by_id: Option<Uuid>,
+}
+
+impl CatalogueMerge {
+    async fn create(
+        conn: &mut PgConnection,
+        table_name: &str,
+        source: &(Uuid, String),
+        target: &(Uuid, String),
+        repointed: Value,
+        created_by_id: &Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let query = sqlx::query_as(
+            "
+            INSERT INTO
+                catalogue_merge (
+                    table_name,
+                    source_id,
+                    source_name,
+                    target_id,
+                    target_name,
+                    repointed,
+                    created_by_id
+                )
+            VALUES
+                ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING
+                *
+            ",
+        )
+        .bind(table_name)
+        .bind(source.0)
+        .bind(&source.1)
+        .bind(target.0)
+        .bind(&target.1)
+        .bind(repointed)
+        .bind(created_by_id)
+        .fetch_one(conn)
+        .await?;
+        Ok(query)
+    }
+    pub async fn count(pool: &PgPool) -> Result<i64, sqlx::Error> {
+        let count = sqlx::query("SELECT COUNT(*) FROM catalogue_merge")
+            .fetch_one(pool)
+            .await?
+            .get("count");
+        Ok(count)
+    }
+    pub async fn all(pool: &PgPool, params: &QueryParams) -> Result<Vec<Self>, sqlx::Error> {
+        let mut stream = Vec::new();
+        let mut q = sqlx::QueryBuilder::new("SELECT * FROM catalogue_merge t1 WHERE TRUE");
+        q.filter_icontains("t1.source_name", &params.search);
+        q.push(" ORDER BY t1.created_at DESC");
+        q.paginate(params.page, params.size);
+        let mut rows = q.build_query_as().fetch(pool);
+        while let Some(row) = rows.try_next().await? {
+            stream.push(row);
+        }
+        Ok(stream)
+    }
+}
+
+// Locks the target and sources for the rest of the transaction, and fails unless
+// every one of them exists.
+async fn lock_rows(
+    conn: &mut PgConnection,
+    table_name: &str,
+    target_id: &Uuid,
+    source_ids: &[Uuid],
+) -> Result<((Uuid, String), Vec<(Uuid, String)>), AppError> {
+    // The serializer rejects this too, but the target would otherwise be taken
+    // from the locked rows and its source entry reported as missing.
+    if source_ids.contains(target_id) {
+        return Err(AppError::BadRequestMessage(String::from(
+            "A row cannot be merged into itself",
+        )));
+    }
+    let mut rows: HashMap<Uuid, String> = sqlx::query(&format!(
+        "SELECT id, name FROM {table_name} WHERE id = $1 OR id = ANY ($2) FOR UPDATE"
+    ))
+    .bind(target_id)
+    .bind(source_ids)
+    .fetch_all(conn)
+    .await?
+    .into_iter()
+    .map(|row| (row.get("id"), row.get("name")))
+    .collect();
+    let target = rows
+        .remove(target_id)
+        .map(|name| (*target_id, name))
+        .ok_or(AppError::NotFound)?;
+    let mut sources = Vec::new();
+    for id in source_ids {
+        match rows.remove(id) {
+            Some(name) => sources.push((*id, name)),
+            // Listed twice.
+            None if sources.iter().any(|(source_id, _)| source_id == id) => continue,
+            None => return Err(AppError::NotFound),
+        }
+    }
+    Ok((target, sources))
+}
+
+pub async fn merge_foods(
+    pool: &PgPool,
+    target_id: &Uuid,
+    source_ids: &[Uuid],
+    user_id: &Uuid,
+) -> Result<Vec<CatalogueMerge>, AppError> {
+    let mut tx = pool.begin().await?;
+    set_actor(&mut tx, user_id).await?;
+    let (target, sources) = lock_rows(&mut tx, "food", target_id, source_ids).await?;
+    let mut merges = Vec::new();
+    for source in &sources {
+        let food_log = sqlx::query("UPDATE food_log SET food_id = $1 WHERE food_id = $2")
+            .bind(target.0)
+            .bind(source.0)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        let meal_food = sqlx::query("UPDATE meal_food SET food_id = $1 WHERE food_id = $2")
+            .bind(target.0)
+            .bind(source.0)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        let planned_food = sqlx::query("UPDATE planned_food SET food_id = $1 WHERE food_id = $2")
+            .bind(target.0)
+            .bind(source.0)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        // Pins the user already has on the target are left to cascade with the source.
+        let food_favourite = sqlx::query(
+            "
+            UPDATE food_favourite t1
+            SET
+                food_id = $1
+            WHERE
+                t1.food_id = $2
+                AND NOT EXISTS (
+                    SELECT 1 FROM food_favourite t2
+                    WHERE
+                        t2.user_id = t1.user_id
+                        AND t2.food_id = $1
+                        AND t2.meal_of_day_id IS NOT DISTINCT FROM t1.meal_of_day_id
+                )
+            ",
+        )
+        .bind(target.0)
+        .bind(source.0)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        sqlx::query("DELETE FROM food WHERE id = $1")
+            .bind(source.0)
+            .execute(&mut *tx)
+            .await?;
+        let repointed = json!({
+            "food_log": food_log,
+            "meal_food": meal_food,
+            "planned_food": planned_food,
+            "food_favourite": food_favourite,
+        });
+        merges.push(
+            CatalogueMerge::create(&mut tx, "food", source, &target, repointed, user_id).await?,
+        );
+    }
+    tx.commit().await?;
+    Ok(merges)
+}
+
+pub async fn merge_brands(
+    pool: &PgPool,
+    target_id: &Uuid,
+    source_ids: &[Uuid],
+    user_id: &Uuid,
+) -> Result<Vec<CatalogueMerge>, AppError> {
+    let mut tx = pool.begin().await?;
+    set_actor(&mut tx, user_id).await?;
+    let (target, sources) = lock_rows(&mut tx, "food_brand", target_id, source_ids).await?;
+    let mut merges = Vec::new();
+    for source in &sources {
+        // Stamped as an edit so the food revisions name who moved them.
+        let food = sqlx::query(
+            "
+            UPDATE food
+            SET
+                brand_id = $1,
+                updated_at = $2,
+                updated_by_id = $3
+            WHERE
+                brand_id = $4
+            ",
+        )
+        .bind(target.0)
+        .bind(Utc::now())
+        .bind(user_id)
+        .bind(source.0)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        sqlx::query("DELETE FROM food_brand WHERE id = $1")
+            .bind(source.0)
+            .execute(&mut *tx)
+            .await?;
+        let repointed = json!({ "food": food });
+        merges.push(
+            CatalogueMerge::create(&mut tx, "food_brand", source, &target, repointed, user_id)
+                .await?,
+        );
+    }
+    tx.commit().await?;
+    Ok(merges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidate_suggested_target() {
+        let brand = |name: &str, usage_count: i64, created_at: DateTime<Utc>| DuplicateBrand {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            slug: String::new(),
+            usage_count,
+            created_at,
+        };
+        let now = Utc::now();
+        let (tesco, tesco_dot) = (brand("Tesco", 1, now), brand("TESCO.", 3, now));
+        let pair = |a: &DuplicateBrand, b: &DuplicateBrand| DuplicatePair {
+            a_id: a.id,
+            b_id: b.id,
+            similarity: 0.996,
+            reasons: vec![String::from("similar_name")],
+        };
+        let rows: HashMap<Uuid, DuplicateBrand> = [tesco.clone(), tesco_dot.clone()]
+            .into_iter()
+            .map(|brand| (brand.id, brand))
+            .collect();
+        let usage = |brand: &DuplicateBrand| (brand.usage_count, brand.created_at);
+
+        let candidate = DuplicateCandidate::from_pair(pair(&tesco, &tesco_dot), &rows, usage)
+            .expect("both rows exist");
+        assert_eq!(candidate.target.name, "TESCO.");
+        assert_eq!(candidate.source.name, "Tesco");
+        assert_eq!(candidate.similarity, 1.0);
+
+        // Equal usage falls back to the older row.
+        let older = brand("Tesco", 3, now - chrono::Duration::days(1));
+        let rows: HashMap<Uuid, DuplicateBrand> = [tesco_dot.clone(), older.clone()]
+            .into_iter()
+            .map(|brand| (brand.id, brand))
+            .collect();
+        let candidate =
+            DuplicateCandidate::from_pair(pair(&tesco_dot, &older), &rows, usage).unwrap();
+        assert_eq!(candidate.target.id, older.id);
+
+        // A row deleted since the pair was found drops the pair.
+        assert!(DuplicateCandidate::from_pair(pair(&tesco, &older), &rows, usage).is_none());
+    }
+}
@@ -0,0 +1,21 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::AppState;
+
+use super::view::{
+    brand_duplicate_list_view, brand_merge_view, food_duplicate_list_view, food_merge_view,
+    merge_list_view,
+};
+
+pub fn merge_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(merge_list_view))
+        .route("/food", post(food_merge_view))
+        .route("/food/candidates", get(food_duplicate_list_view))
+        .route("/brands", post(brand_merge_view))
+        .route("/brands/candidates", get(brand_duplicate_list_view))
+}
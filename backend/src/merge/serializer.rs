@@ -0,0 +1,22 @@
+use serde::Deserialize;
+use std::borrow::Cow;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_target_not_in_sources"))]
+pub struct MergeSerializer {
+    pub target_id: Uuid,
+    #[validate(length(min = 1, max = 50, message = "Between 1 and 50 rows can be merged"))]
+    pub source_ids: Vec<Uuid>,
+}
+
+fn validate_target_not_in_sources(data: &MergeSerializer) -> Result<(), ValidationError> {
+    if data.source_ids.contains(&data.target_id) {
+        let mut error = ValidationError::new("target_in_sources");
+        error.message = Some(Cow::from("Must not include the target"));
+        error.add_param(Cow::from("field"), &"source_ids");
+        return Err(error);
+    }
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use axum::{
+    extract::{Query, State},
+    Extension, Json,
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::{
+    error::AppError, extractor::JsonExtractor, middleware::RequestUser, util::query::QueryParams,
+    AppState,
+};
+
+use super::{
+    model::{
+        merge_brands, merge_foods, CatalogueMerge, DuplicateBrand, DuplicateCandidate,
+        DuplicateFood, DuplicatePair,
+    },
+    serializer::MergeSerializer,
+};
+
+pub async fn merge_list_view(
+    Query(params): Query<QueryParams>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<Value>, AppError> {
+    request_user.staff_required()?;
+    let count = CatalogueMerge::count(&state.pool).await?;
+    let query = CatalogueMerge::all(&state.pool, &params).await?;
+    let response = json!({"count": count, "results": query});
+    Ok(Json(response))
+}
+
+pub async fn food_duplicate_list_view(
+    Query(params): Query<QueryParams>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<Value>, AppError> {
+    request_user.staff_required()?;
+    let count = DuplicatePair::food_count(&state.pool, &params).await?;
+    let pairs = DuplicatePair::food_all(&state.pool, &params).await?;
+    let foods = DuplicateFood::for_pairs(&state.pool, &pairs).await?;
+    let results: Vec<DuplicateCandidate<DuplicateFood>> = pairs
+        .into_iter()
+        .filter_map(|pair| {
+            DuplicateCandidate::from_pair(pair, &foods, |food| (food.usage_count, food.created_at))
+        })
+        .collect();
+    let response = json!({"count": count, "results": results});
+    Ok(Json(response))
+}
+
+pub async fn brand_duplicate_list_view(
+    Query(params): Query<QueryParams>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<Value>, AppError> {
+    request_user.staff_required()?;
+    let count = DuplicatePair::brand_count(&state.pool).await?;
+    let pairs = DuplicatePair::brand_all(&state.pool, &params).await?;
+    let brands = DuplicateBrand::for_pairs(&state.pool, &pairs).await?;
+    let results: Vec<DuplicateCandidate<DuplicateBrand>> = pairs
+        .into_iter()
+        .filter_map(|pair| {
+            DuplicateCandidate::from_pair(pair, &brands, |brand| {
+                (brand.usage_count, brand.created_at)
+            })
+        })
+        .collect();
+    let response = json!({"count": count, "results": results});
+    Ok(Json(response))
+}
+
+pub async fn food_merge_view(
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    JsonExtractor(data): JsonExtractor<MergeSerializer>,
+) -> Result<Json<Vec<CatalogueMerge>>, AppError> {
+    request_user.staff_required()?;
+    let query = merge_foods(
+        &state.pool,
+        &data.target_id,
+        &data.source_ids,
+        &request_user.id,
+    )
+    .await?;
+    Ok(Json(query))
+}
+
+pub async fn brand_merge_view(
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    JsonExtractor(data): JsonExtractor<MergeSerializer>,
+) -> Result<Json<Vec<CatalogueMerge>>, AppError> {
+    request_user.staff_required()?;
+    let query = merge_brands(
+        &state.pool,
+        &data.target_id,
+        &data.source_ids,
+        &request_user.id,
+    )
+    .await?;
+    Ok(Json(query))
+}
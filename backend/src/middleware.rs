@@ -44,6 +44,15 @@ impl RequestUser {
         }
         Ok(())
     }
+    pub fn is_moderator(&self) -> bool {
+        self.is_authenticated && (self.is_staff || self.is_superuser)
+    }
+    pub fn staff_required(&self) -> Result<(), AppError> {
+        if !self.is_moderator() {
+            return Err(AppError::Unauthorized(String::from("Staff required")));
+        }
+        Ok(())
+    }
     // pub async fn superuser_required(&self) -> Result<bool, AppError> {
     //     if !self.is_superuser {
     //         return Err(AppError::APIBadRequest(String::from("Superuser required")));
@@ -51,13 +60,6 @@ impl RequestUser {
     //         return Ok(true);
     //     }
     // }
-    // pub async fn staff_required(&self) -> Result<bool, AppError> {
-    //     if !self.is_staff {
-    //         return Err(AppError::APIBadRequest(String::from("Staff required")));
-    //     } else {
-    //         return Ok(true);
-    //     }
-    // }
 }
 
 pub async fn authorization_middleware(
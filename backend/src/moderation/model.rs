@@ -0,0 +1,201 @@
+use chrono::prelude::*;
+use serde::Serialize;
+use sqlx::{FromRow, PgConnection};
+use uuid::Uuid;
+
+use crate::{error::AppError, middleware::RequestUser};
+
+pub const REVIEW_PENDING: &str = "pending";
+pub const REVIEW_APPROVED: &str = "approved";
+pub const REVIEW_REJECTED: &str = "rejected";
+
+#[derive(Debug, Clone, Copy)]
+pub enum CatalogueTable {
+    Food,
+    Brand,
+}
+
+impl CatalogueTable {
+    fn table_name(self) -> &'static str {
+        match self {
+            Self::Food => "food",
+            Self::Brand => "food_brand",
+        }
+    }
+}
+
+// Moderators publish straight to the catalogue, everyone else goes through the queue.
+pub fn initial_review_status(request_user: &RequestUser) -> &'static str {
+    if request_user.is_moderator() {
+        REVIEW_APPROVED
+    } else {
+        REVIEW_PENDING
+    }
+}
+
+pub fn is_visible(request_user: &RequestUser, created_by_id: &Uuid, review_status: &str) -> bool {
+    review_status == REVIEW_APPROVED
+        || request_user.is_moderator()
+        || (request_user.is_authenticated && request_user.id == *created_by_id)
+}
+
+// Creators may keep editing their own submissions until they are approved.
+pub fn edit_required(
+    request_user: &RequestUser,
+    created_by_id: &Uuid,
+    review_status: &str,
+) -> Result<(), AppError> {
+    request_user.login_required()?;
+    if request_user.is_moderator()
+        || (request_user.id == *created_by_id && review_status != REVIEW_APPROVED)
+    {
+        return Ok(());
+    }
+    Err(AppError::Unauthorized(String::from(
+        "Only moderators can change approved entries",
+    )))
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct Review {
+    pub id: Uuid,
+    pub name: String,
+    pub review_status: String,
+    pub review_reason: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub reviewed_by_id: Option<Uuid>,
+}
+
+impl Review {
+    pub async fn get(
+        conn: &mut PgConnection,
+        table: CatalogueTable,
+        id: &Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let query = sqlx::query_as(&format!(
+            "
+            SELECT
+                id,
+                name,
+                review_status,
+                review_reason,
+                reviewed_at,
+                reviewed_by_id
+            FROM
+                {}
+            WHERE
+                id = $1
+            ",
+            table.table_name()
+        ))
+        .bind(id)
+        .fetch_optional(conn)
+        .await?;
+        Ok(query)
+    }
+    pub async fn set(
+        conn: &mut PgConnection,
+        table: CatalogueTable,
+        id: &Uuid,
+        review_status: &str,
+        review_reason: Option<&str>,
+        reviewed_by_id: &Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let query = sqlx::query_as(&format!(
+            "
+            UPDATE {}
+            SET
+                review_status = $1,
+                review_reason = $2,
+                reviewed_at = $3,
+                reviewed_by_id = $4
+            WHERE
+                id = $5
+            RETURNING
+                id,
+                name,
+                review_status,
+                review_reason,
+                reviewed_at,
+                reviewed_by_id
+            ",
+            table.table_name()
+        ))
+        .bind(review_status)
+        .bind(review_reason)
+        .bind(Utc::now())
+        .bind(reviewed_by_id)
+        .bind(id)
+        .fetch_one(conn)
+        .await?;
+        Ok(query)
+    }
+    // An edited rejection goes back into the queue.
+    pub async fn resubmit(
+        conn: &mut PgConnection,
+        table: CatalogueTable,
+        id: &Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(&format!(
+            "
+            UPDATE {}
+            SET
+                review_status = $1,
+                review_reason = NULL,
+                reviewed_at = NULL,
+                reviewed_by_id = NULL
+            WHERE
+                id = $2
+                AND review_status = $3
+            ",
+            table.table_name()
+        ))
+        .bind(REVIEW_PENDING)
+        .bind(id)
+        .bind(REVIEW_REJECTED)
+        .execute(conn)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(is_staff: bool) -> RequestUser {
+        RequestUser {
+            id: Uuid::new_v4(),
+            is_staff,
+            is_authenticated: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_pending_entries_are_visible_to_creator_and_moderators() {
+        let creator = user(false);
+        assert!(is_visible(&creator, &creator.id, REVIEW_PENDING));
+        assert!(is_visible(&user(true), &creator.id, REVIEW_PENDING));
+        assert!(!is_visible(&user(false), &creator.id, REVIEW_PENDING));
+        assert!(!is_visible(
+            &RequestUser::default(),
+            &Uuid::nil(),
+            REVIEW_REJECTED
+        ));
+        assert!(is_visible(
+            &RequestUser::default(),
+            &creator.id,
+            REVIEW_APPROVED
+        ));
+    }
+
+    #[test]
+    fn test_creators_cannot_edit_approved_entries() {
+        let creator = user(false);
+        assert!(edit_required(&creator, &creator.id, REVIEW_REJECTED).is_ok());
+        assert!(edit_required(&creator, &creator.id, REVIEW_APPROVED).is_err());
+        assert!(edit_required(&user(true), &creator.id, REVIEW_APPROVED).is_ok());
+        assert_eq!(initial_review_status(&creator), REVIEW_PENDING);
+    }
+}
@@ -0,0 +1,25 @@
+use axum::{
+    routing::{get, post, put},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::AppState;
+
+use super::view::{
+    moderation_brand_approve_view, moderation_brand_list_view, moderation_brand_reject_view,
+    moderation_brand_update_view, moderation_food_approve_view, moderation_food_list_view,
+    moderation_food_reject_view, moderation_food_update_view,
+};
+
+pub fn moderation_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/food", get(moderation_food_list_view))
+        .route("/food/:id", put(moderation_food_update_view))
+        .route("/food/:id/approve", post(moderation_food_approve_view))
+        .route("/food/:id/reject", post(moderation_food_reject_view))
+        .route("/brands", get(moderation_brand_list_view))
+        .route("/brands/:id", put(moderation_brand_update_view))
+        .route("/brands/:id/approve", post(moderation_brand_approve_view))
+        .route("/brands/:id/reject", post(moderation_brand_reject_view))
+}
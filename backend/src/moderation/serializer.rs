@@ -0,0 +1,17 @@
+use serde::Deserialize;
+use validator::Validate;
+
+use crate::util::validator::validate_not_empty_string;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ReviewRejectSerializer {
+    #[validate(
+        length(min = 3, message = "Minimum of 3 characters"),
+        length(max = 500, message = "Maximum of 500 characters"),
+        custom(
+            function = "validate_not_empty_string",
+            message = "Reason must not be empty"
+        )
+    )]
+    pub reason: String,
+}
@@ -0,0 +1,212 @@
+use axum::{
+    extract::{Path, Query, State},
+    Extension, Json,
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    brand::{
+        model::{Brand, BrandSerializer},
+        serializer::BrandCreateSerializer,
+    },
+    error::AppError,
+    extractor::JsonExtractor,
+    food::{
+        model::{Food, FoodSerializer},
+        serializer::FoodDeserializer,
+    },
+    middleware::RequestUser,
+    util::query::QueryParams,
+    AppState,
+};
+
+use super::{
+    model::{CatalogueTable, Review, REVIEW_APPROVED, REVIEW_PENDING, REVIEW_REJECTED},
+    serializer::ReviewRejectSerializer,
+};
+
+// Foods cannot be published under a brand that is not itself published.
+async fn approved_brand_required(state: &AppState, brand_id: &Uuid) -> Result<(), AppError> {
+    let brand = Brand::get(&state.pool, brand_id).await?;
+    if brand.review_status != REVIEW_APPROVED {
+        return Err(AppError::BadRequestMessage(format!(
+            "Brand {} must be approved first",
+            brand.name
+        )));
+    }
+    Ok(())
+}
+
+pub async fn moderation_food_list_view(
+    Query(mut params): Query<QueryParams>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<Value>, AppError> {
+    request_user.staff_required()?;
+    params
+        .status
+        .get_or_insert_with(|| String::from(REVIEW_PENDING));
+    let count = FoodSerializer::count(&state.pool, &params, &request_user).await?;
+    let query = FoodSerializer::all(&state.pool, &params, request_user).await?;
+    let response = json!({"count": count, "results": query});
+    Ok(Json(response))
+}
+
+pub async fn moderation_food_approve_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<Review>, AppError> {
+    request_user.staff_required()?;
+    let food = Food::get_opt(&state.pool, &id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    approved_brand_required(&state, &food.brand_id).await?;
+    let mut conn = state.pool.acquire().await?;
+    let query = Review::set(
+        &mut conn,
+        CatalogueTable::Food,
+        &id,
+        REVIEW_APPROVED,
+        None,
+        &request_user.id,
+    )
+    .await?;
+    Ok(Json(query))
+}
+
+pub async fn moderation_food_reject_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    JsonExtractor(data): JsonExtractor<ReviewRejectSerializer>,
+) -> Result<Json<Review>, AppError> {
+    request_user.staff_required()?;
+    let mut conn = state.pool.acquire().await?;
+    Review::get(&mut conn, CatalogueTable::Food, &id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    let query = Review::set(
+        &mut conn,
+        CatalogueTable::Food,
+        &id,
+        REVIEW_REJECTED,
+        Some(data.reason.trim()),
+        &request_user.id,
+    )
+    .await?;
+    Ok(Json(query))
+}
+
+pub async fn moderation_food_update_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    JsonExtractor(data): JsonExtractor<FoodDeserializer>,
+) -> Result<Json<Food>, AppError> {
+    request_user.staff_required()?;
+    Food::get_opt(&state.pool, &id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    approved_brand_required(&state, &data.brand_id).await?;
+    let mut tx = state.pool.begin().await?;
+    Review::set(
+        &mut tx,
+        CatalogueTable::Food,
+        &id,
+        REVIEW_APPROVED,
+        None,
+        &request_user.id,
+    )
+    .await?;
+    let query = Food::update(&mut *tx, &id, &data, &request_user.id).await?;
+    tx.commit().await?;
+    Ok(Json(query))
+}
+
+pub async fn moderation_brand_list_view(
+    Query(mut params): Query<QueryParams>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<Value>, AppError> {
+    request_user.staff_required()?;
+    params
+        .status
+        .get_or_insert_with(|| String::from(REVIEW_PENDING));
+    let count = BrandSerializer::count(&state.pool, &params, &request_user).await?;
+    let query = BrandSerializer::builder(&state.pool, &params, &request_user).await?;
+    let response = json!({"count": count, "results": query});
+    Ok(Json(response))
+}
+
+pub async fn moderation_brand_approve_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<Review>, AppError> {
+    request_user.staff_required()?;
+    let mut conn = state.pool.acquire().await?;
+    Review::get(&mut conn, CatalogueTable::Brand, &id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    let query = Review::set(
+        &mut conn,
+        CatalogueTable::Brand,
+        &id,
+        REVIEW_APPROVED,
+        None,
+        &request_user.id,
+    )
+    .await?;
+    Ok(Json(query))
+}
+
+pub async fn moderation_brand_reject_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    JsonExtractor(data): JsonExtractor<ReviewRejectSerializer>,
+) -> Result<Json<Review>, AppError> {
+    request_user.staff_required()?;
+    let mut conn = state.pool.acquire().await?;
+    Review::get(&mut conn, CatalogueTable::Brand, &id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    let query = Review::set(
+        &mut conn,
+        CatalogueTable::Brand,
+        &id,
+        REVIEW_REJECTED,
+        Some(data.reason.trim()),
+        &request_user.id,
+    )
+    .await?;
+    Ok(Json(query))
+}
+
+pub async fn moderation_brand_update_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    JsonExtractor(data): JsonExtractor<BrandCreateSerializer>,
+) -> Result<Json<Brand>, AppError> {
+    request_user.staff_required()?;
+    let mut tx = state.pool.begin().await?;
+    Review::get(&mut tx, CatalogueTable::Brand, &id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    Review::set(
+        &mut tx,
+        CatalogueTable::Brand,
+        &id,
+        REVIEW_APPROVED,
+        None,
+        &request_user.id,
+    )
+    .await?;
+    let query = Brand::update(&mut *tx, id, data, request_user.id).await?;
+    tx.commit().await?;
+    Ok(Json(query))
+}
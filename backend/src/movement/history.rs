@@ -0,0 +1,220 @@
+use chrono::{prelude::*, Duration};
+use futures::TryStreamExt;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::{db::Filters, personal_record::model::Formula};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryBucket {
+    #[default]
+    Session,
+    #[serde(alias = "weekly")]
+    Week,
+    #[serde(alias = "monthly")]
+    Month,
+}
+
+impl HistoryBucket {
+    // Weeks start on Monday, as in the diet week views.
+    fn start(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            HistoryBucket::Session => date,
+            HistoryBucket::Week => {
+                date - Duration::days(date.weekday().num_days_from_monday().into())
+            }
+            HistoryBucket::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+}
+
+#[derive(Debug, FromRow)]
+pub struct HistorySet {
+    pub workout_id: Uuid,
+    pub date: NaiveDate,
+    pub weight: Decimal,
+    pub reps: i32,
+}
+
+impl HistorySet {
+    pub async fn all(
+        pool: &PgPool,
+        user_id: Uuid,
+        movement_id: Uuid,
+        date_from: &Option<NaiveDate>,
+        date_to: &Option<NaiveDate>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let mut stream = Vec::new();
+        let mut q: QueryBuilder<'static, Postgres> = QueryBuilder::new(
+            "
+            SELECT
+                t1.workout_id,
+                t2.date,
+                t3.weight,
+                t3.reps
+            FROM
+                exercise t1
+                JOIN workout t2 ON t2.id = t1.workout_id
+                JOIN tracked_set t3 ON t3.exercise_id = t1.id
+            WHERE
+                t2.user_id = ",
+        );
+        q.push_bind(user_id);
+        q.push(" AND t1.movement_id = ");
+        q.push_bind(movement_id);
+        q.filter_date("t2.date", ">=", date_from);
+        q.filter_date("t2.date", "<=", date_to);
+        q.push(" ORDER BY t2.date, t2.created_at, t1.order, t3.order");
+        let mut rows = q.build_query_as().fetch(pool);
+        while let Some(row) = rows.try_next().await? {
+            stream.push(row);
+        }
+        Ok(stream)
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct MovementHistoryPoint {
+    // The session date, or the first day of the week or month.
+    pub date: NaiveDate,
+    pub session_count: i64,
+    pub set_count: i64,
+    pub total_reps: i64,
+    pub volume: Decimal,
+    // The heaviest set, then the one with the most reps at that weight.
+    pub top_weight: Decimal,
+    pub top_reps: i32,
+    pub estimated_1rm: Option<Decimal>,
+}
+
+impl MovementHistoryPoint {
+    fn new(date: NaiveDate) -> Self {
+        Self {
+            date,
+            session_count: 0,
+            set_count: 0,
+            total_reps: 0,
+            volume: Decimal::ZERO,
+            top_weight: Decimal::ZERO,
+            top_reps: 0,
+            estimated_1rm: None,
+        }
+    }
+    fn add(&mut self, set: &HistorySet, formula: Formula) {
+        self.set_count += 1;
+        self.total_reps += i64::from(set.reps);
+        self.volume += set.weight * Decimal::from(set.reps);
+        if (set.weight, set.reps) > (self.top_weight, self.top_reps) {
+            self.top_weight = set.weight;
+            self.top_reps = set.reps;
+        }
+        self.estimated_1rm = self
+            .estimated_1rm
+            .max(formula.estimate(set.weight, set.reps));
+    }
+}
+
+// Sets arrive in date order. Each workout is one session; with a week or month bucket
+// the sessions in it are totalled and the best top set and 1RM kept.
+pub fn build_history(
+    sets: &[HistorySet],
+    bucket: HistoryBucket,
+    formula: Formula,
+) -> Vec<MovementHistoryPoint> {
+    let mut points: Vec<MovementHistoryPoint> = Vec::new();
+    let mut last_workout_id = None;
+    for set in sets {
+        let date = bucket.start(set.date);
+        let new_session = last_workout_id != Some(set.workout_id);
+        let starts_point = match points.last() {
+            Some(_) if bucket == HistoryBucket::Session => new_session,
+            Some(point) => point.date != date,
+            None => true,
+        };
+        if starts_point {
+            points.push(MovementHistoryPoint::new(date));
+        }
+        if let Some(point) = points.last_mut() {
+            if new_session {
+                point.session_count += 1;
+            }
+            point.add(set, formula);
+        }
+        last_workout_id = Some(set.workout_id);
+    }
+    points
+}
+
+#[derive(Debug, Serialize)]
+pub struct MovementHistory {
+    pub movement_id: Uuid,
+    pub movement_name: String,
+    pub bucket: HistoryBucket,
+    pub formula: Formula,
+    pub date_from: Option<NaiveDate>,
+    pub date_to: Option<NaiveDate>,
+    pub best_estimated_1rm: Option<Decimal>,
+    pub count: usize,
+    pub results: Vec<MovementHistoryPoint>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn set(workout: u128, day: u32, weight: Decimal, reps: i32) -> HistorySet {
+        HistorySet {
+            workout_id: Uuid::from_u128(workout),
+            date: NaiveDate::from_ymd_opt(2024, 1, day).unwrap(),
+            weight,
+            reps,
+        }
+    }
+
+    fn sets() -> Vec<HistorySet> {
+        vec![
+            set(1, 1, dec!(100), 5),
+            set(1, 1, dec!(100), 6),
+            set(2, 3, dec!(105), 3),
+            set(3, 8, dec!(90), 10),
+        ]
+    }
+
+    #[test]
+    fn test_history_by_session() {
+        let history = build_history(&sets(), HistoryBucket::Session, Formula::Epley);
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0].set_count, 2);
+        assert_eq!(history[0].total_reps, 11);
+        assert_eq!(history[0].volume, dec!(1100));
+        assert_eq!((history[0].top_weight, history[0].top_reps), (dec!(100), 6));
+        assert_eq!(history[0].estimated_1rm, Some(dec!(120)));
+        assert_eq!(history[2].estimated_1rm, Some(dec!(120)));
+    }
+
+    #[test]
+    fn test_history_by_week() {
+        let history = build_history(&sets(), HistoryBucket::Week, Formula::Brzycki);
+        assert_eq!(history.len(), 2);
+        assert_eq!(
+            history[0].date,
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()
+        );
+        assert_eq!(history[0].session_count, 2);
+        assert_eq!(history[0].set_count, 3);
+        assert_eq!(history[0].top_weight, dec!(105));
+        assert_eq!(
+            history[1].date,
+            NaiveDate::from_ymd_opt(2024, 1, 8).unwrap()
+        );
+
+        let history = build_history(&sets(), HistoryBucket::Month, Formula::Epley);
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].session_count, 3);
+        assert_eq!(history[0].volume, dec!(2315));
+    }
+}
@@ -1,3 +1,4 @@
+pub mod history;
 pub mod model;
 pub mod router;
 pub mod serializer;
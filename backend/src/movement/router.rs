@@ -8,8 +8,8 @@ use crate::AppState;
 
 use super::view::{
     movement_create_view, movement_delete_id_range_view, movement_delete_view,
-    movement_detail_view, movement_filter_view, movement_list_view, movement_select_view,
-    movement_update_view,
+    movement_detail_view, movement_filter_view, movement_history_view, movement_list_view,
+    movement_select_view, movement_update_view,
 };
 
 pub fn movement_router() -> Router<Arc<AppState>> {
@@ -19,6 +19,7 @@ pub fn movement_router() -> Router<Arc<AppState>> {
         .route("/:id", get(movement_detail_view))
         .route("/:id", put(movement_update_view))
         .route("/:id", delete(movement_delete_view))
+        .route("/:id/history", get(movement_history_view))
         .route("/filter", get(movement_filter_view))
         .route("/select", get(movement_select_view))
         .route("/delete-id-range", delete(movement_delete_id_range_view))
@@ -1,7 +1,12 @@
+use chrono::prelude::*;
 use serde::Deserialize;
 use uuid::Uuid;
 use validator::{Validate, ValidationError};
 
+use crate::{personal_record::model::Formula, util::query::empty_string_as_none};
+
+use super::history::HistoryBucket;
+
 #[derive(Debug, Deserialize, Validate)]
 pub struct MovementInput {
     #[validate(
@@ -22,3 +27,15 @@ pub fn validate_not_empty_string(name: &str) -> Result<(), ValidationError> {
     };
     Ok(())
 }
+
+#[derive(Debug, Deserialize)]
+pub struct MovementHistoryParams {
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    pub date_from: Option<NaiveDate>,
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    pub date_to: Option<NaiveDate>,
+    #[serde(default)]
+    pub bucket: HistoryBucket,
+    #[serde(default)]
+    pub formula: Option<Formula>,
+}
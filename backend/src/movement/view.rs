@@ -10,13 +10,16 @@ use crate::{
     error::AppError,
     extractor::JsonExtractor,
     middleware::RequestUser,
+    personal_record::model::Formula,
+    revision::model::set_actor,
     util::{extract::IdRange, query::QueryParams},
     AppState,
 };
 
 use super::{
+    history::{build_history, HistorySet, MovementHistory},
     model::{Movement, MovementFilter, MovementSelect, MovementSerializer},
-    serializer::MovementInput,
+    serializer::{MovementHistoryParams, MovementInput},
 };
 
 pub async fn movement_list_view(
@@ -67,7 +70,10 @@ pub async fn movement_delete_view(
 ) -> Result<Json<Movement>, AppError> {
     request_user.superuser_required()?;
     let query = Movement::get(&state.pool, &id).await?;
-    let result = Movement::delete(&state.pool, &query.id).await?;
+    let mut tx = state.pool.begin().await?;
+    set_actor(&mut tx, &request_user.id).await?;
+    let result = Movement::delete(&mut *tx, &query.id).await?;
+    tx.commit().await?;
     Ok(Json(result))
 }
 
@@ -91,6 +97,50 @@ pub async fn movement_delete_id_range_view(
     JsonExtractor(data): JsonExtractor<IdRange>,
 ) -> Result<Json<Vec<Movement>>, AppError> {
     request_user.superuser_required()?;
-    let query = Movement::delete_id_range(&state.pool, data.id_range).await?;
+    let mut tx = state.pool.begin().await?;
+    set_actor(&mut tx, &request_user.id).await?;
+    let query = Movement::delete_id_range(&mut *tx, data.id_range).await?;
+    tx.commit().await?;
     Ok(Json(query))
 }
+
+// The request user's own sessions of the movement, oldest first.
+pub async fn movement_history_view(
+    Path(id): Path<Uuid>,
+    Query(params): Query<MovementHistoryParams>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<MovementHistory>, AppError> {
+    request_user.login_required()?;
+    let movement = MovementSerializer::get(&state.pool, &id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    if let (Some(date_from), Some(date_to)) = (params.date_from, params.date_to) {
+        if date_from > date_to {
+            return Err(AppError::BadRequestMessage(String::from(
+                "Date to must be on or after date from.",
+            )));
+        }
+    }
+    let formula = params.formula.unwrap_or(Formula::Epley);
+    let sets = HistorySet::all(
+        &state.pool,
+        request_user.id,
+        movement.id,
+        &params.date_from,
+        &params.date_to,
+    )
+    .await?;
+    let results = build_history(&sets, params.bucket, formula);
+    Ok(Json(MovementHistory {
+        movement_id: movement.id,
+        movement_name: movement.name,
+        bucket: params.bucket,
+        formula,
+        date_from: params.date_from,
+        date_to: params.date_to,
+        best_estimated_1rm: results.iter().filter_map(|point| point.estimated_1rm).max(),
+        count: results.len(),
+        results,
+    }))
+}
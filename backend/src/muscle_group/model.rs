@@ -1,7 +1,7 @@
 use chrono::prelude::*;
 use futures::TryStreamExt;
 use serde::Serialize;
-use sqlx::{FromRow, PgPool, Row};
+use sqlx::{FromRow, PgExecutor, PgPool, Row};
 use uuid::Uuid;
 
 use crate::{db::Filters, util::query::QueryParams};
@@ -85,15 +85,15 @@ impl MuscleGroup {
         .await?;
         Ok(query)
     }
-    pub async fn delete(pool: &PgPool, id: &Uuid) -> Result<Self, sqlx::Error> {
+    pub async fn delete(executor: impl PgExecutor<'_>, id: &Uuid) -> Result<Self, sqlx::Error> {
         let query = sqlx::query_as("DELETE FROM muscle_group WHERE id = $1 RETURNING *")
             .bind(id)
-            .fetch_one(pool)
+            .fetch_one(executor)
             .await?;
         Ok(query)
     }
     pub async fn delete_id_range(
-        pool: &PgPool,
+        executor: impl PgExecutor<'_>,
         id_range: Vec<Uuid>,
     ) -> Result<Vec<Self>, sqlx::Error> {
         let query = sqlx::query_as(
@@ -106,7 +106,7 @@ impl MuscleGroup {
             ",
         )
         .bind(id_range)
-        .fetch_all(pool)
+        .fetch_all(executor)
         .await?;
         Ok(query)
     }
@@ -10,6 +10,7 @@ use crate::{
     error::AppError,
     extractor::JsonExtractor,
     middleware::RequestUser,
+    revision::model::set_actor,
     util::{extract::IdRange, query::QueryParams},
     AppState,
 };
@@ -67,7 +68,10 @@ pub async fn muscle_group_delete_view(
 ) -> Result<Json<MuscleGroup>, AppError> {
     request_user.superuser_required()?;
     let query = MuscleGroup::get(&state.pool, &id).await?;
-    let result = MuscleGroup::delete(&state.pool, &query.id).await?;
+    let mut tx = state.pool.begin().await?;
+    set_actor(&mut tx, &request_user.id).await?;
+    let result = MuscleGroup::delete(&mut *tx, &query.id).await?;
+    tx.commit().await?;
     Ok(Json(result))
 }
 
@@ -91,6 +95,9 @@ pub async fn muscle_group_delete_id_range_view(
     JsonExtractor(data): JsonExtractor<IdRange>,
 ) -> Result<Json<Vec<MuscleGroup>>, AppError> {
     request_user.superuser_required()?;
-    let query = MuscleGroup::delete_id_range(&state.pool, data.id_range).await?;
+    let mut tx = state.pool.begin().await?;
+    set_actor(&mut tx, &request_user.id).await?;
+    let query = MuscleGroup::delete_id_range(&mut *tx, data.id_range).await?;
+    tx.commit().await?;
     Ok(Json(query))
 }
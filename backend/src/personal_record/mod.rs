@@ -0,0 +1,4 @@
+pub mod model;
+pub mod router;
+pub mod serializer;
+pub mod view;
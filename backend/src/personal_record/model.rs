@@ -0,0 +1,617 @@
+use chrono::prelude::*;
+use futures::TryStreamExt;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgConnection, PgPool, Postgres, QueryBuilder};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::db::Filters;
+
+use super::serializer::PersonalRecordParams;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Formula {
+    Epley,
+    Brzycki,
+}
+
+impl Formula {
+    pub const ALL: [Formula; 2] = [Formula::Epley, Formula::Brzycki];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Formula::Epley => "epley",
+            Formula::Brzycki => "brzycki",
+        }
+    }
+    // A single is its own 1RM. Brzycki's estimate breaks down from 37 reps.
+    pub fn estimate(&self, weight: Decimal, reps: i32) -> Option<Decimal> {
+        if weight <= Decimal::ZERO || reps <= 0 {
+            return None;
+        }
+        if reps == 1 {
+            return Some(weight);
+        }
+        let reps = Decimal::from(reps);
+        let estimate = match self {
+            Formula::Epley => weight * (Decimal::ONE + reps / dec!(30)),
+            Formula::Brzycki if reps < dec!(37) => weight * dec!(36) / (dec!(37) - reps),
+            Formula::Brzycki => return None,
+        };
+        Some(estimate.round_dp(2))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordType {
+    Weight,
+    E1rm,
+    Reps,
+    Volume,
+}
+
+impl RecordType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RecordType::Weight => "weight",
+            RecordType::E1rm => "e1rm",
+            RecordType::Reps => "reps",
+            RecordType::Volume => "volume",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct NewRecord {
+    pub record_type: RecordType,
+    pub formula: Option<Formula>,
+    pub weight: Option<Decimal>,
+    pub reps: Option<i32>,
+    pub value: Decimal,
+    pub previous_value: Decimal,
+}
+
+// The best lifts so far for one movement, with the most reps done at each weight.
+#[derive(Debug, Default)]
+pub struct Bests {
+    weight: Option<Decimal>,
+    e1rm: [Option<Decimal>; 2],
+    reps_at: HashMap<Decimal, i32>,
+}
+
+impl Bests {
+    pub fn add(&mut self, weight: Decimal, reps: i32) {
+        if reps <= 0 {
+            return;
+        }
+        self.weight = self.weight.max(Some(weight));
+        for (best, formula) in self.e1rm.iter_mut().zip(Formula::ALL) {
+            *best = (*best).max(formula.estimate(weight, reps));
+        }
+        let best_reps = self.reps_at.entry(weight.normalize()).or_default();
+        *best_reps = (*best_reps).max(reps);
+    }
+    // Records the set beats, before counting it towards the bests. A record needs an
+    // earlier best to beat, so the first time a weight is lifted is not one.
+    pub fn check(&mut self, weight: Decimal, reps: i32) -> Vec<NewRecord> {
+        let mut records = Vec::new();
+        if reps <= 0 {
+            return records;
+        }
+        if let Some(best) = self.weight.filter(|best| weight > *best) {
+            records.push(NewRecord {
+                record_type: RecordType::Weight,
+                formula: None,
+                weight: Some(weight),
+                reps: Some(reps),
+                value: weight,
+                previous_value: best,
+            });
+        }
+        for (best, formula) in self.e1rm.iter().zip(Formula::ALL) {
+            if let (Some(best), Some(estimate)) = (best, formula.estimate(weight, reps)) {
+                if estimate > *best {
+                    records.push(NewRecord {
+                        record_type: RecordType::E1rm,
+                        formula: Some(formula),
+                        weight: Some(weight),
+                        reps: Some(reps),
+                        value: estimate,
+                        previous_value: *best,
+                    });
+                }
+            }
+        }
+        if let Some(best) = self.reps_at.get(&weight.normalize()) {
+            if reps > *best {
+                records.push(NewRecord {
+                    record_type: RecordType::Reps,
+                    formula: None,
+                    weight: Some(weight),
+                    reps: Some(reps),
+                    value: Decimal::from(reps),
+                    previous_value: Decimal::from(*best),
+                });
+            }
+        }
+        self.add(weight, reps);
+        records
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct CreatedSet {
+    id: Uuid,
+    exercise_id: Uuid,
+    workout_id: Uuid,
+    movement_id: Uuid,
+    user_id: Uuid,
+    weight: Decimal,
+    reps: i32,
+}
+
+#[derive(Debug, FromRow)]
+struct WorkoutVolume {
+    workout_id: Uuid,
+    volume: Decimal,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct PersonalRecord {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub movement_id: Uuid,
+    pub workout_id: Uuid,
+    pub exercise_id: Uuid,
+    pub set_id: Option<Uuid>,
+    pub record_type: String,
+    pub formula: Option<String>,
+    pub weight: Option<Decimal>,
+    pub reps: Option<i32>,
+    pub value: Decimal,
+    pub previous_value: Decimal,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Default)]
+struct RecordColumns {
+    user_id: Vec<Uuid>,
+    movement_id: Vec<Uuid>,
+    workout_id: Vec<Uuid>,
+    exercise_id: Vec<Uuid>,
+    set_id: Vec<Option<Uuid>>,
+    record_type: Vec<&'static str>,
+    formula: Vec<Option<&'static str>>,
+    weight: Vec<Option<Decimal>>,
+    reps: Vec<Option<i32>>,
+    value: Vec<Decimal>,
+    previous_value: Vec<Decimal>,
+}
+
+impl RecordColumns {
+    fn push(&mut self, set: &CreatedSet, set_id: Option<Uuid>, record: NewRecord) {
+        self.user_id.push(set.user_id);
+        self.movement_id.push(set.movement_id);
+        self.workout_id.push(set.workout_id);
+        self.exercise_id.push(set.exercise_id);
+        self.set_id.push(set_id);
+        self.record_type.push(record.record_type.as_str());
+        self.formula
+            .push(record.formula.map(|formula| formula.as_str()));
+        self.weight.push(record.weight);
+        self.reps.push(record.reps);
+        self.value.push(record.value);
+        self.previous_value.push(record.previous_value);
+    }
+}
+
+// A workout's volume is a record when it beats every other workout of the movement.
+fn volume_record(volumes: &[WorkoutVolume], workout_id: Uuid) -> Option<NewRecord> {
+    let current = volumes
+        .iter()
+        .find(|row| row.workout_id == workout_id)
+        .map(|row| row.volume)?;
+    let previous = volumes
+        .iter()
+        .filter(|row| row.workout_id != workout_id)
+        .map(|row| row.volume)
+        .max()?;
+    (current > previous).then_some(NewRecord {
+        record_type: RecordType::Volume,
+        formula: None,
+        weight: None,
+        reps: None,
+        value: current,
+        previous_value: previous,
+    })
+}
+
+impl PersonalRecord {
+    // Called once new sets are saved, in the same transaction. Sets are compared with
+    // the user's other sets of the movement, in the order they were added.
+    pub async fn detect(
+        conn: &mut PgConnection,
+        set_ids: &[Uuid],
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let created: Vec<CreatedSet> = sqlx::query_as(
+            r#"
+            SELECT
+                t1.id,
+                t1.exercise_id,
+                t2.workout_id,
+                t2.movement_id,
+                t3.user_id,
+                t1.weight,
+                t1.reps
+            FROM
+                tracked_set t1
+                JOIN exercise t2 ON t2.id = t1.exercise_id
+                JOIN workout t3 ON t3.id = t2.workout_id
+            WHERE
+                t1.id = ANY ($1)
+            ORDER BY
+                t1.created_at,
+                t1."order"
+            "#,
+        )
+        .bind(set_ids)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut columns = RecordColumns::default();
+        let mut movements: Vec<(Uuid, Uuid)> = created
+            .iter()
+            .map(|set| (set.user_id, set.movement_id))
+            .collect();
+        movements.sort();
+        movements.dedup();
+        for (user_id, movement_id) in movements {
+            let previous: Vec<(Decimal, i32)> = sqlx::query_as(
+                "
+                SELECT DISTINCT
+                    t1.weight,
+                    t1.reps
+                FROM
+                    tracked_set t1
+                    JOIN exercise t2 ON t2.id = t1.exercise_id
+                    JOIN workout t3 ON t3.id = t2.workout_id
+                WHERE
+                    t3.user_id = $1
+                    AND t2.movement_id = $2
+                    AND t1.id <> ALL ($3)
+                ",
+            )
+            .bind(user_id)
+            .bind(movement_id)
+            .bind(set_ids)
+            .fetch_all(&mut *conn)
+            .await?;
+            let mut bests = Bests::default();
+            for (weight, reps) in previous {
+                bests.add(weight, reps);
+            }
+            let sets: Vec<&CreatedSet> = created
+                .iter()
+                .filter(|set| set.user_id == user_id && set.movement_id == movement_id)
+                .collect();
+            for set in &sets {
+                for record in bests.check(set.weight, set.reps) {
+                    columns.push(set, Some(set.id), record);
+                }
+            }
+
+            let volumes = Self::workout_volumes(conn, user_id, movement_id).await?;
+            let mut workout_ids: Vec<Uuid> = sets.iter().map(|set| set.workout_id).collect();
+            workout_ids.sort();
+            workout_ids.dedup();
+            for workout_id in workout_ids {
+                let Some(last_set) = sets.iter().rev().find(|set| set.workout_id == workout_id)
+                else {
+                    continue;
+                };
+                if let Some(record) = volume_record(&volumes, workout_id) {
+                    Self::delete_volume(conn, workout_id, movement_id).await?;
+                    columns.push(last_set, None, record);
+                }
+            }
+        }
+        Self::insert(conn, columns).await
+    }
+    async fn insert(
+        conn: &mut PgConnection,
+        columns: RecordColumns,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        if columns.value.is_empty() {
+            return Ok(Vec::new());
+        }
+        let query = sqlx::query_as(
+            "
+            INSERT INTO
+                personal_record (
+                    user_id,
+                    movement_id,
+                    workout_id,
+                    exercise_id,
+                    set_id,
+                    record_type,
+                    formula,
+                    weight,
+                    reps,
+                    value,
+                    previous_value
+                )
+            SELECT
+                *
+            FROM
+                UNNEST(
+                    $1::UUID[],
+                    $2::UUID[],
+                    $3::UUID[],
+                    $4::UUID[],
+                    $5::UUID[],
+                    $6::VARCHAR[],
+                    $7::VARCHAR[],
+                    $8::DECIMAL[],
+                    $9::INTEGER[],
+                    $10::DECIMAL[],
+                    $11::DECIMAL[]
+                )
+            RETURNING
+                *
+            ",
+        )
+        .bind(&columns.user_id)
+        .bind(&columns.movement_id)
+        .bind(&columns.workout_id)
+        .bind(&columns.exercise_id)
+        .bind(&columns.set_id)
+        .bind(&columns.record_type)
+        .bind(&columns.formula)
+        .bind(&columns.weight)
+        .bind(&columns.reps)
+        .bind(&columns.value)
+        .bind(&columns.previous_value)
+        .fetch_all(&mut *conn)
+        .await?;
+        Ok(query)
+    }
+    // A workout keeps a single volume record per movement, its latest total.
+    async fn delete_volume(
+        conn: &mut PgConnection,
+        workout_id: Uuid,
+        movement_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "
+            DELETE FROM personal_record
+            WHERE
+                workout_id = $1
+                AND movement_id = $2
+                AND record_type = 'volume'
+            ",
+        )
+        .bind(workout_id)
+        .bind(movement_id)
+        .execute(&mut *conn)
+        .await?;
+        Ok(())
+    }
+    async fn workout_volumes(
+        conn: &mut PgConnection,
+        user_id: Uuid,
+        movement_id: Uuid,
+    ) -> Result<Vec<WorkoutVolume>, sqlx::Error> {
+        let query = sqlx::query_as(
+            "
+            SELECT
+                t2.workout_id,
+                SUM(t1.weight * t1.reps) AS volume
+            FROM
+                tracked_set t1
+                JOIN exercise t2 ON t2.id = t1.exercise_id
+                JOIN workout t3 ON t3.id = t2.workout_id
+            WHERE
+                t3.user_id = $1
+                AND t2.movement_id = $2
+            GROUP BY
+                t2.workout_id
+            ",
+        )
+        .bind(user_id)
+        .bind(movement_id)
+        .fetch_all(&mut *conn)
+        .await?;
+        Ok(query)
+    }
+    // Called when sets are edited, in the same transaction. Their weight, e1rm and
+    // reps records are replaced by those the new values set.
+    pub async fn redetect(conn: &mut PgConnection, set_ids: &[Uuid]) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM personal_record WHERE set_id = ANY ($1)")
+            .bind(set_ids)
+            .execute(&mut *conn)
+            .await?;
+        Self::detect(conn, set_ids).await?;
+        Ok(())
+    }
+    // Called when sets are edited or deleted, in the same transaction. The volume
+    // records of the exercises' workouts are worked out again from the sets left.
+    pub async fn recompute_volume(
+        conn: &mut PgConnection,
+        exercise_ids: &[Uuid],
+    ) -> Result<(), sqlx::Error> {
+        let workouts: Vec<(Uuid, Uuid, Uuid)> = sqlx::query_as(
+            "
+            SELECT DISTINCT
+                t2.user_id,
+                t1.workout_id,
+                t1.movement_id
+            FROM
+                exercise t1
+                JOIN workout t2 ON t2.id = t1.workout_id
+            WHERE
+                t1.id = ANY ($1)
+            ",
+        )
+        .bind(exercise_ids)
+        .fetch_all(&mut *conn)
+        .await?;
+        let mut columns = RecordColumns::default();
+        for (user_id, workout_id, movement_id) in workouts {
+            Self::delete_volume(conn, workout_id, movement_id).await?;
+            let last_set: Option<CreatedSet> = sqlx::query_as(
+                r#"
+                SELECT
+                    t1.id,
+                    t1.exercise_id,
+                    t2.workout_id,
+                    t2.movement_id,
+                    t3.user_id,
+                    t1.weight,
+                    t1.reps
+                FROM
+                    tracked_set t1
+                    JOIN exercise t2 ON t2.id = t1.exercise_id
+                    JOIN workout t3 ON t3.id = t2.workout_id
+                WHERE
+                    t2.workout_id = $1
+                    AND t2.movement_id = $2
+                ORDER BY
+                    t1.created_at DESC,
+                    t1."order" DESC
+                LIMIT
+                    1
+                "#,
+            )
+            .bind(workout_id)
+            .bind(movement_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+            let Some(last_set) = last_set else {
+                continue;
+            };
+            let volumes = Self::workout_volumes(conn, user_id, movement_id).await?;
+            if let Some(record) = volume_record(&volumes, workout_id) {
+                columns.push(&last_set, None, record);
+            }
+        }
+        Self::insert(conn, columns).await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct PersonalRecordSerializer {
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub record: PersonalRecord,
+    pub date: NaiveDate,
+    pub movement_name: String,
+    pub movement_slug: String,
+}
+
+impl PersonalRecordSerializer {
+    pub async fn all(
+        pool: &PgPool,
+        user_id: Uuid,
+        params: &PersonalRecordParams,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let mut stream = Vec::new();
+        let mut q: QueryBuilder<'static, Postgres> = QueryBuilder::new(
+            "
+            SELECT
+                t1.*,
+                t2.date,
+                t3.name AS movement_name,
+                t3.slug AS movement_slug
+            FROM
+                personal_record t1
+                JOIN workout t2 ON t2.id = t1.workout_id
+                JOIN movement t3 ON t3.id = t1.movement_id
+            WHERE
+                t1.user_id = ",
+        );
+        q.push_bind(user_id);
+        q.filter_uuid_exact("t1.movement_id", &params.movement_id);
+        if let Some(record_type) = params.record_type {
+            q.push(" AND t1.record_type = ");
+            q.push_bind(record_type.as_str());
+        }
+        q.push(" AND (t1.formula IS NULL OR t1.formula = ");
+        q.push_bind(params.formula.unwrap_or(Formula::Epley).as_str());
+        q.push(")");
+        q.filter_date("t2.date", ">=", &params.date_from);
+        q.filter_date("t2.date", "<=", &params.date_to);
+        q.push(" ORDER BY t2.date DESC, t1.created_at DESC");
+        let mut rows = q.build_query_as().fetch(pool);
+        while let Some(row) = rows.try_next().await? {
+            stream.push(row);
+        }
+        Ok(stream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate() {
+        assert_eq!(Formula::Epley.estimate(dec!(100), 1), Some(dec!(100)));
+        assert_eq!(Formula::Epley.estimate(dec!(100), 5), Some(dec!(116.67)));
+        assert_eq!(Formula::Brzycki.estimate(dec!(100), 5), Some(dec!(112.5)));
+        assert_eq!(Formula::Brzycki.estimate(dec!(100), 37), None);
+        assert_eq!(Formula::Epley.estimate(dec!(0), 10), None);
+    }
+
+    #[test]
+    fn test_first_sets_are_not_records() {
+        let mut bests = Bests::default();
+        assert!(bests.check(dec!(100), 5).is_empty());
+        assert!(bests.check(dec!(100), 5).is_empty());
+    }
+
+    #[test]
+    fn test_check_records() {
+        let mut bests = Bests::default();
+        bests.add(dec!(100), 5);
+        bests.add(dec!(80), 10);
+
+        let records = bests.check(dec!(100.00), 6);
+        let types: Vec<_> = records.iter().map(|record| record.record_type).collect();
+        assert_eq!(
+            types,
+            vec![RecordType::E1rm, RecordType::E1rm, RecordType::Reps]
+        );
+        assert_eq!(records[0].formula, Some(Formula::Epley));
+        assert_eq!(records[0].value, dec!(120));
+        assert_eq!(records[1].formula, Some(Formula::Brzycki));
+        assert_eq!(records[2].previous_value, dec!(5));
+
+        let records = bests.check(dec!(80), 11);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].record_type, RecordType::Reps);
+
+        let records = bests.check(dec!(105), 1);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].record_type, RecordType::Weight);
+        assert_eq!(records[0].previous_value, dec!(100));
+    }
+
+    #[test]
+    fn test_volume_record() {
+        let volume = |workout: u128, volume: Decimal| WorkoutVolume {
+            workout_id: Uuid::from_u128(workout),
+            volume,
+        };
+        let volumes = vec![volume(1, dec!(1000)), volume(2, dec!(1200))];
+        let record = volume_record(&volumes, Uuid::from_u128(2)).unwrap();
+        assert_eq!(record.value, dec!(1200));
+        assert_eq!(record.previous_value, dec!(1000));
+        assert!(volume_record(&volumes, Uuid::from_u128(1)).is_none());
+        assert!(volume_record(&volumes[..1], Uuid::from_u128(1)).is_none());
+    }
+}
@@ -0,0 +1,9 @@
+use axum::{routing::get, Router};
+use std::sync::Arc;
+
+use crate::personal_record::view::personal_record_list_view;
+use crate::AppState;
+
+pub fn personal_record_router() -> Router<Arc<AppState>> {
+    Router::new().route("/:username", get(personal_record_list_view))
+}
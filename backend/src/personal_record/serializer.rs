@@ -0,0 +1,22 @@
+use chrono::prelude::*;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::util::query::empty_string_as_none;
+
+use super::model::{Formula, RecordType};
+
+#[derive(Debug, Deserialize)]
+pub struct PersonalRecordParams {
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    pub movement_id: Option<Uuid>,
+    #[serde(default)]
+    pub record_type: Option<RecordType>,
+    // Estimated 1RM records are kept for both formulas; this picks which to list.
+    #[serde(default)]
+    pub formula: Option<Formula>,
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    pub date_from: Option<NaiveDate>,
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    pub date_to: Option<NaiveDate>,
+}
@@ -0,0 +1,28 @@
+use axum::{
+    extract::{Path, Query, State},
+    Extension, Json,
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::{
+    error::AppError, middleware::RequestUser, user::model::User,
+    util::permission::user_privacy_check, AppState,
+};
+
+use super::{model::PersonalRecordSerializer, serializer::PersonalRecordParams};
+
+pub async fn personal_record_list_view(
+    Path(username): Path<String>,
+    Query(params): Query<PersonalRecordParams>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<Value>, AppError> {
+    let user = User::get_from_username(&state.pool, &username)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    user_privacy_check(&state.pool, &request_user, &user).await?;
+    let query = PersonalRecordSerializer::all(&state.pool, user.id, &params).await?;
+    let response = json!({"count": query.len(), "results": query});
+    Ok(Json(response))
+}
@@ -0,0 +1,543 @@
+use chrono::prelude::*;
+use futures::TryStreamExt;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::{
+    diet::quick_add::{QuickAdd, QuickAddColumns},
+    meal_food::model::MealFood,
+};
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct PlannedFood {
+    pub id: Uuid,
+    pub date: NaiveDate,
+    pub user_id: Uuid,
+    pub meal_of_day_id: Uuid,
+    pub food_id: Option<Uuid>,
+    pub quantity: Decimal,
+    pub meal_id: Option<Uuid>,
+    pub food_log_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub created_by_id: Uuid,
+    pub updated_by_id: Option<Uuid>,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub quick_add: QuickAdd,
+}
+
+impl PlannedFood {
+    pub async fn create(
+        pool: &PgPool,
+        user_id: Uuid,
+        date: NaiveDate,
+        meal_of_day_id: Uuid,
+        food_id: Uuid,
+        quantity: Decimal,
+        created_by_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let query = sqlx::query_as(
+            "
+            INSERT INTO
+                planned_food (
+                    date,
+                    user_id,
+                    meal_of_day_id,
+                    food_id,
+                    quantity,
+                    created_by_id
+                )
+            VALUES
+                ($1, $2, $3, $4, $5, $6)
+            RETURNING
+                *
+            ",
+        )
+        .bind(date)
+        .bind(user_id)
+        .bind(meal_of_day_id)
+        .bind(food_id)
+        .bind(quantity)
+        .bind(created_by_id)
+        .fetch_one(pool)
+        .await?;
+        Ok(query)
+    }
+    pub async fn get(pool: &PgPool, id: &Uuid) -> Result<Option<Self>, sqlx::Error> {
+        let query = sqlx::query_as("SELECT * FROM planned_food WHERE id = $1")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(query)
+    }
+    pub async fn update(
+        pool: &PgPool,
+        id: &Uuid,
+        date: NaiveDate,
+        meal_of_day_id: Uuid,
+        quantity: Decimal,
+        updated_by_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let query = sqlx::query_as(
+            "
+            UPDATE planned_food
+            SET
+                date = $1,
+                meal_of_day_id = $2,
+                quantity = $3,
+                updated_at = $4,
+                updated_by_id = $5
+            WHERE
+                id = $6
+            RETURNING
+                *
+            ",
+        )
+        .bind(date)
+        .bind(meal_of_day_id)
+        .bind(quantity)
+        .bind(Utc::now())
+        .bind(updated_by_id)
+        .bind(id)
+        .fetch_one(pool)
+        .await?;
+        Ok(query)
+    }
+    pub async fn delete(pool: &PgPool, id: &Uuid) -> Result<Self, sqlx::Error> {
+        let query = sqlx::query_as("DELETE FROM planned_food WHERE id = $1 RETURNING *")
+            .bind(id)
+            .fetch_one(pool)
+            .await?;
+        Ok(query)
+    }
+    // One copy of every meal item for each date, quick-adds included.
+    pub async fn create_from_meal_food(
+        pool: &PgPool,
+        user_id: Uuid,
+        dates: &[NaiveDate],
+        meal_of_day_id: Uuid,
+        meal_id: Uuid,
+        meal_food: Vec<MealFood>,
+        created_by_id: Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let mut date_list = Vec::new();
+        let mut food_id_list = Vec::new();
+        let mut quantity_list = Vec::new();
+        let mut quick_add_list = QuickAddColumns::default();
+        for date in dates {
+            for food in meal_food.iter() {
+                date_list.push(*date);
+                food_id_list.push(food.food_id);
+                quantity_list.push(food.quantity);
+                quick_add_list.push(food.quick_add.clone());
+            }
+        }
+        let query = sqlx::query_as(
+            "
+            INSERT INTO
+                planned_food (
+                    user_id,
+                    meal_of_day_id,
+                    meal_id,
+                    created_by_id,
+                    date,
+                    food_id,
+                    quantity,
+                    label,
+                    energy,
+                    fat,
+                    saturates,
+                    carbohydrate,
+                    sugars,
+                    fibre,
+                    protein,
+                    salt
+                )
+            SELECT
+                $1,
+                $2,
+                $3,
+                $4,
+                t.*
+            FROM
+                UNNEST(
+                    $5::DATE[],
+                    $6::UUID[],
+                    $7::DECIMAL[],
+                    $8::VARCHAR[],
+                    $9::INTEGER[],
+                    $10::DECIMAL[],
+                    $11::DECIMAL[],
+                    $12::DECIMAL[],
+                    $13::DECIMAL[],
+                    $14::DECIMAL[],
+                    $15::DECIMAL[],
+                    $16::DECIMAL[]
+                ) t
+            RETURNING
+                *
+            ",
+        )
+        .bind(user_id)
+        .bind(meal_of_day_id)
+        .bind(meal_id)
+        .bind(created_by_id)
+        .bind(date_list)
+        .bind(food_id_list)
+        .bind(quantity_list)
+        .bind(quick_add_list.label)
+        .bind(quick_add_list.energy)
+        .bind(quick_add_list.fat)
+        .bind(quick_add_list.saturates)
+        .bind(quick_add_list.carbohydrate)
+        .bind(quick_add_list.sugars)
+        .bind(quick_add_list.fibre)
+        .bind(quick_add_list.protein)
+        .bind(quick_add_list.salt)
+        .fetch_all(pool)
+        .await?;
+        Ok(query)
+    }
+    pub async fn slot_ids(
+        pool: &PgPool,
+        user_id: &Uuid,
+        date: &NaiveDate,
+        meal_of_day_id: &Uuid,
+    ) -> Result<Vec<Uuid>, sqlx::Error> {
+        let query = sqlx::query_scalar(
+            "
+            SELECT
+                id
+            FROM
+                planned_food
+            WHERE
+                user_id = $1
+                AND date = $2
+                AND meal_of_day_id = $3
+                AND food_log_id IS NULL
+            ",
+        )
+        .bind(user_id)
+        .bind(date)
+        .bind(meal_of_day_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(query)
+    }
+    // Copies the user's not yet eaten entries into food_log in one statement and
+    // links each plan to its new diary row. Already eaten entries are skipped.
+    pub async fn mark_eaten(
+        pool: &PgPool,
+        user_id: &Uuid,
+        ids: &[Uuid],
+        eaten_at: Option<DateTime<Utc>>,
+        created_by_id: &Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let query = sqlx::query_as(
+            "
+            WITH
+                planned AS (
+                    SELECT
+                        uuid_generate_v4 () AS new_food_log_id,
+                        t1.*
+                    FROM
+                        planned_food t1
+                    WHERE
+                        t1.id = ANY ($1)
+                        AND t1.user_id = $2
+                        AND t1.food_log_id IS NULL
+                    FOR UPDATE
+                ),
+                logged AS (
+                    INSERT INTO
+                        food_log (
+                            id,
+                            date,
+                            user_id,
+                            meal_of_day_id,
+                            food_id,
+                            quantity,
+                            eaten_at,
+                            label,
+                            energy,
+                            fat,
+                            saturates,
+                            carbohydrate,
+                            sugars,
+                            fibre,
+                            protein,
+                            salt,
+                            created_by_id
+                        )
+                    SELECT
+                        new_food_log_id,
+                        date,
+                        user_id,
+                        meal_of_day_id,
+                        food_id,
+                        quantity,
+                        $3,
+                        label,
+                        energy,
+                        fat,
+                        saturates,
+                        carbohydrate,
+                        sugars,
+                        fibre,
+                        protein,
+                        salt,
+                        $4
+                    FROM
+                        planned
+                )
+            UPDATE planned_food t1
+            SET
+                food_log_id = t2.new_food_log_id
+            FROM
+                planned t2
+            WHERE
+                t1.id = t2.id
+            RETURNING
+                t1.*
+            ",
+        )
+        .bind(ids)
+        .bind(user_id)
+        .bind(eaten_at)
+        .bind(created_by_id)
+        .fetch_all(pool)
+        .await?;
+        Ok(query)
+    }
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct PlannedFoodSerializer {
+    pub id: Uuid,
+    pub date: NaiveDate,
+    pub meal_of_day_id: Uuid,
+    pub meal_of_day_name: String,
+    pub meal_of_day_slug: String,
+    pub food_id: Option<Uuid>,
+    pub food_name: String,
+    pub brand_name: Option<String>,
+    pub meal_id: Option<Uuid>,
+    pub food_log_id: Option<Uuid>,
+    pub eaten: bool,
+    pub quantity: Decimal,
+    pub data_value: Option<Decimal>,
+    pub data_measurement: Option<String>,
+    pub energy: Decimal,
+    pub protein: Decimal,
+    pub carbohydrate: Decimal,
+    pub fat: Decimal,
+    pub saturates: Decimal,
+    pub sugars: Decimal,
+    pub fibre: Decimal,
+    pub salt: Decimal,
+}
+
+impl PlannedFoodSerializer {
+    pub async fn all(
+        pool: &PgPool,
+        user_id: &Uuid,
+        date_from: &NaiveDate,
+        date_to: &NaiveDate,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let mut stream = Vec::new();
+        let mut rows = sqlx::query_as(
+            "
+            SELECT
+                t1.id,
+                t1.date,
+                t1.meal_of_day_id,
+                t4.name AS meal_of_day_name,
+                t4.slug AS meal_of_day_slug,
+                t1.food_id,
+                COALESCE(t2.name, t1.label) AS food_name,
+                t3.name AS brand_name,
+                t1.meal_id,
+                t1.food_log_id,
+                t1.food_log_id IS NOT NULL AS eaten,
+                t1.quantity,
+                t1.quantity * t2.data_value AS data_value,
+                t2.data_measurement,
+                t1.quantity * COALESCE(t2.energy, t1.energy) AS energy,
+                t1.quantity * COALESCE(t2.protein, t1.protein) AS protein,
+                t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate) AS carbohydrate,
+                t1.quantity * COALESCE(t2.fat, t1.fat) AS fat,
+                t1.quantity * COALESCE(t2.saturates, t1.saturates) AS saturates,
+                t1.quantity * COALESCE(t2.sugars, t1.sugars) AS sugars,
+                t1.quantity * COALESCE(t2.fibre, t1.fibre) AS fibre,
+                t1.quantity * COALESCE(t2.salt, t1.salt) AS salt
+            FROM
+                planned_food t1
+                LEFT JOIN food t2 ON t2.id = t1.food_id
+                LEFT JOIN food_brand t3 ON t3.id = t2.brand_id
+                LEFT JOIN meal_of_day t4 ON t4.id = t1.meal_of_day_id
+            WHERE
+                t1.user_id = $1
+                AND t1.date BETWEEN $2 AND $3
+            ORDER BY
+                t1.date,
+                t4.ordering,
+                t1.created_at
+            ",
+        )
+        .bind(user_id)
+        .bind(date_from)
+        .bind(date_to)
+        .fetch(pool);
+        while let Some(row) = rows.try_next().await? {
+            stream.push(row);
+        }
+        Ok(stream)
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, FromRow)]
+pub struct PlannedTotal {
+    pub date: Option<NaiveDate>,
+    pub energy: Decimal,
+    pub protein: Decimal,
+    pub carbohydrate: Decimal,
+    pub fat: Decimal,
+    pub saturates: Decimal,
+    pub sugars: Decimal,
+    pub fibre: Decimal,
+    pub salt: Decimal,
+    pub entry_count: i64,
+    pub eaten_count: i64,
+}
+
+impl PlannedTotal {
+    pub async fn by_date(
+        pool: &PgPool,
+        username: &str,
+        date_from: &NaiveDate,
+        date_to: &NaiveDate,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let mut stream = Vec::new();
+        let mut rows = sqlx::query_as(
+            "
+            SELECT
+                t1.date,
+                SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) AS energy,
+                SUM(t1.quantity * COALESCE(t2.protein, t1.protein)) AS protein,
+                SUM(t1.quantity * COALESCE(t2.carbohydrate, t1.carbohydrate)) AS carbohydrate,
+                SUM(t1.quantity * COALESCE(t2.fat, t1.fat)) AS fat,
+                SUM(t1.quantity * COALESCE(t2.saturates, t1.saturates)) AS saturates,
+                SUM(t1.quantity * COALESCE(t2.sugars, t1.sugars)) AS sugars,
+                SUM(t1.quantity * COALESCE(t2.fibre, t1.fibre)) AS fibre,
+                SUM(t1.quantity * COALESCE(t2.salt, t1.salt)) AS salt,
+                COUNT(*) AS entry_count,
+                COUNT(t1.food_log_id) AS eaten_count
+            FROM
+                planned_food t1
+                LEFT JOIN food t2 ON t2.id = t1.food_id
+                JOIN users_user t3 ON t3.id = t1.user_id
+            WHERE
+                t3.username = $1
+                AND t1.date BETWEEN $2 AND $3
+            GROUP BY
+                t1.date
+            ORDER BY
+                t1.date
+            ",
+        )
+        .bind(username)
+        .bind(date_from)
+        .bind(date_to)
+        .fetch(pool);
+        while let Some(row) = rows.try_next().await? {
+            stream.push(row);
+        }
+        Ok(stream)
+    }
+    pub async fn day(pool: &PgPool, username: &str, date: &NaiveDate) -> Result<Self, sqlx::Error> {
+        let query = Self::by_date(pool, username, date, date)
+            .await?
+            .pop()
+            .unwrap_or_default();
+        Ok(query)
+    }
+    pub fn total(date: NaiveDate, days: &[Self]) -> Self {
+        let mut total = days.iter().fold(Self::default(), |mut total, day| {
+            total.energy += day.energy;
+            total.protein += day.protein;
+            total.carbohydrate += day.carbohydrate;
+            total.fat += day.fat;
+            total.saturates += day.saturates;
+            total.sugars += day.sugars;
+            total.fibre += day.fibre;
+            total.salt += day.salt;
+            total.entry_count += day.entry_count;
+            total.eaten_count += day.eaten_count;
+            total
+        });
+        total.date = Some(date);
+        total
+    }
+    // Averaged over the days that have a plan, matching how the eaten week
+    // average only counts logged days.
+    pub fn average(date: NaiveDate, days: &[Self]) -> Self {
+        let mut average = Self::total(date, days);
+        if days.is_empty() {
+            return average;
+        }
+        let count = Decimal::from(days.len());
+        average.energy /= count;
+        average.protein /= count;
+        average.carbohydrate /= count;
+        average.fat /= count;
+        average.saturates /= count;
+        average.sugars /= count;
+        average.fibre /= count;
+        average.salt /= count;
+        average
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::*;
+
+    fn day(day: u32, energy: Decimal, entry_count: i64) -> PlannedTotal {
+        PlannedTotal {
+            date: NaiveDate::from_ymd_opt(2024, 1, day),
+            energy,
+            protein: dec!(10),
+            entry_count,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_total_and_average_over_planned_days() {
+        let week = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let days = [day(1, dec!(2000), 3), day(3, dec!(2400), 4)];
+
+        let total = PlannedTotal::total(week, &days);
+        assert_eq!(total.date, Some(week));
+        assert_eq!(total.energy, dec!(4400));
+        assert_eq!(total.protein, dec!(20));
+        assert_eq!(total.entry_count, 7);
+
+        let average = PlannedTotal::average(week, &days);
+        assert_eq!(average.energy, dec!(2200));
+        assert_eq!(average.entry_count, 7);
+    }
+
+    #[test]
+    fn test_average_without_plans_is_zero() {
+        let week = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let average = PlannedTotal::average(week, &[]);
+        assert_eq!(average.energy, Decimal::ZERO);
+        assert_eq!(average.entry_count, 0);
+    }
+}
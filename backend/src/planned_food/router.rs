@@ -0,0 +1,25 @@
+use axum::{
+    routing::{delete, get, post, put},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::planned_food::view::{
+    planned_food_create_from_meal_view, planned_food_create_view, planned_food_day_view,
+    planned_food_delete_view, planned_food_detail_view, planned_food_eaten_view,
+    planned_food_list_view, planned_food_slot_eaten_view, planned_food_update_view,
+};
+use crate::AppState;
+
+pub fn planned_food_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(planned_food_list_view))
+        .route("/", post(planned_food_create_view))
+        .route("/from-meal", post(planned_food_create_from_meal_view))
+        .route("/eaten", post(planned_food_slot_eaten_view))
+        .route("/:id", get(planned_food_detail_view))
+        .route("/:id", put(planned_food_update_view))
+        .route("/:id", delete(planned_food_delete_view))
+        .route("/:id/eaten", post(planned_food_eaten_view))
+        .route("/:username/:date", get(planned_food_day_view))
+}
@@ -0,0 +1,97 @@
+use chrono::prelude::*;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::borrow::Cow;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::util::validator::{validate_max_quantity, validate_min_quantity};
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct PlannedFoodInput {
+    pub username: String,
+    pub date: NaiveDate,
+    pub food_id: Uuid,
+    pub meal_of_day_id: Option<Uuid>,
+    pub meal_of_day_slug: Option<String>,
+    #[validate(
+        custom(
+            function = "validate_min_quantity",
+            message = "Quantity must be a minimum of 0.01"
+        ),
+        custom(
+            function = "validate_max_quantity",
+            message = "Quantity must be a maximum of 999.99"
+        )
+    )]
+    pub quantity: Decimal,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_plan_range"))]
+pub struct PlannedFromMealInput {
+    pub username: String,
+    pub meal_id: Uuid,
+    pub meal_of_day_id: Option<Uuid>,
+    pub meal_of_day_slug: Option<String>,
+    pub date_from: NaiveDate,
+    pub date_to: NaiveDate,
+}
+
+const MAX_PLAN_DAYS: i64 = 31;
+
+fn validate_plan_range(data: &PlannedFromMealInput) -> Result<(), ValidationError> {
+    let days = (data.date_to - data.date_from).num_days();
+    let (code, message) = if days < 0 {
+        ("date_range", "Date to must be on or after date from")
+    } else if days >= MAX_PLAN_DAYS {
+        ("date_range_length", "Plans can cover at most 31 days")
+    } else {
+        return Ok(());
+    };
+    let mut error = ValidationError::new(code);
+    error.message = Some(Cow::from(message));
+    error.add_param(Cow::from("field"), &"date_to");
+    Err(error)
+}
+
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct PlannedEatenInput {
+    pub eaten_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct PlannedSlotEatenInput {
+    pub username: String,
+    pub date: NaiveDate,
+    pub meal_of_day_id: Option<Uuid>,
+    pub meal_of_day_slug: Option<String>,
+    pub eaten_at: Option<DateTime<Utc>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    fn input(date_from: NaiveDate, date_to: NaiveDate) -> PlannedFromMealInput {
+        PlannedFromMealInput {
+            username: String::from("test"),
+            meal_id: Uuid::nil(),
+            meal_of_day_id: None,
+            meal_of_day_slug: Some(String::from("breakfast")),
+            date_from,
+            date_to,
+        }
+    }
+
+    #[test]
+    fn test_plan_range() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        assert!(input(start, start).validate().is_ok());
+        assert!(input(start, start + Duration::days(30)).validate().is_ok());
+        assert!(input(start, start + Duration::days(31)).validate().is_err());
+        assert!(input(start, start - Duration::days(1)).validate().is_err());
+    }
+}
@@ -0,0 +1,254 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::{prelude::*, Duration};
+use rust_decimal::Decimal;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    diet::{serializer::validate_eaten_at, view::get_meal_of_day_id},
+    error::AppError,
+    extractor::JsonExtractor,
+    food::model::Food,
+    meal_food::model::MealFood,
+    middleware::RequestUser,
+    moderation::model::is_visible,
+    user::model::User,
+    util::{
+        datetime::DateRange,
+        permission::{owner_required, user_privacy_check},
+        query::QueryParams,
+    },
+    AppState,
+};
+
+use super::{
+    model::{PlannedFood, PlannedFoodSerializer, PlannedTotal},
+    serializer::{
+        PlannedEatenInput, PlannedFoodInput, PlannedFromMealInput, PlannedSlotEatenInput,
+    },
+};
+
+const OWNER_MESSAGE: &str = "You are unable to update another users meal plan.";
+
+async fn get_own_plan(
+    state: &AppState,
+    request_user: &RequestUser,
+    id: &Uuid,
+) -> Result<PlannedFood, AppError> {
+    request_user.login_required()?;
+    let planned_food = PlannedFood::get(&state.pool, id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    if request_user.id != planned_food.user_id {
+        return Err(AppError::NotFound);
+    }
+    Ok(planned_food)
+}
+
+// Defaults to the coming week when no range is given.
+pub async fn planned_food_list_view(
+    Query(params): Query<QueryParams>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<Value>, AppError> {
+    request_user.login_required()?;
+    let date_from = params.date_from.unwrap_or_else(|| Utc::now().date_naive());
+    let date_to = params.date_to.unwrap_or(date_from + Duration::days(6));
+    let query =
+        PlannedFoodSerializer::all(&state.pool, &request_user.id, &date_from, &date_to).await?;
+    let response = json!({"count": query.len(), "results": query});
+    Ok(Json(response))
+}
+
+pub async fn planned_food_create_view(
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    JsonExtractor(data): JsonExtractor<PlannedFoodInput>,
+) -> Result<(StatusCode, Json<PlannedFood>), AppError> {
+    let user = owner_required(&state.pool, &request_user, &data.username, OWNER_MESSAGE).await?;
+    let food = Food::get_opt(&state.pool, &data.food_id)
+        .await?
+        .filter(|food| is_visible(&request_user, &food.created_by_id, &food.review_status))
+        .ok_or(AppError::APIBadRequest(format!(
+            "Food {} not found",
+            data.food_id
+        )))?;
+    let meal_of_day_id =
+        get_meal_of_day_id(&state, data.meal_of_day_id, &data.meal_of_day_slug).await?;
+    let quantity = match food.data_measurement.as_str() {
+        "g" => data.quantity * Decimal::new(1, 2),
+        "ml" => data.quantity * Decimal::new(1, 2),
+        "srv" => data.quantity * Decimal::new(1, 0),
+        _ => data.quantity * Decimal::new(1, 0),
+    };
+    let query = PlannedFood::create(
+        &state.pool,
+        user.id,
+        data.date,
+        meal_of_day_id,
+        food.id,
+        quantity,
+        request_user.id,
+    )
+    .await?;
+    Ok((StatusCode::CREATED, Json(query)))
+}
+
+pub async fn planned_food_detail_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<PlannedFood>, AppError> {
+    let query = get_own_plan(&state, &request_user, &id).await?;
+    Ok(Json(query))
+}
+
+// Only the date, slot and quantity of a plan can change; the food stays fixed.
+pub async fn planned_food_update_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    JsonExtractor(data): JsonExtractor<PlannedFoodInput>,
+) -> Result<Json<PlannedFood>, AppError> {
+    let planned_food = get_own_plan(&state, &request_user, &id).await?;
+    owner_required(&state.pool, &request_user, &data.username, OWNER_MESSAGE).await?;
+    if planned_food.food_id != Some(data.food_id) {
+        return Err(AppError::BadRequestMessage(String::from(
+            "The food of a planned entry cannot be changed.",
+        )));
+    }
+    if planned_food.food_log_id.is_some() {
+        return Err(AppError::BadRequestMessage(String::from(
+            "This entry has already been eaten, update the diary entry instead.",
+        )));
+    }
+    let food = Food::get_opt(&state.pool, &data.food_id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    let meal_of_day_id =
+        get_meal_of_day_id(&state, data.meal_of_day_id, &data.meal_of_day_slug).await?;
+    let quantity = match food.data_measurement.as_str() {
+        "g" => data.quantity * Decimal::new(1, 2),
+        "ml" => data.quantity * Decimal::new(1, 2),
+        "srv" => data.quantity * Decimal::new(1, 0),
+        _ => data.quantity * Decimal::new(1, 0),
+    };
+    let query = PlannedFood::update(
+        &state.pool,
+        &planned_food.id,
+        data.date,
+        meal_of_day_id,
+        quantity,
+        request_user.id,
+    )
+    .await?;
+    Ok(Json(query))
+}
+
+pub async fn planned_food_delete_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<PlannedFood>, AppError> {
+    let planned_food = get_own_plan(&state, &request_user, &id).await?;
+    let query = PlannedFood::delete(&state.pool, &planned_food.id).await?;
+    Ok(Json(query))
+}
+
+pub async fn planned_food_create_from_meal_view(
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    JsonExtractor(data): JsonExtractor<PlannedFromMealInput>,
+) -> Result<(StatusCode, Json<Vec<PlannedFood>>), AppError> {
+    let user = owner_required(&state.pool, &request_user, &data.username, OWNER_MESSAGE).await?;
+    let meal_of_day_id =
+        get_meal_of_day_id(&state, data.meal_of_day_id, &data.meal_of_day_slug).await?;
+    let meal_food = MealFood::food_from_meal(&state.pool, &data.meal_id).await?;
+    if meal_food.is_empty() {
+        return Err(AppError::BadRequestMessage(String::from(
+            "This meal has no food to plan.",
+        )));
+    }
+    let dates: Vec<NaiveDate> = DateRange(data.date_from, data.date_to).collect();
+    let query = PlannedFood::create_from_meal_food(
+        &state.pool,
+        user.id,
+        &dates,
+        meal_of_day_id,
+        data.meal_id,
+        meal_food,
+        request_user.id,
+    )
+    .await?;
+    Ok((StatusCode::CREATED, Json(query)))
+}
+
+pub async fn planned_food_eaten_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    JsonExtractor(data): JsonExtractor<PlannedEatenInput>,
+) -> Result<Json<PlannedFood>, AppError> {
+    let planned_food = get_own_plan(&state, &request_user, &id).await?;
+    if planned_food.food_log_id.is_some() {
+        return Err(AppError::BadRequestMessage(String::from(
+            "This entry has already been eaten.",
+        )));
+    }
+    if !validate_eaten_at(planned_food.date, data.eaten_at) {
+        return Err(AppError::BadRequestMessage(String::from(
+            "Eaten at must be within a day of the planned date.",
+        )));
+    }
+    let query = PlannedFood::mark_eaten(
+        &state.pool,
+        &request_user.id,
+        &[planned_food.id],
+        data.eaten_at,
+        &request_user.id,
+    )
+    .await?
+    .pop()
+    .ok_or(AppError::NotFound)?;
+    Ok(Json(query))
+}
+
+pub async fn planned_food_slot_eaten_view(
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    JsonExtractor(data): JsonExtractor<PlannedSlotEatenInput>,
+) -> Result<Json<Vec<PlannedFood>>, AppError> {
+    let user = owner_required(&state.pool, &request_user, &data.username, OWNER_MESSAGE).await?;
+    let meal_of_day_id =
+        get_meal_of_day_id(&state, data.meal_of_day_id, &data.meal_of_day_slug).await?;
+    if !validate_eaten_at(data.date, data.eaten_at) {
+        return Err(AppError::BadRequestMessage(String::from(
+            "Eaten at must be within a day of the planned date.",
+        )));
+    }
+    let ids = PlannedFood::slot_ids(&state.pool, &user.id, &data.date, &meal_of_day_id).await?;
+    let query =
+        PlannedFood::mark_eaten(&state.pool, &user.id, &ids, data.eaten_at, &request_user.id)
+            .await?;
+    Ok(Json(query))
+}
+
+pub async fn planned_food_day_view(
+    Path((username, date)): Path<(String, NaiveDate)>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<Value>, AppError> {
+    let user = User::get_from_username(&state.pool, &username)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    user_privacy_check(&state.pool, &request_user, &user).await?;
+    let results = PlannedFoodSerializer::all(&state.pool, &user.id, &date, &date).await?;
+    let total = PlannedTotal::day(&state.pool, &username, &date).await?;
+    let response = json!({"date": date, "results": results, "total": total});
+    Ok(Json(response))
+}
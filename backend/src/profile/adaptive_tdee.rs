@@ -0,0 +1,258 @@
+use chrono::{prelude::*, Duration};
+use rust_decimal::prelude::*;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::util::trend::{
+    daily_values, exponential_moving_average, ENERGY_PER_KG, WEIGHT_SMOOTHING_FACTOR,
+};
+
+pub const MIN_WINDOW: i64 = 14;
+pub const MAX_WINDOW: i64 = 28;
+
+const MIN_INTAKE_DAYS: i64 = 7;
+const MIN_WEIGHT_ENTRIES: i64 = 2;
+
+const HIGH_INTAKE_COVERAGE: Decimal = dec!(0.8);
+const MEDIUM_INTAKE_COVERAGE: Decimal = dec!(0.5);
+const HIGH_WEIGHT_ENTRIES: i64 = 8;
+const MEDIUM_WEIGHT_ENTRIES: i64 = 4;
+
+#[derive(Debug, FromRow)]
+pub struct DailyIntake {
+    pub date: NaiveDate,
+    pub energy: Decimal,
+}
+
+#[derive(Debug, FromRow)]
+pub struct DailyWeight {
+    pub date: NaiveDate,
+    pub weight_kg: Decimal,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AdaptiveTdee {
+    pub date_from: NaiveDate,
+    pub date_to: NaiveDate,
+    pub window: i64,
+    pub intake_day_count: i64,
+    pub weight_entry_count: i64,
+    pub average_intake: Option<Decimal>,
+    pub trend_weight_start: Option<Decimal>,
+    pub trend_weight_end: Option<Decimal>,
+    pub trend_weight_change: Option<Decimal>,
+    pub tdee: Option<Decimal>,
+    pub target_calories: Option<Decimal>,
+    pub confidence: String,
+}
+
+impl AdaptiveTdee {
+    pub async fn get(
+        pool: &PgPool,
+        user_id: &Uuid,
+        date: NaiveDate,
+        window: Option<i64>,
+    ) -> Result<Self, sqlx::Error> {
+        let window = window.unwrap_or(MAX_WINDOW).clamp(MIN_WINDOW, MAX_WINDOW);
+        let date_from = date - Duration::days(window - 1);
+        let intake: Vec<DailyIntake> = sqlx::query_as(
+            "
+            SELECT
+                t1.date,
+                SUM(t1.quantity * COALESCE(t2.energy, t1.energy)) AS energy
+            FROM
+                food_log t1
+                LEFT JOIN food t2 ON t2.id = t1.food_id
+            WHERE
+                t1.user_id = $1
+                AND t1.date BETWEEN $2 AND $3
+            GROUP BY
+                t1.date
+            ORDER BY
+                t1.date
+            ",
+        )
+        .bind(user_id)
+        .bind(date_from)
+        .bind(date)
+        .fetch_all(pool)
+        .await?;
+        let weights: Vec<DailyWeight> = sqlx::query_as(
+            "
+            SELECT
+                date,
+                weight_kg
+            FROM
+                progress
+            WHERE
+                user_id = $1
+                AND date BETWEEN $2 AND $3
+                AND weight_kg IS NOT NULL
+            ORDER BY
+                date
+            ",
+        )
+        .bind(user_id)
+        .bind(date_from)
+        .bind(date)
+        .fetch_all(pool)
+        .await?;
+        Ok(Self::calculate(date_from, date, &intake, &weights))
+    }
+
+    pub fn calculate(
+        date_from: NaiveDate,
+        date_to: NaiveDate,
+        intake: &[DailyIntake],
+        weights: &[DailyWeight],
+    ) -> Self {
+        let window = (date_to - date_from).num_days() + 1;
+        let intake_day_count = intake.len() as i64;
+        let weight_entry_count = weights.len() as i64;
+
+        let average_intake = if intake.is_empty() {
+            None
+        } else {
+            let total: Decimal = intake.iter().map(|day| day.energy).sum();
+            Some(total / Decimal::from(intake_day_count))
+        };
+
+        let entries: Vec<(NaiveDate, Decimal)> =
+            weights.iter().map(|w| (w.date, w.weight_kg)).collect();
+        let trend = exponential_moving_average(&daily_values(&entries), WEIGHT_SMOOTHING_FACTOR);
+        let trend_weight_start = trend.first().copied();
+        let trend_weight_end = trend.last().copied();
+        let trend_weight_change = match (trend_weight_start, trend_weight_end) {
+            (Some(start), Some(end)) => Some(end - start),
+            _ => None,
+        };
+        let trend_days = match (weights.first(), weights.last()) {
+            (Some(first), Some(last)) => (last.date - first.date).num_days(),
+            _ => 0,
+        };
+
+        let sufficient = intake_day_count >= MIN_INTAKE_DAYS
+            && weight_entry_count >= MIN_WEIGHT_ENTRIES
+            && trend_days > 0;
+        let tdee = match (sufficient, average_intake, trend_weight_change) {
+            (true, Some(average_intake), Some(change)) => {
+                let daily_balance = change * ENERGY_PER_KG / Decimal::from(trend_days);
+                Some((average_intake - daily_balance).round_dp(0))
+            }
+            _ => None,
+        };
+        let confidence = if tdee.is_none() {
+            "insufficient"
+        } else {
+            let coverage = Decimal::from(intake_day_count) / Decimal::from(window);
+            if coverage >= HIGH_INTAKE_COVERAGE && weight_entry_count >= HIGH_WEIGHT_ENTRIES {
+                "high"
+            } else if coverage >= MEDIUM_INTAKE_COVERAGE
+                && weight_entry_count >= MEDIUM_WEIGHT_ENTRIES
+            {
+                "medium"
+            } else {
+                "low"
+            }
+        };
+
+        Self {
+            date_from,
+            date_to,
+            window,
+            intake_day_count,
+            weight_entry_count,
+            average_intake: average_intake.map(|value| value.round_dp(0)),
+            trend_weight_start: trend_weight_start.map(|value| value.round_dp(2)),
+            trend_weight_end: trend_weight_end.map(|value| value.round_dp(2)),
+            trend_weight_change: trend_weight_change.map(|value| value.round_dp(2)),
+            tdee,
+            target_calories: None,
+            confidence: confidence.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, 11, day).unwrap()
+    }
+
+    #[test]
+    fn test_stable_weight_matches_intake() {
+        let intake: Vec<DailyIntake> = (1..=28)
+            .map(|day| DailyIntake {
+                date: date(day),
+                energy: dec!(2500),
+            })
+            .collect();
+        let weights: Vec<DailyWeight> = (1..=28)
+            .step_by(2)
+            .map(|day| DailyWeight {
+                date: date(day),
+                weight_kg: dec!(80),
+            })
+            .collect();
+        let result = AdaptiveTdee::calculate(date(1), date(28), &intake, &weights);
+        assert_eq!(result.tdee, Some(dec!(2500)));
+        assert_eq!(result.confidence, "high");
+    }
+
+    #[test]
+    fn test_weight_loss_raises_estimate() {
+        let intake: Vec<DailyIntake> = (1..=28)
+            .map(|day| DailyIntake {
+                date: date(day),
+                energy: dec!(2000),
+            })
+            .collect();
+        let weights: Vec<DailyWeight> = (1..=28)
+            .map(|day| DailyWeight {
+                date: date(day),
+                weight_kg: dec!(80) - Decimal::from(day) * dec!(0.05),
+            })
+            .collect();
+        let result = AdaptiveTdee::calculate(date(1), date(28), &intake, &weights);
+        assert!(result.tdee.unwrap() > dec!(2000));
+    }
+
+    #[test]
+    fn test_weigh_in_frequency_does_not_change_estimate() {
+        let intake: Vec<DailyIntake> = (1..=28)
+            .map(|day| DailyIntake {
+                date: date(day),
+                energy: dec!(2000),
+            })
+            .collect();
+        let weight = |day: u32| DailyWeight {
+            date: date(day),
+            weight_kg: dec!(80) - Decimal::from(day) * dec!(0.05),
+        };
+        let daily: Vec<DailyWeight> = (1..=28).map(weight).collect();
+        let weekly: Vec<DailyWeight> = (1..=28).step_by(7).chain([28]).map(weight).collect();
+        let daily = AdaptiveTdee::calculate(date(1), date(28), &intake, &daily);
+        let weekly = AdaptiveTdee::calculate(date(1), date(28), &intake, &weekly);
+        assert_eq!(weekly.trend_weight_change, daily.trend_weight_change);
+        assert_eq!(weekly.tdee, daily.tdee);
+    }
+
+    #[test]
+    fn test_sparse_logging_is_insufficient() {
+        let intake = vec![DailyIntake {
+            date: date(1),
+            energy: dec!(2000),
+        }];
+        let weights = vec![DailyWeight {
+            date: date(1),
+            weight_kg: dec!(80),
+        }];
+        let result = AdaptiveTdee::calculate(date(1), date(28), &intake, &weights);
+        assert_eq!(result.tdee, None);
+        assert_eq!(result.confidence, "insufficient");
+    }
+}
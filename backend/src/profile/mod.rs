@@ -1,3 +1,4 @@
+pub mod adaptive_tdee;
 pub mod model;
 pub mod router;
 pub mod serializer;
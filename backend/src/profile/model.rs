@@ -8,7 +8,10 @@ use uuid::Uuid;
 
 use crate::util::query::QueryParams;
 
-use super::serializer::{ProfileInput, ProfileUpdateInput};
+use super::{
+    adaptive_tdee::AdaptiveTdee,
+    serializer::{ProfileInput, ProfileUpdateInput},
+};
 
 #[derive(Debug, Serialize, FromRow)]
 pub struct Profile {
@@ -330,6 +333,7 @@ pub struct ProfileMetric {
     pub latest_weight_id: Option<Uuid>,
     pub latest_weight_date: Option<NaiveDate>,
     pub latest_weight: Option<Decimal>,
+    pub adaptive_tdee: Option<AdaptiveTdee>,
 }
 
 impl ProfileSerializer {
@@ -505,6 +509,16 @@ impl ProfileSerializer {
             latest_weight_id: self.latest_weight_id,
             latest_weight_date: self.latest_weight_date,
             latest_weight: self.latest_weight,
+            adaptive_tdee: None,
         }
     }
+
+    pub fn to_metric_with_adaptive_tdee(&self, mut adaptive_tdee: AdaptiveTdee) -> ProfileMetric {
+        adaptive_tdee.target_calories = adaptive_tdee
+            .tdee
+            .map(|tdee| (tdee * self.get_fitness_goal()).round_dp(0));
+        let mut metric = self.into_metric();
+        metric.adaptive_tdee = Some(adaptive_tdee);
+        metric
+    }
 }
@@ -8,8 +8,9 @@ use crate::AppState;
 
 use super::view::{
     admin_profile_delete_view, admin_profile_detail_view, admin_profile_update_view,
-    profile_create_view, profile_date_detail_view, profile_delete_id_range_view,
-    profile_delete_view, profile_detail_view, profile_list_view, profile_update_view,
+    profile_adaptive_tdee_view, profile_create_view, profile_date_detail_view,
+    profile_delete_id_range_view, profile_delete_view, profile_detail_view, profile_list_view,
+    profile_update_view,
 };
 
 pub fn profile_router() -> Router<Arc<AppState>> {
@@ -18,6 +19,10 @@ pub fn profile_router() -> Router<Arc<AppState>> {
         .route("/", post(profile_create_view))
         .route("/:username", get(profile_detail_view))
         .route("/:username/:date", get(profile_date_detail_view))
+        .route(
+            "/:username/:date/adaptive-tdee",
+            get(profile_adaptive_tdee_view),
+        )
         .route("/:username", put(profile_update_view))
         .route("/:username", delete(profile_delete_view))
         .route("/delete-id-range", delete(profile_delete_id_range_view))
@@ -24,3 +24,8 @@ pub struct ProfileUpdateInput {
     pub fitness_goal: String,
     pub activity_level: String,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct AdaptiveTdeeParams {
+    pub window: Option<i64>,
+}
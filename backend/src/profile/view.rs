@@ -18,8 +18,9 @@ use crate::{
 };
 
 use super::{
+    adaptive_tdee::AdaptiveTdee,
     model::{Profile, ProfileMetric, ProfileSerializer},
-    serializer::{ProfileInput, ProfileUpdateInput},
+    serializer::{AdaptiveTdeeParams, ProfileInput, ProfileUpdateInput},
 };
 
 pub async fn profile_list_view(
@@ -113,6 +114,23 @@ pub async fn profile_date_detail_view(
     Ok(Json(query.into_metric()))
 }
 
+// The adaptive estimate reads weeks of diary and weigh-ins, so it has its own
+// endpoint rather than being worked out on every profile request.
+pub async fn profile_adaptive_tdee_view(
+    Path((username, date)): Path<(String, NaiveDate)>,
+    Query(params): Query<AdaptiveTdeeParams>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ProfileMetric>, AppError> {
+    let user = User::get_from_username(&state.pool, &username)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    let query = ProfileSerializer::get(&state.pool, &user.id, Some(date))
+        .await?
+        .ok_or(AppError::NotFound)?;
+    let adaptive_tdee = AdaptiveTdee::get(&state.pool, &user.id, date, params.window).await?;
+    Ok(Json(query.to_metric_with_adaptive_tdee(adaptive_tdee)))
+}
+
 pub async fn profile_delete_id_range_view(
     State(state): State<Arc<AppState>>,
     Extension(request_user): Extension<RequestUser>,
@@ -0,0 +1,143 @@
+use rust_decimal::prelude::*;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::model::Progress;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BodyComposition {
+    pub body_fat_pct_estimate: Option<Decimal>,
+    pub lean_mass_kg: Option<Decimal>,
+    pub fat_mass_kg: Option<Decimal>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProgressDetail {
+    #[serde(flatten)]
+    pub progress: Progress,
+    #[serde(flatten)]
+    pub body_composition: BodyComposition,
+}
+
+// U.S. Navy circumference method, all measurements in centimetres.
+pub fn navy_body_fat_pct(
+    sex: &str,
+    height_cm: Decimal,
+    waist_cm: Decimal,
+    neck_cm: Decimal,
+    hips_cm: Option<Decimal>,
+) -> Option<Decimal> {
+    let height = height_cm.to_f64()?;
+    let waist = waist_cm.to_f64()?;
+    let neck = neck_cm.to_f64()?;
+    let density = match sex {
+        "F" => {
+            let hips = hips_cm?.to_f64()?;
+            let circumference = waist + hips - neck;
+            if circumference <= 0.0 {
+                return None;
+            }
+            1.29579 - 0.35004 * circumference.log10() + 0.22100 * height.log10()
+        }
+        _ => {
+            let circumference = waist - neck;
+            if circumference <= 0.0 {
+                return None;
+            }
+            1.0324 - 0.19077 * circumference.log10() + 0.15456 * height.log10()
+        }
+    };
+    let body_fat = 495.0 / density - 450.0;
+    if !(0.0..100.0).contains(&body_fat) {
+        return None;
+    }
+    Decimal::from_f64(body_fat).map(|value| value.round_dp(2))
+}
+
+impl BodyComposition {
+    pub fn from_progress(progress: &Progress, sex: Option<&str>, height: Option<Decimal>) -> Self {
+        let body_fat_pct_estimate = match (sex, height, progress.waist_cm, progress.neck_cm) {
+            (Some(sex), Some(height), Some(waist), Some(neck)) => {
+                navy_body_fat_pct(sex, height, waist, neck, progress.hips_cm)
+            }
+            _ => None,
+        };
+        // A recorded body-fat percentage takes precedence over the estimate.
+        let body_fat_pct = progress.body_fat_pct.or(body_fat_pct_estimate);
+        let fat_mass_kg = match (progress.weight_kg, body_fat_pct) {
+            (Some(weight), Some(body_fat_pct)) => {
+                Some((weight * body_fat_pct / dec!(100)).round_dp(2))
+            }
+            _ => None,
+        };
+        let lean_mass_kg = match (progress.weight_kg, fat_mass_kg) {
+            (Some(weight), Some(fat_mass)) => Some(weight - fat_mass),
+            _ => None,
+        };
+        Self {
+            body_fat_pct_estimate,
+            lean_mass_kg,
+            fat_mass_kg,
+        }
+    }
+}
+
+impl ProgressDetail {
+    pub async fn build(
+        pool: &PgPool,
+        user_id: Uuid,
+        progress: Vec<Progress>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let profile: Option<(String, Decimal)> =
+            sqlx::query_as("SELECT sex, height FROM user_profile WHERE user_id = $1")
+                .bind(user_id)
+                .fetch_optional(pool)
+                .await?;
+        let sex = profile.as_ref().map(|(sex, _)| sex.as_str());
+        let height = profile.as_ref().map(|(_, height)| *height);
+        let result = progress
+            .into_iter()
+            .map(|progress| Self::new(progress, sex, height))
+            .collect();
+        Ok(result)
+    }
+
+    pub fn new(progress: Progress, sex: Option<&str>, height: Option<Decimal>) -> Self {
+        let body_composition = BodyComposition::from_progress(&progress, sex, height);
+        Self {
+            progress,
+            body_composition,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_navy_body_fat_male() {
+        let result = navy_body_fat_pct("M", dec!(180), dec!(85), dec!(38), None).unwrap();
+        assert!(result > dec!(15) && result < dec!(20));
+    }
+
+    #[test]
+    fn test_navy_body_fat_female_requires_hips() {
+        assert_eq!(
+            navy_body_fat_pct("F", dec!(165), dec!(70), dec!(32), None),
+            None
+        );
+        let result = navy_body_fat_pct("F", dec!(165), dec!(70), dec!(32), Some(dec!(98))).unwrap();
+        assert!(result > dec!(20) && result < dec!(35));
+    }
+
+    #[test]
+    fn test_navy_body_fat_invalid_measurements() {
+        assert_eq!(
+            navy_body_fat_pct("M", dec!(180), dec!(30), dec!(38), None),
+            None
+        );
+    }
+}
@@ -1,4 +1,6 @@
+pub mod body_composition;
 pub mod model;
 pub mod router;
 pub mod serializer;
+pub mod trend;
 pub mod view;
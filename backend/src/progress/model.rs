@@ -7,6 +7,11 @@ use uuid::Uuid;
 
 use crate::util::query::QueryParams;
 
+use super::{
+    body_composition::{BodyComposition, ProgressDetail},
+    serializer::{ProgressInput, ProgressUpdateInput},
+};
+
 #[derive(Debug, Deserialize, Serialize, FromRow)]
 pub struct Progress {
     pub id: Uuid,
@@ -15,6 +20,13 @@ pub struct Progress {
     pub weight_kg: Option<Decimal>,
     pub energy_burnt: Option<i32>,
     pub notes: Option<String>,
+    pub waist_cm: Option<Decimal>,
+    pub hips_cm: Option<Decimal>,
+    pub chest_cm: Option<Decimal>,
+    pub neck_cm: Option<Decimal>,
+    pub arms_cm: Option<Decimal>,
+    pub thighs_cm: Option<Decimal>,
+    pub body_fat_pct: Option<Decimal>,
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
     pub created_by_id: Uuid,
@@ -47,11 +59,7 @@ impl Progress {
     }
     pub async fn create(
         pool: &PgPool,
-        user_id: Uuid,
-        date: NaiveDate,
-        weight: Option<Decimal>,
-        energy_burnt: Option<i32>,
-        notes: Option<String>,
+        data: ProgressInput,
         created_by_id: Uuid,
     ) -> Result<Self, sqlx::Error> {
         let query = sqlx::query_as(
@@ -63,19 +71,33 @@ impl Progress {
                     weight_kg,
                     energy_burnt,
                     notes,
+                    waist_cm,
+                    hips_cm,
+                    chest_cm,
+                    neck_cm,
+                    arms_cm,
+                    thighs_cm,
+                    body_fat_pct,
                     created_by_id
                 )
             VALUES
-                ($1, $2, $3, $4, $5, $6)
+                ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
             RETURNING
                 *
             ",
         )
-        .bind(user_id)
-        .bind(date)
-        .bind(weight)
-        .bind(energy_burnt)
-        .bind(notes)
+        .bind(data.user_id)
+        .bind(data.date)
+        .bind(data.weight_kg)
+        .bind(data.energy_burnt)
+        .bind(data.notes)
+        .bind(data.waist_cm)
+        .bind(data.hips_cm)
+        .bind(data.chest_cm)
+        .bind(data.neck_cm)
+        .bind(data.arms_cm)
+        .bind(data.thighs_cm)
+        .bind(data.body_fat_pct)
         .bind(created_by_id)
         .fetch_one(pool)
         .await?;
@@ -128,10 +150,7 @@ impl Progress {
     pub async fn update(
         pool: &PgPool,
         id: Uuid,
-        date: NaiveDate,
-        weight: Option<Decimal>,
-        energy_burnt: Option<i32>,
-        notes: Option<String>,
+        data: ProgressUpdateInput,
         updated_by_id: Uuid,
     ) -> Result<Self, sqlx::Error> {
         let query = sqlx::query_as(
@@ -142,18 +161,32 @@ impl Progress {
                 weight_kg = $2,
                 energy_burnt = $3,
                 notes = $4,
-                updated_at = $5,
-                updated_by_id = $6
+                waist_cm = $5,
+                hips_cm = $6,
+                chest_cm = $7,
+                neck_cm = $8,
+                arms_cm = $9,
+                thighs_cm = $10,
+                body_fat_pct = $11,
+                updated_at = $12,
+                updated_by_id = $13
             WHERE
-                id = $7
+                id = $14
             RETURNING
                 *
             ",
         )
-        .bind(date)
-        .bind(weight)
-        .bind(energy_burnt)
-        .bind(notes)
+        .bind(data.date)
+        .bind(data.weight_kg)
+        .bind(data.energy_burnt)
+        .bind(data.notes)
+        .bind(data.waist_cm)
+        .bind(data.hips_cm)
+        .bind(data.chest_cm)
+        .bind(data.neck_cm)
+        .bind(data.arms_cm)
+        .bind(data.thighs_cm)
+        .bind(data.body_fat_pct)
         .bind(Utc::now())
         .bind(updated_by_id)
         .bind(id)
@@ -170,9 +203,48 @@ impl Progress {
     }
     pub async fn stream(pool: &PgPool, username: &str) -> Result<Vec<Self>, sqlx::Error> {
         let mut stream = Vec::new();
-        let mut rows = sqlx::query_as("SELECT * FROM progress WHERE username = $1")
-            .bind(username)
-            .fetch(pool);
+        let mut rows = sqlx::query_as(
+            "
+            SELECT
+                t1.*
+            FROM
+                progress t1
+                LEFT JOIN users_user t2 ON t2.id = t1.user_id
+            WHERE
+                t2.username = $1
+            ORDER BY
+                t1.date DESC
+            ",
+        )
+        .bind(username)
+        .fetch(pool);
+        while let Some(row) = rows.try_next().await? {
+            stream.push(row);
+        }
+        Ok(stream)
+    }
+    pub async fn month(
+        pool: &PgPool,
+        user_id: Uuid,
+        date: NaiveDate,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let mut stream = Vec::new();
+        let mut rows = sqlx::query_as(
+            "
+            SELECT
+                t1.*
+            FROM
+                progress t1
+            WHERE
+                t1.user_id = $1
+                AND DATE_TRUNC('month', t1.date) = DATE_TRUNC('month', $2::date)
+            ORDER BY
+                t1.date
+            ",
+        )
+        .bind(user_id)
+        .bind(date)
+        .fetch(pool);
         while let Some(row) = rows.try_next().await? {
             stream.push(row);
         }
@@ -283,11 +355,26 @@ pub struct ProgressAggregation {
     pub latest_weight_id: Option<Uuid>,
     pub latest_weight_date: Option<NaiveDate>,
     pub latest_weight: Option<Decimal>,
+    pub waist_cm: Option<Decimal>,
+    pub hips_cm: Option<Decimal>,
+    pub chest_cm: Option<Decimal>,
+    pub neck_cm: Option<Decimal>,
+    pub arms_cm: Option<Decimal>,
+    pub thighs_cm: Option<Decimal>,
+    pub body_fat_pct: Option<Decimal>,
+    pub week_avg_waist_cm: Option<Decimal>,
+    pub month_avg_waist_cm: Option<Decimal>,
+    pub week_avg_body_fat_pct: Option<Decimal>,
+    pub month_avg_body_fat_pct: Option<Decimal>,
+    #[sqlx(skip)]
+    pub body_composition: BodyComposition,
+    #[sqlx(skip)]
+    pub body_composition_series: Vec<ProgressDetail>,
 }
 
 impl ProgressAggregation {
     pub async fn get(pool: &PgPool, user_id: Uuid, date: NaiveDate) -> Result<Self, sqlx::Error> {
-        let query = sqlx::query_as(
+        let mut query: Self = sqlx::query_as(
             "
             WITH
             week_avg AS (
@@ -295,6 +382,8 @@ impl ProgressAggregation {
                     t1.user_id,
                     AVG(t1.weight_kg) AS week_avg_weight,
                     AVG(t1.energy_burnt) AS week_avg_energy_burnt,
+                    AVG(t1.waist_cm) AS week_avg_waist_cm,
+                    AVG(t1.body_fat_pct) AS week_avg_body_fat_pct,
                     SUM(t1.energy_burnt) AS week_total_energy_burnt
                 FROM
                     progress t1
@@ -310,6 +399,8 @@ impl ProgressAggregation {
                     t1.user_id,
                     AVG(t1.weight_kg) AS month_avg_weight,
                     AVG(t1.energy_burnt) AS month_avg_energy_burnt,
+                    AVG(t1.waist_cm) AS month_avg_waist_cm,
+                    AVG(t1.body_fat_pct) AS month_avg_body_fat_pct,
                     SUM(t1.energy_burnt) AS month_total_energy_burnt
                 FROM
                     progress t1
@@ -344,10 +435,21 @@ impl ProgressAggregation {
             t5.id as progress_id,
             t5.weight_kg as weight,
             t5.energy_burnt::decimal,
+            t5.waist_cm,
+            t5.hips_cm,
+            t5.chest_cm,
+            t5.neck_cm,
+            t5.arms_cm,
+            t5.thighs_cm,
+            t5.body_fat_pct,
             t2.week_avg_weight,
             t2.week_avg_energy_burnt,
+            t2.week_avg_waist_cm,
+            t2.week_avg_body_fat_pct,
             t3.month_avg_weight,
             t3.month_avg_energy_burnt,
+            t3.month_avg_waist_cm,
+            t3.month_avg_body_fat_pct,
             t4.id AS latest_weight_id,
             t4.date AS latest_weight_date,
             t4.weight_kg AS latest_weight
@@ -366,6 +468,12 @@ impl ProgressAggregation {
         .bind(date)
         .fetch_one(pool)
         .await?;
+        let month = Progress::month(pool, user_id, date).await?;
+        let series = ProgressDetail::build(pool, user_id, month).await?;
+        if let Some(detail) = series.iter().find(|detail| detail.progress.date == date) {
+            query.body_composition = detail.body_composition.clone();
+        }
+        query.body_composition_series = series;
         Ok(query)
     }
 }
@@ -9,7 +9,7 @@ use crate::AppState;
 use super::view::{
     progress_aggregation_detail_view, progress_create_view, progress_delete_date_range_view,
     progress_delete_id_range_view, progress_delete_view, progress_detail_view, progress_list_view,
-    progress_update_view, user_progress_detail_latest_view,
+    progress_trend_detail_view, progress_update_view, user_progress_detail_latest_view,
     user_progress_detail_latest_weight_view, user_progress_detail_view, user_progress_list_view,
 };
 
@@ -39,4 +39,5 @@ pub fn progress_router() -> Router<Arc<AppState>> {
             "/:username/:date/aggregation",
             get(progress_aggregation_detail_view),
         )
+        .route("/:username/:date/trend", get(progress_trend_detail_view))
 }
@@ -4,6 +4,11 @@ use serde::Deserialize;
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::util::{
+    query::empty_string_as_none,
+    validator::{validate_body_fat_pct, validate_measurement_cm},
+};
+
 #[derive(Debug, Deserialize, Validate)]
 pub struct ProgressInput {
     pub user_id: Uuid,
@@ -11,6 +16,41 @@ pub struct ProgressInput {
     pub weight_kg: Option<Decimal>,
     pub energy_burnt: Option<i32>,
     pub notes: Option<String>,
+    #[validate(custom(
+        function = "validate_measurement_cm",
+        message = "Waist must be between 0 and 999.99cm"
+    ))]
+    pub waist_cm: Option<Decimal>,
+    #[validate(custom(
+        function = "validate_measurement_cm",
+        message = "Hips must be between 0 and 999.99cm"
+    ))]
+    pub hips_cm: Option<Decimal>,
+    #[validate(custom(
+        function = "validate_measurement_cm",
+        message = "Chest must be between 0 and 999.99cm"
+    ))]
+    pub chest_cm: Option<Decimal>,
+    #[validate(custom(
+        function = "validate_measurement_cm",
+        message = "Neck must be between 0 and 999.99cm"
+    ))]
+    pub neck_cm: Option<Decimal>,
+    #[validate(custom(
+        function = "validate_measurement_cm",
+        message = "Arms must be between 0 and 999.99cm"
+    ))]
+    pub arms_cm: Option<Decimal>,
+    #[validate(custom(
+        function = "validate_measurement_cm",
+        message = "Thighs must be between 0 and 999.99cm"
+    ))]
+    pub thighs_cm: Option<Decimal>,
+    #[validate(custom(
+        function = "validate_body_fat_pct",
+        message = "Body fat must be at least 0 and below 100"
+    ))]
+    pub body_fat_pct: Option<Decimal>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -19,4 +59,49 @@ pub struct ProgressUpdateInput {
     pub weight_kg: Option<Decimal>,
     pub energy_burnt: Option<i32>,
     pub notes: Option<String>,
+    #[validate(custom(
+        function = "validate_measurement_cm",
+        message = "Waist must be between 0 and 999.99cm"
+    ))]
+    pub waist_cm: Option<Decimal>,
+    #[validate(custom(
+        function = "validate_measurement_cm",
+        message = "Hips must be between 0 and 999.99cm"
+    ))]
+    pub hips_cm: Option<Decimal>,
+    #[validate(custom(
+        function = "validate_measurement_cm",
+        message = "Chest must be between 0 and 999.99cm"
+    ))]
+    pub chest_cm: Option<Decimal>,
+    #[validate(custom(
+        function = "validate_measurement_cm",
+        message = "Neck must be between 0 and 999.99cm"
+    ))]
+    pub neck_cm: Option<Decimal>,
+    #[validate(custom(
+        function = "validate_measurement_cm",
+        message = "Arms must be between 0 and 999.99cm"
+    ))]
+    pub arms_cm: Option<Decimal>,
+    #[validate(custom(
+        function = "validate_measurement_cm",
+        message = "Thighs must be between 0 and 999.99cm"
+    ))]
+    pub thighs_cm: Option<Decimal>,
+    #[validate(custom(
+        function = "validate_body_fat_pct",
+        message = "Body fat must be at least 0 and below 100"
+    ))]
+    pub body_fat_pct: Option<Decimal>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProgressTrendParams {
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    pub date_from: Option<NaiveDate>,
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    pub goal_weight: Option<Decimal>,
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    pub goal_date: Option<NaiveDate>,
 }
@@ -0,0 +1,258 @@
+use chrono::{prelude::*, Duration};
+use rust_decimal::prelude::*;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    profile::adaptive_tdee::DailyWeight,
+    util::trend::{
+        daily_values, exponential_moving_average, ENERGY_PER_KG, WEIGHT_SMOOTHING_FACTOR,
+    },
+};
+
+pub const DEFAULT_TREND_DAYS: i64 = 90;
+
+// Number of trailing days used to derive the current rate of change.
+const RATE_WINDOW_DAYS: i64 = 28;
+
+#[derive(Debug, Serialize)]
+pub struct ProgressTrendPoint {
+    pub date: NaiveDate,
+    pub weight_kg: Decimal,
+    pub trend_weight_kg: Decimal,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProgressGoalProjection {
+    pub goal_weight_kg: Decimal,
+    pub goal_date: Option<NaiveDate>,
+    // Without any weigh-ins there is nothing to project from, so these are all empty.
+    pub remaining_kg: Option<Decimal>,
+    pub projected_goal_date: Option<NaiveDate>,
+    pub required_weekly_rate_kg: Option<Decimal>,
+    // Negative values are a deficit, positive values a surplus (kcal per day).
+    pub required_daily_energy_balance: Option<Decimal>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProgressTrend {
+    pub user_id: Uuid,
+    pub date_from: NaiveDate,
+    pub date_to: NaiveDate,
+    pub latest_trend_weight_kg: Option<Decimal>,
+    pub weekly_rate_kg: Option<Decimal>,
+    pub goal: Option<ProgressGoalProjection>,
+    pub series: Vec<ProgressTrendPoint>,
+}
+
+impl ProgressTrend {
+    pub async fn get(
+        pool: &PgPool,
+        user_id: Uuid,
+        date_from: NaiveDate,
+        date_to: NaiveDate,
+        goal_weight: Option<Decimal>,
+        goal_date: Option<NaiveDate>,
+    ) -> Result<Self, sqlx::Error> {
+        let weights: Vec<DailyWeight> = sqlx::query_as(
+            "
+            SELECT
+                date,
+                weight_kg
+            FROM
+                progress
+            WHERE
+                user_id = $1
+                AND date BETWEEN $2 AND $3
+                AND weight_kg IS NOT NULL
+            ORDER BY
+                date
+            ",
+        )
+        .bind(user_id)
+        .bind(date_from)
+        .bind(date_to)
+        .fetch_all(pool)
+        .await?;
+        let mut trend = Self::build(user_id, date_from, date_to, &weights);
+        if let Some(goal_weight) = goal_weight {
+            trend.goal = Some(trend.project(goal_weight, goal_date));
+        }
+        Ok(trend)
+    }
+
+    pub fn build(
+        user_id: Uuid,
+        date_from: NaiveDate,
+        date_to: NaiveDate,
+        weights: &[DailyWeight],
+    ) -> Self {
+        // Smoothed per day, as for the adaptive TDEE, then read off at each weigh-in.
+        let entries: Vec<(NaiveDate, Decimal)> =
+            weights.iter().map(|w| (w.date, w.weight_kg)).collect();
+        let smoothed = exponential_moving_average(&daily_values(&entries), WEIGHT_SMOOTHING_FACTOR);
+        let series: Vec<ProgressTrendPoint> = weights
+            .iter()
+            .filter_map(|weight| {
+                let day = (weight.date - weights.first()?.date).num_days();
+                Some(ProgressTrendPoint {
+                    date: weight.date,
+                    weight_kg: weight.weight_kg,
+                    trend_weight_kg: smoothed.get(usize::try_from(day).ok()?)?.round_dp(2),
+                })
+            })
+            .collect();
+
+        let weekly_rate_kg = series.last().and_then(|last| {
+            let since = last.date - Duration::days(RATE_WINDOW_DAYS);
+            let first = series.iter().find(|point| point.date >= since)?;
+            let days = (last.date - first.date).num_days();
+            if days == 0 {
+                return None;
+            }
+            let change = last.trend_weight_kg - first.trend_weight_kg;
+            Some((change / Decimal::from(days) * dec!(7)).round_dp(2))
+        });
+
+        Self {
+            user_id,
+            date_from,
+            date_to,
+            latest_trend_weight_kg: series.last().map(|point| point.trend_weight_kg),
+            weekly_rate_kg,
+            goal: None,
+            series,
+        }
+    }
+
+    pub fn project(
+        &self,
+        goal_weight_kg: Decimal,
+        goal_date: Option<NaiveDate>,
+    ) -> ProgressGoalProjection {
+        let Some(last) = self.series.last() else {
+            return ProgressGoalProjection {
+                goal_weight_kg,
+                goal_date,
+                remaining_kg: None,
+                projected_goal_date: None,
+                required_weekly_rate_kg: None,
+                required_daily_energy_balance: None,
+            };
+        };
+        let remaining_kg = goal_weight_kg - last.trend_weight_kg;
+
+        // Only project when the current trend moves towards the goal, counting from the
+        // last weigh-in rather than the end of the requested range.
+        let projected_goal_date = match self.weekly_rate_kg {
+            _ if remaining_kg.is_zero() => Some(last.date),
+            Some(rate)
+                if !rate.is_zero()
+                    && rate.is_sign_negative() == remaining_kg.is_sign_negative() =>
+            {
+                let weeks = remaining_kg / rate;
+                let days = (weeks * dec!(7)).ceil().to_i64().unwrap_or_default();
+                Some(last.date + Duration::days(days))
+            }
+            _ => None,
+        };
+
+        let days_until_goal = goal_date
+            .map(|goal_date| (goal_date - last.date).num_days())
+            .filter(|days| *days > 0);
+        let required_weekly_rate_kg =
+            days_until_goal.map(|days| (remaining_kg / Decimal::from(days) * dec!(7)).round_dp(2));
+        let required_daily_energy_balance = days_until_goal
+            .map(|days| (remaining_kg * ENERGY_PER_KG / Decimal::from(days)).round_dp(0));
+
+        ProgressGoalProjection {
+            goal_weight_kg,
+            goal_date,
+            remaining_kg: Some(remaining_kg.round_dp(2)),
+            projected_goal_date,
+            required_weekly_rate_kg,
+            required_daily_energy_balance,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, 11, day).unwrap()
+    }
+
+    fn losing_trend() -> ProgressTrend {
+        let weights: Vec<DailyWeight> = (1..=29)
+            .map(|day| DailyWeight {
+                date: date(day),
+                weight_kg: dec!(90) - Decimal::from(day - 1) * dec!(0.1),
+            })
+            .collect();
+        ProgressTrend::build(Uuid::nil(), date(1), date(29), &weights)
+    }
+
+    #[test]
+    fn test_weekly_rate_is_negative_when_losing() {
+        let trend = losing_trend();
+        assert_eq!(trend.series.len(), 29);
+        assert!(trend.weekly_rate_kg.unwrap() < dec!(0));
+    }
+
+    #[test]
+    fn test_projection_towards_goal() {
+        let trend = losing_trend();
+        let goal = trend.project(dec!(80), Some(date(29) + Duration::days(70)));
+        assert!(goal.projected_goal_date.unwrap() > date(29));
+        assert!(goal.required_daily_energy_balance.unwrap() < dec!(0));
+    }
+
+    #[test]
+    fn test_weigh_in_frequency_does_not_change_trend() {
+        let daily = losing_trend();
+        let weights: Vec<DailyWeight> = (1..=29)
+            .step_by(4)
+            .map(|day| DailyWeight {
+                date: date(day),
+                weight_kg: dec!(90) - Decimal::from(day - 1) * dec!(0.1),
+            })
+            .collect();
+        let sparse = ProgressTrend::build(Uuid::nil(), date(1), date(29), &weights);
+        assert_eq!(sparse.series.len(), 8);
+        assert_eq!(sparse.latest_trend_weight_kg, daily.latest_trend_weight_kg);
+        assert_eq!(sparse.weekly_rate_kg, daily.weekly_rate_kg);
+    }
+
+    #[test]
+    fn test_projection_starts_at_last_weigh_in() {
+        let mut trend = losing_trend();
+        let goal = trend.project(dec!(80), None);
+        trend.date_to = date(29) + Duration::days(30);
+        assert_eq!(
+            trend.project(dec!(80), None).projected_goal_date,
+            goal.projected_goal_date
+        );
+    }
+
+    #[test]
+    fn test_no_projection_away_from_goal() {
+        let trend = losing_trend();
+        let goal = trend.project(dec!(100), None);
+        assert_eq!(goal.projected_goal_date, None);
+        assert_eq!(goal.required_daily_energy_balance, None);
+    }
+
+    #[test]
+    fn test_no_projection_without_weigh_ins() {
+        let trend = ProgressTrend::build(Uuid::nil(), date(1), date(29), &[]);
+        let goal = trend.project(dec!(80), Some(date(29) + Duration::days(70)));
+        assert_eq!(goal.remaining_kg, None);
+        assert_eq!(goal.projected_goal_date, None);
+        assert_eq!(goal.required_weekly_rate_kg, None);
+        assert_eq!(goal.required_daily_energy_balance, None);
+    }
+}
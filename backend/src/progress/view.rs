@@ -2,7 +2,7 @@ use axum::{
     extract::{Path, Query, State},
     Extension, Json,
 };
-use chrono::NaiveDate;
+use chrono::{Duration, NaiveDate};
 use serde_json::{json, Value};
 use std::sync::Arc;
 use uuid::Uuid;
@@ -11,6 +11,7 @@ use crate::{
     error::AppError,
     extractor::JsonExtractor,
     middleware::RequestUser,
+    progress_photo::model::ProgressPhoto,
     user::model::User,
     util::{
         extract::{IdRange, UsernameDateRange},
@@ -20,8 +21,10 @@ use crate::{
 };
 
 use super::{
+    body_composition::ProgressDetail,
     model::{Progress, ProgressAggregation},
-    serializer::{ProgressInput, ProgressUpdateInput},
+    serializer::{ProgressInput, ProgressTrendParams, ProgressUpdateInput},
+    trend::{ProgressTrend, DEFAULT_TREND_DAYS},
 };
 
 pub async fn progress_list_view(
@@ -37,8 +40,12 @@ pub async fn progress_list_view(
 pub async fn user_progress_list_view(
     Path(username): Path<String>,
     State(state): State<Arc<AppState>>,
-) -> Result<Json<Vec<Progress>>, AppError> {
+) -> Result<Json<Vec<ProgressDetail>>, AppError> {
+    let user = User::get_from_username(&state.pool, &username)
+        .await?
+        .ok_or(AppError::NotFound)?;
     let query = Progress::stream(&state.pool, &username).await?;
+    let query = ProgressDetail::build(&state.pool, user.id, query).await?;
     Ok(Json(query))
 }
 
@@ -47,16 +54,7 @@ pub async fn progress_create_view(
     Extension(request_user): Extension<RequestUser>,
     JsonExtractor(data): JsonExtractor<ProgressInput>,
 ) -> Result<Json<Progress>, AppError> {
-    let query = Progress::create(
-        &state.pool,
-        data.user_id,
-        data.date,
-        data.weight_kg,
-        data.energy_burnt,
-        data.notes,
-        request_user.id,
-    )
-    .await?;
+    let query = Progress::create(&state.pool, data, request_user.id).await?;
     Ok(Json(query))
 }
 
@@ -76,45 +74,42 @@ pub async fn progress_update_view(
     Extension(request_user): Extension<RequestUser>,
     JsonExtractor(data): JsonExtractor<ProgressUpdateInput>,
 ) -> Result<Json<Progress>, AppError> {
-    let query = Progress::update(
-        &state.pool,
-        id,
-        data.date,
-        data.weight_kg,
-        data.energy_burnt,
-        data.notes,
-        request_user.id,
-    )
-    .await?;
+    let query = Progress::update(&state.pool, id, data, request_user.id).await?;
     Ok(Json(query))
 }
 
 pub async fn user_progress_detail_view(
     Path((username, date)): Path<(String, NaiveDate)>,
     State(state): State<Arc<AppState>>,
-) -> Result<Json<Progress>, AppError> {
+) -> Result<Json<ProgressDetail>, AppError> {
     let query = Progress::get_by_username_date(&state.pool, username, &date)
         .await?
         .ok_or(AppError::NotFound)?;
-    Ok(Json(query))
+    let mut query = ProgressDetail::build(&state.pool, query.user_id, vec![query]).await?;
+    Ok(Json(query.remove(0)))
 }
 
 pub async fn progress_delete_view(
     Path(id): Path<Uuid>,
     State(state): State<Arc<AppState>>,
 ) -> Result<Json<Progress>, AppError> {
+    let photos = ProgressPhoto::all_by_progress_id(&state.pool, &id).await?;
     let result = Progress::delete(&state.pool, &id).await?;
+    for photo in photos {
+        photo.delete_files(state.storage.as_ref()).await?;
+    }
     Ok(Json(result))
 }
 
 pub async fn user_progress_detail_latest_view(
     Path((username, date)): Path<(String, NaiveDate)>,
     State(state): State<Arc<AppState>>,
-) -> Result<Json<Progress>, AppError> {
+) -> Result<Json<ProgressDetail>, AppError> {
     let query = Progress::get_latest(&state.pool, &username, &date)
         .await?
         .ok_or(AppError::NotFound)?;
-    Ok(Json(query))
+    let mut query = ProgressDetail::build(&state.pool, query.user_id, vec![query]).await?;
+    Ok(Json(query.remove(0)))
 }
 
 pub async fn user_progress_detail_latest_weight_view(
@@ -131,7 +126,11 @@ pub async fn progress_delete_id_range_view(
     State(state): State<Arc<AppState>>,
     JsonExtractor(data): JsonExtractor<IdRange>,
 ) -> Result<Json<Vec<Progress>>, AppError> {
+    let photos = ProgressPhoto::all_by_progress_id_range(&state.pool, &data.id_range).await?;
     let query = Progress::delete_id_range(&state.pool, data.id_range).await?;
+    for photo in photos {
+        photo.delete_files(state.storage.as_ref()).await?;
+    }
     Ok(Json(query))
 }
 
@@ -142,7 +141,12 @@ pub async fn progress_delete_date_range_view(
     let user = User::get_from_username(&state.pool, &data.username)
         .await?
         .ok_or(AppError::NotFound)?;
+    let photos =
+        ProgressPhoto::all_by_user_date_range(&state.pool, &user.id, &data.date_range).await?;
     let query = Progress::delete_date_range(&state.pool, user.id, data.date_range).await?;
+    for photo in photos {
+        photo.delete_files(state.storage.as_ref()).await?;
+    }
     Ok(Json(query))
 }
 
@@ -156,3 +160,29 @@ pub async fn progress_aggregation_detail_view(
     let query = ProgressAggregation::get(&state.pool, user.id, date).await?;
     Ok(Json(query))
 }
+
+pub async fn progress_trend_detail_view(
+    Path((username, date)): Path<(String, NaiveDate)>,
+    Query(params): Query<ProgressTrendParams>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ProgressTrend>, AppError> {
+    let user = User::get_from_username(&state.pool, &username)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    let date_from = params
+        .date_from
+        .unwrap_or(date - Duration::days(DEFAULT_TREND_DAYS));
+    if date_from > date {
+        return Err(AppError::BadRequest);
+    }
+    let query = ProgressTrend::get(
+        &state.pool,
+        user.id,
+        date_from,
+        date,
+        params.goal_weight,
+        params.goal_date,
+    )
+    .await?;
+    Ok(Json(query))
+}
@@ -0,0 +1,158 @@
+use chrono::prelude::*;
+use futures::TryStreamExt;
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::storage::{Storage, StorageError};
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct ProgressPhoto {
+    pub id: Uuid,
+    pub progress_id: Uuid,
+    pub user_id: Uuid,
+    #[serde(skip)]
+    pub image_key: String,
+    #[serde(skip)]
+    pub thumbnail_key: String,
+    pub content_type: String,
+    pub width: i32,
+    pub height: i32,
+    pub file_size: i32,
+    pub created_at: DateTime<Utc>,
+    pub created_by_id: Uuid,
+}
+
+pub struct ProgressPhotoInput {
+    pub progress_id: Uuid,
+    pub user_id: Uuid,
+    pub image_key: String,
+    pub thumbnail_key: String,
+    pub content_type: String,
+    pub width: i32,
+    pub height: i32,
+    pub file_size: i32,
+}
+
+impl ProgressPhoto {
+    pub async fn all_by_progress_id(
+        pool: &PgPool,
+        progress_id: &Uuid,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let mut stream = Vec::new();
+        let mut rows = sqlx::query_as(
+            "
+            SELECT
+                *
+            FROM
+                progress_photo
+            WHERE
+                progress_id = $1
+            ORDER BY
+                created_at
+            ",
+        )
+        .bind(progress_id)
+        .fetch(pool);
+        while let Some(row) = rows.try_next().await? {
+            stream.push(row);
+        }
+        Ok(stream)
+    }
+
+    pub async fn all_by_progress_id_range(
+        pool: &PgPool,
+        progress_id_range: &[Uuid],
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let query = sqlx::query_as("SELECT * FROM progress_photo WHERE progress_id = ANY ($1)")
+            .bind(progress_id_range)
+            .fetch_all(pool)
+            .await?;
+        Ok(query)
+    }
+
+    pub async fn all_by_user_date_range(
+        pool: &PgPool,
+        user_id: &Uuid,
+        date_range: &[NaiveDate],
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let query = sqlx::query_as(
+            "
+            SELECT
+                t1.*
+            FROM
+                progress_photo t1
+                LEFT JOIN progress t2 ON t2.id = t1.progress_id
+            WHERE
+                t2.user_id = $1
+                AND t2.date = ANY ($2)
+            ",
+        )
+        .bind(user_id)
+        .bind(date_range)
+        .fetch_all(pool)
+        .await?;
+        Ok(query)
+    }
+
+    pub async fn create(
+        pool: &PgPool,
+        data: ProgressPhotoInput,
+        created_by_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let query = sqlx::query_as(
+            "
+            INSERT INTO
+                progress_photo (
+                    progress_id,
+                    user_id,
+                    image_key,
+                    thumbnail_key,
+                    content_type,
+                    width,
+                    height,
+                    file_size,
+                    created_by_id
+                )
+            VALUES
+                ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING
+                *
+            ",
+        )
+        .bind(data.progress_id)
+        .bind(data.user_id)
+        .bind(data.image_key)
+        .bind(data.thumbnail_key)
+        .bind(data.content_type)
+        .bind(data.width)
+        .bind(data.height)
+        .bind(data.file_size)
+        .bind(created_by_id)
+        .fetch_one(pool)
+        .await?;
+        Ok(query)
+    }
+
+    pub async fn get(pool: &PgPool, id: &Uuid) -> Result<Option<Self>, sqlx::Error> {
+        let query = sqlx::query_as("SELECT * FROM progress_photo WHERE id = $1")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(query)
+    }
+
+    pub async fn delete(pool: &PgPool, id: &Uuid) -> Result<Self, sqlx::Error> {
+        let query = sqlx::query_as("DELETE FROM progress_photo WHERE id = $1 RETURNING *")
+            .bind(id)
+            .fetch_one(pool)
+            .await?;
+        Ok(query)
+    }
+
+    pub async fn delete_files(&self, storage: &dyn Storage) -> Result<(), StorageError> {
+        storage.delete(&self.image_key).await?;
+        storage.delete(&self.thumbnail_key).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,28 @@
+use axum::{
+    extract::DefaultBodyLimit,
+    routing::{delete, get, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::AppState;
+
+use super::view::{
+    progress_photo_create_view, progress_photo_delete_view, progress_photo_detail_view,
+    progress_photo_image_view, progress_photo_list_view, progress_photo_thumbnail_view,
+    MAX_PHOTO_SIZE,
+};
+
+pub fn progress_photo_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route(
+            "/progress/:progress_id",
+            post(progress_photo_create_view)
+                .layer(DefaultBodyLimit::max(MAX_PHOTO_SIZE + 1024 * 64)),
+        )
+        .route("/progress/:progress_id", get(progress_photo_list_view))
+        .route("/:id", get(progress_photo_detail_view))
+        .route("/:id", delete(progress_photo_delete_view))
+        .route("/:id/image", get(progress_photo_image_view))
+        .route("/:id/thumbnail", get(progress_photo_thumbnail_view))
+}
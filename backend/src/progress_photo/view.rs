@@ -0,0 +1,218 @@
+use axum::{
+    extract::{Multipart, Path, State},
+    http::header,
+    response::IntoResponse,
+    Extension, Json,
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    middleware::RequestUser,
+    progress::model::Progress,
+    storage::Storage,
+    user::model::User,
+    util::{
+        image::{decode_image, encode_jpeg, resize_to_fit},
+        permission::user_privacy_check,
+    },
+    AppState,
+};
+
+use super::model::{ProgressPhoto, ProgressPhotoInput};
+
+pub const MAX_PHOTO_SIZE: usize = 10 * 1024 * 1024;
+const PHOTO_SIZE: u32 = 2048;
+const THUMBNAIL_SIZE: u32 = 320;
+const PHOTO_CONTENT_TYPE: &str = "image/jpeg";
+
+struct ProcessedPhoto {
+    image: Vec<u8>,
+    thumbnail: Vec<u8>,
+    width: u32,
+    height: u32,
+}
+
+fn process_photo(data: &[u8]) -> Result<ProcessedPhoto, AppError> {
+    let image = decode_image(data)?;
+    let image = resize_to_fit(&image, PHOTO_SIZE);
+    let thumbnail = resize_to_fit(&image, THUMBNAIL_SIZE);
+    Ok(ProcessedPhoto {
+        image: encode_jpeg(&image)?,
+        thumbnail: encode_jpeg(&thumbnail)?,
+        width: image.width(),
+        height: image.height(),
+    })
+}
+
+async fn photo_access_check(
+    state: &AppState,
+    request_user: &RequestUser,
+    user_id: &Uuid,
+) -> Result<(), AppError> {
+    if &request_user.id == user_id {
+        return Ok(());
+    }
+    let user = User::get(&state.pool, user_id).await?;
+    user_privacy_check(&state.pool, request_user, &user).await?;
+    Ok(())
+}
+
+fn photo_owner_check(request_user: &RequestUser, user_id: &Uuid) -> Result<(), AppError> {
+    request_user.login_required()?;
+    if &request_user.id != user_id && !request_user.is_superuser {
+        return Err(AppError::UnauthorizedMessage(String::from(
+            "You can only manage your own progress photos",
+        )));
+    }
+    Ok(())
+}
+
+// Cleans up files stored for an upload that could not be saved.
+async fn delete_stored(storage: &dyn Storage, keys: &[&str]) {
+    for key in keys {
+        if let Err(err) = storage.delete(key).await {
+            tracing::warn!("could not delete stored file {}: {:?}", key, err);
+        }
+    }
+}
+
+pub async fn progress_photo_create_view(
+    Path(progress_id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    mut multipart: Multipart,
+) -> Result<Json<ProgressPhoto>, AppError> {
+    let progress = Progress::get(&state.pool, &progress_id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    photo_owner_check(&request_user, &progress.user_id)?;
+
+    let mut data = None;
+    while let Some(field) = multipart.next_field().await? {
+        if field.name() == Some("image") {
+            data = Some(field.bytes().await?);
+            break;
+        }
+    }
+    let data = data.ok_or(AppError::BadRequestMessage(String::from(
+        "An image file is required",
+    )))?;
+    if data.len() > MAX_PHOTO_SIZE {
+        return Err(AppError::BadRequestMessage(String::from(
+            "Image must be 10MB or smaller",
+        )));
+    }
+
+    let photo = tokio::task::spawn_blocking(move || process_photo(&data)).await??;
+    let key = Uuid::new_v4();
+    let image_key = format!("progress/{}/{}.jpg", progress.user_id, key);
+    let thumbnail_key = format!("progress/{}/{}_thumb.jpg", progress.user_id, key);
+    let file_size = photo.image.len() as i32;
+    state
+        .storage
+        .put(&image_key, PHOTO_CONTENT_TYPE, photo.image)
+        .await?;
+    if let Err(err) = state
+        .storage
+        .put(&thumbnail_key, PHOTO_CONTENT_TYPE, photo.thumbnail)
+        .await
+    {
+        delete_stored(state.storage.as_ref(), &[&image_key]).await;
+        return Err(err.into());
+    }
+
+    let input = ProgressPhotoInput {
+        progress_id: progress.id,
+        user_id: progress.user_id,
+        image_key: image_key.clone(),
+        thumbnail_key: thumbnail_key.clone(),
+        content_type: PHOTO_CONTENT_TYPE.to_string(),
+        width: photo.width as i32,
+        height: photo.height as i32,
+        file_size,
+    };
+    match ProgressPhoto::create(&state.pool, input, request_user.id).await {
+        Ok(query) => Ok(Json(query)),
+        Err(err) => {
+            delete_stored(state.storage.as_ref(), &[&image_key, &thumbnail_key]).await;
+            Err(err.into())
+        }
+    }
+}
+
+pub async fn progress_photo_list_view(
+    Path(progress_id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<Value>, AppError> {
+    let progress = Progress::get(&state.pool, &progress_id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    photo_access_check(&state, &request_user, &progress.user_id).await?;
+    let query = ProgressPhoto::all_by_progress_id(&state.pool, &progress.id).await?;
+    let response = json!({"count": query.len(), "results": query});
+    Ok(Json(response))
+}
+
+pub async fn progress_photo_detail_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<ProgressPhoto>, AppError> {
+    let query = ProgressPhoto::get(&state.pool, &id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    photo_access_check(&state, &request_user, &query.user_id).await?;
+    Ok(Json(query))
+}
+
+pub async fn progress_photo_image_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<impl IntoResponse, AppError> {
+    let query = ProgressPhoto::get(&state.pool, &id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    photo_access_check(&state, &request_user, &query.user_id).await?;
+    let data = state.storage.get(&query.image_key).await?;
+    let headers = [
+        (header::CONTENT_TYPE, query.content_type),
+        (header::CACHE_CONTROL, String::from("private, max-age=3600")),
+    ];
+    Ok((headers, data))
+}
+
+pub async fn progress_photo_thumbnail_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<impl IntoResponse, AppError> {
+    let query = ProgressPhoto::get(&state.pool, &id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    photo_access_check(&state, &request_user, &query.user_id).await?;
+    let data = state.storage.get(&query.thumbnail_key).await?;
+    let headers = [
+        (header::CONTENT_TYPE, query.content_type),
+        (header::CACHE_CONTROL, String::from("private, max-age=3600")),
+    ];
+    Ok((headers, data))
+}
+
+pub async fn progress_photo_delete_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<ProgressPhoto>, AppError> {
+    let query = ProgressPhoto::get(&state.pool, &id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    photo_owner_check(&request_user, &query.user_id)?;
+    let query = ProgressPhoto::delete(&state.pool, &query.id).await?;
+    query.delete_files(state.storage.as_ref()).await?;
+    Ok(Json(query))
+}
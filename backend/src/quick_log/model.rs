@@ -0,0 +1,356 @@
+use chrono::prelude::*;
+use futures::TryStreamExt;
+use rust_decimal::prelude::*;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+use sqlx::{FromRow, PgPool, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::{
+    db::Filters,
+    food::usage::push_food_usage,
+    middleware::RequestUser,
+    util::similarity::{normalize, similarity},
+};
+
+use super::parser::{ParsedEntry, Unit};
+
+pub const MATCH_THRESHOLD: f64 = 0.6;
+// Foods the user has logged before win close calls against the wider catalogue.
+const HISTORY_BONUS: f64 = 0.2;
+const MAX_ALTERNATIVES: usize = 3;
+const SEARCH_LIMIT: i64 = 50;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct FoodCandidate {
+    pub id: Uuid,
+    pub name: String,
+    pub brand_name: String,
+    pub data_value: i32,
+    pub data_measurement: String,
+    pub energy: i32,
+    pub log_count: Option<i64>,
+    pub last_quantity: Option<Decimal>,
+}
+
+const CANDIDATE_COLUMNS: &str = "
+    SELECT
+        t1.id,
+        t1.name,
+        t2.name AS brand_name,
+        t1.data_value,
+        t1.data_measurement,
+        t1.energy,
+        t3.log_count,
+        t3.last_quantity
+    ";
+
+impl FoodCandidate {
+    pub async fn history(pool: &PgPool, user_id: Uuid) -> Result<Vec<Self>, sqlx::Error> {
+        let mut stream = Vec::new();
+        let mut q: QueryBuilder<'static, Postgres> = QueryBuilder::new(CANDIDATE_COLUMNS);
+        q.push(" FROM (");
+        push_food_usage(&mut q, user_id, None);
+        q.push(
+            ") t3
+            JOIN food t1 ON t1.id = t3.food_id
+            JOIN food_brand t2 ON t2.id = t1.brand_id
+            ORDER BY t3.log_count DESC",
+        );
+        let mut rows = q.build_query_as::<Self>().fetch(pool);
+        while let Some(row) = rows.try_next().await? {
+            stream.push(row);
+        }
+        Ok(stream)
+    }
+    // Catalogue foods sharing a word with the phrase, limited to what the user can see.
+    pub async fn search(
+        pool: &PgPool,
+        request_user: &RequestUser,
+        phrase: &str,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let words: Vec<String> = normalize(phrase)
+            .split(' ')
+            .filter(|word| word.len() > 2)
+            .map(|word| format!("%{}%", word))
+            .collect();
+        if words.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut stream = Vec::new();
+        let mut q: QueryBuilder<'static, Postgres> = QueryBuilder::new(CANDIDATE_COLUMNS);
+        q.push(
+            "
+            FROM
+                food t1
+                JOIN food_brand t2 ON t2.id = t1.brand_id
+                LEFT JOIN (",
+        );
+        push_food_usage(&mut q, request_user.id, None);
+        q.push(
+            ") t3 ON t3.food_id = t1.id
+            WHERE
+                t1.name ILIKE ANY (",
+        );
+        q.push_bind(words);
+        q.push(")");
+        q.filter_review_status("t1", &None, request_user);
+        q.push(" ORDER BY LENGTH(t1.name) LIMIT ");
+        q.push_bind(SEARCH_LIMIT);
+        let mut rows = q.build_query_as::<Self>().fetch(pool);
+        while let Some(row) = rows.try_next().await? {
+            stream.push(row);
+        }
+        Ok(stream)
+    }
+    // The diary quantity multiplier for a parsed amount. Weights and volumes use the
+    // food's data_value; counts are servings of a serving food, or else repeat the
+    // user's usual portion when there is one.
+    pub fn quantity(&self, entry: &ParsedEntry) -> (Decimal, &'static str) {
+        let count = entry.amount.unwrap_or(Decimal::ONE);
+        let data_value = Decimal::from(self.data_value.max(1));
+        let measured = matches!(self.data_measurement.as_str(), "g" | "ml");
+        let (quantity, source) = match (entry.unit, measured) {
+            (Some(Unit::Gram | Unit::Millilitre), true) => (count / data_value, "amount"),
+            (Some(Unit::Gram | Unit::Millilitre), false) => (Decimal::ONE, "default"),
+            (_, false) => (count, "servings"),
+            (_, true) => match self.last_quantity {
+                Some(last) => (count.checked_mul(last).unwrap_or(Decimal::MAX), "usual"),
+                None => (count, "default"),
+            },
+        };
+        (quantity.round_dp(2).clamp(dec!(0.01), dec!(999.99)), source)
+    }
+}
+
+// Shared words catch "toast" in "White Bread Toast" and "Oats" in "rolled oats",
+// which bigram similarity alone scores poorly. Covering the typed phrase counts for
+// more than covering the food name, so the plainest full match wins.
+fn match_score(phrase: &str, name: &str) -> f64 {
+    let (phrase, name) = (normalize(phrase), normalize(name));
+    let name_words: Vec<&str> = name.split(' ').collect();
+    let phrase_words: Vec<&str> = phrase.split(' ').collect();
+    let shared = phrase_words
+        .iter()
+        .filter(|word| name_words.contains(word))
+        .count() as f64;
+    let coverage =
+        shared / phrase_words.len() as f64 * 0.55 + shared / name_words.len() as f64 * 0.35;
+    similarity(&phrase, &name).max(coverage)
+}
+
+pub struct FoodMatch<'a> {
+    pub food: &'a FoodCandidate,
+    pub score: f64,
+    pub from_history: bool,
+}
+
+pub fn rank_matches<'a>(
+    phrase: &str,
+    history: &'a [FoodCandidate],
+    catalogue: &'a [FoodCandidate],
+) -> Vec<FoodMatch<'a>> {
+    let mut matches: Vec<FoodMatch> = history
+        .iter()
+        .map(|food| FoodMatch {
+            food,
+            score: match_score(phrase, &food.name) + HISTORY_BONUS,
+            from_history: true,
+        })
+        .chain(
+            catalogue
+                .iter()
+                .filter(|food| !history.iter().any(|known| known.id == food.id))
+                .map(|food| FoodMatch {
+                    food,
+                    score: match_score(phrase, &food.name),
+                    from_history: false,
+                }),
+        )
+        .filter(|found| found.score >= MATCH_THRESHOLD)
+        .collect();
+    matches.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(b.food.log_count.cmp(&a.food.log_count))
+    });
+    matches
+}
+
+#[derive(Debug, Serialize)]
+pub struct DraftFood {
+    pub food_id: Uuid,
+    pub name: String,
+    pub brand_name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QuickLogDraftEntry {
+    #[serde(flatten)]
+    pub parsed: ParsedEntry,
+    pub food_id: Option<Uuid>,
+    pub food_name: Option<String>,
+    pub brand_name: Option<String>,
+    pub data_value: Option<i32>,
+    pub data_measurement: Option<String>,
+    pub quantity: Option<Decimal>,
+    // How the quantity was worked out: amount, servings, usual or default.
+    pub quantity_source: Option<&'static str>,
+    pub energy: Option<Decimal>,
+    pub from_history: bool,
+    pub alternatives: Vec<DraftFood>,
+}
+
+impl QuickLogDraftEntry {
+    pub fn build(parsed: ParsedEntry, matches: &[FoodMatch]) -> Self {
+        let alternatives = matches
+            .iter()
+            .skip(1)
+            .take(MAX_ALTERNATIVES)
+            .map(|found| DraftFood {
+                food_id: found.food.id,
+                name: found.food.name.clone(),
+                brand_name: found.food.brand_name.clone(),
+            })
+            .collect();
+        let Some(best) = matches.first() else {
+            return Self {
+                parsed,
+                food_id: None,
+                food_name: None,
+                brand_name: None,
+                data_value: None,
+                data_measurement: None,
+                quantity: None,
+                quantity_source: None,
+                energy: None,
+                from_history: false,
+                alternatives,
+            };
+        };
+        let (quantity, source) = best.food.quantity(&parsed);
+        Self {
+            food_id: Some(best.food.id),
+            food_name: Some(best.food.name.clone()),
+            brand_name: Some(best.food.brand_name.clone()),
+            data_value: Some(best.food.data_value),
+            data_measurement: Some(best.food.data_measurement.clone()),
+            quantity: Some(quantity),
+            quantity_source: Some(source),
+            energy: Some((quantity * Decimal::from(best.food.energy)).round()),
+            from_history: best.from_history,
+            alternatives,
+            parsed,
+        }
+    }
+    pub fn scale(&mut self, factor: Decimal) {
+        if let (Some(quantity), Some(energy)) = (self.quantity, self.energy) {
+            let scaled = quantity
+                .checked_mul(factor)
+                .unwrap_or(Decimal::MAX)
+                .round_dp(2)
+                .clamp(dec!(0.01), dec!(999.99));
+            self.energy = Some((energy * scaled / quantity).round());
+            self.quantity = Some(scaled);
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct QuickLogDraft {
+    pub date: NaiveDate,
+    pub meal_phrase: Option<String>,
+    pub meal_of_day_id: Option<Uuid>,
+    pub entries: Vec<QuickLogDraftEntry>,
+    pub matched_count: usize,
+    pub unmatched_count: usize,
+    pub energy: Decimal,
+}
+
+impl QuickLogDraft {
+    pub fn new(
+        date: NaiveDate,
+        meal_phrase: Option<String>,
+        meal_of_day_id: Option<Uuid>,
+        entries: Vec<QuickLogDraftEntry>,
+    ) -> Self {
+        let matched_count = entries
+            .iter()
+            .filter(|entry| entry.food_id.is_some())
+            .count();
+        let energy = entries.iter().filter_map(|entry| entry.energy).sum();
+        Self {
+            date,
+            meal_phrase,
+            meal_of_day_id,
+            unmatched_count: entries.len() - matched_count,
+            matched_count,
+            entries,
+            energy,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quick_log::parser::parse_log;
+
+    fn food(name: &str, measurement: &str, log_count: Option<i64>) -> FoodCandidate {
+        FoodCandidate {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            brand_name: String::from("Brand"),
+            data_value: if measurement == "srv" { 1 } else { 100 },
+            data_measurement: measurement.to_string(),
+            energy: 100,
+            log_count,
+            last_quantity: log_count.map(|_| dec!(0.6)),
+        }
+    }
+
+    #[test]
+    fn test_history_is_preferred() {
+        let history = vec![food("Free Range Eggs", "g", Some(12))];
+        let catalogue = vec![food("Egg Noodles", "g", None), food("Pasta", "g", None)];
+        let matches = rank_matches("eggs", &history, &catalogue);
+        assert_eq!(matches[0].food.id, history[0].id);
+        assert!(matches[0].from_history);
+        assert_eq!(matches[1].food.id, catalogue[0].id);
+    }
+
+    #[test]
+    fn test_unrelated_food_is_not_matched() {
+        let catalogue = vec![food("Porridge Oats", "g", None)];
+        assert!(rank_matches("coffee", &[], &catalogue).is_empty());
+        assert_eq!(rank_matches("oats", &[], &catalogue).len(), 1);
+        let catalogue = vec![food("Oats", "g", None)];
+        assert_eq!(rank_matches("rolled oats", &[], &catalogue).len(), 1);
+    }
+
+    #[test]
+    fn test_quantity_from_parsed_amount() {
+        let log = parse_log("150g oats, 2 coffees, 2 eggs, a banana");
+        let oats = food("Oats", "g", None);
+        let coffee = food("Coffee", "srv", None);
+        let eggs = food("Eggs", "g", Some(3));
+        let banana = food("Banana", "g", None);
+        assert_eq!(oats.quantity(&log.entries[0]), (dec!(1.5), "amount"));
+        assert_eq!(coffee.quantity(&log.entries[1]), (dec!(2), "servings"));
+        assert_eq!(eggs.quantity(&log.entries[2]), (dec!(1.2), "usual"));
+        assert_eq!(banana.quantity(&log.entries[3]), (dec!(1), "default"));
+        let log = parse_log("79228162514264337593543950335 eggs");
+        assert_eq!(eggs.quantity(&log.entries[0]), (dec!(999.99), "usual"));
+    }
+
+    #[test]
+    fn test_scale_is_clamped() {
+        let oats = food("Oats", "g", None);
+        let log = parse_log("900g oats");
+        let matches = rank_matches("oats", &[], std::slice::from_ref(&oats));
+        let mut entry = QuickLogDraftEntry::build(log.entries[0].clone(), &matches);
+        entry.scale(Decimal::MAX);
+        assert_eq!(entry.quantity, Some(dec!(999.99)));
+        assert_eq!(entry.energy, Some(dec!(99999)));
+    }
+}
@@ -0,0 +1,321 @@
+use rust_decimal::prelude::*;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub enum Unit {
+    #[serde(rename = "g")]
+    Gram,
+    #[serde(rename = "ml")]
+    Millilitre,
+    #[serde(rename = "srv")]
+    Serving,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParsedEntry {
+    pub text: String,
+    pub amount: Option<Decimal>,
+    pub unit: Option<Unit>,
+    pub food: String,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ParsedLog {
+    pub meal: Option<String>,
+    pub entries: Vec<ParsedEntry>,
+}
+
+const LEADING_PHRASES: &[&str] = &["i had", "i ate", "had", "ate", "log"];
+const SEPARATORS: &[&str] = &[" and ", " with ", " plus ", "&", "+", ";", "\n"];
+const MEAL_PREPOSITIONS: &[&str] = &[" for ", " at ", " as "];
+// A trailing "for ..." longer than this is part of a food, e.g. "for two".
+const MAX_MEAL_WORDS: usize = 2;
+
+fn number_word(word: &str) -> Option<Decimal> {
+    let value = match word {
+        "a" | "an" | "one" => dec!(1),
+        "half" => dec!(0.5),
+        "two" | "couple" => dec!(2),
+        "three" => dec!(3),
+        "four" => dec!(4),
+        "five" => dec!(5),
+        "six" => dec!(6),
+        "seven" => dec!(7),
+        "eight" => dec!(8),
+        "nine" => dec!(9),
+        "ten" => dec!(10),
+        "dozen" => dec!(12),
+        _ => return None,
+    };
+    Some(value)
+}
+
+fn number(value: &str) -> Option<Decimal> {
+    match value {
+        "½" => return Some(dec!(0.5)),
+        "¼" => return Some(dec!(0.25)),
+        "¾" => return Some(dec!(0.75)),
+        _ => {}
+    }
+    if let Some((top, bottom)) = value.split_once('/') {
+        let (top, bottom) = (
+            Decimal::from_str(top).ok()?,
+            Decimal::from_str(bottom).ok()?,
+        );
+        return top.checked_div(bottom);
+    }
+    Decimal::from_str(value).ok()
+}
+
+// Amounts are returned in grams or millilitres; countable units are servings.
+// Amounts too large to scale make the entry unparseable.
+fn unit(word: &str) -> Option<(Unit, Decimal)> {
+    let unit = match word.trim_end_matches('.') {
+        "g" | "gram" | "grams" | "gr" => (Unit::Gram, dec!(1)),
+        "kg" | "kilo" | "kilos" => (Unit::Gram, dec!(1000)),
+        "oz" | "ounce" | "ounces" => (Unit::Gram, dec!(28.35)),
+        "lb" | "lbs" | "pound" | "pounds" => (Unit::Gram, dec!(453.59)),
+        "ml" | "millilitre" | "millilitres" | "milliliter" | "milliliters" => {
+            (Unit::Millilitre, dec!(1))
+        }
+        "l" | "litre" | "litres" | "liter" | "liters" => (Unit::Millilitre, dec!(1000)),
+        "tbsp" | "tablespoon" | "tablespoons" => (Unit::Millilitre, dec!(15)),
+        "tsp" | "teaspoon" | "teaspoons" => (Unit::Millilitre, dec!(5)),
+        "slice" | "slices" | "piece" | "pieces" | "serving" | "servings" | "portion"
+        | "portions" | "bowl" | "bowls" | "cup" | "cups" | "glass" | "glasses" | "can" | "cans"
+        | "tin" | "tins" | "bottle" | "bottles" | "scoop" | "scoops" | "bar" | "bars" | "clove"
+        | "cloves" | "handful" | "handfuls" | "pinch" | "x" => (Unit::Serving, dec!(1)),
+        _ => return None,
+    };
+    Some(unit)
+}
+
+// "100g" and "2x" carry their unit on the number.
+fn split_number(token: &str) -> Option<(Decimal, Option<&str>)> {
+    if let Some(value) = number(token) {
+        return Some((value, None));
+    }
+    let index = token.find(|c: char| c.is_alphabetic())?;
+    let (value, rest) = token.split_at(index);
+    Some((number(value)?, Some(rest)))
+}
+
+// A measured amount such as "400g" or "180 ml", in grams or millilitres.
+pub fn parse_amount(text: &str) -> Option<(Decimal, Unit)> {
+    let mut words = text.split_whitespace();
+    let (value, glued) = split_number(words.next()?)?;
+    let (unit, factor) = unit(glued.or_else(|| words.next())?)?;
+    if unit == Unit::Serving {
+        return None;
+    }
+    Some((value.checked_mul(factor)?, unit))
+}
+
+fn is_fraction(word: &str) -> bool {
+    word.contains(['/', '½', '¼', '¾']) && number(word).is_some()
+}
+
+pub fn parse_entry(text: &str) -> Option<ParsedEntry> {
+    let mut words: Vec<&str> = text.split_whitespace().collect();
+    let mut amount = None;
+    let mut unit_amount = None;
+    if let Some(first) = words.first().copied() {
+        if let Some((value, glued)) = split_number(first) {
+            amount = Some(value);
+            words.remove(0);
+            // "1 1/2 cups"
+            if glued.is_none() && words.first().is_some_and(|word| is_fraction(word)) {
+                let fraction = number(words.remove(0))?;
+                amount = Some(value.checked_add(fraction)?);
+            }
+            if let Some(found) = glued.and_then(unit) {
+                unit_amount = Some(found);
+            } else if glued.is_some() {
+                return None;
+            }
+        } else if let Some(value) = number_word(first) {
+            amount = Some(value);
+            words.remove(0);
+            // "half a bagel", "a couple of eggs"
+            while matches!(words.first(), Some(&"a" | &"an" | &"couple")) {
+                if words[0] == "couple" {
+                    amount = Some(dec!(2));
+                }
+                words.remove(0);
+            }
+        }
+    }
+    if unit_amount.is_none() {
+        if let Some(found) = words.first().and_then(|word| unit(word)) {
+            if words.len() > 1 {
+                unit_amount = Some(found);
+                words.remove(0);
+            }
+        }
+    }
+    if words.first() == Some(&"of") {
+        words.remove(0);
+    }
+    let food = words.join(" ");
+    if food.is_empty() {
+        return None;
+    }
+    let (amount, unit) = match (amount, unit_amount) {
+        (amount, Some((unit, factor))) => {
+            let amount = amount.unwrap_or(Decimal::ONE).checked_mul(factor)?;
+            (Some(amount), Some(unit))
+        }
+        (amount, None) => (amount, None),
+    };
+    Some(ParsedEntry {
+        text: text.to_string(),
+        amount,
+        unit,
+        food,
+    })
+}
+
+fn split_meal(text: &str) -> (&str, Option<String>) {
+    if let Some((meal, rest)) = text.split_once(':') {
+        if meal.split_whitespace().count() <= MAX_MEAL_WORDS {
+            return (rest, Some(meal.trim().to_string()));
+        }
+    }
+    let found = MEAL_PREPOSITIONS
+        .iter()
+        .filter_map(|preposition| text.rfind(preposition).map(|i| (i, preposition.len())))
+        .max_by_key(|(i, _)| *i);
+    if let Some((i, len)) = found {
+        let meal = text[i + len..].trim();
+        if !meal.is_empty() && meal.split_whitespace().count() <= MAX_MEAL_WORDS {
+            return (&text[..i], Some(meal.to_string()));
+        }
+    }
+    (text, None)
+}
+
+pub fn parse_log(text: &str) -> ParsedLog {
+    let text = text
+        .to_lowercase()
+        .trim()
+        .trim_end_matches(['.', '!'])
+        .to_string();
+    let mut body = text.as_str();
+    for phrase in LEADING_PHRASES {
+        if let Some(rest) = body
+            .strip_prefix(phrase)
+            .filter(|rest| rest.starts_with(' '))
+        {
+            body = rest;
+            break;
+        }
+    }
+    let (body, meal) = split_meal(body);
+    let mut body = body.to_string();
+    for separator in SEPARATORS {
+        body = body.replace(separator, ",");
+    }
+    let entries = body
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .filter_map(parse_entry)
+        .collect();
+    ParsedLog { meal, entries }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(
+        food: &str,
+        amount: Option<Decimal>,
+        unit: Option<Unit>,
+    ) -> (String, Option<Decimal>, Option<Unit>) {
+        (food.to_string(), amount, unit)
+    }
+
+    fn entries(log: &ParsedLog) -> Vec<(String, Option<Decimal>, Option<Unit>)> {
+        log.entries
+            .iter()
+            .map(|entry| (entry.food.clone(), entry.amount, entry.unit))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_breakfast_sentence() {
+        let log = parse_log("2 eggs, 2 slices toast and a coffee for breakfast");
+        assert_eq!(log.meal.as_deref(), Some("breakfast"));
+        assert_eq!(
+            entries(&log),
+            vec![
+                entry("eggs", Some(dec!(2)), None),
+                entry("toast", Some(dec!(2)), Some(Unit::Serving)),
+                entry("coffee", Some(dec!(1)), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_units() {
+        let log = parse_log("I had 100g oats, 1.5 cups of milk & 0.2kg chicken breast");
+        assert_eq!(log.meal, None);
+        assert_eq!(
+            entries(&log),
+            vec![
+                entry("oats", Some(dec!(100)), Some(Unit::Gram)),
+                entry("milk", Some(dec!(1.5)), Some(Unit::Serving)),
+                entry("chicken breast", Some(dec!(200.0)), Some(Unit::Gram)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_meal_prefix_and_fractions() {
+        let log = parse_log("Lunch: half a bagel, 1/2 tbsp peanut butter, banana");
+        assert_eq!(log.meal.as_deref(), Some("lunch"));
+        assert_eq!(
+            entries(&log),
+            vec![
+                entry("bagel", Some(dec!(0.5)), None),
+                entry("peanut butter", Some(dec!(7.5)), Some(Unit::Millilitre)),
+                entry("banana", None, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_mixed_number() {
+        let entry = parse_entry("1 1/2 cups rolled oats").unwrap();
+        assert_eq!(entry.amount, Some(dec!(1.5)));
+        assert_eq!(entry.unit, Some(Unit::Serving));
+        assert_eq!(entry.food, "rolled oats");
+    }
+
+    #[test]
+    fn test_huge_amount_is_dropped() {
+        let log = parse_log("79228162514264337593543950335 kg rice, 100g oats");
+        assert_eq!(
+            entries(&log),
+            vec![entry("oats", Some(dec!(100)), Some(Unit::Gram))]
+        );
+        assert_eq!(
+            parse_entry("79228162514264337593543950335 1/2 cups rice"),
+            None
+        );
+        assert_eq!(
+            parse_entry("1/0.0000000000000000000000000001 kg rice"),
+            None
+        );
+        assert_eq!(parse_amount("79228162514264337593543950335 lb"), None);
+    }
+
+    #[test]
+    fn test_long_trailing_phrase_is_not_a_meal() {
+        let log = parse_log("a pizza for me and my friend");
+        assert_eq!(log.meal, None);
+        assert_eq!(log.entries[0].food, "pizza for me");
+    }
+}
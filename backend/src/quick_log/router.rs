@@ -0,0 +1,11 @@
+use axum::{routing::post, Router};
+use std::sync::Arc;
+
+use crate::quick_log::view::{quick_log_confirm_view, quick_log_parse_view};
+use crate::AppState;
+
+pub fn quick_log_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/parse", post(quick_log_parse_view))
+        .route("/confirm", post(quick_log_confirm_view))
+}
@@ -0,0 +1,47 @@
+use chrono::prelude::*;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::util::validator::{validate_max_quantity, validate_min_quantity};
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct QuickLogParseInput {
+    pub username: String,
+    pub date: Option<NaiveDate>,
+    #[validate(length(min = 1, max = 500, message = "Text must be 1-500 characters"))]
+    pub text: String,
+    // Overrides a meal found in the text.
+    pub meal_of_day_id: Option<Uuid>,
+    pub meal_of_day_slug: Option<String>,
+}
+
+// Serialize lets the length rule on entries report the rejected value.
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct QuickLogConfirmEntryInput {
+    pub food_id: Uuid,
+    #[validate(
+        custom(
+            function = "validate_min_quantity",
+            message = "Quantity must be a minimum of 0.01"
+        ),
+        custom(
+            function = "validate_max_quantity",
+            message = "Quantity must be a maximum of 999.99"
+        )
+    )]
+    pub quantity: Decimal,
+}
+
+// The matched entries of a draft, posted back once the user has checked them.
+#[derive(Debug, Deserialize, Validate)]
+pub struct QuickLogConfirmInput {
+    pub username: String,
+    pub date: NaiveDate,
+    pub meal_of_day_id: Option<Uuid>,
+    pub meal_of_day_slug: Option<String>,
+    #[validate]
+    #[validate(length(min = 1, max = 20, message = "Choose between 1 and 20 foods"))]
+    pub entries: Vec<QuickLogConfirmEntryInput>,
+}
@@ -0,0 +1,101 @@
+use axum::{extract::State, http::StatusCode, Extension, Json};
+use chrono::prelude::*;
+use rust_decimal::Decimal;
+use std::sync::Arc;
+
+use crate::{
+    diet::{model::Diet, suggestion::log_suggestion, view::get_meal_of_day_id},
+    diet_import::model::MealMatcher,
+    error::AppError,
+    extractor::JsonExtractor,
+    food::model::Food,
+    meal_of_day::model::MealOfDay,
+    middleware::RequestUser,
+    moderation::model::is_visible,
+    util::permission::owner_required,
+    AppState,
+};
+
+use super::{
+    model::{rank_matches, FoodCandidate, QuickLogDraft, QuickLogDraftEntry},
+    parser::parse_log,
+    serializer::{QuickLogConfirmInput, QuickLogParseInput},
+};
+
+const MAX_ENTRIES: usize = 20;
+const OWNER_MESSAGE: &str = "You are unable to add to another users food diary.";
+
+// Nothing is written here; the draft is returned for the user to check and confirm.
+pub async fn quick_log_parse_view(
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    JsonExtractor(data): JsonExtractor<QuickLogParseInput>,
+) -> Result<Json<QuickLogDraft>, AppError> {
+    let user = owner_required(&state.pool, &request_user, &data.username, OWNER_MESSAGE).await?;
+    let parsed = parse_log(&data.text);
+    if parsed.entries.is_empty() {
+        return Err(AppError::BadRequestMessage(String::from(
+            "No food found in the text.",
+        )));
+    }
+    if parsed.entries.len() > MAX_ENTRIES {
+        return Err(AppError::BadRequestMessage(format!(
+            "Log at most {} foods at once.",
+            MAX_ENTRIES
+        )));
+    }
+    let meal_of_day_id = if data.meal_of_day_id.is_some() || data.meal_of_day_slug.is_some() {
+        Some(get_meal_of_day_id(&state, data.meal_of_day_id, &data.meal_of_day_slug).await?)
+    } else {
+        let meals = MealOfDay::all(&state.pool).await?;
+        parsed
+            .meal
+            .as_deref()
+            .and_then(|meal| MealMatcher::new(&meals).find(meal))
+    };
+    let history = FoodCandidate::history(&state.pool, user.id).await?;
+    let mut entries = Vec::new();
+    for entry in parsed.entries {
+        let catalogue = FoodCandidate::search(&state.pool, &request_user, &entry.food).await?;
+        let matches = rank_matches(&entry.food, &history, &catalogue);
+        entries.push(QuickLogDraftEntry::build(entry, &matches));
+    }
+    let date = data.date.unwrap_or_else(|| Utc::now().date_naive());
+    Ok(Json(QuickLogDraft::new(
+        date,
+        parsed.meal,
+        meal_of_day_id,
+        entries,
+    )))
+}
+
+pub async fn quick_log_confirm_view(
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    JsonExtractor(data): JsonExtractor<QuickLogConfirmInput>,
+) -> Result<(StatusCode, Json<Vec<Diet>>), AppError> {
+    let user = owner_required(&state.pool, &request_user, &data.username, OWNER_MESSAGE).await?;
+    let meal_of_day_id =
+        get_meal_of_day_id(&state, data.meal_of_day_id, &data.meal_of_day_slug).await?;
+    let mut items = Vec::new();
+    for entry in &data.entries {
+        Food::get_opt(&state.pool, &entry.food_id)
+            .await?
+            .filter(|food| is_visible(&request_user, &food.created_by_id, &food.review_status))
+            .ok_or(AppError::APIBadRequest(format!(
+                "Food {} not found",
+                entry.food_id
+            )))?;
+        items.push((Some(entry.food_id), None, entry.quantity));
+    }
+    let query = log_suggestion(
+        &state.pool,
+        user.id,
+        data.date,
+        meal_of_day_id,
+        &items,
+        request_user.id,
+    )
+    .await?;
+    Ok((StatusCode::CREATED, Json(query)))
+}
@@ -0,0 +1,153 @@
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::{
+    error::AppError,
+    meal::model::Meal,
+    meal_food::model::MealFood,
+    middleware::RequestUser,
+    quick_log::{
+        model::{rank_matches, FoodCandidate, QuickLogDraftEntry},
+        parser::{parse_entry, ParsedEntry},
+    },
+};
+
+use super::parser::{clean_ingredient, metric_amount, ParsedRecipe};
+
+const MAX_NAME_LENGTH: usize = 255;
+pub const MAX_INGREDIENTS: usize = 60;
+
+#[derive(Debug, Serialize)]
+pub struct RecipeImportReport {
+    pub name: String,
+    pub recipe_yield: Option<String>,
+    pub servings: Decimal,
+    pub per_serving: bool,
+    pub dry_run: bool,
+    pub ingredient_count: usize,
+    pub matched_count: usize,
+    pub unmatched_count: usize,
+    pub energy: Decimal,
+    pub meal: Option<Meal>,
+    pub ingredients: Vec<QuickLogDraftEntry>,
+}
+
+pub struct RecipeImportOptions {
+    pub name: Option<String>,
+    pub per_serving: bool,
+    pub dry_run: bool,
+}
+
+fn parse_ingredient(line: &str) -> ParsedEntry {
+    let cleaned = clean_ingredient(line);
+    let mut entry = parse_entry(&cleaned).unwrap_or(ParsedEntry {
+        text: String::new(),
+        amount: None,
+        unit: None,
+        food: cleaned,
+    });
+    entry.text = line.to_string();
+    if let Some((amount, unit)) = metric_amount(line) {
+        entry.amount = Some(amount);
+        entry.unit = Some(unit);
+    }
+    entry
+}
+
+// Unmatched ingredients are reported but left out of the meal. With per_serving the
+// meal holds one portion, dividing each quantity by the recipe's yield.
+pub async fn import_recipe(
+    pool: &PgPool,
+    request_user: &RequestUser,
+    parsed: ParsedRecipe,
+    options: RecipeImportOptions,
+) -> Result<RecipeImportReport, AppError> {
+    if parsed.ingredients.len() > MAX_INGREDIENTS {
+        return Err(AppError::BadRequestMessage(format!(
+            "Recipes can have at most {} ingredients",
+            MAX_INGREDIENTS
+        )));
+    }
+    let servings = parsed.servings.unwrap_or(Decimal::ONE);
+    let history = FoodCandidate::history(pool, request_user.id).await?;
+    let mut ingredients = Vec::new();
+    for line in &parsed.ingredients {
+        let entry = parse_ingredient(line);
+        let catalogue = FoodCandidate::search(pool, request_user, &entry.food).await?;
+        let matches = rank_matches(&entry.food, &history, &catalogue);
+        let mut ingredient = QuickLogDraftEntry::build(entry, &matches);
+        if options.per_serving {
+            ingredient.scale(Decimal::ONE / servings);
+        }
+        ingredients.push(ingredient);
+    }
+
+    let food_range: Vec<_> = ingredients
+        .iter()
+        .filter_map(|ingredient| ingredient.food_id.zip(ingredient.quantity))
+        .collect();
+    let name: String = options
+        .name
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or(parsed.name)
+        .chars()
+        .take(MAX_NAME_LENGTH)
+        .collect();
+    let meal = if options.dry_run || food_range.is_empty() {
+        None
+    } else {
+        let mut tx = pool.begin().await?;
+        let meal = Meal::create(&mut tx, request_user.id, name.clone(), request_user.id).await?;
+        MealFood::create_from_food_range(&mut tx, meal.id, &food_range, request_user.id).await?;
+        tx.commit().await?;
+        Some(meal)
+    };
+
+    Ok(RecipeImportReport {
+        name,
+        recipe_yield: parsed.recipe_yield,
+        servings,
+        per_serving: options.per_serving,
+        dry_run: options.dry_run,
+        ingredient_count: ingredients.len(),
+        matched_count: food_range.len(),
+        unmatched_count: ingredients.len() - food_range.len(),
+        energy: ingredients
+            .iter()
+            .filter_map(|ingredient| ingredient.energy)
+            .sum(),
+        meal,
+        ingredients,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quick_log::parser::Unit;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_parse_ingredient() {
+        let entry = parse_ingredient("1 1/2 cups (180g) plain flour, sifted");
+        assert_eq!(entry.text, "1 1/2 cups (180g) plain flour, sifted");
+        assert_eq!(entry.amount, Some(dec!(180)));
+        assert_eq!(entry.unit, Some(Unit::Gram));
+        assert_eq!(entry.food, "plain flour");
+
+        let entry = parse_ingredient("1 (400g) tin chopped tomatoes, drained");
+        assert_eq!(entry.amount, Some(dec!(400)));
+        assert_eq!(entry.unit, Some(Unit::Gram));
+        assert_eq!(entry.food, "tomatoes");
+
+        let entry = parse_ingredient("2 large eggs");
+        assert_eq!(entry.amount, Some(dec!(2)));
+        assert_eq!(entry.unit, None);
+
+        let entry = parse_ingredient("Salt");
+        assert_eq!(entry.amount, None);
+        assert_eq!(entry.food, "salt");
+    }
+}
@@ -0,0 +1,282 @@
+use rust_decimal::prelude::*;
+use serde_json::Value;
+
+use crate::{
+    error::AppError,
+    quick_log::parser::{parse_amount, Unit},
+};
+
+#[derive(Debug, PartialEq)]
+pub struct ParsedRecipe {
+    pub name: String,
+    pub recipe_yield: Option<String>,
+    pub servings: Option<Decimal>,
+    pub ingredients: Vec<String>,
+}
+
+const SCRIPT_TYPE: &str = "application/ld+json";
+// Preparation and size words that stop "2 large eggs, beaten" matching "Eggs".
+const DESCRIPTORS: &[&str] = &[
+    "large",
+    "medium",
+    "small",
+    "fresh",
+    "freshly",
+    "chopped",
+    "diced",
+    "sliced",
+    "minced",
+    "grated",
+    "crushed",
+    "finely",
+    "roughly",
+    "thinly",
+    "peeled",
+    "ground",
+    "softened",
+    "melted",
+    "beaten",
+    "room",
+    "temperature",
+    "heaped",
+    "level",
+    "about",
+    "approx",
+];
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&nbsp;", " ")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#039;", "'")
+        .replace("&apos;", "'")
+        .replace("&frac12;", "½")
+        .replace("&frac14;", "¼")
+        .replace("&frac34;", "¾")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+fn strip_tags(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => stripped.push(c),
+            _ => {}
+        }
+    }
+    stripped
+}
+
+fn clean_text(text: &str) -> String {
+    decode_entities(&strip_tags(text))
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// The contents of every <script type="application/ld+json"> block. Pages are often
+// not well-formed, so this searches the text rather than parsing the document.
+fn json_ld_blocks(html: &str) -> Vec<&str> {
+    let lower = html.to_ascii_lowercase();
+    let mut blocks = Vec::new();
+    let mut offset = 0;
+    while let Some(start) = lower[offset..].find("<script") {
+        let start = offset + start;
+        let Some(tag_end) = lower[start..].find('>').map(|i| start + i + 1) else {
+            break;
+        };
+        let Some(end) = lower[tag_end..].find("</script").map(|i| tag_end + i) else {
+            break;
+        };
+        if lower[start..tag_end].contains(SCRIPT_TYPE) {
+            blocks.push(&html[tag_end..end]);
+        }
+        offset = end;
+    }
+    blocks
+}
+
+fn is_recipe(value: &Value) -> bool {
+    match &value["@type"] {
+        Value::String(kind) => kind == "Recipe",
+        Value::Array(kinds) => kinds.iter().any(|kind| kind == "Recipe"),
+        _ => false,
+    }
+}
+
+// Recipes can sit at the top level, in a list, or inside an @graph.
+fn find_recipe(value: &Value) -> Option<&Value> {
+    match value {
+        Value::Object(_) if is_recipe(value) => Some(value),
+        Value::Object(object) => object.get("@graph").and_then(find_recipe),
+        Value::Array(values) => values.iter().find_map(find_recipe),
+        _ => None,
+    }
+}
+
+fn text_value(value: &Value) -> Option<String> {
+    let text = match value {
+        Value::String(text) => clean_text(text),
+        Value::Number(number) => number.to_string(),
+        Value::Array(values) => return values.iter().find_map(text_value),
+        _ => return None,
+    };
+    Some(text).filter(|text| !text.is_empty())
+}
+
+// "4", "Serves 4", "4-6 servings" all give 4. Yields outside 1 to 100 are ignored,
+// as the page could otherwise scale quantities to nothing or past any limit.
+fn servings(recipe_yield: &str) -> Option<Decimal> {
+    recipe_yield
+        .split(|c: char| !c.is_ascii_digit() && c != '.')
+        .find_map(|part| Decimal::from_str(part).ok())
+        .filter(|servings| (Decimal::ONE..=Decimal::ONE_HUNDRED).contains(servings))
+}
+
+// Notes after a comma and anything in brackets are dropped, so
+// "1 (400g) tin chopped tomatoes, drained" becomes "1 tin tomatoes". The bracketed
+// weight is read by metric_amount.
+pub fn clean_ingredient(line: &str) -> String {
+    let line = line.split(',').next().unwrap_or_default().to_lowercase();
+    let mut cleaned = String::with_capacity(line.len());
+    let mut depth = 0;
+    for c in line.chars() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = (depth - 1).max(0),
+            _ if depth == 0 => cleaned.push(c),
+            _ => {}
+        }
+    }
+    cleaned
+        .split_whitespace()
+        .filter(|word| !DESCRIPTORS.contains(word))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Recipes often give a weight or volume in brackets beside a count or cup measure,
+// as in "1 (400g) tin" or "1 1/2 cups (180 g / 6 oz)", and it is the reliable amount.
+pub fn metric_amount(line: &str) -> Option<(Decimal, Unit)> {
+    let line = line.split(',').next().unwrap_or_default().to_lowercase();
+    line.split(['(', '['])
+        .skip(1)
+        .filter_map(|group| group.split([')', ']']).next())
+        .flat_map(|group| group.split('/'))
+        .find_map(|part| {
+            let words: Vec<&str> = part.split_whitespace().collect();
+            (0..words.len()).find_map(|i| parse_amount(&words[i..].join(" ")))
+        })
+}
+
+pub fn parse_recipe(html: &str) -> Result<ParsedRecipe, AppError> {
+    let recipe = json_ld_blocks(html)
+        .into_iter()
+        .filter_map(|block| serde_json::from_str::<Value>(block.trim()).ok())
+        .find_map(|value| find_recipe(&value).cloned())
+        .ok_or(AppError::BadRequestMessage(String::from(
+            "No schema.org Recipe found in the page",
+        )))?;
+    // recipeIngredient replaced the older ingredients property.
+    let ingredients: Vec<String> = match recipe
+        .get("recipeIngredient")
+        .or_else(|| recipe.get("ingredients"))
+    {
+        Some(Value::Array(lines)) => lines.iter().filter_map(text_value).collect(),
+        Some(Value::String(lines)) => lines.lines().map(clean_text).collect(),
+        _ => Vec::new(),
+    };
+    let ingredients: Vec<String> = ingredients
+        .into_iter()
+        .filter(|line| !line.is_empty())
+        .collect();
+    if ingredients.is_empty() {
+        return Err(AppError::BadRequestMessage(String::from(
+            "The recipe has no ingredients",
+        )));
+    }
+    let recipe_yield = recipe.get("recipeYield").and_then(text_value);
+    Ok(ParsedRecipe {
+        name: recipe
+            .get("name")
+            .and_then(text_value)
+            .unwrap_or_else(|| String::from("Imported recipe")),
+        servings: recipe_yield.as_deref().and_then(servings),
+        recipe_yield,
+        ingredients,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    const PAGE: &str = r#"<html><head>
+        <script type="application/ld+json">{"@type": "WebSite", "name": "Cooking"}</script>
+        <SCRIPT type="application/ld+json">
+        {"@context": "https://schema.org", "@graph": [
+            {"@type": "Organization", "name": "Cooking"},
+            {"@type": ["Recipe"], "name": "Mac &amp; Cheese",
+             "recipeYield": ["4", "4 servings"],
+             "recipeIngredient": ["250g macaroni", "2 cups <b>grated</b> cheddar", " "]}
+        ]}
+        </SCRIPT></head><body></body></html>"#;
+
+    #[test]
+    fn test_parse_recipe_in_graph() {
+        let recipe = parse_recipe(PAGE).unwrap();
+        assert_eq!(recipe.name, "Mac & Cheese");
+        assert_eq!(recipe.recipe_yield.as_deref(), Some("4"));
+        assert_eq!(recipe.servings, Some(dec!(4)));
+        assert_eq!(
+            recipe.ingredients,
+            vec!["250g macaroni", "2 cups grated cheddar"]
+        );
+    }
+
+    #[test]
+    fn test_parse_recipe_missing() {
+        assert!(parse_recipe("<html><body>No recipe</body></html>").is_err());
+    }
+
+    #[test]
+    fn test_servings_and_clean_ingredient() {
+        assert_eq!(servings("Serves 4-6"), Some(dec!(4)));
+        assert_eq!(servings("a crowd"), None);
+        assert_eq!(servings("0.0000000000000000000000001"), None);
+        assert_eq!(servings("Serves 1000"), None);
+        assert_eq!(
+            clean_ingredient("1 (400g) tin Chopped Tomatoes, drained"),
+            "1 tin tomatoes"
+        );
+        assert_eq!(clean_ingredient("2 large eggs, beaten"), "2 eggs");
+    }
+
+    #[test]
+    fn test_metric_amount() {
+        assert_eq!(
+            metric_amount("1 (400g) tin tomatoes"),
+            Some((dec!(400), Unit::Gram))
+        );
+        assert_eq!(
+            metric_amount("1 cup milk (about 240 ml)"),
+            Some((dec!(240), Unit::Millilitre))
+        );
+        assert_eq!(
+            metric_amount("1 1/2 cups flour (180g / 6 oz)"),
+            Some((dec!(180), Unit::Gram))
+        );
+        assert_eq!(
+            metric_amount("1 (14 oz) can beans"),
+            Some((dec!(396.90), Unit::Gram))
+        );
+        assert_eq!(metric_amount("2 eggs (large)"), None);
+        assert_eq!(metric_amount("2 eggs, (optional 50g)"), None);
+    }
+}
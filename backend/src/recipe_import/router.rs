@@ -0,0 +1,13 @@
+use axum::{extract::DefaultBodyLimit, routing::post, Router};
+use std::sync::Arc;
+
+use crate::AppState;
+
+use super::view::{recipe_import_view, MAX_IMPORT_SIZE};
+
+pub fn recipe_import_router() -> Router<Arc<AppState>> {
+    Router::new().route(
+        "/",
+        post(recipe_import_view).layer(DefaultBodyLimit::max(MAX_IMPORT_SIZE + 1024 * 64)),
+    )
+}
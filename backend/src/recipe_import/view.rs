@@ -0,0 +1,64 @@
+use axum::{
+    extract::{Multipart, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use std::sync::Arc;
+
+use crate::{error::AppError, middleware::RequestUser, AppState};
+
+use super::{
+    model::{import_recipe, RecipeImportOptions, RecipeImportReport},
+    parser::parse_recipe,
+};
+
+pub const MAX_IMPORT_SIZE: usize = 5 * 1024 * 1024;
+
+// Takes a saved page as a file, or its source pasted into the html field. The page is
+// only read for its JSON-LD, nothing is fetched. Pass dry_run=true to preview the
+// matches without creating the meal, and per_serving=false to save the whole recipe.
+pub async fn recipe_import_view(
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<RecipeImportReport>), AppError> {
+    request_user.login_required()?;
+
+    let mut html = None;
+    let mut options = RecipeImportOptions {
+        name: None,
+        per_serving: true,
+        dry_run: false,
+    };
+    while let Some(field) = multipart.next_field().await? {
+        match field.name() {
+            Some("file") => {
+                html = Some(String::from_utf8_lossy(&field.bytes().await?).into_owned())
+            }
+            Some("html") => html = Some(field.text().await?),
+            Some("name") => options.name = Some(field.text().await?),
+            Some("per_serving") => {
+                options.per_serving = !matches!(field.text().await?.trim(), "false" | "0")
+            }
+            Some("dry_run") => options.dry_run = matches!(field.text().await?.trim(), "true" | "1"),
+            _ => {}
+        }
+    }
+    let html = html.ok_or(AppError::BadRequestMessage(String::from(
+        "An HTML file or pasted page is required",
+    )))?;
+    if html.len() > MAX_IMPORT_SIZE {
+        return Err(AppError::BadRequestMessage(String::from(
+            "Page must be 5MB or smaller",
+        )));
+    }
+
+    let parsed = parse_recipe(&html)?;
+    let report = import_recipe(&state.pool, &request_user, parsed, options).await?;
+    let status = if report.meal.is_some() {
+        StatusCode::CREATED
+    } else {
+        StatusCode::OK
+    };
+    Ok((status, Json(report)))
+}
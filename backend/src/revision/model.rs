@@ -0,0 +1,234 @@
+use chrono::prelude::*;
+use futures::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{FromRow, PgConnection, PgPool, Row};
+use uuid::Uuid;
+
+use crate::{db::Filters, util::query::QueryParams};
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RevisionTable {
+    Food,
+    Brand,
+    Movement,
+    MuscleGroup,
+}
+
+impl RevisionTable {
+    fn table_name(self) -> &'static str {
+        match self {
+            Self::Food => "food",
+            Self::Brand => "food_brand",
+            Self::Movement => "movement",
+            Self::MuscleGroup => "muscle_group",
+        }
+    }
+    // The user editable columns; a revert leaves ownership, review state and
+    // uploaded files alone.
+    fn revert_columns(self) -> &'static str {
+        match self {
+            Self::Food => {
+                "name, slug, brand_id, data_value, data_measurement, energy, fat, saturates, \
+                carbohydrate, sugars, fibre, protein, salt"
+            }
+            Self::Brand => "name, slug",
+            Self::Movement => "name, slug, muscle_group_id",
+            Self::MuscleGroup => "name, slug",
+        }
+    }
+    // The reverted column that points at another catalogue row, with that table.
+    pub fn reference(self) -> Option<(&'static str, &'static str)> {
+        match self {
+            Self::Food => Some(("brand_id", "food_brand")),
+            Self::Movement => Some(("muscle_group_id", "muscle_group")),
+            Self::Brand | Self::MuscleGroup => None,
+        }
+    }
+}
+
+// Names who made the catalogue writes of the current transaction, for the revision
+// trigger. Deletes and reassignments don't stamp updated_by_id, so the row alone
+// cannot say.
+pub async fn set_actor(conn: &mut PgConnection, actor_id: &Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT set_config('app.actor_id', $1, TRUE)")
+        .bind(actor_id.to_string())
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct Revision {
+    pub id: Uuid,
+    pub table_name: String,
+    pub row_id: Uuid,
+    pub version: i32,
+    pub action: String,
+    pub data: Value,
+    pub diff: Option<Value>,
+    pub created_at: DateTime<Utc>,
+    pub created_by_id: Option<Uuid>,
+    pub created_by: Option<String>,
+}
+
+impl Revision {
+    pub async fn count(
+        pool: &PgPool,
+        table: RevisionTable,
+        row_id: &Uuid,
+    ) -> Result<i64, sqlx::Error> {
+        let count =
+            sqlx::query("SELECT COUNT(*) FROM revision WHERE table_name = $1 AND row_id = $2")
+                .bind(table.table_name())
+                .bind(row_id)
+                .fetch_one(pool)
+                .await?
+                .get("count");
+        Ok(count)
+    }
+    pub async fn all(
+        pool: &PgPool,
+        table: RevisionTable,
+        row_id: &Uuid,
+        params: &QueryParams,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let mut stream = Vec::new();
+        let mut q = sqlx::QueryBuilder::new(
+            "
+            SELECT
+                t1.*,
+                t2.username AS created_by
+            FROM
+                revision t1
+                LEFT JOIN users_user t2 ON t2.id = t1.created_by_id
+            WHERE
+                t1.table_name =
+            ",
+        );
+        q.push_bind(table.table_name());
+        q.push(" AND t1.row_id = ");
+        q.push_bind(*row_id);
+        q.push(" ORDER BY t1.version DESC");
+        q.paginate(params.page, params.size);
+        let mut rows = q.build_query_as().fetch(pool);
+        while let Some(row) = rows.try_next().await? {
+            stream.push(row);
+        }
+        Ok(stream)
+    }
+    pub async fn get(
+        conn: &mut PgConnection,
+        table: RevisionTable,
+        row_id: &Uuid,
+        version: i32,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let query = sqlx::query_as(
+            "
+            SELECT
+                t1.*,
+                t2.username AS created_by
+            FROM
+                revision t1
+                LEFT JOIN users_user t2 ON t2.id = t1.created_by_id
+            WHERE
+                t1.table_name = $1
+                AND t1.row_id = $2
+                AND t1.version = $3
+            ",
+        )
+        .bind(table.table_name())
+        .bind(row_id)
+        .bind(version)
+        .fetch_optional(conn)
+        .await?;
+        Ok(query)
+    }
+    pub async fn latest(
+        conn: &mut PgConnection,
+        table: RevisionTable,
+        row_id: &Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let query = sqlx::query_as(
+            "
+            SELECT
+                t1.*,
+                t2.username AS created_by
+            FROM
+                revision t1
+                LEFT JOIN users_user t2 ON t2.id = t1.created_by_id
+            WHERE
+                t1.table_name = $1
+                AND t1.row_id = $2
+            ORDER BY
+                t1.version DESC
+            LIMIT
+                1
+            ",
+        )
+        .bind(table.table_name())
+        .bind(row_id)
+        .fetch_optional(conn)
+        .await?;
+        Ok(query)
+    }
+    // A snapshot may point at a brand or muscle group that has since been deleted.
+    pub async fn reference_exists(
+        &self,
+        conn: &mut PgConnection,
+        table: RevisionTable,
+    ) -> Result<bool, sqlx::Error> {
+        let Some((column, ref_table)) = table.reference() else {
+            return Ok(true);
+        };
+        let query = sqlx::query(&format!(
+            "
+            SELECT
+                $1::JSONB ->> '{column}' IS NULL
+                OR EXISTS (
+                    SELECT 1 FROM {ref_table} WHERE id = ($1::JSONB ->> '{column}')::UUID
+                ) AS exists
+            "
+        ))
+        .bind(&self.data)
+        .fetch_one(conn)
+        .await?
+        .get("exists");
+        Ok(query)
+    }
+    // Writes the snapshot back onto the row. The revision trigger records the revert
+    // as a new version, so history is never rewritten.
+    pub async fn revert(
+        &self,
+        conn: &mut PgConnection,
+        table: RevisionTable,
+        updated_by_id: &Uuid,
+    ) -> Result<(), sqlx::Error> {
+        let table_name = table.table_name();
+        let columns = table.revert_columns();
+        sqlx::query(&format!(
+            "
+            UPDATE {table_name}
+            SET
+                ({columns}) = (
+                    SELECT
+                        {columns}
+                    FROM
+                        jsonb_populate_record(NULL::{table_name}, $1)
+                ),
+                updated_at = $2,
+                updated_by_id = $3
+            WHERE
+                id = $4
+            "
+        ))
+        .bind(&self.data)
+        .bind(Utc::now())
+        .bind(updated_by_id)
+        .bind(self.row_id)
+        .execute(conn)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,16 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::AppState;
+
+use super::view::{revision_detail_view, revision_list_view, revision_revert_view};
+
+pub fn revision_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/:table/:id", get(revision_list_view))
+        .route("/:table/:id/:version", get(revision_detail_view))
+        .route("/:table/:id/:version/revert", post(revision_revert_view))
+}
@@ -0,0 +1,74 @@
+use axum::{
+    extract::{Path, Query, State},
+    Extension, Json,
+};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{error::AppError, middleware::RequestUser, util::query::QueryParams, AppState};
+
+use super::model::{set_actor, Revision, RevisionTable};
+
+pub async fn revision_list_view(
+    Path((table, row_id)): Path<(RevisionTable, Uuid)>,
+    Query(params): Query<QueryParams>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<Value>, AppError> {
+    request_user.staff_required()?;
+    let count = Revision::count(&state.pool, table, &row_id).await?;
+    let query = Revision::all(&state.pool, table, &row_id, &params).await?;
+    let response = json!({"count": count, "results": query});
+    Ok(Json(response))
+}
+
+pub async fn revision_detail_view(
+    Path((table, row_id, version)): Path<(RevisionTable, Uuid, i32)>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<Revision>, AppError> {
+    request_user.staff_required()?;
+    let mut conn = state.pool.acquire().await?;
+    let query = Revision::get(&mut conn, table, &row_id, version)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    Ok(Json(query))
+}
+
+pub async fn revision_revert_view(
+    Path((table, row_id, version)): Path<(RevisionTable, Uuid, i32)>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<Revision>, AppError> {
+    request_user.staff_required()?;
+    let mut tx = state.pool.begin().await?;
+    let revision = Revision::get(&mut tx, table, &row_id, version)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    let latest = Revision::latest(&mut tx, table, &row_id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    if latest.action == "delete" {
+        return Err(AppError::BadRequestMessage(String::from(
+            "Deleted rows cannot be reverted",
+        )));
+    }
+    if revision.action == "delete" {
+        return Err(AppError::BadRequestMessage(String::from(
+            "Cannot revert to a deletion",
+        )));
+    }
+    if !revision.reference_exists(&mut tx, table).await? {
+        return Err(AppError::BadRequestMessage(String::from(
+            "The brand or muscle group of this revision no longer exists",
+        )));
+    }
+    set_actor(&mut tx, &request_user.id).await?;
+    revision.revert(&mut tx, table, &request_user.id).await?;
+    let query = Revision::latest(&mut tx, table, &row_id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    tx.commit().await?;
+    Ok(Json(query))
+}
@@ -5,7 +5,7 @@ use serde::Serialize;
 use sqlx::{FromRow, PgPool, Row};
 use uuid::Uuid;
 
-use crate::{db::Filters, util::query::QueryParams};
+use crate::{db::Filters, personal_record::model::PersonalRecord, util::query::QueryParams};
 
 use super::serializer::{SetInput, SetRangeInput};
 
@@ -39,7 +39,8 @@ impl Set {
         data: &SetInput,
         created_by_id: &Uuid,
     ) -> Result<Self, sqlx::Error> {
-        let query = sqlx::query_as(
+        let mut tx = pool.begin().await?;
+        let query: Self = sqlx::query_as(
             "
             INSERT INTO
             tracked_set (exercise_id, weight, reps, rest, notes, created_by_id)
@@ -55,8 +56,10 @@ impl Set {
         .bind(&data.rest)
         .bind(&data.notes)
         .bind(created_by_id)
-        .fetch_one(pool)
+        .fetch_one(&mut *tx)
         .await?;
+        PersonalRecord::detect(&mut tx, &[query.id]).await?;
+        tx.commit().await?;
         Ok(query)
     }
     pub async fn get(pool: &PgPool, id: &Uuid) -> Result<Self, sqlx::Error> {
@@ -73,7 +76,12 @@ impl Set {
         updated_by_id: &Uuid,
     ) -> Result<Self, sqlx::Error> {
         let updated_at = Utc::now();
-        let query = sqlx::query_as(
+        let mut tx = pool.begin().await?;
+        let previous: Self = sqlx::query_as("SELECT * FROM tracked_set WHERE id = $1")
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await?;
+        let query: Self = sqlx::query_as(
             r#"
             UPDATE tracked_set
             SET
@@ -100,22 +108,30 @@ impl Set {
         .bind(updated_at)
         .bind(updated_by_id)
         .bind(id)
-        .fetch_one(pool)
+        .fetch_one(&mut *tx)
         .await?;
+        PersonalRecord::redetect(&mut tx, &[query.id]).await?;
+        PersonalRecord::recompute_volume(&mut tx, &[previous.exercise_id, query.exercise_id])
+            .await?;
+        tx.commit().await?;
         Ok(query)
     }
     pub async fn delete(pool: &PgPool, id: &Uuid) -> Result<Self, sqlx::Error> {
-        let query = sqlx::query_as("DELETE FROM tracked_set WHERE id = $1 RETURNING *")
+        let mut tx = pool.begin().await?;
+        let query: Self = sqlx::query_as("DELETE FROM tracked_set WHERE id = $1 RETURNING *")
             .bind(id)
-            .fetch_one(pool)
+            .fetch_one(&mut *tx)
             .await?;
+        PersonalRecord::recompute_volume(&mut tx, &[query.exercise_id]).await?;
+        tx.commit().await?;
         Ok(query)
     }
     pub async fn delete_id_range(
         pool: &PgPool,
         id_range: Vec<Uuid>,
     ) -> Result<Vec<Self>, sqlx::Error> {
-        let query = sqlx::query_as(
+        let mut tx = pool.begin().await?;
+        let query: Vec<Self> = sqlx::query_as(
             "
             DELETE FROM tracked_set
             WHERE
@@ -125,8 +141,11 @@ impl Set {
             ",
         )
         .bind(id_range)
-        .fetch_all(pool)
+        .fetch_all(&mut *tx)
         .await?;
+        let exercise_ids: Vec<Uuid> = query.iter().map(|set| set.exercise_id).collect();
+        PersonalRecord::recompute_volume(&mut tx, &exercise_ids).await?;
+        tx.commit().await?;
         Ok(query)
     }
     pub async fn get_last_added(
@@ -175,7 +194,8 @@ impl Set {
             created_by_id_list.push(created_by_id);
         }
 
-        let query = sqlx::query_as(
+        let mut tx = pool.begin().await?;
+        let query: Vec<Self> = sqlx::query_as(
             r#"
             INSERT INTO
             tracked_set (exercise_id, "order", weight, reps, rest, notes, created_by_id)
@@ -202,8 +222,11 @@ impl Set {
         .bind(&rest_list)
         .bind(&notes_list)
         .bind(&created_by_id_list)
-        .fetch_all(pool)
+        .fetch_all(&mut *tx)
         .await?;
+        let set_ids: Vec<Uuid> = query.iter().map(|set| set.id).collect();
+        PersonalRecord::detect(&mut tx, &set_ids).await?;
+        tx.commit().await?;
         Ok(query)
     }
 }
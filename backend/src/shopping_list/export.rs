@@ -0,0 +1,79 @@
+use std::fmt::Write;
+
+use super::model::ShoppingList;
+
+pub fn to_text(list: &ShoppingList) -> String {
+    let mut text = format!("Shopping list {} to {}\n", list.date_from, list.date_to);
+    for brand in &list.brands {
+        let _ = write!(text, "\n{}\n", brand.brand_name);
+        for item in &brand.items {
+            let _ = writeln!(
+                text,
+                "- {}: {} {}",
+                item.food_name, item.quantity, item.unit
+            );
+        }
+    }
+    text
+}
+
+pub fn to_csv(list: &ShoppingList) -> Result<Vec<u8>, csv::Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(["brand", "food", "quantity", "unit"])?;
+    for brand in &list.brands {
+        for item in &brand.items {
+            writer.write_record([
+                brand.brand_name.as_str(),
+                item.food_name.as_str(),
+                &item.quantity.to_string(),
+                item.unit.as_str(),
+            ])?;
+        }
+    }
+    writer
+        .into_inner()
+        .map_err(|err| csv::Error::from(err.into_error()))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::prelude::*;
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::shopping_list::model::ShoppingListItem;
+
+    fn list() -> ShoppingList {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let item = ShoppingListItem {
+            food_id: Uuid::nil(),
+            food_name: String::from("Oats, rolled"),
+            brand_id: Uuid::nil(),
+            brand_name: String::from("Test Brand"),
+            data_measurement: String::from("g"),
+            amount: dec!(1500),
+            entry_count: 3,
+            quantity: dec!(0),
+            unit: String::new(),
+        };
+        ShoppingList::build(date, date + chrono::Duration::days(2), vec![item])
+    }
+
+    #[test]
+    fn test_to_text() {
+        assert_eq!(
+            to_text(&list()),
+            "Shopping list 2024-01-01 to 2024-01-03\n\nTest Brand\n- Oats, rolled: 1.5 kg\n"
+        );
+    }
+
+    #[test]
+    fn test_to_csv_quotes_fields() {
+        let csv = String::from_utf8(to_csv(&list()).unwrap()).unwrap();
+        assert_eq!(
+            csv,
+            "brand,food,quantity,unit\nTest Brand,\"Oats, rolled\",1.5,kg\n"
+        );
+    }
+}
@@ -0,0 +1,5 @@
+pub mod export;
+pub mod model;
+pub mod router;
+pub mod serializer;
+pub mod view;
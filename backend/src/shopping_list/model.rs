@@ -0,0 +1,217 @@
+use chrono::prelude::*;
+use futures::TryStreamExt;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ShoppingListItem {
+    pub food_id: Uuid,
+    pub food_name: String,
+    pub brand_id: Uuid,
+    pub brand_name: String,
+    pub data_measurement: String,
+    // Total in the food's own measurement, e.g. grams for a "g" food.
+    pub amount: Decimal,
+    pub entry_count: i64,
+    #[sqlx(skip)]
+    pub quantity: Decimal,
+    #[sqlx(skip)]
+    pub unit: String,
+}
+
+impl ShoppingListItem {
+    // Quick-add entries have no food to buy and are left off the list.
+    pub async fn from_planned(
+        pool: &PgPool,
+        user_id: &Uuid,
+        date_from: &NaiveDate,
+        date_to: &NaiveDate,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let mut stream = Vec::new();
+        let mut rows = sqlx::query_as(
+            "
+            SELECT
+                t2.id AS food_id,
+                t2.name AS food_name,
+                t3.id AS brand_id,
+                t3.name AS brand_name,
+                t2.data_measurement,
+                SUM(t1.quantity * t2.data_value) AS amount,
+                COUNT(*) AS entry_count
+            FROM
+                planned_food t1
+                JOIN food t2 ON t2.id = t1.food_id
+                JOIN food_brand t3 ON t3.id = t2.brand_id
+            WHERE
+                t1.user_id = $1
+                AND t1.date BETWEEN $2 AND $3
+                AND t1.food_log_id IS NULL
+            GROUP BY
+                t2.id,
+                t3.id
+            ORDER BY
+                t3.name,
+                t3.id,
+                t2.name
+            ",
+        )
+        .bind(user_id)
+        .bind(date_from)
+        .bind(date_to)
+        .fetch(pool);
+        while let Some(row) = rows.try_next().await? {
+            stream.push(row);
+        }
+        Ok(stream)
+    }
+    // Each meal is counted once per day; a meal listed twice is bought twice.
+    pub async fn from_meals(
+        pool: &PgPool,
+        meal_ids: &[Uuid],
+        days: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let mut stream = Vec::new();
+        let mut rows = sqlx::query_as(
+            "
+            SELECT
+                t2.id AS food_id,
+                t2.name AS food_name,
+                t3.id AS brand_id,
+                t3.name AS brand_name,
+                t2.data_measurement,
+                SUM(t1.quantity * t2.data_value) * $2 AS amount,
+                COUNT(*) * $2 AS entry_count
+            FROM
+                UNNEST($1::UUID[]) t4 (meal_id)
+                JOIN meal_food t1 ON t1.meal_id = t4.meal_id
+                JOIN food t2 ON t2.id = t1.food_id
+                JOIN food_brand t3 ON t3.id = t2.brand_id
+            GROUP BY
+                t2.id,
+                t3.id
+            ORDER BY
+                t3.name,
+                t3.id,
+                t2.name
+            ",
+        )
+        .bind(meal_ids)
+        .bind(days)
+        .fetch(pool);
+        while let Some(row) = rows.try_next().await? {
+            stream.push(row);
+        }
+        Ok(stream)
+    }
+}
+
+// Grams and millilitres move up to kilograms and litres from 1000, servings are
+// left as they are.
+pub fn convert_unit(amount: Decimal, data_measurement: &str) -> (Decimal, String) {
+    let (quantity, unit) = match data_measurement {
+        "g" if amount >= dec!(1000) => (amount / dec!(1000), "kg"),
+        "ml" if amount >= dec!(1000) => (amount / dec!(1000), "l"),
+        "g" | "ml" => (amount.round(), data_measurement),
+        _ => (amount, data_measurement),
+    };
+    (quantity.round_dp(2).normalize(), unit.to_string())
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShoppingListBrand {
+    pub brand_id: Uuid,
+    pub brand_name: String,
+    pub items: Vec<ShoppingListItem>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShoppingList {
+    pub date_from: NaiveDate,
+    pub date_to: NaiveDate,
+    pub item_count: usize,
+    pub brands: Vec<ShoppingListBrand>,
+}
+
+impl ShoppingList {
+    // Items arrive ordered by brand name, so each brand is one consecutive run.
+    pub fn build(date_from: NaiveDate, date_to: NaiveDate, items: Vec<ShoppingListItem>) -> Self {
+        let item_count = items.len();
+        let mut brands: Vec<ShoppingListBrand> = Vec::new();
+        for mut item in items {
+            (item.quantity, item.unit) = convert_unit(item.amount, &item.data_measurement);
+            match brands.last_mut() {
+                Some(brand) if brand.brand_id == item.brand_id => brand.items.push(item),
+                _ => brands.push(ShoppingListBrand {
+                    brand_id: item.brand_id,
+                    brand_name: item.brand_name.clone(),
+                    items: vec![item],
+                }),
+            }
+        }
+        Self {
+            date_from,
+            date_to,
+            item_count,
+            brands,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(brand: u128, brand_name: &str, food_name: &str, amount: Decimal) -> ShoppingListItem {
+        ShoppingListItem {
+            food_id: Uuid::new_v4(),
+            food_name: food_name.to_string(),
+            brand_id: Uuid::from_u128(brand),
+            brand_name: brand_name.to_string(),
+            data_measurement: String::from("g"),
+            amount,
+            entry_count: 1,
+            quantity: Decimal::ZERO,
+            unit: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_convert_unit() {
+        assert_eq!(
+            convert_unit(dec!(250.40), "g"),
+            (dec!(250), String::from("g"))
+        );
+        assert_eq!(
+            convert_unit(dec!(1500), "g"),
+            (dec!(1.5), String::from("kg"))
+        );
+        assert_eq!(convert_unit(dec!(2000), "ml"), (dec!(2), String::from("l")));
+        assert_eq!(
+            convert_unit(dec!(1.50), "srv"),
+            (dec!(1.5), String::from("srv"))
+        );
+    }
+
+    #[test]
+    fn test_build_groups_by_brand() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let list = ShoppingList::build(
+            date,
+            date,
+            vec![
+                item(1, "Brand A", "Oats", dec!(1200)),
+                item(1, "Brand A", "Rice", dec!(300)),
+                item(2, "Brand B", "Milk", dec!(500)),
+            ],
+        );
+        assert_eq!(list.item_count, 3);
+        assert_eq!(list.brands.len(), 2);
+        assert_eq!(list.brands[0].items.len(), 2);
+        assert_eq!(list.brands[0].items[0].quantity, dec!(1.2));
+        assert_eq!(list.brands[0].items[0].unit, "kg");
+        assert_eq!(list.brands[1].brand_name, "Brand B");
+    }
+}
@@ -0,0 +1,14 @@
+use axum::{
+    routing::{get, post},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::shopping_list::view::{shopping_list_meal_view, shopping_list_planned_view};
+use crate::AppState;
+
+pub fn shopping_list_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(shopping_list_planned_view))
+        .route("/meal", post(shopping_list_meal_view))
+}
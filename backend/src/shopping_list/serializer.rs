@@ -0,0 +1,35 @@
+use chrono::prelude::*;
+use serde::Deserialize;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::util::query::empty_string_as_none;
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShoppingListFormat {
+    #[default]
+    Json,
+    Text,
+    Csv,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ShoppingListParams {
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    pub date_from: Option<NaiveDate>,
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    pub date_to: Option<NaiveDate>,
+    #[serde(default)]
+    pub format: ShoppingListFormat,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ShoppingListMealInput {
+    #[validate(length(min = 1, max = 20, message = "Choose between 1 and 20 meals"))]
+    pub meal_id: Vec<Uuid>,
+    pub date_from: NaiveDate,
+    pub date_to: NaiveDate,
+    #[serde(default)]
+    pub format: ShoppingListFormat,
+}
@@ -0,0 +1,95 @@
+use axum::{
+    extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::{prelude::*, Duration};
+use std::sync::Arc;
+
+use crate::{error::AppError, extractor::JsonExtractor, middleware::RequestUser, AppState};
+
+use super::{
+    export::{to_csv, to_text},
+    model::{ShoppingList, ShoppingListItem},
+    serializer::{ShoppingListFormat, ShoppingListMealInput, ShoppingListParams},
+};
+
+const MAX_SHOPPING_DAYS: i64 = 31;
+
+fn days_in_range(date_from: NaiveDate, date_to: NaiveDate) -> Result<i64, AppError> {
+    let days = (date_to - date_from).num_days() + 1;
+    if days < 1 {
+        return Err(AppError::BadRequestMessage(String::from(
+            "Date to must be on or after date from.",
+        )));
+    }
+    if days > MAX_SHOPPING_DAYS {
+        return Err(AppError::BadRequestMessage(String::from(
+            "Shopping lists can cover at most 31 days.",
+        )));
+    }
+    Ok(days)
+}
+
+fn shopping_list_response(
+    list: ShoppingList,
+    format: ShoppingListFormat,
+) -> Result<Response, AppError> {
+    let response = match format {
+        ShoppingListFormat::Json => Json(list).into_response(),
+        ShoppingListFormat::Text => (
+            [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+            to_text(&list),
+        )
+            .into_response(),
+        ShoppingListFormat::Csv => {
+            let filename = format!("shopping-list-{}.csv", list.date_from.format("%Y%m%d"));
+            let headers = [
+                (
+                    header::CONTENT_TYPE,
+                    String::from("text/csv; charset=utf-8"),
+                ),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{}\"", filename),
+                ),
+            ];
+            (headers, to_csv(&list)?).into_response()
+        }
+    };
+    Ok(response)
+}
+
+// Built from the request user's planned entries that are not yet eaten, for the
+// coming week unless a range is given.
+pub async fn shopping_list_planned_view(
+    Query(params): Query<ShoppingListParams>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Response, AppError> {
+    request_user.login_required()?;
+    let date_from = params.date_from.unwrap_or_else(|| Utc::now().date_naive());
+    let date_to = params.date_to.unwrap_or(date_from + Duration::days(6));
+    days_in_range(date_from, date_to)?;
+    let items =
+        ShoppingListItem::from_planned(&state.pool, &request_user.id, &date_from, &date_to).await?;
+    shopping_list_response(
+        ShoppingList::build(date_from, date_to, items),
+        params.format,
+    )
+}
+
+pub async fn shopping_list_meal_view(
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    JsonExtractor(data): JsonExtractor<ShoppingListMealInput>,
+) -> Result<Response, AppError> {
+    request_user.login_required()?;
+    let days = days_in_range(data.date_from, data.date_to)?;
+    let items = ShoppingListItem::from_meals(&state.pool, &data.meal_id, days).await?;
+    shopping_list_response(
+        ShoppingList::build(data.date_from, data.date_to, items),
+        data.format,
+    )
+}
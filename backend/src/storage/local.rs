@@ -0,0 +1,59 @@
+use async_trait::async_trait;
+use std::path::{Component, Path, PathBuf};
+use tokio::fs;
+
+use super::{Storage, StorageError};
+
+#[derive(Debug)]
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn path(&self, key: &str) -> Result<PathBuf, StorageError> {
+        let key = Path::new(key);
+        // Keys are generated by the server, but never allow them to escape the root.
+        if !key.components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(StorageError::InvalidKey);
+        }
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, _content_type: &str, data: Vec<u8>) -> Result<(), StorageError> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        fs::write(path, data).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+        let path = self.path(key)?;
+        match fs::read(path).await {
+            Ok(data) => Ok(data),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Err(StorageError::NotFound),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        let path = self.path(key)?;
+        match fs::remove_file(path).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
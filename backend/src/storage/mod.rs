@@ -0,0 +1,55 @@
+use async_trait::async_trait;
+use std::{env, fmt::Debug, sync::Arc};
+
+pub mod local;
+pub mod s3;
+
+use self::{local::LocalStorage, s3::S3Storage};
+
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    S3(#[from] ::s3::error::S3Error),
+    #[error("storage request failed with status {0}")]
+    Status(u16),
+    #[error("invalid storage key")]
+    InvalidKey,
+    #[error("object not found")]
+    NotFound,
+}
+
+#[async_trait]
+pub trait Storage: Debug + Send + Sync {
+    async fn put(&self, key: &str, content_type: &str, data: Vec<u8>) -> Result<(), StorageError>;
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError>;
+    async fn delete(&self, key: &str) -> Result<(), StorageError>;
+}
+
+pub fn storage_from_env() -> Arc<dyn Storage> {
+    let backend = env::var("STORAGE_BACKEND").unwrap_or_else(|_| String::from("local"));
+    match backend.as_str() {
+        "s3" => {
+            let bucket = env::var("S3_BUCKET").expect("S3_BUCKET must be set");
+            let endpoint = env::var("S3_ENDPOINT").expect("S3_ENDPOINT must be set");
+            let region = env::var("S3_REGION").unwrap_or_else(|_| String::from("us-east-1"));
+            let access_key = env::var("S3_ACCESS_KEY").expect("S3_ACCESS_KEY must be set");
+            let secret_key = env::var("S3_SECRET_KEY").expect("S3_SECRET_KEY must be set");
+            let storage = S3Storage::new(&bucket, &endpoint, &region, &access_key, &secret_key)
+                .expect("could not configure s3 storage");
+            Arc::new(storage)
+        }
+        _ => {
+            let root = env::var("MEDIA_ROOT").unwrap_or_else(|_| String::from("media"));
+            Arc::new(LocalStorage::new(root))
+        }
+    }
+}
+
+// Brand logos are served from disk by tower-http once the logo view has checked the
+// brand, so they always live locally, whichever backend holds private uploads.
+pub fn brand_logo_storage_from_env() -> Arc<LocalStorage> {
+    let root = env::var("BRAND_LOGO_ROOT").unwrap_or_else(|_| String::from("media/brand-logos"));
+    Arc::new(LocalStorage::new(root))
+}
@@ -0,0 +1,61 @@
+use async_trait::async_trait;
+use s3::{creds::Credentials, error::S3Error, Bucket, Region};
+
+use super::{Storage, StorageError};
+
+#[derive(Debug)]
+pub struct S3Storage {
+    bucket: Box<Bucket>,
+}
+
+impl S3Storage {
+    pub fn new(
+        bucket: &str,
+        endpoint: &str,
+        region: &str,
+        access_key: &str,
+        secret_key: &str,
+    ) -> Result<Self, S3Error> {
+        let region = Region::Custom {
+            region: region.to_string(),
+            endpoint: endpoint.to_string(),
+        };
+        let credentials = Credentials::new(Some(access_key), Some(secret_key), None, None, None)?;
+        // Path-style addressing keeps MinIO and other S3-compatible services working.
+        let bucket = Bucket::new(bucket, region, credentials)?.with_path_style();
+        Ok(Self { bucket })
+    }
+}
+
+#[async_trait]
+impl Storage for S3Storage {
+    async fn put(&self, key: &str, content_type: &str, data: Vec<u8>) -> Result<(), StorageError> {
+        let response = self
+            .bucket
+            .put_object_with_content_type(key, &data, content_type)
+            .await?;
+        check_status(response.status_code())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+        let response = self.bucket.get_object(key).await?;
+        check_status(response.status_code())?;
+        Ok(response.bytes().to_vec())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        let response = self.bucket.delete_object(key).await?;
+        match response.status_code() {
+            404 => Ok(()),
+            status => check_status(status),
+        }
+    }
+}
+
+fn check_status(status: u16) -> Result<(), StorageError> {
+    match status {
+        200..=299 => Ok(()),
+        404 => Err(StorageError::NotFound),
+        status => Err(StorageError::Status(status)),
+    }
+}
@@ -0,0 +1,239 @@
+use chrono::{prelude::*, Duration};
+use futures::TryStreamExt;
+use serde::Serialize;
+use sqlx::{FromRow, PgPool};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{error::AppError, revision::model::set_actor, storage::Storage};
+
+use super::model::User;
+
+pub const SYSTEM_USER_ID: Uuid = Uuid::nil();
+pub const DELETION_GRACE_DAYS: i64 = 14;
+
+const DELETION_SWEEP_INTERVAL_SECS: u64 = 60 * 60;
+
+// Personal data, deleted in dependency order. Each statement is bound to the
+// user's id as $1.
+const PERSONAL_DATA: &[&str] = &[
+    "DELETE FROM personal_record WHERE user_id = $1",
+    "
+    DELETE FROM tracked_set
+    WHERE exercise_id IN (
+        SELECT t1.id FROM exercise t1 JOIN workout t2 ON t2.id = t1.workout_id
+        WHERE t2.user_id = $1
+    )
+    ",
+    "DELETE FROM exercise WHERE workout_id IN (SELECT id FROM workout WHERE user_id = $1)",
+    "DELETE FROM workout WHERE user_id = $1",
+    "
+    DELETE FROM set_plan
+    WHERE exercise_plan_id IN (
+        SELECT t1.id FROM exercise_plan t1
+            JOIN workout_plan t2 ON t2.id = t1.workout_plan_id
+            JOIN training_plan t3 ON t3.id = t2.training_plan_id
+        WHERE t3.user_id = $1
+    )
+    ",
+    "
+    DELETE FROM exercise_plan
+    WHERE workout_plan_id IN (
+        SELECT t1.id FROM workout_plan t1
+            JOIN training_plan t2 ON t2.id = t1.training_plan_id
+        WHERE t2.user_id = $1
+    )
+    ",
+    "
+    DELETE FROM workout_plan
+    WHERE training_plan_id IN (SELECT id FROM training_plan WHERE user_id = $1)
+    ",
+    "DELETE FROM training_plan WHERE user_id = $1",
+    "DELETE FROM planned_food WHERE user_id = $1",
+    "DELETE FROM meal_food WHERE meal_id IN (SELECT id FROM meal WHERE user_id = $1)",
+    "DELETE FROM meal WHERE user_id = $1",
+    "DELETE FROM food_favourite WHERE user_id = $1",
+    "DELETE FROM food_log WHERE user_id = $1",
+    "DELETE FROM water_log WHERE user_id = $1",
+    "DELETE FROM fast WHERE user_id = $1",
+    "DELETE FROM eating_window WHERE user_id = $1",
+    "DELETE FROM diet_target WHERE user_id = $1",
+    "DELETE FROM progress_photo WHERE user_id = $1",
+    "DELETE FROM progress WHERE user_id = $1",
+    "DELETE FROM user_meal_of_day WHERE user_id = $1",
+    "DELETE FROM user_profile WHERE user_id = $1",
+    "DELETE FROM user_follower WHERE user_id = $1 OR follower_id = $1",
+    "DELETE FROM data_export WHERE user_id = $1",
+];
+
+const EDITED_BY: &[&str] = &["created_by_id", "updated_by_id"];
+const CREATED_BY: &[&str] = &["created_by_id"];
+
+// Tables whose remaining rows may still name the user as creator or editor,
+// either catalogue rows they created or other users' rows they edited.
+// Progress photos are never edited, so only their creator is recorded.
+const AUDITED_TABLES: &[(&str, &[&str])] = &[
+    ("diet_target", EDITED_BY),
+    ("eating_window", EDITED_BY),
+    ("exercise", EDITED_BY),
+    ("exercise_plan", EDITED_BY),
+    ("fast", EDITED_BY),
+    ("food", EDITED_BY),
+    ("food_brand", EDITED_BY),
+    ("food_log", EDITED_BY),
+    ("meal", EDITED_BY),
+    ("meal_food", EDITED_BY),
+    ("meal_of_day", EDITED_BY),
+    ("movement", EDITED_BY),
+    ("muscle_group", EDITED_BY),
+    ("planned_food", EDITED_BY),
+    ("progress", EDITED_BY),
+    ("progress_photo", CREATED_BY),
+    ("set_plan", EDITED_BY),
+    ("tracked_set", EDITED_BY),
+    ("training_plan", EDITED_BY),
+    ("user_meal_of_day", EDITED_BY),
+    ("user_profile", EDITED_BY),
+    ("water_log", EDITED_BY),
+    ("workout", EDITED_BY),
+    ("workout_plan", EDITED_BY),
+];
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct AccountDeletion {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub requested_at: DateTime<Utc>,
+    pub scheduled_for: DateTime<Utc>,
+}
+
+impl AccountDeletion {
+    pub async fn get_from_user_id(
+        pool: &PgPool,
+        user_id: &Uuid,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let query = sqlx::query_as("SELECT * FROM account_deletion WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(query)
+    }
+    pub async fn create(pool: &PgPool, user_id: &Uuid) -> Result<Self, sqlx::Error> {
+        let scheduled_for = Utc::now() + Duration::days(DELETION_GRACE_DAYS);
+        let query = sqlx::query_as(
+            "
+            INSERT INTO
+                account_deletion (user_id, scheduled_for)
+            VALUES
+                ($1, $2)
+            RETURNING
+                *
+            ",
+        )
+        .bind(user_id)
+        .bind(scheduled_for)
+        .fetch_one(pool)
+        .await?;
+        Ok(query)
+    }
+    pub async fn delete(pool: &PgPool, id: &Uuid) -> Result<Self, sqlx::Error> {
+        let query = sqlx::query_as("DELETE FROM account_deletion WHERE id = $1 RETURNING *")
+            .bind(id)
+            .fetch_one(pool)
+            .await?;
+        Ok(query)
+    }
+    pub async fn all_due(pool: &PgPool) -> Result<Vec<Self>, sqlx::Error> {
+        let mut stream = Vec::new();
+        let mut rows = sqlx::query_as(
+            "SELECT * FROM account_deletion WHERE scheduled_for <= $1 ORDER BY scheduled_for",
+        )
+        .bind(Utc::now())
+        .fetch(pool);
+        while let Some(row) = rows.try_next().await? {
+            stream.push(row);
+        }
+        Ok(stream)
+    }
+}
+
+// Hard deletes the user's personal data and reassigns anything shared to the
+// system account, then removes stored files once the transaction commits.
+pub async fn delete_account(
+    pool: &PgPool,
+    storage: &dyn Storage,
+    user_id: &Uuid,
+) -> Result<User, AppError> {
+    if *user_id == SYSTEM_USER_ID {
+        return Err(AppError::BadRequestMessage(String::from(
+            "The system account cannot be deleted.",
+        )));
+    }
+    let file_keys: Vec<String> = sqlx::query_scalar(
+        "
+        SELECT image_key FROM progress_photo WHERE user_id = $1
+        UNION ALL
+        SELECT thumbnail_key FROM progress_photo WHERE user_id = $1
+        UNION ALL
+        SELECT file_key FROM data_export WHERE user_id = $1 AND file_key IS NOT NULL
+        ",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut tx = pool.begin().await?;
+    set_actor(&mut tx, &SYSTEM_USER_ID).await?;
+    for statement in PERSONAL_DATA {
+        sqlx::query(statement)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    for (table, columns) in AUDITED_TABLES {
+        for column in *columns {
+            let statement = format!("UPDATE {table} SET {column} = $2 WHERE {column} = $1");
+            sqlx::query(&statement)
+                .bind(user_id)
+                .bind(SYSTEM_USER_ID)
+                .execute(&mut *tx)
+                .await?;
+        }
+    }
+    let user = sqlx::query_as("DELETE FROM users_user WHERE id = $1 RETURNING *")
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    tx.commit().await?;
+
+    for key in file_keys {
+        if let Err(err) = storage.delete(&key).await {
+            tracing::warn!("could not delete stored file {}: {:?}", key, err);
+        }
+    }
+    Ok(user)
+}
+
+pub async fn run_scheduled_deletions(pool: PgPool, storage: Arc<dyn Storage>) {
+    let mut interval =
+        tokio::time::interval(std::time::Duration::from_secs(DELETION_SWEEP_INTERVAL_SECS));
+    loop {
+        interval.tick().await;
+        let due = match AccountDeletion::all_due(&pool).await {
+            Ok(due) => due,
+            Err(err) => {
+                tracing::error!("could not load scheduled account deletions: {:?}", err);
+                continue;
+            }
+        };
+        for deletion in due {
+            match delete_account(&pool, storage.as_ref(), &deletion.user_id).await {
+                Ok(user) => tracing::info!("deleted account {}", user.id),
+                Err(err) => {
+                    tracing::error!("could not delete account {}: {:?}", deletion.user_id, err)
+                }
+            }
+        }
+    }
+}
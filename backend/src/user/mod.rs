@@ -1,3 +1,4 @@
+pub mod deletion;
 pub mod model;
 pub mod router;
 pub mod serializer;
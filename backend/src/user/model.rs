@@ -195,14 +195,6 @@ impl User {
         Ok(query)
     }
 
-    pub async fn delete(pool: &PgPool, id: &Uuid) -> Result<Self, sqlx::Error> {
-        let query = sqlx::query_as("DELETE FROM users_user WHERE id = $1 RETURNING *")
-            .bind(id)
-            .fetch_one(pool)
-            .await?;
-        Ok(query)
-    }
-
     pub async fn signup(pool: &PgPool, data: &SignupSerializer) -> Result<Self, AppError> {
         let username = data.username.to_lowercase();
         let email = data.username.to_lowercase();
@@ -296,17 +288,6 @@ impl User {
         Ok(query)
     }
 
-    pub async fn delete_id_range(
-        pool: &PgPool,
-        id_range: Vec<Uuid>,
-    ) -> Result<Vec<Self>, sqlx::Error> {
-        let query = sqlx::query_as("DELETE FROM users_user WHERE id = ANY ($1) RETURNING *")
-            .bind(id_range)
-            .fetch_all(pool)
-            .await?;
-        Ok(query)
-    }
-
     pub async fn update_last_login(pool: &PgPool, id: &Uuid) -> Result<Self, sqlx::Error> {
         let last_login = Utc::now();
         let query =
@@ -7,6 +7,7 @@ use std::sync::Arc;
 use crate::AppState;
 
 use super::view::{
+    account_deletion_cancel_view, account_deletion_create_view, account_deletion_detail_view,
     admin_user_delete_view, admin_user_detail_view, admin_user_update_view, user_create_view,
     user_delete_id_range_view, user_delete_view, user_detail_view, user_header_view,
     user_list_view, user_select_view, user_stats_detail_view, user_stats_list_view,
@@ -26,6 +27,9 @@ pub fn user_router() -> Router<Arc<AppState>> {
         .route("/:username/header", get(user_header_view))
         .route("/delete-id-range", delete(user_delete_id_range_view))
         .route("/select", get(user_select_view))
+        .route("/account/deletion", get(account_deletion_detail_view))
+        .route("/account/deletion", post(account_deletion_create_view))
+        .route("/account/deletion", delete(account_deletion_cancel_view))
         .route("/admin/:id/stats", get(user_stats_detail_view))
         .route("/admin/stats", get(user_stats_list_view))
 }
@@ -52,3 +52,11 @@ pub struct AdminUpdateInput {
     pub is_superuser: bool,
     pub privacy_level: i32,
 }
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct AccountDeletionInput {
+    #[validate(length(min = 1, message = "Password is required"))]
+    pub password: String,
+    #[validate(length(min = 1, message = "Type your username to confirm"))]
+    pub confirm_username: String,
+}
@@ -1,5 +1,6 @@
 use axum::{
     extract::{Path, Query, State},
+    http::StatusCode,
     Extension, Json,
 };
 use serde_json::{json, Value};
@@ -15,9 +16,10 @@ use crate::{
 };
 
 use super::{
+    deletion::{delete_account, AccountDeletion},
     model::UserSerializer,
     model::{User, UserHeaderQuery, UserSelect},
-    serializer::{AdminUpdateInput, CreateUserSerializer},
+    serializer::{AccountDeletionInput, AdminUpdateInput, CreateUserSerializer},
     stats::UserStats,
 };
 
@@ -71,7 +73,7 @@ pub async fn user_delete_view(
     let query = User::get_from_username(&state.pool, &username)
         .await?
         .ok_or(AppError::NotFound)?;
-    let query = User::delete(&state.pool, &query.id).await?;
+    let query = delete_account(&state.pool, state.storage.as_ref(), &query.id).await?;
     Ok(Json(query))
 }
 
@@ -81,7 +83,10 @@ pub async fn user_delete_id_range_view(
     JsonExtractor(data): JsonExtractor<IdRange>,
 ) -> Result<Json<Vec<User>>, AppError> {
     request_user.superuser_required()?;
-    let query = User::delete_id_range(&state.pool, data.id_range).await?;
+    let mut query = Vec::new();
+    for id in data.id_range {
+        query.push(delete_account(&state.pool, state.storage.as_ref(), &id).await?);
+    }
     Ok(Json(query))
 }
 
@@ -117,7 +122,7 @@ pub async fn admin_user_delete_view(
 ) -> Result<Json<User>, AppError> {
     request_user.superuser_required()?;
     // let query = User::get(&state.pool, &id).await?;
-    let query = User::delete(&state.pool, &id).await?;
+    let query = delete_account(&state.pool, state.storage.as_ref(), &id).await?;
     Ok(Json(query))
 }
 
@@ -150,3 +155,51 @@ pub async fn user_stats_detail_view(
     let query = UserStats::get(&state.pool, &id).await?;
     Ok(Json(query))
 }
+
+pub async fn account_deletion_detail_view(
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<AccountDeletion>, AppError> {
+    request_user.login_required()?;
+    let query = AccountDeletion::get_from_user_id(&state.pool, &request_user.id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    Ok(Json(query))
+}
+
+pub async fn account_deletion_create_view(
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    JsonExtractor(data): JsonExtractor<AccountDeletionInput>,
+) -> Result<(StatusCode, Json<AccountDeletion>), AppError> {
+    request_user.login_required()?;
+    let user = User::get(&state.pool, &request_user.id).await?;
+    user.self_validate_password(&data.password)?;
+    if data.confirm_username != user.username {
+        return Err(AppError::BadRequestMessage(String::from(
+            "Username confirmation does not match.",
+        )));
+    }
+    if AccountDeletion::get_from_user_id(&state.pool, &user.id)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::BadRequestMessage(String::from(
+            "Account deletion has already been requested.",
+        )));
+    }
+    let query = AccountDeletion::create(&state.pool, &user.id).await?;
+    Ok((StatusCode::CREATED, Json(query)))
+}
+
+pub async fn account_deletion_cancel_view(
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<AccountDeletion>, AppError> {
+    request_user.login_required()?;
+    let query = AccountDeletion::get_from_user_id(&state.pool, &request_user.id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    let query = AccountDeletion::delete(&state.pool, &query.id).await?;
+    Ok(Json(query))
+}
@@ -8,6 +8,12 @@ pub trait NaiveDateExt {
     fn is_leap_year(&self) -> bool;
 }
 
+// Clients send their offset from UTC in minutes, e.g. 60 for UTC+1, so times of day
+// and dates can be worked out in the user's time zone.
+pub fn utc_offset(minutes: Option<i32>) -> Option<FixedOffset> {
+    FixedOffset::east_opt(minutes.unwrap_or(0).checked_mul(60)?)
+}
+
 #[derive(Debug)]
 pub struct DateHourRange(pub NaiveDateTime, pub NaiveDateTime);
 
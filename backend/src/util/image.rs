@@ -0,0 +1,66 @@
+use image::{
+    codecs::{jpeg::JpegEncoder, png::PngEncoder},
+    DynamicImage, ImageDecoder, ImageFormat, ImageReader, Limits,
+};
+use std::io::Cursor;
+
+use crate::error::AppError;
+
+pub const ALLOWED_IMAGE_FORMATS: [ImageFormat; 3] =
+    [ImageFormat::Jpeg, ImageFormat::Png, ImageFormat::WebP];
+
+const MAX_IMAGE_DIMENSION: u32 = 8000;
+const JPEG_QUALITY: u8 = 85;
+
+pub fn sniff_image_format(data: &[u8]) -> Result<ImageFormat, AppError> {
+    let format = image::guess_format(data)
+        .map_err(|_| AppError::BadRequestMessage(String::from("Unrecognised image type")))?;
+    if !ALLOWED_IMAGE_FORMATS.contains(&format) {
+        return Err(AppError::BadRequestMessage(String::from(
+            "Image must be a JPEG, PNG or WebP",
+        )));
+    }
+    Ok(format)
+}
+
+// Decodes the upload and applies its EXIF orientation. Only pixel data survives
+// decoding, so re-encoding the result strips EXIF and any other metadata.
+pub fn decode_image(data: &[u8]) -> Result<DynamicImage, AppError> {
+    let format = sniff_image_format(data)?;
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_IMAGE_DIMENSION);
+    limits.max_image_height = Some(MAX_IMAGE_DIMENSION);
+    let mut reader = ImageReader::with_format(Cursor::new(data), format);
+    reader.limits(limits);
+    let invalid = |_| AppError::BadRequestMessage(String::from("Invalid image"));
+    let mut decoder = reader.into_decoder().map_err(invalid)?;
+    let orientation = decoder.orientation().map_err(invalid)?;
+    let mut image = DynamicImage::from_decoder(decoder).map_err(invalid)?;
+    image.apply_orientation(orientation);
+    Ok(image)
+}
+
+pub fn resize_to_fit(image: &DynamicImage, size: u32) -> DynamicImage {
+    if image.width() <= size && image.height() <= size {
+        return image.clone();
+    }
+    image.thumbnail(size, size)
+}
+
+pub fn encode_jpeg(image: &DynamicImage) -> Result<Vec<u8>, AppError> {
+    let mut buffer = Vec::new();
+    let encoder = JpegEncoder::new_with_quality(&mut buffer, JPEG_QUALITY);
+    DynamicImage::ImageRgb8(image.to_rgb8())
+        .write_with_encoder(encoder)
+        .map_err(|_| AppError::BadRequestMessage(String::from("Could not encode image")))?;
+    Ok(buffer)
+}
+
+pub fn encode_png(image: &DynamicImage) -> Result<Vec<u8>, AppError> {
+    let mut buffer = Vec::new();
+    let encoder = PngEncoder::new(&mut buffer);
+    DynamicImage::ImageRgba8(image.to_rgba8())
+        .write_with_encoder(encoder)
+        .map_err(|_| AppError::BadRequestMessage(String::from("Could not encode image")))?;
+    Ok(buffer)
+}
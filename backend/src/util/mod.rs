@@ -2,6 +2,9 @@ pub mod app_macro;
 pub mod datetime;
 pub mod email;
 pub mod extract;
+pub mod image;
 pub mod permission;
 pub mod query;
+pub mod similarity;
+pub mod trend;
 pub mod validator;
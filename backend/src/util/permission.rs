@@ -36,3 +36,23 @@ pub async fn user_privacy_check(
     }
     Ok(true)
 }
+
+// Looks up the user named in a request body, who must be the request user since
+// only your own logs can be written to. The message says what cannot be changed.
+pub async fn owner_required(
+    pool: &PgPool,
+    request_user: &RequestUser,
+    username: &str,
+    message: &str,
+) -> Result<User, AppError> {
+    let user = User::get_from_username(pool, username)
+        .await?
+        .ok_or(AppError::APIBadRequest(format!(
+            "User {} not found",
+            username
+        )))?;
+    if request_user.id != user.id {
+        return Err(AppError::BadRequestMessage(message.to_string()));
+    }
+    Ok(user)
+}
@@ -44,6 +44,10 @@ pub struct QueryParams {
     #[serde(default, deserialize_with = "empty_string_as_none")]
     pub serving: Option<String>,
     #[serde(default, deserialize_with = "empty_string_as_none")]
+    pub grade: Option<String>,
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    pub status: Option<String>,
+    #[serde(default, deserialize_with = "empty_string_as_none")]
     pub order: Option<String>,
     #[serde(default, deserialize_with = "empty_string_as_none")]
     pub page: Option<i32>,
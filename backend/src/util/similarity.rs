@@ -0,0 +1,54 @@
+use std::collections::HashSet;
+
+// Lowercases, drops punctuation and plurals, and sorts the words so that
+// "Bench Press (Barbell)" and "Barbell Bench Press" compare equal.
+pub fn normalize(name: &str) -> String {
+    let mut words: Vec<String> = name
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| match word.strip_suffix('s') {
+            Some(stem) if word.len() > 3 && !stem.ends_with('s') => stem.to_string(),
+            _ => word.to_string(),
+        })
+        .collect();
+    words.sort();
+    words.dedup();
+    words.join(" ")
+}
+
+fn bigrams(value: &str) -> HashSet<(char, char)> {
+    let chars: Vec<char> = value.chars().collect();
+    chars.windows(2).map(|pair| (pair[0], pair[1])).collect()
+}
+
+// Dice coefficient over character bigrams of the normalised names.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    if a == b {
+        return 1.0;
+    }
+    let (a, b) = (bigrams(a), bigrams(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let shared = a.intersection(&b).count();
+    (2 * shared) as f64 / (a.len() + b.len()) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("Bench Press (Barbell)"), "barbell bench press");
+        assert_eq!(normalize("Barbell Squats"), normalize("Squat (Barbell)"));
+    }
+
+    #[test]
+    fn test_similarity() {
+        assert_eq!(similarity("oat", "oat"), 1.0);
+        assert_eq!(similarity("a", "oat"), 0.0);
+        assert!(similarity(&normalize("Rolled Oats"), &normalize("Oats, Rolled")) > 0.99);
+    }
+}
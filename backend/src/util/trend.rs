@@ -0,0 +1,40 @@
+use chrono::NaiveDate;
+use rust_decimal::prelude::*;
+use rust_decimal_macros::dec;
+
+// Approximate energy stored in one kilogram of body mass (kcal).
+pub const ENERGY_PER_KG: Decimal = dec!(7700);
+
+pub const WEIGHT_SMOOTHING_FACTOR: Decimal = dec!(0.1);
+
+// One value per day from the first entry to the last, with the days between two
+// entries filled by linear interpolation, so smoothing happens per day however often
+// the user weighs in. Entries must be in date order; a repeated date keeps the last.
+pub fn daily_values(entries: &[(NaiveDate, Decimal)]) -> Vec<Decimal> {
+    let mut result = Vec::new();
+    for pair in entries.windows(2) {
+        let ((from, start), (to, end)) = (pair[0], pair[1]);
+        let days = (to - from).num_days();
+        for day in 0..days {
+            result.push(start + (end - start) * Decimal::from(day) / Decimal::from(days));
+        }
+    }
+    if let Some((_, last)) = entries.last() {
+        result.push(*last);
+    }
+    result
+}
+
+pub fn exponential_moving_average(values: &[Decimal], alpha: Decimal) -> Vec<Decimal> {
+    let mut result = Vec::with_capacity(values.len());
+    let mut previous: Option<Decimal> = None;
+    for value in values {
+        let smoothed = match previous {
+            Some(previous) => previous + alpha * (*value - previous),
+            None => *value,
+        };
+        result.push(smoothed);
+        previous = Some(smoothed);
+    }
+    result
+}
@@ -36,6 +36,22 @@ pub fn validate_non_negative_decimal(value: &Decimal) -> Result<(), ValidationEr
     Ok(())
 }
 
+// Body measurements are stored as NUMERIC(5, 2) and body fat as NUMERIC(4, 2), both
+// rounded to two places on insert.
+pub fn validate_measurement_cm(value: &Decimal) -> Result<(), ValidationError> {
+    if value.is_sign_negative() || value.round_dp(2) > Decimal::new(99999, 2) {
+        return Err(ValidationError::new("measurement_cm"));
+    }
+    Ok(())
+}
+
+pub fn validate_body_fat_pct(value: &Decimal) -> Result<(), ValidationError> {
+    if value.is_sign_negative() || value.round_dp(2) >= Decimal::ONE_HUNDRED {
+        return Err(ValidationError::new("body_fat_pct"));
+    }
+    Ok(())
+}
+
 pub fn validate_positive_int(value: i32) -> Result<(), ValidationError> {
     dbg!(value);
     if value <= 0 {
@@ -0,0 +1,234 @@
+use chrono::prelude::*;
+use futures::TryStreamExt;
+use serde::Serialize;
+use sqlx::{FromRow, PgPool, Row};
+use uuid::Uuid;
+
+use crate::{db::Filters, diet_target::model::DietTarget, util::query::QueryParams};
+
+use super::serializer::WaterLogInput;
+
+const ORDERING_FIELDS: &[&str] = &["date", "time", "volume_ml", "created_at", "updated_at"];
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct WaterLog {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub date: NaiveDate,
+    pub time: NaiveTime,
+    pub volume_ml: i32,
+    pub beverage: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub created_by_id: Uuid,
+    pub updated_by_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WaterLogDay {
+    pub username: String,
+    pub date: NaiveDate,
+    pub volume_ml: i64,
+    pub target_ml: Option<i32>,
+    pub remaining_ml: Option<i64>,
+    pub entries: Vec<WaterLog>,
+}
+
+impl WaterLogDay {
+    pub async fn get(
+        pool: &PgPool,
+        user_id: &Uuid,
+        username: String,
+        date: NaiveDate,
+    ) -> Result<Self, sqlx::Error> {
+        let entries = WaterLog::all_by_user_date(pool, user_id, &date).await?;
+        let volume_ml: i64 = entries.iter().map(|entry| entry.volume_ml as i64).sum();
+        let target_ml = DietTarget::get_from_user_id_date(pool, user_id, &date)
+            .await?
+            .map(|target| target.water_ml);
+        let remaining_ml = target_ml.map(|target| (target as i64 - volume_ml).max(0));
+        Ok(Self {
+            username,
+            date,
+            volume_ml,
+            target_ml,
+            remaining_ml,
+            entries,
+        })
+    }
+}
+
+impl WaterLog {
+    pub async fn count(pool: &PgPool, params: &QueryParams) -> Result<i64, sqlx::Error> {
+        let mut q = sqlx::QueryBuilder::new("SELECT COUNT(t1.*) FROM water_log t1 WHERE TRUE");
+        q.filter_uuid_exact("t1.user_id", &params.user_id);
+        q.filter_date("t1.date", " = ", &params.date);
+        let count = q.build().fetch_one(pool).await?.get("count");
+        Ok(count)
+    }
+    pub async fn all(pool: &PgPool, params: &QueryParams) -> Result<Vec<Self>, sqlx::Error> {
+        let mut stream = Vec::new();
+        let mut q = sqlx::QueryBuilder::new("SELECT t1.* FROM water_log t1 WHERE TRUE");
+        q.filter_uuid_exact("t1.user_id", &params.user_id);
+        q.filter_date("t1.date", " = ", &params.date);
+        q.ordering_filter(params, ORDERING_FIELDS, "date desc, time desc");
+        q.paginate(params.page, params.size);
+        let mut rows = q.build_query_as().fetch(pool);
+        while let Some(row) = rows.try_next().await? {
+            stream.push(row);
+        }
+        Ok(stream)
+    }
+    pub async fn all_by_user_date(
+        pool: &PgPool,
+        user_id: &Uuid,
+        date: &NaiveDate,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let mut stream = Vec::new();
+        let mut rows = sqlx::query_as(
+            "
+            SELECT
+                *
+            FROM
+                water_log
+            WHERE
+                user_id = $1
+                AND date = $2
+            ORDER BY
+                time
+            ",
+        )
+        .bind(user_id)
+        .bind(date)
+        .fetch(pool);
+        while let Some(row) = rows.try_next().await? {
+            stream.push(row);
+        }
+        Ok(stream)
+    }
+    pub async fn day_total(
+        pool: &PgPool,
+        user_id: &Uuid,
+        date: &NaiveDate,
+    ) -> Result<i64, sqlx::Error> {
+        let total = sqlx::query(
+            "SELECT COALESCE(SUM(volume_ml), 0) AS total FROM water_log WHERE user_id = $1 AND date = $2",
+        )
+        .bind(user_id)
+        .bind(date)
+        .fetch_one(pool)
+        .await?
+        .get("total");
+        Ok(total)
+    }
+    pub async fn create(
+        pool: &PgPool,
+        user_id: Uuid,
+        data: WaterLogInput,
+        offset: FixedOffset,
+        created_by_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let time = data.time.unwrap_or_else(|| {
+            Utc::now()
+                .with_timezone(&offset)
+                .time()
+                .with_nanosecond(0)
+                .unwrap_or_default()
+        });
+        let beverage = data.beverage.unwrap_or_else(|| String::from("Water"));
+        let query = sqlx::query_as(
+            "
+            INSERT INTO
+                water_log (
+                    user_id,
+                    date,
+                    time,
+                    volume_ml,
+                    beverage,
+                    created_by_id
+                )
+            VALUES
+                ($1, $2, $3, $4, $5, $6)
+            RETURNING
+                *
+            ",
+        )
+        .bind(user_id)
+        .bind(data.date)
+        .bind(time)
+        .bind(data.volume_ml)
+        .bind(beverage)
+        .bind(created_by_id)
+        .fetch_one(pool)
+        .await?;
+        Ok(query)
+    }
+    pub async fn get(pool: &PgPool, id: &Uuid) -> Result<Option<Self>, sqlx::Error> {
+        let query = sqlx::query_as("SELECT * FROM water_log WHERE id = $1")
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
+        Ok(query)
+    }
+    pub async fn update(
+        pool: &PgPool,
+        id: &Uuid,
+        data: WaterLogInput,
+        updated_by_id: Uuid,
+    ) -> Result<Self, sqlx::Error> {
+        let query = sqlx::query_as(
+            "
+            UPDATE water_log
+            SET
+                date = $1,
+                time = COALESCE($2, time),
+                volume_ml = $3,
+                beverage = COALESCE($4, beverage),
+                updated_at = $5,
+                updated_by_id = $6
+            WHERE
+                id = $7
+            RETURNING
+                *
+            ",
+        )
+        .bind(data.date)
+        .bind(data.time)
+        .bind(data.volume_ml)
+        .bind(data.beverage)
+        .bind(Utc::now())
+        .bind(updated_by_id)
+        .bind(id)
+        .fetch_one(pool)
+        .await?;
+        Ok(query)
+    }
+    pub async fn delete(pool: &PgPool, id: &Uuid) -> Result<Self, sqlx::Error> {
+        let query = sqlx::query_as("DELETE FROM water_log WHERE id = $1 RETURNING *")
+            .bind(id)
+            .fetch_one(pool)
+            .await?;
+        Ok(query)
+    }
+    pub async fn delete_id_range(
+        pool: &PgPool,
+        user_id: &Uuid,
+        id_range: Vec<Uuid>,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let query = sqlx::query_as(
+            "
+            DELETE FROM water_log
+            WHERE
+                user_id = $1
+                AND id = ANY ($2)
+            RETURNING
+                *
+            ",
+        )
+        .bind(user_id)
+        .bind(id_range)
+        .fetch_all(pool)
+        .await?;
+        Ok(query)
+    }
+}
@@ -0,0 +1,22 @@
+use axum::{
+    routing::{delete, get, post, put},
+    Router,
+};
+use std::sync::Arc;
+
+use crate::water_log::view::{
+    water_log_create_view, water_log_day_view, water_log_delete_id_range_view,
+    water_log_delete_view, water_log_detail_view, water_log_list_view, water_log_update_view,
+};
+use crate::AppState;
+
+pub fn water_log_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(water_log_list_view))
+        .route("/", post(water_log_create_view))
+        .route("/:id", get(water_log_detail_view))
+        .route("/:id", put(water_log_update_view))
+        .route("/:id", delete(water_log_delete_view))
+        .route("/delete-id-range", delete(water_log_delete_id_range_view))
+        .route("/:username/:date", get(water_log_day_view))
+}
@@ -0,0 +1,16 @@
+use chrono::prelude::*;
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct WaterLogInput {
+    pub username: String,
+    pub date: NaiveDate,
+    pub time: Option<NaiveTime>,
+    #[validate(range(min = 1, max = 5000, message = "Volume must be between 1 and 5000ml"))]
+    pub volume_ml: i32,
+    #[validate(length(min = 1, max = 50, message = "Beverage must be 1-50 characters"))]
+    pub beverage: Option<String>,
+    // Minutes from UTC, used for the default time when none is given.
+    pub utc_offset: Option<i32>,
+}
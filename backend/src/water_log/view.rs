@@ -0,0 +1,123 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::prelude::*;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError,
+    extractor::JsonExtractor,
+    middleware::RequestUser,
+    user::model::User,
+    util::{
+        datetime::utc_offset,
+        extract::IdRange,
+        permission::{owner_required, user_privacy_check},
+        query::QueryParams,
+    },
+    AppState,
+};
+
+use super::{
+    model::{WaterLog, WaterLogDay},
+    serializer::WaterLogInput,
+};
+
+const OWNER_MESSAGE: &str = "You are unable to update another users water log.";
+
+pub async fn water_log_list_view(
+    Query(params): Query<QueryParams>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<Value>, AppError> {
+    request_user.superuser_required()?;
+    let count = WaterLog::count(&state.pool, &params).await?;
+    let query = WaterLog::all(&state.pool, &params).await?;
+    let response = json!({"count": count, "results": query});
+    Ok(Json(response))
+}
+
+pub async fn water_log_create_view(
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    JsonExtractor(data): JsonExtractor<WaterLogInput>,
+) -> Result<(StatusCode, Json<WaterLog>), AppError> {
+    let user = owner_required(&state.pool, &request_user, &data.username, OWNER_MESSAGE).await?;
+    let offset = utc_offset(data.utc_offset).ok_or(AppError::BadRequest)?;
+    let query = WaterLog::create(&state.pool, user.id, data, offset, request_user.id).await?;
+    Ok((StatusCode::CREATED, Json(query)))
+}
+
+pub async fn water_log_detail_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<WaterLog>, AppError> {
+    let query = WaterLog::get(&state.pool, &id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    if request_user.id != query.user_id {
+        let user = User::get(&state.pool, &query.user_id).await?;
+        user_privacy_check(&state.pool, &request_user, &user).await?;
+    }
+    Ok(Json(query))
+}
+
+pub async fn water_log_update_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    JsonExtractor(data): JsonExtractor<WaterLogInput>,
+) -> Result<Json<WaterLog>, AppError> {
+    let water_log = WaterLog::get(&state.pool, &id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    let user = owner_required(&state.pool, &request_user, &data.username, OWNER_MESSAGE).await?;
+    if water_log.user_id != user.id {
+        return Err(AppError::NotFound);
+    }
+    let query = WaterLog::update(&state.pool, &water_log.id, data, request_user.id).await?;
+    Ok(Json(query))
+}
+
+pub async fn water_log_delete_view(
+    Path(id): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<WaterLog>, AppError> {
+    let water_log = WaterLog::get(&state.pool, &id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    if request_user.id != water_log.user_id {
+        return Err(AppError::NotFound);
+    }
+    let query = WaterLog::delete(&state.pool, &water_log.id).await?;
+    Ok(Json(query))
+}
+
+pub async fn water_log_delete_id_range_view(
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    JsonExtractor(data): JsonExtractor<IdRange>,
+) -> Result<Json<Vec<WaterLog>>, AppError> {
+    request_user.login_required()?;
+    let query = WaterLog::delete_id_range(&state.pool, &request_user.id, data.id_range).await?;
+    Ok(Json(query))
+}
+
+pub async fn water_log_day_view(
+    Path((username, date)): Path<(String, NaiveDate)>,
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+) -> Result<Json<WaterLogDay>, AppError> {
+    let user = User::get_from_username(&state.pool, &username)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    user_privacy_check(&state.pool, &request_user, &user).await?;
+    let query = WaterLogDay::get(&state.pool, &user.id, username, date).await?;
+    Ok(Json(query))
+}
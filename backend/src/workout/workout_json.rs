@@ -17,6 +17,8 @@ pub struct SetJSON {
     pub rest: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: Option<DateTime<Utc>>,
+    // Personal record badges: weight, e1rm and reps.
+    pub records: Vec<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, FromRow)]
@@ -28,6 +30,8 @@ pub struct ExerciseJSON {
     pub set_count: i64,
     pub rep_count: i64,
     pub created_at: DateTime<Utc>,
+    // A volume badge when the workout's total for the movement was a record.
+    pub records: Vec<String>,
     pub sets: sqlx::types::Json<Vec<Option<SetJSON>>>,
 }
 
@@ -40,6 +44,7 @@ pub struct WorkoutJSON {
     pub set_count: Option<Decimal>,
     pub rep_count: Option<Decimal>,
     pub exercise_count: Option<i64>,
+    pub record_count: Option<i64>,
     pub exercises: sqlx::types::Json<Vec<Option<ExerciseJSON>>>,
 }
 
@@ -58,7 +63,17 @@ impl WorkoutJSON {
                     t0.reps,
                     t0.rest,
                     t0.created_at,
-                    t0.updated_at
+                    t0.updated_at,
+                    ARRAY(
+                        SELECT DISTINCT
+                            t9.record_type
+                        FROM
+                            personal_record t9
+                        WHERE
+                            t9.set_id = t0.id
+                        ORDER BY
+                            t9.record_type
+                    ) AS records
                 FROM
                     tracked_set t0
             ),
@@ -69,6 +84,15 @@ impl WorkoutJSON {
                     t1.order as exercise_order,
                     t1.created_at,
                     t2.name,
+                    ARRAY(
+                        SELECT DISTINCT
+                            t9.record_type
+                        FROM
+                            personal_record t9
+                        WHERE
+                            t9.exercise_id = t1.id
+                            AND t9.set_id IS NULL
+                    ) AS records,
                     COUNT(t3.*) AS set_count,
                     SUM(COALESCE(t3.reps, 0)) AS rep_count,
                     JSON_AGG(t3 ORDER BY t3.set_order) AS SETS
@@ -97,6 +121,14 @@ impl WorkoutJSON {
                 WHERE
                     workout_id = t1.id
             ) AS exercise_count,
+            (
+                SELECT
+                    COUNT(DISTINCT (COALESCE(set_id, exercise_id), record_type))
+                FROM
+                    personal_record
+                WHERE
+                    workout_id = t1.id
+            ) AS record_count,
             JSON_AGG(t2 ORDER BY t2.created_at, t2.exercise_order) AS exercises
         FROM
             workout t1
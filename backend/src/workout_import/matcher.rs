@@ -0,0 +1,76 @@
+use uuid::Uuid;
+
+use crate::util::similarity::{normalize, similarity};
+
+pub const MATCH_THRESHOLD: f64 = 0.75;
+
+fn best_match<'a>(
+    normalized: &str,
+    candidates: impl Iterator<Item = &'a str>,
+) -> Option<(usize, f64)> {
+    candidates
+        .map(|candidate| similarity(normalized, candidate))
+        .enumerate()
+        .filter(|(_, score)| *score >= MATCH_THRESHOLD)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+pub fn closest_name(name: &str, candidates: &[String]) -> Option<usize> {
+    let normalized: Vec<String> = candidates.iter().map(|c| normalize(c)).collect();
+    best_match(&normalize(name), normalized.iter().map(String::as_str)).map(|(index, _)| index)
+}
+
+pub struct MovementMatch {
+    pub id: Uuid,
+    pub name: String,
+    pub score: f64,
+}
+
+pub struct MovementMatcher(Vec<(Uuid, String, String)>);
+
+impl MovementMatcher {
+    pub fn new(movements: Vec<(Uuid, String)>) -> Self {
+        Self(
+            movements
+                .into_iter()
+                .map(|(id, name)| {
+                    let normalized = normalize(&name);
+                    (id, name, normalized)
+                })
+                .collect(),
+        )
+    }
+    pub fn find(&self, name: &str) -> Option<MovementMatch> {
+        let candidates = self.0.iter().map(|(_, _, normalized)| normalized.as_str());
+        best_match(&normalize(name), candidates).map(|(index, score)| MovementMatch {
+            id: self.0[index].0,
+            name: self.0[index].1.clone(),
+            score,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_movement_matcher() {
+        let bench = Uuid::new_v4();
+        let curl = Uuid::new_v4();
+        let matcher = MovementMatcher::new(vec![
+            (bench, String::from("Barbell Bench Press")),
+            (curl, String::from("Dumbbell Bicep Curl")),
+        ]);
+        assert_eq!(matcher.find("Bench Press (Barbell)").unwrap().id, bench);
+        assert_eq!(matcher.find("Bicep Curls (Dumbbell)").unwrap().id, curl);
+        assert!(matcher.find("Deadlift (Barbell)").is_none());
+    }
+
+    #[test]
+    fn test_closest_name() {
+        let names = vec![String::from("Deadlift (Barbell)"), String::from("Plank")];
+        assert_eq!(closest_name("Barbell Deadlifts", &names), Some(0));
+        assert_eq!(closest_name("Push Up", &names), None);
+    }
+}
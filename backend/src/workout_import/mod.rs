@@ -0,0 +1,5 @@
+pub mod matcher;
+pub mod model;
+pub mod parser;
+pub mod router;
+pub mod view;
@@ -0,0 +1,299 @@
+use chrono::prelude::*;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::{diet_import::parser::SkippedRow, error::AppError};
+
+use super::{
+    matcher::{closest_name, MovementMatcher},
+    parser::{ImportedSet, ParsedHistory, WorkoutSource},
+};
+
+#[derive(Debug, Serialize)]
+pub struct MatchedMovement {
+    pub name: String,
+    pub movement_id: Uuid,
+    pub movement: String,
+    pub score: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkoutConflict {
+    pub date: NaiveDate,
+    pub name: String,
+    pub existing_workout_ids: Vec<Uuid>,
+    pub skipped: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkoutImportReport {
+    pub source: WorkoutSource,
+    pub dry_run: bool,
+    pub row_count: usize,
+    pub date_from: Option<NaiveDate>,
+    pub date_to: Option<NaiveDate>,
+    pub workout_count: usize,
+    pub exercise_count: usize,
+    pub set_count: usize,
+    pub matched_movements: Vec<MatchedMovement>,
+    pub new_movements: Vec<String>,
+    pub conflicts: Vec<WorkoutConflict>,
+    pub skipped: Vec<SkippedRow>,
+}
+
+pub struct WorkoutImportOptions {
+    pub muscle_group_id: Option<Uuid>,
+    pub skip_conflicts: bool,
+    pub dry_run: bool,
+}
+
+async fn existing_workouts(
+    pool: &PgPool,
+    user_id: Uuid,
+    dates: Vec<NaiveDate>,
+) -> Result<HashMap<NaiveDate, Vec<Uuid>>, sqlx::Error> {
+    let rows = sqlx::query(
+        "
+        SELECT
+            date,
+            ARRAY_AGG(id ORDER BY created_at) AS ids
+        FROM
+            workout
+        WHERE
+            user_id = $1
+            AND date = ANY ($2)
+        GROUP BY
+            date
+        ",
+    )
+    .bind(user_id)
+    .bind(dates)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.get("date"), row.get("ids")))
+        .collect())
+}
+
+// Workouts on dates that already have one are reported as conflicts and, unless
+// the caller opts in, left out. Re-running the same file therefore adds nothing.
+pub async fn import_history(
+    pool: &PgPool,
+    user_id: Uuid,
+    parsed: ParsedHistory,
+    options: WorkoutImportOptions,
+) -> Result<WorkoutImportReport, AppError> {
+    let movements = sqlx::query("SELECT id, name FROM movement ORDER BY created_at")
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|row| (row.get("id"), row.get("name")))
+        .collect();
+    let matcher = MovementMatcher::new(movements);
+
+    // Unknown names are matched against each other too, so spelling variants within
+    // one file share a single new movement.
+    let mut matched_movements: Vec<MatchedMovement> = Vec::new();
+    let mut new_movements: Vec<String> = Vec::new();
+    let mut movement_refs: HashMap<String, Result<Uuid, usize>> = HashMap::new();
+    for exercise in parsed.workouts.iter().flat_map(|w| &w.exercises) {
+        let key = exercise.name.to_lowercase();
+        if movement_refs.contains_key(&key) {
+            continue;
+        }
+        let movement_ref = match matcher.find(&exercise.name) {
+            Some(found) => {
+                matched_movements.push(MatchedMovement {
+                    name: exercise.name.clone(),
+                    movement_id: found.id,
+                    movement: found.name,
+                    score: (found.score * 100.0).round() / 100.0,
+                });
+                Ok(found.id)
+            }
+            None => match closest_name(&exercise.name, &new_movements) {
+                Some(index) => Err(index),
+                None => {
+                    new_movements.push(exercise.name.clone());
+                    Err(new_movements.len() - 1)
+                }
+            },
+        };
+        movement_refs.insert(key, movement_ref);
+    }
+    if !new_movements.is_empty() && options.muscle_group_id.is_none() && !options.dry_run {
+        return Err(AppError::BadRequestMessage(format!(
+            "A muscle group is required to create {} unknown movements",
+            new_movements.len()
+        )));
+    }
+
+    let mut dates: Vec<NaiveDate> = parsed.workouts.iter().map(|w| w.date).collect();
+    dates.sort();
+    dates.dedup();
+    let existing = existing_workouts(pool, user_id, dates.clone()).await?;
+    let conflicts: Vec<WorkoutConflict> = parsed
+        .workouts
+        .iter()
+        .filter_map(|workout| {
+            existing.get(&workout.date).map(|ids| WorkoutConflict {
+                date: workout.date,
+                name: workout.name.clone(),
+                existing_workout_ids: ids.clone(),
+                skipped: options.skip_conflicts,
+            })
+        })
+        .collect();
+
+    let mut workout_count = 0;
+    let mut exercise_count = 0;
+    let mut set_count = 0;
+    let mut tx = pool.begin().await?;
+    let mut new_movement_ids = Vec::new();
+    if let Some(muscle_group_id) = options.muscle_group_id {
+        for name in &new_movements {
+            let trimmed_name = name.trim();
+            let id: Uuid = sqlx::query(
+                "
+                INSERT INTO
+                    movement (name, slug, muscle_group_id, created_by_id)
+                VALUES
+                    ($1, $2, $3, $4)
+                RETURNING
+                    id
+                ",
+            )
+            .bind(trimmed_name)
+            .bind(slug::slugify(trimmed_name))
+            .bind(muscle_group_id)
+            .bind(user_id)
+            .fetch_one(&mut *tx)
+            .await?
+            .get("id");
+            new_movement_ids.push(id);
+        }
+    }
+
+    for workout in &parsed.workouts {
+        if options.skip_conflicts && existing.contains_key(&workout.date) {
+            continue;
+        }
+        let workout_id: Uuid = sqlx::query(
+            "
+            INSERT INTO
+                workout (user_id, date, created_by_id)
+            VALUES
+                ($1, $2, $1)
+            RETURNING
+                id
+            ",
+        )
+        .bind(user_id)
+        .bind(workout.date)
+        .fetch_one(&mut *tx)
+        .await?
+        .get("id");
+        workout_count += 1;
+
+        // Names that resolve to the same movement become one exercise.
+        let mut grouped: Vec<(Result<Uuid, usize>, Vec<&ImportedSet>)> = Vec::new();
+        for exercise in &workout.exercises {
+            let movement_ref = movement_refs[&exercise.name.to_lowercase()];
+            match grouped.iter_mut().find(|(r, _)| *r == movement_ref) {
+                Some((_, sets)) => sets.extend(&exercise.sets),
+                None => grouped.push((movement_ref, exercise.sets.iter().collect())),
+            }
+        }
+        for (order, (movement_ref, sets)) in (1..).zip(grouped) {
+            let movement_id = match movement_ref {
+                Ok(id) => id,
+                Err(index) => match new_movement_ids.get(index) {
+                    Some(id) => *id,
+                    // Only reachable in a dry run without a muscle group.
+                    None => continue,
+                },
+            };
+            let exercise_id: Uuid = sqlx::query(
+                "
+                INSERT INTO
+                    exercise (workout_id, movement_id, \"order\", created_by_id)
+                VALUES
+                    ($1, $2, $3, $4)
+                RETURNING
+                    id
+                ",
+            )
+            .bind(workout_id)
+            .bind(movement_id)
+            .bind(order)
+            .bind(user_id)
+            .fetch_one(&mut *tx)
+            .await?
+            .get("id");
+            exercise_count += 1;
+
+            let order_list: Vec<i32> = (1..).take(sets.len()).collect();
+            let weight_list: Vec<Decimal> = sets.iter().map(|s| s.weight).collect();
+            let reps_list: Vec<i32> = sets.iter().map(|s| s.reps).collect();
+            let notes_list: Vec<Option<String>> = sets.iter().map(|s| s.notes.clone()).collect();
+            sqlx::query(
+                "
+                INSERT INTO
+                    tracked_set (
+                        exercise_id,
+                        \"order\",
+                        weight,
+                        reps,
+                        rest,
+                        notes,
+                        created_by_id
+                    )
+                SELECT
+                    $1,
+                    t.order,
+                    t.weight,
+                    t.reps,
+                    0,
+                    t.notes,
+                    $2
+                FROM
+                    UNNEST($3::INTEGER[], $4::DECIMAL[], $5::INTEGER[], $6::TEXT[]) AS t (\"order\", weight, reps, notes)
+                ",
+            )
+            .bind(exercise_id)
+            .bind(user_id)
+            .bind(order_list)
+            .bind(weight_list)
+            .bind(reps_list)
+            .bind(notes_list)
+            .execute(&mut *tx)
+            .await?;
+            set_count += sets.len();
+        }
+    }
+
+    if options.dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+
+    Ok(WorkoutImportReport {
+        source: parsed.source,
+        dry_run: options.dry_run,
+        row_count: parsed.row_count,
+        date_from: dates.first().copied(),
+        date_to: dates.last().copied(),
+        workout_count,
+        exercise_count,
+        set_count,
+        matched_movements,
+        new_movements,
+        conflicts,
+        skipped: parsed.skipped,
+    })
+}
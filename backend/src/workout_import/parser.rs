@@ -0,0 +1,397 @@
+use chrono::prelude::*;
+use rust_decimal::prelude::*;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::{diet_import::parser::SkippedRow, error::AppError};
+
+const KG_PER_LB: Decimal = dec!(0.45359237);
+const MAX_WEIGHT: Decimal = dec!(999999.99);
+const MAX_NOTES_LENGTH: usize = 255;
+const DATETIME_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M:%S",
+    "%d %b %Y, %H:%M",
+    "%d %b %Y %H:%M",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WorkoutSource {
+    Strong,
+    Hevy,
+}
+
+impl WorkoutSource {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "strong" => Some(Self::Strong),
+            "hevy" => Some(Self::Hevy),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WeightUnit {
+    Kg,
+    Lbs,
+}
+
+impl WeightUnit {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "kg" | "kgs" => Some(Self::Kg),
+            "lb" | "lbs" => Some(Self::Lbs),
+            _ => None,
+        }
+    }
+    fn to_kg(self, weight: Decimal) -> Decimal {
+        match self {
+            Self::Kg => weight.round_dp(2),
+            Self::Lbs => (weight * KG_PER_LB).round_dp(2),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ImportedSet {
+    pub line: u64,
+    pub weight: Decimal,
+    pub reps: i32,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct ImportedExercise {
+    pub name: String,
+    pub sets: Vec<ImportedSet>,
+}
+
+#[derive(Debug)]
+pub struct ImportedWorkout {
+    pub date: NaiveDate,
+    pub name: String,
+    pub exercises: Vec<ImportedExercise>,
+}
+
+#[derive(Debug)]
+pub struct ParsedHistory {
+    pub source: WorkoutSource,
+    pub row_count: usize,
+    pub workouts: Vec<ImportedWorkout>,
+    pub skipped: Vec<SkippedRow>,
+}
+
+struct ColumnMap {
+    started: usize,
+    workout: Option<usize>,
+    exercise: usize,
+    set_type: Option<usize>,
+    weight: Option<usize>,
+    weight_unit: Option<usize>,
+    reps: usize,
+    rpe: Option<usize>,
+    notes: Option<usize>,
+    unit: WeightUnit,
+}
+
+impl ColumnMap {
+    fn new(columns: &[String], source: WorkoutSource, unit: WeightUnit) -> Result<Self, AppError> {
+        let find = |name: &str| columns.iter().position(|column| column == name);
+        let required = |name: &str| {
+            find(name).ok_or(AppError::BadRequestMessage(format!(
+                "Missing required column: {}",
+                name
+            )))
+        };
+        let map = match source {
+            WorkoutSource::Strong => Self {
+                started: required("date")?,
+                workout: find("workout name"),
+                exercise: required("exercise name")?,
+                set_type: find("set order"),
+                weight: find("weight"),
+                weight_unit: find("weight unit"),
+                reps: required("reps")?,
+                rpe: find("rpe"),
+                notes: find("notes"),
+                unit,
+            },
+            WorkoutSource::Hevy => {
+                let (weight, unit) = match (find("weight_kg"), find("weight_lbs")) {
+                    (Some(index), _) => (Some(index), WeightUnit::Kg),
+                    (None, Some(index)) => (Some(index), WeightUnit::Lbs),
+                    (None, None) => (None, unit),
+                };
+                Self {
+                    started: required("start_time")?,
+                    workout: find("title"),
+                    exercise: required("exercise_title")?,
+                    set_type: find("set_type"),
+                    weight,
+                    weight_unit: None,
+                    reps: required("reps")?,
+                    rpe: find("rpe"),
+                    notes: find("exercise_notes"),
+                    unit,
+                }
+            }
+        };
+        Ok(map)
+    }
+}
+
+struct SetRow {
+    started: String,
+    date: NaiveDate,
+    workout: String,
+    exercise: String,
+    set: ImportedSet,
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    let value = value.trim();
+    DATETIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .map(|datetime| datetime.date())
+        .or_else(|| NaiveDate::parse_from_str(value, "%Y-%m-%d").ok())
+}
+
+fn parse_number(value: &str) -> Option<Decimal> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Some(Decimal::ZERO);
+    }
+    Decimal::from_str(value).ok()
+}
+
+fn set_label(source: WorkoutSource, value: &str) -> Option<&'static str> {
+    match (source, value.trim().to_lowercase().as_str()) {
+        (WorkoutSource::Strong, "w") | (WorkoutSource::Hevy, "warmup") => Some("Warm-up"),
+        (WorkoutSource::Strong, "d") | (WorkoutSource::Hevy, "dropset") => Some("Drop set"),
+        (WorkoutSource::Strong, "f") | (WorkoutSource::Hevy, "failure") => Some("Failure"),
+        _ => None,
+    }
+}
+
+fn parse_row(
+    record: &csv::StringRecord,
+    map: &ColumnMap,
+    source: WorkoutSource,
+    line: u64,
+) -> Result<Option<SetRow>, String> {
+    let get = |index: usize| record.get(index).unwrap_or_default().trim();
+    let optional = |index: Option<usize>| index.map(get).unwrap_or_default();
+
+    let set_type = optional(map.set_type);
+    // Strong writes rest timers as their own rows.
+    if set_type.eq_ignore_ascii_case("rest timer") {
+        return Ok(None);
+    }
+    let started = get(map.started);
+    let date = parse_date(started).ok_or(format!("Invalid date: {}", started))?;
+    let exercise = get(map.exercise);
+    if exercise.is_empty() {
+        return Err(String::from("Missing exercise name"));
+    }
+
+    let reps = parse_number(get(map.reps))
+        .and_then(|reps| reps.round().to_i32())
+        .ok_or(String::from("Invalid reps"))?;
+    if reps <= 0 {
+        return Err(String::from("Sets without reps are not supported"));
+    }
+    let weight = parse_number(optional(map.weight)).ok_or(String::from("Invalid weight"))?;
+    let unit = match map.weight_unit.map(get).filter(|value| !value.is_empty()) {
+        Some(value) => {
+            WeightUnit::from_name(value).ok_or(format!("Invalid weight unit: {}", value))?
+        }
+        None => map.unit,
+    };
+    let weight = unit.to_kg(weight);
+    if weight.is_sign_negative() || weight > MAX_WEIGHT {
+        return Err(String::from("Weight is out of range"));
+    }
+
+    let rpe = optional(map.rpe);
+    let rpe = (!rpe.is_empty()).then(|| format!("RPE {}", rpe));
+    let notes: Vec<String> = [
+        set_label(source, set_type).map(String::from),
+        rpe,
+        Some(optional(map.notes).to_string()).filter(|notes| !notes.is_empty()),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    let notes =
+        (!notes.is_empty()).then(|| notes.join(", ").chars().take(MAX_NOTES_LENGTH).collect());
+
+    Ok(Some(SetRow {
+        started: started.to_string(),
+        date,
+        workout: optional(map.workout).to_string(),
+        exercise: exercise.to_string(),
+        set: ImportedSet {
+            line,
+            weight,
+            reps,
+            notes,
+        },
+    }))
+}
+
+fn detect_delimiter(data: &[u8]) -> u8 {
+    let header = data.split(|byte| *byte == b'\n').next().unwrap_or_default();
+    let count = |delimiter: u8| header.iter().filter(|byte| **byte == delimiter).count();
+    if count(b';') > count(b',') {
+        b';'
+    } else {
+        b','
+    }
+}
+
+// Rows are grouped into workouts by start time and name, then into exercises by name,
+// keeping the order things first appear in the file.
+pub fn parse_history(
+    data: &[u8],
+    source: Option<WorkoutSource>,
+    unit: WeightUnit,
+) -> Result<ParsedHistory, AppError> {
+    let data = data.strip_prefix("\u{feff}".as_bytes()).unwrap_or(data);
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(detect_delimiter(data))
+        .flexible(true)
+        .from_reader(data);
+    let columns: Vec<String> = reader
+        .headers()?
+        .iter()
+        .map(|header| header.trim().to_lowercase())
+        .collect();
+    let source = source
+        .or_else(|| {
+            if columns.iter().any(|column| column == "exercise_title") {
+                Some(WorkoutSource::Hevy)
+            } else if columns.iter().any(|column| column == "exercise name") {
+                Some(WorkoutSource::Strong)
+            } else {
+                None
+            }
+        })
+        .ok_or(AppError::BadRequestMessage(String::from(
+            "Could not detect the export format",
+        )))?;
+    let map = ColumnMap::new(&columns, source, unit)?;
+
+    let mut row_count = 0;
+    let mut workouts: Vec<ImportedWorkout> = Vec::new();
+    let mut workout_index: HashMap<(String, String), usize> = HashMap::new();
+    let mut skipped = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let record = record?;
+        row_count += 1;
+        let line = record
+            .position()
+            .map_or(index as u64 + 2, |position| position.line());
+        let row = match parse_row(&record, &map, source, line) {
+            Ok(Some(row)) => row,
+            Ok(None) => continue,
+            Err(reason) => {
+                skipped.push(SkippedRow { line, reason });
+                continue;
+            }
+        };
+        let key = (row.started, row.workout.clone());
+        let index = *workout_index.entry(key).or_insert_with(|| {
+            workouts.push(ImportedWorkout {
+                date: row.date,
+                name: row.workout,
+                exercises: Vec::new(),
+            });
+            workouts.len() - 1
+        });
+        let workout = &mut workouts[index];
+        match workout
+            .exercises
+            .iter_mut()
+            .find(|exercise| exercise.name.eq_ignore_ascii_case(&row.exercise))
+        {
+            Some(exercise) => exercise.sets.push(row.set),
+            None => workout.exercises.push(ImportedExercise {
+                name: row.exercise,
+                sets: vec![row.set],
+            }),
+        }
+    }
+    Ok(ParsedHistory {
+        source,
+        row_count,
+        workouts,
+        skipped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STRONG: &str = "\
+Date,Workout Name,Duration,Exercise Name,Set Order,Weight,Reps,Distance,Seconds,Notes,Workout Notes,RPE
+2023-01-15 08:30:00,Push,1h,Bench Press (Barbell),W,40,10,0,0,,,
+2023-01-15 08:30:00,Push,1h,Bench Press (Barbell),1,80,5,0,0,,,8
+2023-01-15 08:30:00,Push,1h,Bench Press (Barbell),Rest Timer,0,0,0,90,,,
+2023-01-15 08:30:00,Push,1h,Overhead Press (Barbell),1,50,5,0,0,Felt heavy,,
+2023-01-15 08:30:00,Push,1h,Bench Press (Barbell),2,80,5,0,0,,,
+2023-01-17 18:00:00,Legs,1h,Running,1,0,0,5,1800,,,
+2023-01-17 18:00:00,Legs,1h,Squat (Barbell),1,100,5,0,0,,,
+";
+
+    const HEVY: &str = "\
+title,start_time,end_time,description,exercise_title,superset_id,exercise_notes,set_index,set_type,weight_lbs,reps,distance_miles,duration_seconds,rpe
+Pull,\"15 Jan 2023, 08:30\",\"15 Jan 2023, 09:30\",,Deadlift (Barbell),,,0,normal,225,5,,,
+";
+
+    #[test]
+    fn test_parse_strong_groups_sets() {
+        let parsed = parse_history(STRONG.as_bytes(), None, WeightUnit::Kg).unwrap();
+        assert_eq!(parsed.source, WorkoutSource::Strong);
+        assert_eq!(parsed.row_count, 7);
+        assert_eq!(parsed.workouts.len(), 2);
+
+        let push = &parsed.workouts[0];
+        assert_eq!(push.name, "Push");
+        assert_eq!(push.exercises.len(), 2);
+        assert_eq!(push.exercises[0].name, "Bench Press (Barbell)");
+        assert_eq!(push.exercises[0].sets.len(), 3);
+        assert_eq!(push.exercises[0].sets[0].notes.as_deref(), Some("Warm-up"));
+        assert_eq!(push.exercises[0].sets[1].notes.as_deref(), Some("RPE 8"));
+        assert_eq!(
+            push.exercises[1].sets[0].notes.as_deref(),
+            Some("Felt heavy")
+        );
+
+        assert_eq!(parsed.workouts[1].exercises.len(), 1);
+        assert_eq!(parsed.skipped.len(), 1);
+        assert_eq!(parsed.skipped[0].line, 7);
+    }
+
+    #[test]
+    fn test_parse_hevy_converts_pounds() {
+        let parsed = parse_history(HEVY.as_bytes(), None, WeightUnit::Kg).unwrap();
+        assert_eq!(parsed.source, WorkoutSource::Hevy);
+        let workout = &parsed.workouts[0];
+        assert_eq!(workout.date, NaiveDate::from_ymd_opt(2023, 1, 15).unwrap());
+        assert_eq!(workout.exercises[0].sets[0].weight, dec!(102.06));
+    }
+
+    #[test]
+    fn test_parse_semicolon_delimited() {
+        let data = STRONG.replace(',', ";");
+        let parsed = parse_history(data.as_bytes(), None, WeightUnit::Lbs).unwrap();
+        assert_eq!(parsed.workouts.len(), 2);
+        assert_eq!(parsed.workouts[1].exercises[0].sets[0].weight, dec!(45.36));
+    }
+}
@@ -0,0 +1,13 @@
+use axum::{extract::DefaultBodyLimit, routing::post, Router};
+use std::sync::Arc;
+
+use crate::AppState;
+
+use super::view::{workout_import_view, MAX_IMPORT_SIZE};
+
+pub fn workout_import_router() -> Router<Arc<AppState>> {
+    Router::new().route(
+        "/",
+        post(workout_import_view).layer(DefaultBodyLimit::max(MAX_IMPORT_SIZE + 1024 * 64)),
+    )
+}
@@ -0,0 +1,85 @@
+use axum::{
+    extract::{Multipart, State},
+    Extension, Json,
+};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    error::AppError, middleware::RequestUser, muscle_group::model::MuscleGroupSerializer, AppState,
+};
+
+use super::{
+    model::{import_history, WorkoutImportOptions, WorkoutImportReport},
+    parser::{parse_history, WeightUnit, WorkoutSource},
+};
+
+pub const MAX_IMPORT_SIZE: usize = 20 * 1024 * 1024;
+
+fn bad_request(message: &str) -> AppError {
+    AppError::BadRequestMessage(message.to_string())
+}
+
+// Imports into the requesting user's own history. Unknown exercises are created under
+// muscle_group_id; conflict=import keeps workouts on dates that already have one.
+pub async fn workout_import_view(
+    State(state): State<Arc<AppState>>,
+    Extension(request_user): Extension<RequestUser>,
+    mut multipart: Multipart,
+) -> Result<Json<WorkoutImportReport>, AppError> {
+    request_user.login_required()?;
+
+    let mut data = None;
+    let mut source = None;
+    let mut unit = WeightUnit::Kg;
+    let mut options = WorkoutImportOptions {
+        muscle_group_id: None,
+        skip_conflicts: true,
+        dry_run: false,
+    };
+    while let Some(field) = multipart.next_field().await? {
+        let name = field.name().unwrap_or_default().to_string();
+        if name == "file" {
+            data = Some(field.bytes().await?);
+            continue;
+        }
+        let value = field.text().await?;
+        let value = value.trim();
+        match name.as_str() {
+            "source" if value != "auto" => {
+                source = Some(
+                    WorkoutSource::from_name(value)
+                        .ok_or(bad_request("Source must be one of: auto, strong, hevy"))?,
+                )
+            }
+            "unit" => {
+                unit = WeightUnit::from_name(value)
+                    .ok_or(bad_request("Unit must be one of: kg, lbs"))?
+            }
+            "muscle_group_id" if !value.is_empty() => {
+                let id = Uuid::parse_str(value).map_err(|_| bad_request("Invalid muscle group"))?;
+                let muscle_group = MuscleGroupSerializer::get(&state.pool, &id)
+                    .await?
+                    .ok_or(bad_request("Muscle group not found"))?;
+                options.muscle_group_id = Some(muscle_group.id);
+            }
+            "conflict" => {
+                options.skip_conflicts = match value {
+                    "skip" => true,
+                    "import" => false,
+                    _ => return Err(bad_request("Conflict must be one of: skip, import")),
+                }
+            }
+            "dry_run" => options.dry_run = matches!(value, "true" | "1"),
+            _ => {}
+        }
+    }
+    let data = data.ok_or(bad_request("A CSV file is required"))?;
+    if data.len() > MAX_IMPORT_SIZE {
+        return Err(bad_request("File must be 20MB or smaller"));
+    }
+
+    let parsed = parse_history(&data, source, unit)?;
+    let report = import_history(&state.pool, request_user.id, parsed, options).await?;
+    Ok(Json(report))
+}